@@ -4,115 +4,438 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use config_crate::{Config, Environment, File};
+use config_crate::{Config, ConfigError, Environment, File, Source};
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::RwLock;
 use serde::Deserialize;
 
-/// The flags provided by using `-Z` arguments on the command line. These are
-/// almost exclusively used for testing.
-#[derive(Clone, Copy, Default)]
-pub struct ConfigFlags {
-    /// Should Prusti print the AST with desugared specifications.
-    pub print_desugared_specs: bool,
-    /// Should Prusti print the type-checked specifications.
-    pub print_typeckd_specs: bool,
-    /// Should Prusti print the items collected for verification.
-    pub print_collected_verfication_items: bool,
-    /// Should Prusti skip the verification part.
-    pub skip_verify: bool,
-    /// Should Prusti hide the UUIDs of expressions and specifications.
-    pub hide_uuids: bool,
+/// Declares the configuration struct together with the list of its keys.
+macro_rules! config_struct {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            $($(#[$field_attr:meta])* pub $field:ident : $ty:ty,)*
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            $($(#[$field_attr])* pub $field: $ty,)*
+        }
+
+        impl $name {
+            /// The keys of all the configuration flags.
+            pub const KEYS: &'static [&'static str] = &[$(stringify!($field)),*];
+        }
+    };
+}
+
+config_struct! {
+    /// The configuration of Prusti.
+    ///
+    /// The keys are case-insensitive. Each flag can be set (in order of increasing priority) in
+    /// the file `Prusti.toml`, in the file given by the `PRUSTI_CONFIG` environment variable,
+    /// with a `PRUSTI_<FLAG>` environment variable, or with a `--prusti-<flag>=<value>` command
+    /// line argument.
+    #[derive(Debug, Clone, Deserialize, Serialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct PrustiConfig {
+        pub viper_backend: String,
+        pub check_foldunfold_state: bool,
+        pub check_binary_operations: bool,
+        pub check_panics: bool,
+        pub check_index_bounds: bool,
+        pub check_division_by_zero: bool,
+        pub check_unwrap: bool,
+        pub check_overflows: bool,
+        pub check_vacuity: bool,
+        pub infer_loop_invariants: bool,
+        pub print_inferred_loop_invariants: bool,
+        pub encode_unsigned_num_constraint: bool,
+        pub target_pointer_width: u64,
+        pub simplify_encoding: bool,
+        pub enable_whitelist: bool,
+        pub whitelist: Vec<String>,
+        pub verification_include_patterns: Vec<String>,
+        pub verification_exclude_patterns: Vec<String>,
+        pub verify_only_specified: bool,
+        pub log_dir: String,
+        pub dump_debug_info: bool,
+        pub dump_path_ctxt_in_debug_info: bool,
+        pub dump_reborrowing_dag_in_debug_info: bool,
+        pub dump_borrowck_info: bool,
+        pub dump_viper_program: bool,
+        pub record_verification_requests: bool,
+        pub foldunfold_state_filter: String,
+        pub num_parents_for_dumps: u64,
+        pub contracts_lib: String,
+        pub extra_jvm_args: Vec<String>,
+        pub extra_verifier_args: Vec<String>,
+        pub quiet: bool,
+        pub assert_timeout: u64,
+        pub use_more_complete_exhale: bool,
+        pub report_support_status: bool,
+        pub skip_unsupported_functions: bool,
+        pub error_on_partially_supported: bool,
+        pub no_verify: bool,
+        pub full_compilation: bool,
+        pub json_communication: bool,
+        pub json_report_dir: Option<String>,
+        pub trusted_base_report_dir: Option<String>,
+        pub incremental_verification: bool,
+        pub server_max_stored_verifiers: Option<usize>,
+        pub server_max_concurrency: Option<usize>,
+        pub server_address: Option<String>,
+        pub disable_name_mangling: bool,
+        pub verify_only_preamble: bool,
+        pub enable_verify_only_basic_block_path: bool,
+        pub verify_only_basic_block_path: Vec<String>,
+        pub delete_basic_blocks: Vec<String>,
+        pub print_desugared_specs: bool,
+        pub print_typeckd_specs: bool,
+        pub print_collected_verification_items: bool,
+        pub hide_uuids: bool,
+    }
+}
+
+/// The prefix of the command line arguments that set a configuration flag.
+pub const COMMAND_LINE_PREFIX: &str = "--prusti-";
+
+/// The command line argument that asks Prusti to print its configuration.
+pub const PRINT_CONFIG_FLAG: &str = "--print-config";
+
+/// The `-Z` arguments that are accepted as aliases of configuration flags. These are almost
+/// exclusively used for testing.
+const Z_FLAGS: &[(&str, &str)] = &[
+    ("-Zprint-desugared-specs", "print_desugared_specs"),
+    ("-Zprint-typeckd-specs", "print_typeckd_specs"),
+    ("-Zprint-collected-verification-items", "print_collected_verification_items"),
+    ("-Zskip-verify", "no_verify"),
+    ("-Zhide-uuids", "hide_uuids"),
+];
+
+/// Where the value of a configuration flag comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File(String),
+    Environment,
+    CommandLine,
+}
+
+impl ConfigSource {
+    fn describe(&self, key: &str) -> String {
+        match self {
+            ConfigSource::Default => "default".to_string(),
+            ConfigSource::File(path) => format!("file `{}`", path),
+            ConfigSource::Environment => {
+                format!("environment variable `PRUSTI_{}`", key.to_uppercase())
+            }
+            ConfigSource::CommandLine => format!(
+                "command line argument `{}{}`",
+                COMMAND_LINE_PREFIX,
+                key.replace('_', "-")
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "the default values"),
+            ConfigSource::File(path) => write!(f, "file `{}`", path),
+            ConfigSource::Environment => write!(f, "the `PRUSTI_*` environment variables"),
+            ConfigSource::CommandLine => write!(f, "the command line arguments"),
+        }
+    }
+}
+
+struct Settings {
+    config: PrustiConfig,
+    /// The source of each flag that has not its default value.
+    sources: HashMap<String, ConfigSource>,
 }
 
 lazy_static! {
-    // Is this RwLock<..> necessary?
-    static ref SETTINGS: RwLock<Config> = RwLock::new({
-        let mut settings = Config::default();
-
-        // 1. Default values
-        settings.set_default("VIPER_BACKEND", "Silicon").unwrap();
-        settings.set_default("CHECK_FOLDUNFOLD_STATE", false).unwrap();
-        settings.set_default("CHECK_BINARY_OPERATIONS", false).unwrap();
-        settings.set_default("CHECK_PANICS", true).unwrap();
-        settings.set_default("ENCODE_UNSIGNED_NUM_CONSTRAINT", false).unwrap();
-        settings.set_default("SIMPLIFY_ENCODING", true).unwrap();
-        settings.set_default("ENABLE_WHITELIST", false).unwrap();
-        settings.set_default::<Vec<String>>("WHITELIST", vec![]).unwrap();
-        settings.set_default("LOG_DIR", "./log/").unwrap();
-        settings.set_default("DUMP_DEBUG_INFO", false).unwrap();
-        settings.set_default("DUMP_PATH_CTXT_IN_DEBUG_INFO", false).unwrap();
-        settings.set_default("DUMP_REBORROWING_DAG_IN_DEBUG_INFO", false).unwrap();
-        settings.set_default("DUMP_BORROWCK_INFO", false).unwrap();
-        settings.set_default("DUMP_VIPER_PROGRAM", false).unwrap();
-        settings.set_default("FOLDUNFOLD_STATE_FILTER", "").unwrap();
-        settings.set_default("NUM_PARENTS_FOR_DUMPS", 0).unwrap();
-        settings.set_default("CONTRACTS_LIB", "").unwrap();
-        settings.set_default::<Vec<String>>("EXTRA_JVM_ARGS", vec![]).unwrap();
-        settings.set_default::<Vec<String>>("EXTRA_VERIFIER_ARGS", vec![]).unwrap();
-        settings.set_default("QUIET", false).unwrap();
-        settings.set_default("ASSERT_TIMEOUT", 10_000).unwrap();
-        settings.set_default("USE_MORE_COMPLETE_EXHALE", true).unwrap();
-        settings.set_default("REPORT_SUPPORT_STATUS", true).unwrap();
-        settings.set_default("SKIP_UNSUPPORTED_FUNCTIONS", false).unwrap();
-        settings.set_default("ERROR_ON_PARTIALLY_SUPPORTED", false).unwrap();
-        settings.set_default("NO_VERIFY", false).unwrap();
-        settings.set_default("FULL_COMPILATION", false).unwrap();
-        settings.set_default("JSON_COMMUNICATION", false).unwrap();
-
-        // Flags for debugging Prusti that can change verification results.
-        settings.set_default("DISABLE_NAME_MANGLING", false).unwrap();
-        settings.set_default("VERIFY_ONLY_PREAMBLE", false).unwrap();
-        settings.set_default("ENABLE_VERIFY_ONLY_BASIC_BLOCK_PATH", false).unwrap();
-        settings.set_default::<Vec<String>>("VERIFY_ONLY_BASIC_BLOCK_PATH", vec![]).unwrap();
-        settings.set_default::<Vec<String>>("DELETE_BASIC_BLOCKS", vec![]).unwrap();
-
-        // 2. Override with the optional TOML file "Prusti.toml" (if there is any)
-        settings.merge(
-            File::with_name("Prusti.toml").required(false)
-        ).unwrap();
-
-        // 3. Override with an optional TOML file specified by the `PRUSTI_CONFIG` env variable
-        settings.merge(
-            File::with_name(&env::var("PRUSTI_CONFIG").unwrap_or("".to_string())).required(false)
-        ).unwrap();
-
-        // 4. Override with env variables (`PRUSTI_VIPER_BACKEND`, ...)
-        settings.merge(
-            Environment::with_prefix("PRUSTI").ignore_empty(true)
-        ).unwrap();
-
+    /// The environment variables with the `PRUSTI_` prefix that are not configuration flags,
+    /// without the prefix and in lower case.
+    static ref NON_CONFIG_ENV_VARS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+    static ref SETTINGS: Result<Settings, String> = load_settings();
+}
+
+/// Whether the settings have been loaded, after which no environment variable can be registered.
+static SETTINGS_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Declare environment variables that have the `PRUSTI_` prefix but are not configuration
+/// flags, such as `PRUSTI_LOG`, so that they are not rejected as unknown flags. The crate that
+/// reads such a variable registers it in the processes that can see it, before the configuration
+/// is first accessed.
+pub fn register_env_vars(vars: &[&str]) {
+    assert!(
+        !SETTINGS_LOADED.load(Ordering::SeqCst),
+        "environment variables must be registered before the configuration is loaded"
+    );
+    let mut non_config_env_vars = NON_CONFIG_ENV_VARS.write().unwrap();
+    for var in vars {
+        let name = var
+            .strip_prefix("PRUSTI_")
+            .unwrap_or_else(|| panic!("`{}` does not have the prefix `PRUSTI_`", var));
+        non_config_env_vars.insert(name.to_lowercase());
+    }
+}
+
+fn is_non_config_env_var(key: &str) -> bool {
+    // `PRUSTI_CONFIG` is read by `load_settings`.
+    key == "config" || NON_CONFIG_ENV_VARS.read().unwrap().contains(key)
+}
+
+fn default_settings() -> Config {
+    let mut settings = Config::default();
+    settings.set_default("VIPER_BACKEND", "Silicon").unwrap();
+    settings.set_default("CHECK_FOLDUNFOLD_STATE", false).unwrap();
+    settings.set_default("CHECK_BINARY_OPERATIONS", false).unwrap();
+    settings.set_default("CHECK_PANICS", true).unwrap();
+    settings.set_default("CHECK_INDEX_BOUNDS", true).unwrap();
+    settings.set_default("CHECK_DIVISION_BY_ZERO", true).unwrap();
+    settings.set_default("CHECK_UNWRAP", true).unwrap();
+    settings.set_default("CHECK_OVERFLOWS", true).unwrap();
+    settings.set_default("CHECK_VACUITY", false).unwrap();
+    settings.set_default("INFER_LOOP_INVARIANTS", false).unwrap();
+    settings.set_default("PRINT_INFERRED_LOOP_INVARIANTS", false).unwrap();
+    settings.set_default("ENCODE_UNSIGNED_NUM_CONSTRAINT", false).unwrap();
+    settings.set_default("TARGET_POINTER_WIDTH", 64).unwrap();
+    settings.set_default("SIMPLIFY_ENCODING", true).unwrap();
+    settings.set_default("ENABLE_WHITELIST", false).unwrap();
+    settings.set_default::<Vec<String>>("WHITELIST", vec![]).unwrap();
+    settings.set_default::<Vec<String>>("VERIFICATION_INCLUDE_PATTERNS", vec![]).unwrap();
+    settings.set_default::<Vec<String>>("VERIFICATION_EXCLUDE_PATTERNS", vec![]).unwrap();
+    settings.set_default("VERIFY_ONLY_SPECIFIED", false).unwrap();
+    settings.set_default("LOG_DIR", "./log/").unwrap();
+    settings.set_default("DUMP_DEBUG_INFO", false).unwrap();
+    settings.set_default("DUMP_PATH_CTXT_IN_DEBUG_INFO", false).unwrap();
+    settings.set_default("DUMP_REBORROWING_DAG_IN_DEBUG_INFO", false).unwrap();
+    settings.set_default("DUMP_BORROWCK_INFO", false).unwrap();
+    settings.set_default("DUMP_VIPER_PROGRAM", false).unwrap();
+    settings.set_default("RECORD_VERIFICATION_REQUESTS", false).unwrap();
+    settings.set_default("FOLDUNFOLD_STATE_FILTER", "").unwrap();
+    settings.set_default("NUM_PARENTS_FOR_DUMPS", 0).unwrap();
+    settings.set_default("CONTRACTS_LIB", "").unwrap();
+    settings.set_default::<Vec<String>>("EXTRA_JVM_ARGS", vec![]).unwrap();
+    settings.set_default::<Vec<String>>("EXTRA_VERIFIER_ARGS", vec![]).unwrap();
+    settings.set_default("QUIET", false).unwrap();
+    settings.set_default("ASSERT_TIMEOUT", 10_000).unwrap();
+    settings.set_default("USE_MORE_COMPLETE_EXHALE", true).unwrap();
+    settings.set_default("REPORT_SUPPORT_STATUS", true).unwrap();
+    settings.set_default("SKIP_UNSUPPORTED_FUNCTIONS", false).unwrap();
+    settings.set_default("ERROR_ON_PARTIALLY_SUPPORTED", false).unwrap();
+    settings.set_default("NO_VERIFY", false).unwrap();
+    settings.set_default("FULL_COMPILATION", false).unwrap();
+    settings.set_default("JSON_COMMUNICATION", false).unwrap();
+    settings.set_default("INCREMENTAL_VERIFICATION", false).unwrap();
+
+    // Flags for debugging Prusti that can change verification results.
+    settings.set_default("DISABLE_NAME_MANGLING", false).unwrap();
+    settings.set_default("VERIFY_ONLY_PREAMBLE", false).unwrap();
+    settings.set_default("ENABLE_VERIFY_ONLY_BASIC_BLOCK_PATH", false).unwrap();
+    settings.set_default::<Vec<String>>("VERIFY_ONLY_BASIC_BLOCK_PATH", vec![]).unwrap();
+    settings.set_default::<Vec<String>>("DELETE_BASIC_BLOCKS", vec![]).unwrap();
+
+    // Flags for testing Prusti.
+    settings.set_default("PRINT_DESUGARED_SPECS", false).unwrap();
+    settings.set_default("PRINT_TYPECKD_SPECS", false).unwrap();
+    settings.set_default("PRINT_COLLECTED_VERIFICATION_ITEMS", false).unwrap();
+    settings.set_default("HIDE_UUIDS", false).unwrap();
+    settings
+}
+
+/// Parse a command line argument that sets a configuration flag, returning the key and the value
+/// of the flag.
+pub fn parse_command_line_flag(arg: &str) -> Option<(String, String)> {
+    if let Some(&(_, key)) = Z_FLAGS.iter().find(|(flag, _)| *flag == arg) {
+        return Some((key.to_string(), "true".to_string()));
+    }
+    if !arg.starts_with(COMMAND_LINE_PREFIX) {
+        return None;
+    }
+    let flag = &arg[COMMAND_LINE_PREFIX.len()..];
+    let (name, value) = match flag.find('=') {
+        Some(pos) => (&flag[..pos], &flag[pos + 1..]),
+        None => (flag, "true"),
+    };
+    Some((name.replace('-', "_").to_lowercase(), value.to_string()))
+}
+
+/// Whether the command line argument is handled by Prusti (and should not be passed to rustc).
+pub fn is_prusti_argument(arg: &str) -> bool {
+    arg == PRINT_CONFIG_FLAG || parse_command_line_flag(arg).is_some()
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == b[j] {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+fn unknown_key_error(key: &str, source: &ConfigSource) -> String {
+    let mut message = format!("unknown configuration flag in {}", source.describe(key));
+    let suggestion = PrustiConfig::KEYS
+        .iter()
+        .map(|known| (edit_distance(key, known), known))
+        .min();
+    if let Some((distance, known)) = suggestion {
+        if distance <= 3 {
+            message.push_str(&format!("; did you mean {}?", source.describe(known)));
+        }
+    }
+    message
+}
+
+/// Collect the configuration flags given in a source, rejecting unknown keys.
+fn collect_keys<S: Source>(source: &S, origin: &ConfigSource) -> Result<Vec<String>, String> {
+    let values = source
+        .collect()
+        .map_err(|err| format!("failed to read the configuration from {}: {}", origin, err))?;
+    let mut keys = vec![];
+    for key in values.keys() {
+        let key = key.to_lowercase();
+        if *origin == ConfigSource::Environment && is_non_config_env_var(&key) {
+            continue;
+        }
+        if !PrustiConfig::KEYS.contains(&key.as_str()) {
+            return Err(unknown_key_error(&key, origin));
+        }
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+fn type_error(err: ConfigError, origin: &ConfigSource) -> String {
+    format!("invalid configuration in {}: {}", origin, err)
+}
+
+fn load_settings() -> Result<Settings, String> {
+    SETTINGS_LOADED.store(true, Ordering::SeqCst);
+    let mut settings = default_settings();
+    let mut sources = HashMap::new();
+
+    // 1. Override with the optional TOML file "Prusti.toml" (if there is any)
+    // 2. Override with an optional TOML file specified by the `PRUSTI_CONFIG` env variable
+    let mut files = vec!["Prusti.toml".to_string()];
+    if let Ok(path) = env::var("PRUSTI_CONFIG") {
+        if !path.is_empty() {
+            files.push(path);
+        }
+    }
+    for path in files {
+        let origin = ConfigSource::File(path.clone());
+        let file = File::with_name(&path).required(false);
+        for key in collect_keys(&file, &origin)? {
+            sources.insert(key, origin.clone());
+        }
+        settings.merge(file).map_err(|err| type_error(err, &origin))?;
+    }
+
+    // 3. Override with env variables (`PRUSTI_VIPER_BACKEND`, ...)
+    let origin = ConfigSource::Environment;
+    let environment = Environment::with_prefix("PRUSTI").ignore_empty(true);
+    let env_keys = collect_keys(&environment, &origin)?;
+    let env_values = environment.collect().map_err(|err| type_error(err, &origin))?;
+    for key in env_keys {
         settings
-    });
-}
-
-/// Generate a dump of the settings
+            .set(&key, env_values[&key].clone())
+            .map_err(|err| type_error(err, &origin))?;
+        sources.insert(key, origin.clone());
+    }
+
+    // 4. Override with the command line arguments (`--prusti-viper-backend=carbon`, ...)
+    let origin = ConfigSource::CommandLine;
+    for arg in env::args().skip(1) {
+        if let Some((key, value)) = parse_command_line_flag(&arg) {
+            if !PrustiConfig::KEYS.contains(&key.as_str()) {
+                return Err(unknown_key_error(&key, &origin));
+            }
+            settings.set(&key, value).map_err(|err| type_error(err, &origin))?;
+            sources.insert(key, origin.clone());
+        }
+    }
+
+    let config = settings.try_into::<PrustiConfig>().map_err(|err| {
+        let mut origins: Vec<String> = sources.values().map(|source| source.to_string()).collect();
+        origins.sort();
+        origins.dedup();
+        format!(
+            "invalid Prusti configuration: {} (the configuration has been read from {})",
+            err,
+            if origins.is_empty() { "the default values".to_string() } else { origins.join(", ") }
+        )
+    })?;
+    if ![16, 32, 64].contains(&config.target_pointer_width) {
+        return Err(format!(
+            "invalid Prusti configuration: TARGET_POINTER_WIDTH must be 16, 32 or 64, not {}",
+            config.target_pointer_width
+        ));
+    }
+    Ok(Settings { config, sources })
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.as_ref().unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Check that the configuration is valid, i.e. that all flags are known and have the right type.
+pub fn check() -> Result<(), String> {
+    SETTINGS.as_ref().map(|_| ()).map_err(|err| err.clone())
+}
+
+/// The typed configuration.
+pub fn get() -> &'static PrustiConfig {
+    &settings().config
+}
+
+/// Generate a dump of the settings, in the TOML format, with the source of each value.
 pub fn dump() -> String {
-    format!("{:?}", SETTINGS.read().unwrap())
-}
-
-fn read_optional_setting<T>(name: &'static str) -> Option<T>
-where
-    T: Deserialize<'static>,
-{
-    SETTINGS.read().unwrap().get(name).ok()
-}
-
-fn read_setting<T>(name: &'static str) -> T
-where
-    T: Deserialize<'static>,
-{
-    read_optional_setting(name).unwrap()
+    let settings = settings();
+    let table = match toml::Value::try_from(&settings.config) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => unreachable!("the configuration is not a table"),
+    };
+    let mut lines: Vec<String> = table
+        .iter()
+        .map(|(key, value)| {
+            let source = settings.sources.get(key).unwrap_or(&ConfigSource::Default);
+            format!("{} = {}  # {}", key, value, source.describe(key))
+        })
+        .collect();
+    lines.sort();
+    lines.join("\n")
 }
 
 /// Generate additional, *slow*, checks for the foldunfold algorithm
 pub fn check_foldunfold_state() -> bool {
-    read_setting("CHECK_FOLDUNFOLD_STATE")
+    get().check_foldunfold_state
 }
 
 /// The Viper backend that should be used for the verification
 pub fn viper_backend() -> String {
-    read_setting::<String>("VIPER_BACKEND")
+    get()
+        .viper_backend
         .to_lowercase()
         .trim()
         .to_string()
@@ -120,119 +443,200 @@ pub fn viper_backend() -> String {
 
 /// Should we check absence of panics?
 pub fn check_panics() -> bool {
-    read_setting("CHECK_PANICS")
+    get().check_panics
+}
+
+/// Should we check that indices are in bounds (of arrays, slices, vectors and strings)?
+/// Only used if `check_panics` is enabled.
+pub fn check_index_bounds() -> bool {
+    get().check_index_bounds
+}
+
+/// Should we check the absence of divisions and remainders by zero?
+/// Only used if `check_panics` is enabled.
+pub fn check_division_by_zero() -> bool {
+    get().check_division_by_zero
+}
+
+/// Should we check that `unwrap` is not called on a `None` or `Err` value?
+/// Only used if `check_panics` is enabled.
+pub fn check_unwrap() -> bool {
+    get().check_unwrap
+}
+
+/// Should we check the absence of overflows in arithmetic operations, shifts and casts?
+/// Only used if `check_panics` and `check_binary_operations` are enabled.
+pub fn check_overflows() -> bool {
+    get().check_overflows
+}
+
+/// Should we check that the precondition of each procedure and each loop invariant are not
+/// inconsistent, and that each branch of a conditional is reachable? The failures of these checks
+/// are reported as warnings.
+pub fn check_vacuity() -> bool {
+    get().check_vacuity
+}
+
+/// Should we infer simple loop invariants (bounds of and relations between integer local
+/// variables) and add them to the loop invariants written by the user?
+pub fn infer_loop_invariants() -> bool {
+    get().infer_loop_invariants
+}
+
+/// Print the inferred loop invariants (see `infer_loop_invariants`).
+pub fn print_inferred_loop_invariants() -> bool {
+    get().print_inferred_loop_invariants
 }
 
 /// Should we simplify the encoding before passing it to Viper?
 pub fn simplify_encoding() -> bool {
-    read_setting("SIMPLIFY_ENCODING")
+    get().simplify_encoding
 }
 
 /// Whether to use the verifiation whitelist
 pub fn enable_whitelist() -> bool {
-    SETTINGS
-        .read()
-        .unwrap()
-        .get::<bool>("ENABLE_WHITELIST")
-        .unwrap()
+    get().enable_whitelist
 }
 
 /// Get the whitelist of procedures that should be verified
 pub fn verification_whitelist() -> Vec<String> {
-    SETTINGS
-        .read()
-        .unwrap()
-        .get::<Vec<String>>("WHITELIST")
-        .unwrap()
+    get().whitelist.clone()
+}
+
+/// Get the patterns of the def paths of the procedures that should be verified. If empty, all
+/// the procedures are verified. A pattern is either a glob, in which `*` matches any sequence of
+/// characters without `:` (i.e. within a segment of the path) and `**` matches any sequence of
+/// characters, or a regular expression prefixed by `regex:`. Patterns must match the whole path.
+pub fn verification_include_patterns() -> Vec<String> {
+    get().verification_include_patterns.clone()
+}
+
+/// Get the patterns of the def paths of the procedures that should not be verified. The syntax
+/// is the one of `verification_include_patterns`.
+pub fn verification_exclude_patterns() -> Vec<String> {
+    get().verification_exclude_patterns.clone()
+}
+
+/// Should we verify only the procedures that have a contract (or that are marked with
+/// `#[verify]`)?
+pub fn verify_only_specified() -> bool {
+    get().verify_only_specified
 }
 
 /// Should we dump debug files?
 pub fn dump_debug_info() -> bool {
-    read_setting("DUMP_DEBUG_INFO")
+    get().dump_debug_info
 }
 
 /// Should we dump the branch context state in debug files?
 pub fn dump_path_ctxt_in_debug_info() -> bool {
-    read_setting("DUMP_PATH_CTXT_IN_DEBUG_INFO")
+    get().dump_path_ctxt_in_debug_info
 }
 
 /// Should we dump the reborrowing DAGs in debug files?
 pub fn dump_reborrowing_dag_in_debug_info() -> bool {
-    read_setting("DUMP_REBORROWING_DAG_IN_DEBUG_INFO")
+    get().dump_reborrowing_dag_in_debug_info
 }
 
 /// Should we dump borrowck info?
 pub fn dump_borrowck_info() -> bool {
-    read_setting("DUMP_BORROWCK_INFO")
+    get().dump_borrowck_info
 }
 
 /// Should we dump the Viper program?
 pub fn dump_viper_program() -> bool {
-    read_setting("DUMP_VIPER_PROGRAM")
+    get().dump_viper_program
+}
+
+/// Should we save the verification requests to `<LOG_DIR>/verification_requests/`, from where
+/// they can be replayed with `prusti-server --replay <file>`?
+pub fn record_verification_requests() -> bool {
+    get().record_verification_requests
+}
+
+/// When set, Prusti writes a machine-readable JSON report of the verification of each crate to
+/// `<JSON_REPORT_DIR>/<crate name>.json`.
+pub fn json_report_dir() -> Option<String> {
+    get().json_report_dir.clone()
+}
+
+/// When set, Prusti writes a report of the assumptions on which the verification of each
+/// procedure relies (trusted and unspecified callees, skipped procedures, weakening flags) to
+/// `<TRUSTED_BASE_REPORT_DIR>/<crate name>.json` and `<TRUSTED_BASE_REPORT_DIR>/<crate name>.txt`.
+/// The report is not written when `INCREMENTAL_VERIFICATION` is enabled.
+pub fn trusted_base_report_dir() -> Option<String> {
+    get().trusted_base_report_dir.clone()
+}
+
+/// Should we skip the verification of procedures that, together with their dependencies, did not
+/// change since they were last verified successfully?
+pub fn incremental_verification() -> bool {
+    get().incremental_verification
 }
 
 /// The Viper backend that should be used for the verification
 pub fn foldunfold_state_filter() -> String {
-    read_setting("FOLDUNFOLD_STATE_FILTER")
+    get().foldunfold_state_filter.clone()
 }
 
 /// How many parent folders should be used to disambiguate the Viper dumps (and other debug files)?
 pub fn num_parents_for_dumps() -> u64 {
-    SETTINGS
-        .read()
-        .unwrap()
-        .get::<u64>("NUM_PARENTS_FOR_DUMPS")
-        .unwrap()
+    get().num_parents_for_dumps
 }
 
 /// In which folder should we sore log/dumps?
 pub fn log_dir() -> String {
-    read_setting("LOG_DIR")
+    get().log_dir.clone()
 }
 
 /// Check binary operations for overflows
 pub fn check_binary_operations() -> bool {
-    read_setting("CHECK_BINARY_OPERATIONS")
+    get().check_binary_operations
 }
 
 /// Encode (and check) that unsigned integers are non-negative.
 pub fn encode_unsigned_num_constraint() -> bool {
-    read_setting("ENCODE_UNSIGNED_NUM_CONSTRAINT")
+    get().encode_unsigned_num_constraint
+}
+
+/// The number of bits of `usize` and `isize` on the target platform.
+pub fn target_pointer_width() -> u32 {
+    get().target_pointer_width as u32
 }
 
 /// Location of 'libprusti_contracts*.rlib'
 pub fn contracts_lib() -> String {
-    read_setting("CONTRACTS_LIB")
+    get().contracts_lib.clone()
 }
 
 /// Get extra JVM arguments
 pub fn extra_jvm_args() -> Vec<String> {
-    read_setting("EXTRA_JVM_ARGS")
+    get().extra_jvm_args.clone()
 }
 
 /// Get extra arguments for the verifier
 pub fn extra_verifier_args() -> Vec<String> {
-    read_setting("EXTRA_VERIFIER_ARGS")
+    get().extra_verifier_args.clone()
 }
 
 /// Should we hide user messages?
 pub fn quiet() -> bool {
-    read_setting("QUIET")
+    get().quiet
 }
 
 /// The assert timeout (in milliseconds) passed to Silicon.
 pub fn assert_timeout() -> u64 {
-    read_setting("ASSERT_TIMEOUT")
+    get().assert_timeout
 }
 
 /// Use the Silicon configuration option `--enableMoreCompleteExhale`.
 pub fn use_more_complete_exhale() -> bool {
-    read_setting("USE_MORE_COMPLETE_EXHALE")
+    get().use_more_complete_exhale
 }
 
 /// Report the support status of functions using the compiler's error messages
 pub fn report_support_status() -> bool {
-    read_setting("REPORT_SUPPORT_STATUS")
+    get().report_support_status
 }
 
 /**
@@ -244,26 +648,26 @@ It also doesn't make much sense to set this to less than that, since then the se
 pub fn server_max_stored_verifiers() -> Option<usize> {
     // TODO: default to below in prusti-server
     // TODO: warn if lower than below
-    read_optional_setting("SERVER_MAX_STORED_VERIFIERS")
+    get().server_max_stored_verifiers
 }
 
 /// The maximum amount of verification requests the server will work on concurrently.
 ///
 /// If not set, this defaults to the number of (logical) cores on the system
 pub fn server_max_concurrency() -> Option<usize> {
-    read_optional_setting("SERVER_MAX_CONCURRENCY")
+    get().server_max_concurrency
 }
 
 /// When set, Prusti will connect to this server and use it for its verification backend (i.e. the things using the JVM/Viper).
 /// Set to "MOCK" to run the server off-thread, effectively mocking connecting to a server without having to start it up separately.
 /// e.g. "127.0.0.1:2468"
 pub fn server_address() -> Option<String> {
-    read_optional_setting("SERVER_ADDRESS")
+    get().server_address.clone()
 }
 
 /// If true, communication with the server will be encoded as json and not the default of bincode.
 pub fn json_communication() -> bool {
-    read_setting("JSON_COMMUNICATION")
+    get().json_communication
 }
 
 /// Disable mangling of generated Viper names.
@@ -271,21 +675,21 @@ pub fn json_communication() -> bool {
 /// **Note:** This is very likely to result in invalid programs being
 /// generated because of name collisions.
 pub fn disable_name_mangling() -> bool {
-    read_setting("DISABLE_NAME_MANGLING")
+    get().disable_name_mangling
 }
 
 /// Verify only the preamble: domains, functions, and predicates.
 ///
 /// **Note:** With this flag enabled, no methods are verified!
 pub fn verify_only_preamble() -> bool {
-    read_setting("VERIFY_ONLY_PREAMBLE")
+    get().verify_only_preamble
 }
 
 /// Verify only the path given in ``VERIFY_ONLY_BASIC_BLOCK_PATH``.
 ///
 /// **Note:** This flag is only for debugging Prusti!
 pub fn enable_verify_only_basic_block_path() -> bool {
-    read_setting("ENABLE_VERIFY_ONLY_BASIC_BLOCK_PATH")
+    get().enable_verify_only_basic_block_path
 }
 
 /// Verify only the single execution path goes through the given basic blocks.
@@ -295,31 +699,141 @@ pub fn enable_verify_only_basic_block_path() -> bool {
 ///
 /// **Note:** This flag is only for debugging Prusti!
 pub fn verify_only_basic_block_path() -> Vec<String> {
-    read_setting("VERIFY_ONLY_BASIC_BLOCK_PATH")
+    get().verify_only_basic_block_path.clone()
 }
 
 /// Replace the given basic blocks with ``assume false``.
 pub fn delete_basic_blocks() -> Vec<String> {
-    read_setting("DELETE_BASIC_BLOCKS")
+    get().delete_basic_blocks.clone()
 }
 
 /// Skip functions that are unsupported or partially supported
 pub fn skip_unsupported_functions() -> bool {
-    read_setting("SKIP_UNSUPPORTED_FUNCTIONS")
+    get().skip_unsupported_functions
 }
 
 /// Raise error messages even for partially supported language features.
 /// Note: this overrides SKIP_UNSUPPORTED_FUNCTIONS
 pub fn error_on_partially_supported() -> bool {
-    read_setting("ERROR_ON_PARTIALLY_SUPPORTED")
+    get().error_on_partially_supported
 }
 
 /// Skip the verification
 pub fn no_verify() -> bool {
-    read_setting("NO_VERIFY")
+    get().no_verify
 }
 
 /// Continue the compilation and generate the binary after Prusti terminates
 pub fn full_compilation() -> bool {
-    read_setting("FULL_COMPILATION")
-}
\ No newline at end of file
+    get().full_compilation
+}
+/// Print the AST with desugared specifications.
+pub fn print_desugared_specs() -> bool {
+    get().print_desugared_specs
+}
+
+/// Print the type-checked specifications.
+pub fn print_typeckd_specs() -> bool {
+    get().print_typeckd_specs
+}
+
+/// Print the items collected for verification.
+pub fn print_collected_verification_items() -> bool {
+    get().print_collected_verification_items
+}
+
+/// Hide the UUIDs of expressions and specifications.
+pub fn hide_uuids() -> bool {
+    get().hide_uuids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config_crate::FileFormat;
+
+    #[test]
+    fn test_default_settings_are_complete() {
+        let config = default_settings().try_into::<PrustiConfig>();
+        assert!(config.is_ok(), "{:?}", config.err());
+    }
+
+    #[test]
+    fn test_parse_command_line_flag() {
+        assert_eq!(
+            parse_command_line_flag("--prusti-viper-backend=Carbon"),
+            Some(("viper_backend".to_string(), "Carbon".to_string()))
+        );
+        assert_eq!(
+            parse_command_line_flag("--prusti-CHECK-PANICS"),
+            Some(("check_panics".to_string(), "true".to_string()))
+        );
+        assert_eq!(
+            parse_command_line_flag("--prusti-extra-verifier-args=--a=b"),
+            Some(("extra_verifier_args".to_string(), "--a=b".to_string()))
+        );
+        assert_eq!(
+            parse_command_line_flag("-Zskip-verify"),
+            Some(("no_verify".to_string(), "true".to_string()))
+        );
+        assert_eq!(parse_command_line_flag("--edition=2018"), None);
+        assert_eq!(parse_command_line_flag("-Zunknown"), None);
+        assert!(is_prusti_argument(PRINT_CONFIG_FLAG));
+        assert!(!is_prusti_argument("--crate-name"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("check_panics", "check_panics"), 0);
+        assert_eq!(edit_distance("check_panic", "check_panics"), 1);
+        assert_eq!(edit_distance("chekc_panics", "check_panics"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unknown_key_error() {
+        let message = unknown_key_error("check_panic", &ConfigSource::CommandLine);
+        assert_eq!(
+            message,
+            "unknown configuration flag in command line argument `--prusti-check-panic`; \
+             did you mean command line argument `--prusti-check-panics`?"
+        );
+        let message = unknown_key_error("completely_unrelated", &ConfigSource::Environment);
+        assert_eq!(
+            message,
+            "unknown configuration flag in environment variable `PRUSTI_COMPLETELY_UNRELATED`"
+        );
+    }
+
+    #[test]
+    fn test_collect_keys() {
+        let origin = ConfigSource::File("Prusti.toml".to_string());
+        let file = File::from_str("CHECK_PANICS = false\nlog_dir = \"log\"", FileFormat::Toml);
+        let mut keys = collect_keys(&file, &origin).unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["check_panics", "log_dir"]);
+
+        let file = File::from_str("check_panic = false", FileFormat::Toml);
+        assert!(collect_keys(&file, &origin).is_err());
+    }
+
+    #[test]
+    fn test_non_config_env_vars() {
+        // Registered variables such as `PRUSTI_LOG` are not configuration flags, but only when
+        // given as environment variables.
+        register_env_vars(&["PRUSTI_LOG"]);
+        let file = File::from_str(
+            "log = \"info\"\nconfig = \"Prusti.toml\"\ncheck_panics = false",
+            FileFormat::Toml,
+        );
+        let keys = collect_keys(&file, &ConfigSource::Environment).unwrap();
+        assert_eq!(keys, vec!["check_panics"]);
+        let origin = ConfigSource::File("Prusti.toml".to_string());
+        assert!(collect_keys(&file, &origin).is_err());
+
+        let file = File::from_str("log_style = \"always\"", FileFormat::Toml);
+        assert!(collect_keys(&file, &ConfigSource::Environment).is_err());
+    }
+}
@@ -17,6 +17,7 @@ extern crate config as config_crate;
 extern crate lazy_static;
 extern crate regex;
 extern crate serde;
+extern crate toml;
 extern crate uuid;
 extern crate viper;
 #[macro_use]
@@ -44,28 +44,30 @@ impl<Level: LogLevel> Stopwatch<Level> {
     }
 
     /// Finishes up the current section, logging the time taken, and starts timing the next one.
-    pub fn start_next<S: ToString>(&mut self, section_name: S) {
+    ///
+    /// Returns the time taken by the finished section.
+    pub fn start_next<S: ToString>(&mut self, section_name: S) -> Duration {
         let now = Instant::now();
-        Level::log_finish(
-            &self.prefix,
-            &self.section_name,
-            now.duration_since(self.start_time),
-        );
+        let duration = now.duration_since(self.start_time);
+        Level::log_finish(&self.prefix, &self.section_name, duration);
         self.section_name = section_name.to_string();
         Level::log_start(&self.prefix, &self.section_name);
         self.start_time = now;
+        duration
     }
 
-    /// Finishes up the current section, logging the time taken.
+    /// Finishes up the current section, logging and returning the time taken.
     ///
     /// - Note: Simply dropping the stopwatch has the same effect.
-    pub fn finish(mut self) {
-        self._finish();
+    pub fn finish(mut self) -> Duration {
+        self._finish()
     }
 
-    fn _finish(&mut self) {
-        Level::log_finish(&self.prefix, &self.section_name, self.start_time.elapsed());
+    fn _finish(&mut self) -> Duration {
+        let duration = self.start_time.elapsed();
+        Level::log_finish(&self.prefix, &self.section_name, duration);
         self.is_finished = true;
+        duration
     }
 }
 
@@ -70,11 +70,26 @@ impl<'v> VerificationContext<'v> {
                 log_dir_str.to_string(),
                 //"--logLevel".to_string(), "WARN".to_string(),
             ]),
-            VerificationBackend::Carbon => verifier_args.extend(vec![
-                "--disableAllocEncoding".to_string(),
-                "--boogieOpt".to_string(),
-                format!("/logPrefix {}", log_dir_str),
-            ]),
+            VerificationBackend::Carbon => {
+                // Carbon accepts a single `--boogieOpt` argument, so the Boogie options of the
+                // backend configuration are merged with the log prefix.
+                let mut boogie_opts = vec![format!("/logPrefix {}", log_dir_str)];
+                let mut other_args = vec![];
+                let mut args = verifier_args.into_iter();
+                while let Some(arg) = args.next() {
+                    if arg == "--boogieOpt" {
+                        boogie_opts.extend(args.next());
+                    } else {
+                        other_args.push(arg);
+                    }
+                }
+                verifier_args = other_args;
+                verifier_args.extend(vec![
+                    "--disableAllocEncoding".to_string(),
+                    "--boogieOpt".to_string(),
+                    boogie_opts.join(" "),
+                ]);
+            }
         }
         if config::dump_debug_info() {
             match backend_config.backend {
@@ -104,4 +119,8 @@ impl<'v> VerificationContext<'v> {
     pub fn new_ast_utils(&self) -> viper::AstUtils {
         self.verification_ctx.new_ast_utils()
     }
+
+    pub fn new_program_parser(&self) -> viper::ProgramParser {
+        self.verification_ctx.new_program_parser()
+    }
 }
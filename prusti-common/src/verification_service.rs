@@ -23,10 +23,15 @@ pub struct ViperBackendConfig {
     pub verifier_args: Vec<String>,
 }
 
-impl Default for ViperBackendConfig {
-    fn default() -> Self {
-        let backend = VerificationBackend::from_str(&config::viper_backend());
-        let mut verifier_args = config::extra_verifier_args();
+impl ViperBackendConfig {
+    /// Builds the configuration of a backend that uses the given Z3 timeout (in milliseconds)
+    /// for each assertion and the given extra verifier arguments.
+    pub fn new(
+        backend: VerificationBackend,
+        assert_timeout: u64,
+        extra_verifier_args: Vec<String>,
+    ) -> Self {
+        let mut verifier_args = extra_verifier_args;
         if let VerificationBackend::Silicon = backend {
             if config::use_more_complete_exhale() {
                 verifier_args.push("--enableMoreCompleteExhale".to_string());
@@ -34,12 +39,37 @@ impl Default for ViperBackendConfig {
             }
             verifier_args.extend(vec![
                 "--assertTimeout".to_string(),
-                config::assert_timeout().to_string(),
+                assert_timeout.to_string(),
             ]);
         }
+        if config::check_vacuity() {
+            // Vacuity checks are expected to fail, so the verifier must not stop reporting
+            // errors after the first failing ones.
+            match backend {
+                VerificationBackend::Silicon => verifier_args.extend(vec![
+                    "--numberOfErrorsToReport".to_string(),
+                    "0".to_string(),
+                ]),
+                // Boogie has no option to report all the errors of a procedure.
+                VerificationBackend::Carbon => verifier_args.extend(vec![
+                    "--boogieOpt".to_string(),
+                    "/errorLimit:1000000".to_string(),
+                ]),
+            }
+        }
         Self {
             backend,
             verifier_args,
         }
     }
 }
+
+impl Default for ViperBackendConfig {
+    fn default() -> Self {
+        Self::new(
+            VerificationBackend::from_str(&config::viper_backend()),
+            config::assert_timeout(),
+            config::extra_verifier_args(),
+        )
+    }
+}
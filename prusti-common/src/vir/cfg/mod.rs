@@ -8,6 +8,7 @@ pub use self::display::*;
 pub use self::method::*;
 pub use self::to_graphviz::*;
 pub use self::to_viper::*;
+pub use self::to_viper_syntax::*;
 pub use self::visitor::*;
 pub use self::assigned_vars::*;
 
@@ -15,5 +16,6 @@ mod display;
 mod method;
 mod to_graphviz;
 mod to_viper;
+mod to_viper_syntax;
 mod visitor;
 mod assigned_vars;
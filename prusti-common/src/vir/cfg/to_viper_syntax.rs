@@ -0,0 +1,81 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vir::{
+    cfg::method::*,
+    to_viper_syntax::{block, if_stmt, var_decls, ToViperSyntax},
+};
+
+impl ToViperSyntax for CfgMethod {
+    fn to_viper_syntax(&self) -> String {
+        let mut stmts: Vec<String> = self
+            .local_vars
+            .iter()
+            .map(|local_var| format!("var {}", var_decls(&[local_var.clone()])))
+            .collect();
+
+        // Sort blocks by label, except for the first block
+        let mut blocks: Vec<_> = self.basic_blocks.iter().enumerate().skip(1).collect();
+        blocks.sort_by_key(|(index, _)| self.basic_blocks_labels[*index].clone());
+        blocks.insert(0, (0, &self.basic_blocks[0]));
+
+        // The blocks are not nested in `{ ... }`, because Viper would not allow to jump to their
+        // labels from other blocks.
+        for (index, block) in blocks.into_iter() {
+            stmts.push(block_to_viper_syntax(&self.basic_blocks_labels, block, index));
+        }
+        stmts.push(format!("label {}", RETURN_LABEL));
+
+        format!(
+            "method {}() returns ({})\n{}",
+            self.method_name,
+            var_decls(&self.formal_returns),
+            block(&stmts)
+        )
+    }
+}
+
+fn successor_to_viper_syntax(
+    index: usize,
+    basic_block_labels: &[String],
+    successor: &Successor,
+) -> String {
+    match *successor {
+        Successor::Undefined => panic!(
+            "CFG block '{}' has no successor.",
+            basic_block_labels[index].clone()
+        ),
+        Successor::Return => format!("goto {}", RETURN_LABEL),
+        Successor::Goto(target) => format!("goto {}", basic_block_labels[target.block_index]),
+        Successor::GotoSwitch(ref successors, ref default_target) => {
+            let mut stmts: Vec<String> = vec![];
+            for (test, target) in successors {
+                let goto = format!("goto {}", basic_block_labels[target.block_index]);
+                stmts.push(if_stmt(test, &[goto], &[]));
+            }
+            stmts.push(format!(
+                "goto {}",
+                basic_block_labels[default_target.block_index]
+            ));
+            stmts.join("\n")
+        }
+    }
+}
+
+fn block_to_viper_syntax(basic_block_labels: &[String], block: &CfgBlock, index: usize) -> String {
+    let mut label = format!("label {}", basic_block_labels[index]);
+    for inv in &block.invs {
+        label.push_str(&format!("\n  invariant {}", inv.to_viper_syntax()));
+    }
+    let mut stmts = vec![label];
+    stmts.extend(block.stmts.iter().map(|stmt| stmt.to_viper_syntax()));
+    stmts.push(successor_to_viper_syntax(
+        index,
+        basic_block_labels,
+        &block.successor,
+    ));
+    stmts.join("\n")
+}
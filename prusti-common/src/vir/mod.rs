@@ -8,6 +8,7 @@ pub use self::ast::*;
 pub use self::cfg::*;
 pub use self::conversions::*;
 pub use self::to_viper::*;
+pub use self::to_viper_syntax::*;
 pub use self::program::*;
 
 mod ast;
@@ -17,5 +18,6 @@ mod conversions;
 pub mod fixes;
 pub mod optimizations;
 mod to_viper;
+mod to_viper_syntax;
 pub mod utils;
 mod program;
@@ -0,0 +1,485 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A printer of VIR in the textual syntax of Viper that does not need a JVM.
+//!
+//! The generated program is the same that `ToViper` builds on the JVM, except that the
+//! debugging options that select parts of the program (e.g. `VERIFY_ONLY_PREAMBLE`) are ignored.
+
+use vir::{ast::*, borrows::borrow_id, Program};
+
+pub trait ToViperSyntax {
+    /// The Viper source code of the item.
+    fn to_viper_syntax(&self) -> String;
+}
+
+/// Indents every line of `text` by two spaces.
+pub(super) fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A Viper comment, which might span multiple lines.
+pub(super) fn comment(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("// {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn join<T: ToViperSyntax>(items: &[T], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| item.to_viper_syntax())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// The declarations `x: T, y: U` of a list of variables.
+pub(super) fn var_decls(vars: &[LocalVar]) -> String {
+    vars.iter()
+        .map(|var| format!("{}: {}", var.name, var.typ.to_viper_syntax()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A block `{ ... }` of statements, on multiple lines.
+pub(super) fn block(stmts: &[String]) -> String {
+    if stmts.is_empty() {
+        "{\n}".to_string()
+    } else {
+        format!("{{\n{}\n}}", indent(&stmts.join("\n")))
+    }
+}
+
+impl ToViperSyntax for Program {
+    fn to_viper_syntax(&self) -> String {
+        let mut items = vec![];
+        items.extend(self.domains.iter().map(|domain| domain.to_viper_syntax()));
+        items.extend(self.fields.iter().map(|field| field.to_viper_syntax()));
+        items.extend(self.functions.iter().map(|function| function.to_viper_syntax()));
+        // The function that represents the symbolic read permission amount.
+        items.push(
+            "function read$(): Perm\n  ensures none < result\n  ensures result < write"
+                .to_string(),
+        );
+        items.extend(self.viper_predicates.iter().map(|pred| pred.to_viper_syntax()));
+        items.extend(self.methods.iter().map(|method| method.to_viper_syntax()));
+        items.extend(self.builtin_methods.iter().map(|method| method.to_viper_syntax()));
+        let mut text = items.join("\n\n");
+        text.push('\n');
+        text
+    }
+}
+
+impl ToViperSyntax for Type {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            Type::Int => "Int".to_string(),
+            Type::Bool => "Bool".to_string(),
+            Type::TypedRef(_) => "Ref".to_string(),
+            Type::Domain(ref name) => name.clone(),
+        }
+    }
+}
+
+impl ToViperSyntax for LocalVar {
+    fn to_viper_syntax(&self) -> String {
+        if self.name == "__result" {
+            "result".to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+impl ToViperSyntax for Field {
+    fn to_viper_syntax(&self) -> String {
+        format!("field {}: {}", self.name, self.typ.to_viper_syntax())
+    }
+}
+
+impl ToViperSyntax for PermAmount {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            PermAmount::Write => "write".to_string(),
+            PermAmount::Read => "read$()".to_string(),
+            PermAmount::Remaining => "(write - read$())".to_string(),
+        }
+    }
+}
+
+impl ToViperSyntax for Const {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            Const::Bool(true) => "true".to_string(),
+            Const::Bool(false) => "false".to_string(),
+            Const::Int(x) => x.to_string(),
+            Const::BigInt(ref x) => x.clone(),
+        }
+    }
+}
+
+impl ToViperSyntax for Trigger {
+    fn to_viper_syntax(&self) -> String {
+        format!("{{ {} }}", join(self.elements(), ", "))
+    }
+}
+
+/// The access predicate `acc(P(args), perm)`.
+fn predicate_access(predicate_name: &str, args: &[Expr], perm: &PermAmount) -> String {
+    format!(
+        "acc({}({}), {})",
+        predicate_name,
+        join(args, ", "),
+        perm.to_viper_syntax()
+    )
+}
+
+/// The token that represents a dead borrow, which is added to the left-hand side of a wand.
+fn dead_borrow_token(borrow_id: isize) -> String {
+    format!("acc(DeadBorrowToken$({}), write)", borrow_id)
+}
+
+impl ToViperSyntax for BinOpKind {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            BinOpKind::EqCmp => "==",
+            BinOpKind::NeCmp => "!=",
+            BinOpKind::GtCmp => ">",
+            BinOpKind::GeCmp => ">=",
+            BinOpKind::LtCmp => "<",
+            BinOpKind::LeCmp => "<=",
+            BinOpKind::Add => "+",
+            BinOpKind::Sub => "-",
+            BinOpKind::Mul => "*",
+            BinOpKind::Div => "\\",
+            BinOpKind::Mod => "%",
+            BinOpKind::And => "&&",
+            BinOpKind::Or => "||",
+            BinOpKind::Implies => "==>",
+        }
+        .to_string()
+    }
+}
+
+impl ToViperSyntax for Expr {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            Expr::Local(ref local_var, _) => local_var.to_viper_syntax(),
+            Expr::Variant(ref base, ref field, _) | Expr::Field(ref base, ref field, _) => {
+                format!("{}.{}", base.to_viper_syntax(), field.name)
+            }
+            Expr::AddrOf(..) => unreachable!(),
+            Expr::Const(ref value, _) => value.to_viper_syntax(),
+            Expr::LabelledOld(ref label, ref expr, _) => {
+                format!("old[{}]({})", label, expr.to_viper_syntax())
+            }
+            Expr::MagicWand(ref lhs, ref rhs, maybe_borrow, _) => {
+                let borrow_id = if let Some(borrow) = maybe_borrow {
+                    borrow_id(*borrow) as isize
+                } else {
+                    -1
+                };
+                format!(
+                    "({} && {}) --* ({})",
+                    dead_borrow_token(borrow_id),
+                    lhs.to_viper_syntax(),
+                    rhs.to_viper_syntax()
+                )
+            }
+            Expr::PredicateAccessPredicate(ref predicate_name, ref arg, perm, _) => {
+                predicate_access(predicate_name, &[(**arg).clone()], perm)
+            }
+            Expr::FieldAccessPredicate(ref loc, perm, _) => {
+                format!("acc({}, {})", loc.to_viper_syntax(), perm.to_viper_syntax())
+            }
+            Expr::UnaryOp(op, ref expr, _) => match op {
+                UnaryOpKind::Not => format!("!({})", expr.to_viper_syntax()),
+                UnaryOpKind::Minus => format!("-({})", expr.to_viper_syntax()),
+            },
+            Expr::BinOp(op, ref left, ref right, _) => format!(
+                "({} {} {})",
+                left.to_viper_syntax(),
+                op.to_viper_syntax(),
+                right.to_viper_syntax()
+            ),
+            Expr::Unfolding(ref predicate_name, ref args, ref expr, perm, _, _) => format!(
+                "(unfolding {} in {})",
+                predicate_access(predicate_name, args, perm),
+                expr.to_viper_syntax()
+            ),
+            Expr::Cond(ref guard, ref left, ref right, _) => format!(
+                "({} ? {} : {})",
+                guard.to_viper_syntax(),
+                left.to_viper_syntax(),
+                right.to_viper_syntax()
+            ),
+            Expr::ForAll(ref vars, ref triggers, ref body, _) => format!(
+                "(forall {} :: {} {})",
+                var_decls(vars),
+                join(triggers, " "),
+                body.to_viper_syntax()
+            ),
+            Expr::LetExpr(ref var, ref expr, ref body, _) => format!(
+                "(let {} == ({}) in {})",
+                var.name,
+                expr.to_viper_syntax(),
+                body.to_viper_syntax()
+            ),
+            Expr::FuncApp(ref function_name, ref args, ref formal_args, ref return_type, _) => {
+                format!(
+                    "{}({})",
+                    compute_identifier(function_name, formal_args, return_type),
+                    join(args, ", ")
+                )
+            }
+            Expr::DomainFuncApp(ref function, ref args, _) => {
+                format!("{}({})", function.get_identifier(), join(args, ", "))
+            }
+            Expr::InhaleExhale(ref inhale_expr, ref exhale_expr, _) => format!(
+                "[{}, {}]",
+                inhale_expr.to_viper_syntax(),
+                exhale_expr.to_viper_syntax()
+            ),
+        }
+    }
+}
+
+/// The statements of a `package` statement. Like in `ToViper`, Silicon is helped in showing
+/// that it has access to the needed paths.
+fn stmt_to_viper_syntax_in_package(stmt: &Stmt) -> String {
+    let footprint_asserts = |expr: &Expr, perm| -> Vec<String> {
+        expr.compute_footprint(perm)
+            .into_iter()
+            .map(|access| format!("assert {}", access.to_viper_syntax()))
+            .collect()
+    };
+    match stmt {
+        Stmt::Assign(ref lhs, ref rhs, _) => {
+            let mut stmts = footprint_asserts(rhs, PermAmount::Read);
+            stmts.push(stmt.to_viper_syntax());
+            stmts.join("\n")
+        }
+        Stmt::Exhale(ref expr, _) => {
+            let mut stmts = footprint_asserts(expr, PermAmount::Read);
+            stmts.push(stmt.to_viper_syntax());
+            stmts.join("\n")
+        }
+        Stmt::Fold(_, ref args, ..) => {
+            assert_eq!(args.len(), 1);
+            let place = &args[0];
+            assert!(place.is_place());
+            let mut stmts = footprint_asserts(place, PermAmount::Read);
+            stmts.push(stmt.to_viper_syntax());
+            stmts.join("\n")
+        }
+        Stmt::If(ref guard, ref then_stmts, ref else_stmts) => {
+            let then_stmts: Vec<_> = then_stmts.iter().map(stmt_to_viper_syntax_in_package).collect();
+            let else_stmts: Vec<_> = else_stmts.iter().map(stmt_to_viper_syntax_in_package).collect();
+            if_stmt(guard, &then_stmts, &else_stmts)
+        }
+        _ => stmt.to_viper_syntax(),
+    }
+}
+
+pub(super) fn if_stmt(guard: &Expr, then_stmts: &[String], else_stmts: &[String]) -> String {
+    let mut text = format!("if ({}) {}", guard.to_viper_syntax(), block(then_stmts));
+    if !else_stmts.is_empty() {
+        text.push_str(" else ");
+        text.push_str(&block(else_stmts));
+    }
+    text
+}
+
+impl ToViperSyntax for Stmt {
+    fn to_viper_syntax(&self) -> String {
+        match self {
+            Stmt::Comment(ref text) => comment(text),
+            Stmt::Label(ref label) => format!("label {}", label),
+            Stmt::Inhale(ref expr, _) => format!("inhale {}", expr.to_viper_syntax()),
+            Stmt::Exhale(ref expr, _) => format!("exhale {}", expr.to_viper_syntax()),
+            Stmt::Assert(ref expr, _, _) => format!("assert {}", expr.to_viper_syntax()),
+            Stmt::MethodCall(ref method_name, ref args, ref targets) => {
+                let call = format!("{}({})", method_name, join(args, ", "));
+                if targets.is_empty() {
+                    call
+                } else {
+                    format!("{} := {}", join(targets, ", "), call)
+                }
+            }
+            Stmt::Assign(ref lhs, ref rhs, _) => {
+                format!("{} := {}", lhs.to_viper_syntax(), rhs.to_viper_syntax())
+            }
+            Stmt::Fold(ref predicate_name, ref args, perm, _, _) => {
+                format!("fold {}", predicate_access(predicate_name, args, perm))
+            }
+            Stmt::Unfold(ref predicate_name, ref args, perm, _) => {
+                format!("unfold {}", predicate_access(predicate_name, args, perm))
+            }
+            Stmt::Obtain(..)
+            | Stmt::BeginFrame
+            | Stmt::EndFrame
+            | Stmt::TransferPerm(..)
+            | Stmt::ExpireBorrows(_) => {
+                // Skip
+                comment(&self.to_string())
+            }
+            Stmt::PackageMagicWand(ref wand, ref package_stmts, _, ref vars, _) => {
+                let mut stmts: Vec<_> = vars
+                    .iter()
+                    .map(|var| format!("var {}", var_decls(&[var.clone()])))
+                    .collect();
+                stmts.extend(package_stmts.iter().map(stmt_to_viper_syntax_in_package));
+                format!("package {} {}", wand.to_viper_syntax(), block(&stmts))
+            }
+            Stmt::ApplyMagicWand(ref wand, _) => {
+                let borrow = if let Expr::MagicWand(_, _, Some(borrow), _) = wand {
+                    borrow_id(*borrow) as isize
+                } else {
+                    unreachable!()
+                };
+                format!(
+                    "inhale {}\napply {}",
+                    dead_borrow_token(borrow),
+                    wand.to_viper_syntax()
+                )
+            }
+            Stmt::If(ref guard, ref then_stmts, ref else_stmts) => {
+                let then_stmts: Vec<_> = then_stmts.iter().map(|s| s.to_viper_syntax()).collect();
+                let else_stmts: Vec<_> = else_stmts.iter().map(|s| s.to_viper_syntax()).collect();
+                if_stmt(guard, &then_stmts, &else_stmts)
+            }
+        }
+    }
+}
+
+impl ToViperSyntax for Predicate {
+    fn to_viper_syntax(&self) -> String {
+        let (name, this, body) = match self {
+            Predicate::Struct(p) => (&p.name, &p.this, p.body.clone()),
+            Predicate::Enum(p) => (&p.name, &p.this, Some(p.body())),
+            Predicate::Bodyless(name, this) => (name, this, None),
+        };
+        let header = format!("predicate {}({})", name, var_decls(&[this.clone()]));
+        match body {
+            Some(body) => format!("{} {{\n{}\n}}", header, indent(&body.to_viper_syntax())),
+            None => header,
+        }
+    }
+}
+
+impl ToViperSyntax for BodylessMethod {
+    fn to_viper_syntax(&self) -> String {
+        format!(
+            "method {}({}) returns ({})",
+            self.name,
+            var_decls(&self.formal_args),
+            var_decls(&self.formal_returns)
+        )
+    }
+}
+
+impl ToViperSyntax for Function {
+    fn to_viper_syntax(&self) -> String {
+        let mut text = format!(
+            "function {}({}): {}",
+            self.get_identifier(),
+            var_decls(&self.formal_args),
+            self.return_type.to_viper_syntax()
+        );
+        for pre in &self.pres {
+            text.push_str(&format!("\n  requires {}", pre.to_viper_syntax()));
+        }
+        for post in &self.posts {
+            text.push_str(&format!("\n  ensures {}", post.to_viper_syntax()));
+        }
+        if let Some(ref body) = self.body {
+            text.push_str(&format!("\n{{\n{}\n}}", indent(&body.to_viper_syntax())));
+        }
+        text
+    }
+}
+
+impl ToViperSyntax for Domain {
+    fn to_viper_syntax(&self) -> String {
+        let mut header = format!("domain {}", self.name);
+        if !self.type_vars.is_empty() {
+            let type_vars: Vec<_> = self.type_vars.iter().map(|typ| typ.name()).collect();
+            header.push_str(&format!("[{}]", type_vars.join(", ")));
+        }
+        let mut items: Vec<_> = self.functions.iter().map(|f| f.to_viper_syntax()).collect();
+        items.extend(self.axioms.iter().map(|axiom| axiom.to_viper_syntax()));
+        format!("{} {}", header, block(&items))
+    }
+}
+
+impl ToViperSyntax for DomainFunc {
+    fn to_viper_syntax(&self) -> String {
+        format!(
+            "{}function {}({}): {}",
+            if self.unique { "unique " } else { "" },
+            self.get_identifier(),
+            var_decls(&self.formal_args),
+            self.return_type.to_viper_syntax()
+        )
+    }
+}
+
+impl ToViperSyntax for DomainAxiom {
+    fn to_viper_syntax(&self) -> String {
+        format!(
+            "axiom {} {{\n{}\n}}",
+            self.name,
+            indent(&self.expr.to_viper_syntax())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_syntax() {
+        let x = LocalVar::new("x", Type::Int);
+        let function = Function {
+            name: "inc".to_string(),
+            formal_args: vec![x.clone()],
+            return_type: Type::Int,
+            pres: vec![Expr::le_cmp(0.into(), x.clone().into())],
+            posts: vec![],
+            body: Some(Expr::add(x.into(), 1.into())),
+        };
+        assert_eq!(
+            function.to_viper_syntax(),
+            "function inc__$TY$__$int$$$int$(x: Int): Int\n  requires (0 <= x)\n{\n  (x + 1)\n}"
+        );
+    }
+
+    #[test]
+    fn test_predicate_syntax() {
+        let typ = Type::TypedRef("i32".to_string());
+        let predicate = Predicate::new_primitive_value(
+            typ,
+            Field::new("val_int", Type::Int),
+            None,
+            false,
+        );
+        assert_eq!(
+            predicate.to_viper_syntax(),
+            "predicate i32(self: Ref) {\n  (acc(self.val_int, write) && true)\n}"
+        );
+    }
+}
@@ -34,7 +34,37 @@ pub fn trusted(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
 }
 
+#[proc_macro_attribute]
+pub fn skip(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn verify(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn timeout(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn backend(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn verifier_args(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 #[proc_macro_hack]
 pub fn invariant(_tokens: TokenStream) -> TokenStream {
     (quote! { () }).into()
 }
+
+#[proc_macro]
+pub fn predicate(_tokens: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
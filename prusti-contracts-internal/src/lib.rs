@@ -33,7 +33,37 @@ pub fn trusted(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     prusti_specs::trusted(attr.into(), tokens.into()).into()
 }
 
+#[proc_macro_attribute]
+pub fn skip(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::skip(attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn verify(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::verify(attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn timeout(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::timeout(attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn backend(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::backend(attr.into(), tokens.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn verifier_args(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    prusti_specs::verifier_args(attr.into(), tokens.into()).into()
+}
+
 #[proc_macro_hack]
 pub fn invariant(tokens: TokenStream) -> TokenStream {
     prusti_specs::invariant(tokens.into()).into()
 }
+
+#[proc_macro]
+pub fn predicate(tokens: TokenStream) -> TokenStream {
+    prusti_specs::predicate(tokens.into()).into()
+}
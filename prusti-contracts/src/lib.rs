@@ -22,9 +22,30 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_impl::trusted;
 
+    /// A macro for excluding a function, or all the functions of a module or `impl` block, from
+    /// the verification. The contracts of the excluded functions are still used at call sites.
+    pub use prusti_contracts_impl::skip;
+
+    /// A macro for verifying a function, or all the functions of a module or `impl` block, even
+    /// if they are excluded by the configuration or by an enclosing `#[skip]`.
+    pub use prusti_contracts_impl::verify;
+
+    /// A macro for setting the Z3 timeout (in milliseconds) used to verify a function.
+    pub use prusti_contracts_impl::timeout;
+
+    /// A macro for choosing the Viper backend (`silicon` or `carbon`) that verifies a function.
+    pub use prusti_contracts_impl::backend;
+
+    /// A macro for passing extra arguments to the verifier of a function.
+    pub use prusti_contracts_impl::verifier_args;
+
     /// A macro for writing a loop invariant.
     #[proc_macro_hack]
     pub use prusti_contracts_impl::invariant;
+
+    /// A macro for defining a predicate, i.e. a specification-only pure function whose body is
+    /// an assertion. Predicates cannot be called from executable code.
+    pub use prusti_contracts_impl::predicate;
 }
 
 #[cfg(feature = "prusti")]
@@ -49,9 +70,30 @@ mod private {
     /// A macro for marking a function as trusted.
     pub use prusti_contracts_internal::trusted;
 
+    /// A macro for excluding a function, or all the functions of a module or `impl` block, from
+    /// the verification. The contracts of the excluded functions are still used at call sites.
+    pub use prusti_contracts_internal::skip;
+
+    /// A macro for verifying a function, or all the functions of a module or `impl` block, even
+    /// if they are excluded by the configuration or by an enclosing `#[skip]`.
+    pub use prusti_contracts_internal::verify;
+
+    /// A macro for setting the Z3 timeout (in milliseconds) used to verify a function.
+    pub use prusti_contracts_internal::timeout;
+
+    /// A macro for choosing the Viper backend (`silicon` or `carbon`) that verifies a function.
+    pub use prusti_contracts_internal::backend;
+
+    /// A macro for passing extra arguments to the verifier of a function.
+    pub use prusti_contracts_internal::verifier_args;
+
     /// A macro for writing a loop invariant.
     #[proc_macro_hack]
     pub use prusti_contracts_internal::invariant;
+
+    /// A macro for defining a predicate, i.e. a specification-only pure function whose body is
+    /// an assertion. Predicates cannot be called from executable code.
+    pub use prusti_contracts_internal::predicate;
 }
 
 
@@ -67,4 +109,19 @@ pub fn old<T>(arg: T) -> T {
     arg
 }
 
+/// This function is used to state in a specification that the current
+/// function holds the (full) permission to access the target of a raw
+/// pointer, that is `*ptr`.
+pub fn acc<T: ?Sized>(_ptr: *const T) -> bool {
+    true
+}
+
+/// This function is used to state in a specification that the current
+/// function owns the target of a raw pointer, for example because the
+/// pointer has been obtained with `Box::into_raw`. Ownership includes the
+/// permission to access `*ptr`, which is what is checked by Prusti.
+pub fn own<T: ?Sized>(_ptr: *const T) -> bool {
+    true
+}
+
 pub use private::*;
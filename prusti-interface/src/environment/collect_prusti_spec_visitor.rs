@@ -9,10 +9,11 @@ use crate::environment::Environment;
 use rustc_hir as hir;
 use rustc_hir::def_id::DefId;
 use rustc_hir::itemlikevisit::ItemLikeVisitor;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{DefIdTree, TyCtxt};
 use std::collections::HashSet;
 use std::iter::FromIterator;
 use log::{trace, debug};
+use regex::Regex;
 use rustc_ast::ast;
 
 /// Check whether the `attrs` contain `prusti::$name` attribute.
@@ -36,12 +37,80 @@ pub(crate) fn contains_name(attrs: &[ast::Attribute], name: &str) -> bool {
     false
 }
 
+/// Returns the arguments of the `prusti::$name(...)` attribute, if `attrs` contain it. Only the
+/// literals and the identifiers are returned, as strings; string literals are returned without
+/// quotes.
+pub(crate) fn read_args(attrs: &[ast::Attribute], name: &str) -> Option<Vec<String>> {
+    use rustc_ast::token::{Lit, Token, TokenKind};
+    use rustc_ast::tokenstream::TokenTree;
+    for attr in attrs {
+        if let ast::AttrKind::Normal(ast::AttrItem {
+            path: ast::Path { span: _, segments },
+            args,
+        }) = &attr.kind {
+            if segments.len() == 2 && segments[0].ident.name.with(
+                |attr_name| attr_name == "prusti"
+            ) && segments[1].ident.name.with(|attr_name| attr_name == name) {
+                let values = match args {
+                    ast::MacArgs::Delimited(_, _, tokens) => tokens
+                        .trees()
+                        .filter_map(|tree| match tree {
+                            TokenTree::Token(Token {
+                                kind: TokenKind::Literal(Lit { symbol, .. }),
+                                ..
+                            }) | TokenTree::Token(Token {
+                                kind: TokenKind::Ident(symbol, _),
+                                ..
+                            }) => Some(symbol.to_string()),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => vec![],
+                };
+                return Some(values);
+            }
+        }
+    }
+    None
+}
+
+/// Compile a pattern of def paths (see `config::verification_include_patterns`) to a regular
+/// expression.
+fn compile_def_path_pattern(pattern: &str) -> Regex {
+    let source = if let Some(regex) = pattern.strip_prefix("regex:") {
+        format!("^(?:{})$", regex)
+    } else {
+        let mut source = "^".to_string();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '*' {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    source.push_str(".*");
+                } else {
+                    source.push_str("[^:]*");
+                }
+            } else {
+                source.push_str(&regex::escape(&c.to_string()));
+            }
+        }
+        source.push('$');
+        source
+    };
+    Regex::new(&source).unwrap_or_else(|err| {
+        panic!("invalid pattern of def paths '{}': {}", pattern, err)
+    })
+}
+
 pub struct CollectPrustiSpecVisitor<'a, 'tcx: 'a> {
     env: &'a Environment<'tcx>,
     tcx: TyCtxt<'tcx>,
     result: Vec<DefId>,
     use_whitelist: bool,
     whitelist: HashSet<String>,
+    include_patterns: Vec<Regex>,
+    exclude_patterns: Vec<Regex>,
+    verify_only_specified: bool,
 }
 
 impl<'a, 'tcx> CollectPrustiSpecVisitor<'a, 'tcx> {
@@ -52,11 +121,58 @@ impl<'a, 'tcx> CollectPrustiSpecVisitor<'a, 'tcx> {
             result: Vec::new(),
             use_whitelist: config::enable_whitelist(),
             whitelist: HashSet::from_iter(config::verification_whitelist()),
+            include_patterns: config::verification_include_patterns()
+                .iter()
+                .map(|pattern| compile_def_path_pattern(pattern))
+                .collect(),
+            exclude_patterns: config::verification_exclude_patterns()
+                .iter()
+                .map(|pattern| compile_def_path_pattern(pattern))
+                .collect(),
+            verify_only_specified: config::verify_only_specified(),
         }
     }
     pub fn get_annotated_procedures(self) -> Vec<DefId> {
         self.result
     }
+
+    /// Returns whether the procedure has a precondition, a postcondition, a pledge, or is pure.
+    fn has_contract(&self, def_id: DefId) -> bool {
+        let attrs = self.tcx.get_attrs(def_id);
+        ["pre_spec_id_ref", "post_spec_id_ref", "pledge_spec_id_ref", "pure"]
+            .iter()
+            .any(|name| contains_name(attrs, name))
+    }
+
+    /// Returns whether the procedure should be verified. The `#[prusti::verify]` and
+    /// `#[prusti::skip]` attributes take precedence over the configuration.
+    fn is_selected(&self, def_id: DefId, item_def_path: &str) -> bool {
+        if let Some(selected) = self.env.get_selection_attribute(def_id) {
+            return selected;
+        }
+        if self.use_whitelist && !self.whitelist.contains(item_def_path) {
+            return false;
+        }
+        if !self.include_patterns.is_empty()
+            && !self.include_patterns.iter().any(|regex| regex.is_match(item_def_path))
+        {
+            return false;
+        }
+        if self.exclude_patterns.iter().any(|regex| regex.is_match(item_def_path)) {
+            return false;
+        }
+        !self.verify_only_specified || self.has_contract(def_id)
+    }
+
+    fn add_procedure(&mut self, def_id: DefId) {
+        let item_def_path = self.env.get_item_def_path(def_id);
+        if self.is_selected(def_id, &item_def_path) {
+            trace!("Add {} to result", item_def_path);
+            self.result.push(def_id);
+        } else {
+            debug!("Skip the verification of {}", item_def_path);
+        }
+    }
 }
 
 impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
@@ -67,9 +183,7 @@ impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
         }
         if let hir::ItemKind::Fn(..) = item.kind {
             let def_id = self.tcx.hir().local_def_id(item.hir_id).to_def_id();
-            let item_def_path = self.env.get_item_def_path(def_id);
-            trace!("Add {} to result", item_def_path);
-            self.result.push(def_id);
+            self.add_procedure(def_id);
         }
     }
 
@@ -91,9 +205,7 @@ impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
             return;
         }
         let def_id = self.tcx.hir().local_def_id(trait_item.hir_id).to_def_id();
-        let item_def_path = self.env.get_item_def_path(def_id);
-        trace!("Add {} to result", item_def_path);
-        self.result.push(def_id);
+        self.add_procedure(def_id);
     }
 
     fn visit_impl_item(&mut self, impl_item: &hir::ImplItem) {
@@ -110,8 +222,6 @@ impl<'a, 'tcx> ItemLikeVisitor<'tcx> for CollectPrustiSpecVisitor<'a, 'tcx> {
         }
 
         let def_id = self.tcx.hir().local_def_id(impl_item.hir_id).to_def_id();
-        let item_def_path = self.env.get_item_def_path(def_id);
-        trace!("Add {} to result", item_def_path);
-        self.result.push(def_id);
+        self.add_procedure(def_id);
     }
 }
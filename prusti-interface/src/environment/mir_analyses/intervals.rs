@@ -0,0 +1,1250 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! This module provides an interval analysis of the integer local variables of a procedure,
+//! which is used to infer simple loop invariants.
+//!
+//! The abstract state maps each tracked local variable to an interval of its possible values.
+//! Additionally, it keeps difference constraints `x - y <= c` between pairs of tracked local
+//! variables, which capture facts such as `i <= n` or `x == y` (as `x - y <= 0` and
+//! `y - x <= 0`). The values are mathematical integers, as in the encoding of Prusti, so that
+//! the inferred facts can be proven by the verifier.
+//!
+//! The tracked local variables are the local variables of integer type whose address is never
+//! taken outside of the specification blocks, and the tuples that store the result of a checked
+//! binary operation (whose first field is then tracked).
+
+use super::common::{self, WorkItem};
+use crate::environment::Procedure;
+use prusti_common::config;
+use rustc_middle::mir;
+use rustc_middle::ty;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::convert::TryFrom;
+use log::trace;
+
+/// Which bounds of the integer types can be assumed, because they are encoded by Prusti.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerBounds {
+    /// No bounds are encoded: the integers are unbounded.
+    None,
+    /// The values of unsigned integers are non-negative.
+    Unsigned,
+    /// The values of all integers are within the range of their type.
+    All,
+}
+
+/// An interval of integers. A missing bound means that the interval is unbounded on that side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval {
+    pub lower: Option<i128>,
+    pub upper: Option<i128>,
+}
+
+fn add_bounds(a: Option<i128>, b: Option<i128>) -> Option<i128> {
+    a?.checked_add(b?)
+}
+
+impl Interval {
+    pub fn top() -> Self {
+        Interval {
+            lower: None,
+            upper: None,
+        }
+    }
+
+    pub fn constant(value: i128) -> Self {
+        Interval {
+            lower: Some(value),
+            upper: Some(value),
+        }
+    }
+
+    pub fn is_top(&self) -> bool {
+        self.lower.is_none() && self.upper.is_none()
+    }
+
+    fn is_empty(&self) -> bool {
+        match (self.lower, self.upper) {
+            (Some(lower), Some(upper)) => lower > upper,
+            _ => false,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Interval {
+            lower: match (self.lower, other.lower) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                _ => None,
+            },
+            upper: match (self.upper, other.upper) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => None,
+            },
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Interval {
+            lower: match (self.lower, other.lower) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            },
+            upper: match (self.upper, other.upper) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    /// Keep only the bounds of `self` that are not exceeded by `next`.
+    fn widen(&self, next: &Self) -> Self {
+        Interval {
+            lower: match (self.lower, next.lower) {
+                (Some(a), Some(b)) if b >= a => Some(a),
+                _ => None,
+            },
+            upper: match (self.upper, next.upper) {
+                (Some(a), Some(b)) if b <= a => Some(a),
+                _ => None,
+            },
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Interval {
+            lower: add_bounds(self.lower, other.lower),
+            upper: add_bounds(self.upper, other.upper),
+        }
+    }
+
+    fn neg(&self) -> Self {
+        Interval {
+            lower: self.upper.and_then(i128::checked_neg),
+            upper: self.lower.and_then(i128::checked_neg),
+        }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        if let (Some(a), Some(b), Some(c), Some(d)) =
+            (self.lower, self.upper, other.lower, other.upper)
+        {
+            let products = [
+                a.checked_mul(c),
+                a.checked_mul(d),
+                b.checked_mul(c),
+                b.checked_mul(d),
+            ];
+            if products.iter().all(|product| product.is_some()) {
+                let products: Vec<_> = products.iter().map(|product| product.unwrap()).collect();
+                return Interval {
+                    lower: products.iter().min().cloned(),
+                    upper: products.iter().max().cloned(),
+                };
+            }
+        }
+        Interval::top()
+    }
+}
+
+/// A fact about the tracked local variables that holds at a program point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fact {
+    /// `x == value`
+    Value(mir::Local, i128),
+    /// `x >= lower`
+    LowerBound(mir::Local, i128),
+    /// `x <= upper`
+    UpperBound(mir::Local, i128),
+    /// `x == y`
+    Equality(mir::Local, mir::Local),
+    /// `x - y <= c`
+    Difference(mir::Local, mir::Local, i128),
+}
+
+impl Fact {
+    /// The local variables that the fact talks about.
+    pub fn locals(&self) -> Vec<mir::Local> {
+        match *self {
+            Fact::Value(x, _) | Fact::LowerBound(x, _) | Fact::UpperBound(x, _) => vec![x],
+            Fact::Equality(x, y) | Fact::Difference(x, y, _) => vec![x, y],
+        }
+    }
+
+    /// A human-readable representation of the fact, in which the local variables are named
+    /// with `name`.
+    pub fn to_string_with<F: Fn(mir::Local) -> String>(&self, name: F) -> String {
+        match *self {
+            Fact::Value(x, value) => format!("{} == {}", name(x), value),
+            Fact::LowerBound(x, lower) => format!("{} >= {}", name(x), lower),
+            Fact::UpperBound(x, upper) => format!("{} <= {}", name(x), upper),
+            Fact::Equality(x, y) => format!("{} == {}", name(x), name(y)),
+            Fact::Difference(x, y, 0) => format!("{} <= {}", name(x), name(y)),
+            Fact::Difference(x, y, -1) => format!("{} < {}", name(x), name(y)),
+            Fact::Difference(x, y, c) if c > 0 => format!("{} <= {} + {}", name(x), name(y), c),
+            Fact::Difference(x, y, c) => format!("{} <= {} - {}", name(x), name(y), -c),
+        }
+    }
+}
+
+/// One side of a comparison.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Term {
+    /// Any of the local variables, which are known to be equal.
+    Locals(BTreeSet<mir::Local>),
+    Constant(i128),
+}
+
+/// A comparison whose result is stored in a boolean local variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Condition {
+    /// One of `Lt`, `Le`, `Gt`, `Ge`, `Eq` and `Ne`.
+    op: mir::BinOp,
+    left: Term,
+    right: Term,
+}
+
+impl Condition {
+    fn negate(&self) -> Self {
+        let op = match self.op {
+            mir::BinOp::Lt => mir::BinOp::Ge,
+            mir::BinOp::Le => mir::BinOp::Gt,
+            mir::BinOp::Gt => mir::BinOp::Le,
+            mir::BinOp::Ge => mir::BinOp::Lt,
+            mir::BinOp::Eq => mir::BinOp::Ne,
+            mir::BinOp::Ne => mir::BinOp::Eq,
+            op => unreachable!("{:?}", op),
+        };
+        Condition {
+            op,
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+/// The abstract state of the analysis at a program point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalState {
+    /// Whether the program point might be reachable. If not, the other fields are empty.
+    reachable: bool,
+    /// The intervals of the tracked local variables. A missing variable is unbounded.
+    intervals: BTreeMap<mir::Local, Interval>,
+    /// The difference constraints: `(x, y) -> c` means `x - y <= c`.
+    differences: BTreeMap<(mir::Local, mir::Local), i128>,
+    /// The comparisons whose results are stored in boolean local variables.
+    conditions: BTreeMap<mir::Local, Condition>,
+}
+
+impl IntervalState {
+    fn unreachable() -> Self {
+        IntervalState {
+            reachable: false,
+            intervals: BTreeMap::new(),
+            differences: BTreeMap::new(),
+            conditions: BTreeMap::new(),
+        }
+    }
+
+    fn new() -> Self {
+        IntervalState {
+            reachable: true,
+            ..IntervalState::unreachable()
+        }
+    }
+
+    pub fn is_reachable(&self) -> bool {
+        self.reachable
+    }
+
+    /// The interval of the values of the local variable.
+    pub fn interval(&self, local: mir::Local) -> Interval {
+        self.intervals
+            .get(&local)
+            .cloned()
+            .unwrap_or_else(Interval::top)
+    }
+
+    fn set_interval(&mut self, local: mir::Local, interval: Interval) {
+        if interval.is_empty() {
+            *self = IntervalState::unreachable();
+        } else if interval.is_top() {
+            self.intervals.remove(&local);
+        } else {
+            self.intervals.insert(local, interval);
+        }
+    }
+
+    fn refine_interval(&mut self, local: mir::Local, interval: Interval) {
+        let refined = self.interval(local).meet(&interval);
+        self.set_interval(local, refined);
+    }
+
+    /// Record `x - y <= c`, if it is stronger than what is already known.
+    fn add_difference(&mut self, x: mir::Local, y: mir::Local, c: i128) {
+        if !self.reachable || x == y {
+            return;
+        }
+        let entry = self.differences.entry((x, y)).or_insert(c);
+        *entry = (*entry).min(c);
+        if let Some(&d) = self.differences.get(&(y, x)) {
+            // `x - y <= c` and `y - x <= d` are contradictory if `c + d < 0`.
+            if c.checked_add(d).map_or(false, |sum| sum < 0) {
+                *self = IntervalState::unreachable();
+            }
+        }
+    }
+
+    /// The bound of `x - y` that follows from the intervals of `x` and `y`.
+    fn interval_difference_bound(&self, x: mir::Local, y: mir::Local) -> Option<i128> {
+        add_bounds(
+            self.interval(x).upper,
+            self.interval(y).lower.and_then(i128::checked_neg),
+        )
+    }
+
+    /// The best known bound of `x - y`.
+    fn difference_bound(&self, x: mir::Local, y: mir::Local) -> Option<i128> {
+        let explicit = self.differences.get(&(x, y)).cloned();
+        match (explicit, self.interval_difference_bound(x, y)) {
+            (Some(c), Some(d)) => Some(c.min(d)),
+            (c, d) => c.or(d),
+        }
+    }
+
+    /// Whether `x - y <= c` follows from the intervals of `x` and `y`.
+    fn is_implied_by_intervals(&self, x: mir::Local, y: mir::Local, c: i128) -> bool {
+        self.interval_difference_bound(x, y).map_or(false, |d| d <= c)
+    }
+
+    /// The pairs of local variables that are constrained in `self` or in `other`.
+    fn constrained_pairs(&self, other: &Self) -> BTreeSet<(mir::Local, mir::Local)> {
+        self.differences
+            .keys()
+            .chain(other.differences.keys())
+            .cloned()
+            .collect()
+    }
+
+    /// The local variables that are known to be equal to `local`, including itself.
+    fn equal_locals(&self, local: mir::Local) -> BTreeSet<mir::Local> {
+        let mut locals: BTreeSet<_> = self
+            .differences
+            .iter()
+            .filter(|(&(x, y), &c)| {
+                x == local && c <= 0 && self.differences.get(&(y, x)).map_or(false, |&d| d <= 0)
+            })
+            .map(|(&(_, y), _)| y)
+            .collect();
+        locals.insert(local);
+        locals
+    }
+
+    /// Remove `local` from the comparisons.
+    fn forget_in_conditions(&mut self, local: mir::Local) {
+        self.conditions.remove(&local);
+        for condition in self.conditions.values_mut() {
+            for term in &mut [&mut condition.left, &mut condition.right] {
+                if let Term::Locals(locals) = term {
+                    locals.remove(&local);
+                }
+            }
+        }
+        self.conditions.retain(|_, condition| {
+            condition.left != Term::Locals(BTreeSet::new())
+                && condition.right != Term::Locals(BTreeSet::new())
+        });
+    }
+
+    /// Forget everything that is known about `local`.
+    fn forget(&mut self, local: mir::Local) {
+        self.intervals.remove(&local);
+        self.differences.retain(|&(x, y), _| x != local && y != local);
+        self.forget_in_conditions(local);
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if !self.reachable {
+            return other.clone();
+        }
+        if !other.reachable {
+            return self.clone();
+        }
+        let mut result = IntervalState::new();
+        for (&local, interval) in &self.intervals {
+            if let Some(other_interval) = other.intervals.get(&local) {
+                result.set_interval(local, interval.join(other_interval));
+            }
+        }
+        // A constraint that is missing on one side might still follow from the intervals of
+        // that side, e.g. `i <= n` from `i == 0` and `n >= 0` on entry of a loop. Similarly,
+        // the variables that are bounded on both sides might be related, e.g. `i == j` from
+        // `i == 0 && j == 0` and `i == 1 && j == 1`.
+        let mut pairs = self.constrained_pairs(other);
+        let bounded: Vec<_> = self
+            .intervals
+            .keys()
+            .filter(|local| other.intervals.contains_key(local))
+            .cloned()
+            .collect();
+        for &x in &bounded {
+            for &y in &bounded {
+                if x != y {
+                    pairs.insert((x, y));
+                }
+            }
+        }
+        for (x, y) in pairs {
+            let bounds = (self.difference_bound(x, y), other.difference_bound(x, y));
+            if let (Some(c), Some(d)) = bounds {
+                let c = c.max(d);
+                if !result.is_implied_by_intervals(x, y, c) {
+                    result.differences.insert((x, y), c);
+                }
+            }
+        }
+        for (&local, condition) in &self.conditions {
+            if other.conditions.get(&local) == Some(condition) {
+                result.conditions.insert(local, condition.clone());
+            }
+        }
+        result
+    }
+
+    /// Keep only the facts of `self` that are still satisfied by `next`, which ensures that the
+    /// analysis of loops terminates.
+    fn widen(&self, next: &Self) -> Self {
+        if !self.reachable {
+            return next.clone();
+        }
+        if !next.reachable {
+            return self.clone();
+        }
+        let mut result = IntervalState::new();
+        for (&local, interval) in &self.intervals {
+            result.set_interval(local, interval.widen(&next.interval(local)));
+        }
+        for (x, y) in self.constrained_pairs(next) {
+            let bounds = (self.difference_bound(x, y), next.difference_bound(x, y));
+            if let (Some(c), Some(d)) = bounds {
+                if d <= c {
+                    result.differences.insert((x, y), c);
+                }
+            }
+        }
+        for (&local, condition) in &self.conditions {
+            if next.conditions.get(&local) == Some(condition) {
+                result.conditions.insert(local, condition.clone());
+            }
+        }
+        result
+    }
+
+    /// Assume that the condition holds.
+    fn assume(&mut self, condition: &Condition) {
+        let (op, left, right) = match condition.op {
+            mir::BinOp::Gt => (mir::BinOp::Lt, &condition.right, &condition.left),
+            mir::BinOp::Ge => (mir::BinOp::Le, &condition.right, &condition.left),
+            op => (op, &condition.left, &condition.right),
+        };
+        match op {
+            mir::BinOp::Lt => self.assume_difference(left, right, -1),
+            mir::BinOp::Le => self.assume_difference(left, right, 0),
+            mir::BinOp::Eq => {
+                self.assume_difference(left, right, 0);
+                self.assume_difference(right, left, 0);
+            }
+            _ => {}
+        }
+    }
+
+    /// Assume `left - right <= c`.
+    fn assume_difference(&mut self, left: &Term, right: &Term, c: i128) {
+        match (left, right) {
+            (Term::Locals(xs), Term::Locals(ys)) => {
+                for &x in xs {
+                    for &y in ys {
+                        let x_upper = add_bounds(self.interval(y).upper, Some(c));
+                        let y_lower = add_bounds(self.interval(x).lower, c.checked_neg());
+                        self.refine_interval(x, Interval { lower: None, upper: x_upper });
+                        self.refine_interval(y, Interval { lower: y_lower, upper: None });
+                        self.add_difference(x, y, c);
+                    }
+                }
+            }
+            (Term::Locals(xs), &Term::Constant(value)) => {
+                for &x in xs {
+                    let upper = value.checked_add(c);
+                    self.refine_interval(x, Interval { lower: None, upper });
+                }
+            }
+            (&Term::Constant(value), Term::Locals(ys)) => {
+                for &y in ys {
+                    let lower = c.checked_neg().and_then(|d| value.checked_add(d));
+                    self.refine_interval(y, Interval { lower, upper: None });
+                }
+            }
+            (&Term::Constant(a), &Term::Constant(b)) => {
+                if a.checked_sub(b).map_or(false, |d| d > c) {
+                    *self = IntervalState::unreachable();
+                }
+            }
+        }
+    }
+
+    /// The facts that hold in this state, without the ones that follow from others.
+    pub fn facts(&self) -> Vec<Fact> {
+        let mut facts = vec![];
+        if !self.reachable {
+            return facts;
+        }
+        for (&local, interval) in &self.intervals {
+            match (interval.lower, interval.upper) {
+                (Some(lower), Some(upper)) if lower == upper => {
+                    facts.push(Fact::Value(local, lower));
+                }
+                (lower, upper) => {
+                    if let Some(lower) = lower {
+                        facts.push(Fact::LowerBound(local, lower));
+                    }
+                    if let Some(upper) = upper {
+                        facts.push(Fact::UpperBound(local, upper));
+                    }
+                }
+            }
+        }
+        for (&(x, y), &c) in &self.differences {
+            if self.is_implied_by_intervals(x, y, c) {
+                continue;
+            }
+            if c == 0 && self.differences.get(&(y, x)) == Some(&0) {
+                if x < y {
+                    facts.push(Fact::Equality(x, y));
+                }
+            } else {
+                facts.push(Fact::Difference(x, y, c));
+            }
+        }
+        facts
+    }
+}
+
+/// The value of an expression, relative to a tracked local variable if possible.
+enum Value {
+    /// `y + k` for some `k` in the interval.
+    Offset(mir::Local, Interval),
+    Interval(Interval),
+}
+
+/// The result of the interval analysis.
+pub type IntervalAnalysisResult = common::AnalysisResult<IntervalState>;
+
+/// Finds the intervals of and the relations between integer local variables at each MIR
+/// location.
+struct IntervalAnalysis<'a, 'tcx: 'a> {
+    result: IntervalAnalysisResult,
+    /// Work queue.
+    queue: Vec<WorkItem>,
+    procedure: &'a Procedure<'a, 'tcx>,
+    mir: &'a mir::Body<'tcx>,
+    bounds: IntegerBounds,
+    /// The local variables of integer type whose address is never taken.
+    integer_locals: HashSet<mir::Local>,
+    /// The local variables that store only the results of checked binary operations.
+    checked_locals: HashSet<mir::Local>,
+}
+
+impl<'a, 'tcx: 'a> IntervalAnalysis<'a, 'tcx> {
+    fn new(procedure: &'a Procedure<'a, 'tcx>, bounds: IntegerBounds) -> Self {
+        Self {
+            result: IntervalAnalysisResult::new(),
+            queue: Vec::new(),
+            procedure,
+            mir: procedure.get_mir(),
+            bounds,
+            integer_locals: HashSet::new(),
+            checked_locals: HashSet::new(),
+        }
+    }
+
+    /// Find the local variables that can be tracked.
+    fn collect_tracked_locals(&mut self) {
+        let mut borrowed = HashSet::new();
+        let mut assigned_otherwise = HashSet::new();
+        let mut checked_candidates = HashSet::new();
+        for (bb, data) in self.mir.basic_blocks().iter_enumerated() {
+            for statement in &data.statements {
+                if let mir::StatementKind::Assign(box (ref target, ref rvalue)) = statement.kind {
+                    match rvalue {
+                        // The specifications take references to the variables they talk
+                        // about, but are not executed.
+                        mir::Rvalue::Ref(_, _, place) | mir::Rvalue::AddressOf(_, place) => {
+                            if !self.procedure.is_spec_block(bb) {
+                                borrowed.insert(place.local);
+                            }
+                        }
+                        _ => {}
+                    }
+                    match (target.as_local(), rvalue) {
+                        (Some(local), mir::Rvalue::CheckedBinaryOp(..)) => {
+                            checked_candidates.insert(local);
+                        }
+                        _ => {
+                            assigned_otherwise.insert(target.local);
+                        }
+                    }
+                }
+            }
+            match data.terminator().kind {
+                mir::TerminatorKind::Call {
+                    destination: Some((ref place, _)),
+                    ..
+                }
+                | mir::TerminatorKind::DropAndReplace { ref place, .. } => {
+                    assigned_otherwise.insert(place.local);
+                }
+                _ => {}
+            }
+        }
+        for (local, local_decl) in self.mir.local_decls.iter_enumerated() {
+            if local_decl.ty.is_integral() && !borrowed.contains(&local) {
+                self.integer_locals.insert(local);
+            }
+        }
+        self.checked_locals = checked_candidates
+            .into_iter()
+            .filter(|local| !borrowed.contains(local) && !assigned_otherwise.contains(local))
+            .collect();
+    }
+
+    fn is_tracked(&self, local: mir::Local) -> bool {
+        self.integer_locals.contains(&local) || self.checked_locals.contains(&local)
+    }
+
+    /// The values of the type that can be assumed.
+    fn type_bounds(&self, local: mir::Local) -> Interval {
+        let ty = self.mir.local_decls[local].ty;
+        if !ty.is_integral() {
+            return Interval::top();
+        }
+        let bits = self.type_bits(ty);
+        match (self.bounds, ty.is_signed()) {
+            (IntegerBounds::None, _) | (IntegerBounds::Unsigned, true) => Interval::top(),
+            (IntegerBounds::Unsigned, false) => Interval {
+                lower: Some(0),
+                upper: None,
+            },
+            (IntegerBounds::All, true) => {
+                let max = i128::MAX >> (128 - bits);
+                Interval {
+                    lower: Some(-max - 1),
+                    upper: Some(max),
+                }
+            }
+            (IntegerBounds::All, false) => Interval {
+                lower: Some(0),
+                upper: if bits < 128 {
+                    Some(i128::MAX >> (127 - bits))
+                } else {
+                    None
+                },
+            },
+        }
+    }
+
+    /// Initialize all states to unreachable and the arguments to the bounds of their types.
+    fn initialize(&mut self) {
+        self.collect_tracked_locals();
+        for bb in self.mir.basic_blocks().indices() {
+            self.result
+                .before_block
+                .insert(bb, IntervalState::unreachable());
+            let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+            for statement_index in 0..statements.len() + 1 {
+                let location = mir::Location {
+                    block: bb,
+                    statement_index: statement_index,
+                };
+                self.result
+                    .after_statement
+                    .insert(location, IntervalState::unreachable());
+            }
+        }
+        self.result
+            .before_block
+            .insert(mir::START_BLOCK, self.initial_state());
+    }
+
+    fn initial_state(&self) -> IntervalState {
+        let mut state = IntervalState::new();
+        for arg in self.mir.args_iter() {
+            if self.is_tracked(arg) {
+                state.set_interval(arg, self.type_bounds(arg));
+            }
+        }
+        state
+    }
+
+    /// Add all statements to the work queue.
+    fn propagate_work_queue(&mut self) {
+        for bb in self.mir.basic_blocks().indices() {
+            let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+            self.queue.push(WorkItem::MergeEffects(bb));
+            for statement_index in 0..statements.len() + 1 {
+                let location = mir::Location {
+                    block: bb,
+                    statement_index: statement_index,
+                };
+                if statement_index != statements.len() {
+                    self.queue.push(WorkItem::ApplyStatementEffects(location));
+                } else {
+                    self.queue.push(WorkItem::ApplyTerminatorEffects(bb));
+                }
+            }
+        }
+        self.queue.reverse();
+    }
+
+    /// Run the analysis up to a fix-point.
+    fn run(&mut self) {
+        trace!("[enter] run");
+        let mut counter = 0; // For debugging.
+        while let Some(work_item) = self.queue.pop() {
+            assert!(
+                counter <= 1000000,
+                "Interval analysis does not converge."
+            );
+            match work_item {
+                WorkItem::ApplyStatementEffects(location) => {
+                    self.apply_statement_effects(location);
+                }
+                WorkItem::ApplyTerminatorEffects(bb) => {
+                    self.apply_terminator_effects(bb);
+                }
+                WorkItem::MergeEffects(bb) => {
+                    self.merge_effects(bb);
+                }
+            }
+            counter += 1;
+        }
+    }
+
+    /// The value of a constant of integer type.
+    fn constant_value(&self, constant: &mir::Constant<'tcx>) -> Option<i128> {
+        let ty = constant.literal.ty;
+        if !ty.is_integral() {
+            return None;
+        }
+        match constant.literal.val {
+            ty::ConstKind::Value(mir::interpret::ConstValue::Scalar(
+                mir::interpret::Scalar::Raw { data, size },
+            )) => {
+                if ty.is_signed() {
+                    let shift = 128 - u32::from(size) * 8;
+                    Some(((data as i128) << shift) >> shift)
+                } else {
+                    i128::try_from(data).ok()
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The tracked local variable whose value the place denotes.
+    fn tracked_local(&self, place: &mir::Place<'tcx>) -> Option<mir::Local> {
+        if let Some(local) = place.as_local() {
+            if self.integer_locals.contains(&local) {
+                return Some(local);
+            }
+        }
+        // The result of a checked binary operation is the first field of the tuple.
+        if self.checked_locals.contains(&place.local) {
+            if let [mir::ProjectionElem::Field(field, _)] = &place.projection[..] {
+                if field.index() == 0 {
+                    return Some(place.local);
+                }
+            }
+        }
+        None
+    }
+
+    fn evaluate_operand(&self, operand: &mir::Operand<'tcx>) -> Value {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                match self.tracked_local(place) {
+                    Some(local) => Value::Offset(local, Interval::constant(0)),
+                    None => Value::Interval(Interval::top()),
+                }
+            }
+            mir::Operand::Constant(constant) => match self.constant_value(constant) {
+                Some(value) => Value::Interval(Interval::constant(value)),
+                None => Value::Interval(Interval::top()),
+            },
+        }
+    }
+
+    fn evaluate_rvalue(
+        &self,
+        state: &IntervalState,
+        target: mir::Local,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) -> Value {
+        match rvalue {
+            mir::Rvalue::Use(operand) => self.evaluate_operand(operand),
+            mir::Rvalue::BinaryOp(op, left, right)
+            | mir::Rvalue::CheckedBinaryOp(op, left, right) => {
+                let left = self.evaluate_operand(left);
+                let right = self.evaluate_operand(right);
+                match (op, left, right) {
+                    (mir::BinOp::Add, Value::Offset(local, offset), Value::Interval(interval))
+                    | (mir::BinOp::Add, Value::Interval(interval), Value::Offset(local, offset)) =>
+                    {
+                        Value::Offset(local, offset.add(&interval))
+                    }
+                    (mir::BinOp::Sub, Value::Offset(local, offset), Value::Interval(interval)) => {
+                        Value::Offset(local, offset.sub(&interval))
+                    }
+                    (op, left, right) => {
+                        let left = left.resolve(state);
+                        let right = right.resolve(state);
+                        Value::Interval(match op {
+                            mir::BinOp::Add => left.add(&right),
+                            mir::BinOp::Sub => left.sub(&right),
+                            mir::BinOp::Mul => left.mul(&right),
+                            _ => self.type_bounds(target),
+                        })
+                    }
+                }
+            }
+            mir::Rvalue::UnaryOp(mir::UnOp::Neg, operand) => {
+                Value::Interval(self.evaluate_operand(operand).resolve(state).neg())
+            }
+            _ => Value::Interval(self.type_bounds(target)),
+        }
+    }
+
+    /// The comparison of the rvalue, if it is a comparison of integers.
+    fn evaluate_condition(
+        &self,
+        state: &IntervalState,
+        rvalue: &mir::Rvalue<'tcx>,
+    ) -> Option<Condition> {
+        match rvalue {
+            mir::Rvalue::BinaryOp(op, left, right) => match op {
+                mir::BinOp::Lt
+                | mir::BinOp::Le
+                | mir::BinOp::Gt
+                | mir::BinOp::Ge
+                | mir::BinOp::Eq
+                | mir::BinOp::Ne => Some(Condition {
+                    op: *op,
+                    left: self.evaluate_term(state, left)?,
+                    right: self.evaluate_term(state, right)?,
+                }),
+                _ => None,
+            },
+            mir::Rvalue::UnaryOp(mir::UnOp::Not, operand) => {
+                let condition = state.conditions.get(&operand_local(operand)?)?;
+                Some(condition.negate())
+            }
+            mir::Rvalue::Use(operand) => state.conditions.get(&operand_local(operand)?).cloned(),
+            _ => None,
+        }
+    }
+
+    fn evaluate_term(&self, state: &IntervalState, operand: &mir::Operand<'tcx>) -> Option<Term> {
+        match operand {
+            mir::Operand::Copy(place) | mir::Operand::Move(place) => {
+                let local = self.tracked_local(place)?;
+                Some(Term::Locals(state.equal_locals(local)))
+            }
+            mir::Operand::Constant(constant) => {
+                self.constant_value(constant).map(Term::Constant)
+            }
+        }
+    }
+
+    /// Update the state with the assignment `target = value`.
+    fn assign(&self, state: &mut IntervalState, target: mir::Local, value: Value) {
+        match value {
+            Value::Offset(local, offset) if local == target => {
+                // `x = x + k`: the constraints of `x` are shifted.
+                let interval = state.interval(local).add(&offset);
+                let differences = std::mem::replace(&mut state.differences, BTreeMap::new());
+                for ((x, y), c) in differences {
+                    let shifted = if x == target {
+                        add_bounds(Some(c), offset.upper)
+                    } else if y == target {
+                        add_bounds(Some(c), offset.lower.and_then(i128::checked_neg))
+                    } else {
+                        Some(c)
+                    };
+                    if let Some(c) = shifted {
+                        state.differences.insert((x, y), c);
+                    }
+                }
+                state.forget_in_conditions(target);
+                state.set_interval(target, interval);
+            }
+            Value::Offset(local, offset) => {
+                // `x = y + k`: the constraints of `y` are inherited by `x`.
+                let interval = state.interval(local).add(&offset);
+                let mut derived = vec![];
+                for (&(x, y), &c) in &state.differences {
+                    if x == local && y != target {
+                        derived.push((target, y, add_bounds(Some(c), offset.upper)));
+                    } else if y == local && x != target {
+                        let d = add_bounds(Some(c), offset.lower.and_then(i128::checked_neg));
+                        derived.push((x, target, d));
+                    }
+                }
+                derived.push((target, local, offset.upper));
+                derived.push((local, target, offset.lower.and_then(i128::checked_neg)));
+                state.forget(target);
+                state.set_interval(target, interval);
+                for (x, y, c) in derived {
+                    if let Some(c) = c {
+                        state.add_difference(x, y, c);
+                    }
+                }
+            }
+            Value::Interval(interval) => {
+                state.forget(target);
+                state.set_interval(target, interval);
+            }
+        }
+    }
+
+    /// Apply the effects of the statement to the state. If the effect changes the state, add
+    /// the following statement or terminator to the work queue.
+    fn apply_statement_effects(&mut self, location: mir::Location) {
+        trace!("[enter] apply_statement_effects location={:?}", location);
+        let statement = &self.mir[location.block].statements[location.statement_index];
+        let mut state = self.get_state_before_statement(location);
+        if state.reachable {
+            match statement.kind {
+                mir::StatementKind::Assign(box (ref target, ref rvalue)) => {
+                    if let Some(local) = target.as_local() {
+                        if self.is_tracked(local) {
+                            let value = self.evaluate_rvalue(&state, local, rvalue);
+                            self.assign(&mut state, local, value);
+                        } else {
+                            let condition = self.evaluate_condition(&state, rvalue);
+                            state.forget(local);
+                            if let Some(condition) = condition {
+                                state.conditions.insert(local, condition);
+                            }
+                        }
+                    }
+                }
+                mir::StatementKind::StorageDead(local) => state.forget(local),
+                _ => {}
+            }
+        }
+        self.update_state_after_statement(location, state);
+    }
+
+    /// Apply the effects of the terminator to the state. If the effect changes the state, add
+    /// all reachable basic blocks to the work queue.
+    fn apply_terminator_effects(&mut self, bb: mir::BasicBlock) {
+        trace!("[enter] apply_terminator_effects bb={:?}", bb);
+        let mir::BasicBlockData { ref terminator, .. } = self.mir[bb];
+        let mut state = self.get_state_before_terminator(bb);
+        if let (true, Some(terminator)) = (state.reachable, terminator) {
+            match terminator.kind {
+                mir::TerminatorKind::Call {
+                    destination: Some((ref place, _)),
+                    ..
+                } => {
+                    state.forget(place.local);
+                    if self.is_tracked(place.local) {
+                        state.set_interval(place.local, self.type_bounds(place.local));
+                    }
+                }
+                mir::TerminatorKind::DropAndReplace {
+                    ref place,
+                    ref value,
+                    ..
+                } => match place.as_local() {
+                    Some(local) if self.is_tracked(local) => {
+                        let value = self.evaluate_operand(value);
+                        self.assign(&mut state, local, value);
+                    }
+                    _ => state.forget(place.local),
+                },
+                _ => {}
+            }
+        }
+        let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+        let location = mir::Location {
+            block: bb,
+            statement_index: statements.len(),
+        };
+        self.update_state_after_statement(location, state);
+    }
+
+    /// The state after the predecessor, restricted to the executions that take the edge to
+    /// `bb`.
+    fn get_state_on_edge(
+        &self,
+        predecessor: mir::BasicBlock,
+        bb: mir::BasicBlock,
+    ) -> IntervalState {
+        let mut state = self.get_state_after_block(predecessor);
+        let terminator = self.mir[predecessor].terminator();
+        if let mir::TerminatorKind::SwitchInt {
+            ref discr,
+            ref values,
+            ref targets,
+            ..
+        } = terminator.kind
+        {
+            let discr = match operand_local(discr) {
+                Some(discr) if state.reachable => discr,
+                _ => return state,
+            };
+            let mut indices = targets.iter().enumerate().filter(|&(_, &target)| target == bb);
+            let index = match (indices.next(), indices.next()) {
+                (Some((index, _)), None) => index,
+                _ => return state,
+            };
+            if let Some(condition) = state.conditions.get(&discr).cloned() {
+                // The `otherwise` target of a boolean switch is the other value.
+                let holds = if index < values.len() {
+                    values[index] != 0
+                } else if values.len() == 1 {
+                    values[0] == 0
+                } else {
+                    return state;
+                };
+                state.assume(&if holds { condition } else { condition.negate() });
+            } else if self.integer_locals.contains(&discr) && index < values.len() {
+                let ty = self.mir.local_decls[discr].ty;
+                let value = if ty.is_signed() {
+                    let shift = 128 - self.type_bits(ty);
+                    Some(((values[index] as i128) << shift) >> shift)
+                } else {
+                    i128::try_from(values[index]).ok()
+                };
+                if let Some(value) = value {
+                    for local in state.equal_locals(discr) {
+                        state.refine_interval(local, Interval::constant(value));
+                    }
+                }
+            }
+        }
+        state
+    }
+
+    /// The number of bits of an integer type.
+    fn type_bits(&self, ty: ty::Ty<'tcx>) -> u32 {
+        let bits = match ty.kind {
+            ty::TyKind::Int(int_ty) => int_ty.bit_width(),
+            ty::TyKind::Uint(uint_ty) => uint_ty.bit_width(),
+            _ => None,
+        };
+        bits.map(|bits| bits as u32).unwrap_or_else(config::target_pointer_width)
+    }
+
+    /// Join the states of the incoming edges. At loop heads, the state is widened. If the state
+    /// changed, add the first statement of the block to the work queue.
+    fn merge_effects(&mut self, bb: mir::BasicBlock) {
+        trace!("[enter] merge_effects bb={:?}", bb);
+        let mut state = self.mir.predecessors()[bb]
+            .iter()
+            .map(|&predecessor| self.get_state_on_edge(predecessor, bb))
+            .fold(IntervalState::unreachable(), |acc, state| acc.join(&state));
+        if bb == mir::START_BLOCK {
+            state = state.join(&self.initial_state());
+        }
+        let old_state = &self.result.before_block[&bb];
+        if self.procedure.loop_info().is_loop_head(bb) {
+            state = old_state.widen(&state);
+        }
+        if old_state != &state {
+            self.result.before_block.insert(bb, state);
+            let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+            if statements.len() == 0 {
+                self.queue.push(WorkItem::ApplyTerminatorEffects(bb));
+            } else {
+                let location = mir::Location {
+                    block: bb,
+                    statement_index: 0,
+                };
+                self.queue.push(WorkItem::ApplyStatementEffects(location));
+            }
+        }
+    }
+
+    /// Return the state before the given statement.
+    fn get_state_before_statement(&self, mut location: mir::Location) -> IntervalState {
+        if location.statement_index == 0 {
+            self.result.before_block[&location.block].clone()
+        } else {
+            location.statement_index -= 1;
+            self.result.after_statement[&location].clone()
+        }
+    }
+
+    /// Return the state before the terminator of the given basic block.
+    fn get_state_before_terminator(&self, bb: mir::BasicBlock) -> IntervalState {
+        let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+        if statements.len() == 0 {
+            self.result.before_block[&bb].clone()
+        } else {
+            let location = mir::Location {
+                block: bb,
+                statement_index: statements.len() - 1,
+            };
+            self.result.after_statement[&location].clone()
+        }
+    }
+
+    /// Return the state after the given basic block.
+    fn get_state_after_block(&self, bb: mir::BasicBlock) -> IntervalState {
+        let mir::BasicBlockData { ref statements, .. } = self.mir[bb];
+        let location = mir::Location {
+            block: bb,
+            statement_index: statements.len(),
+        };
+        self.result.after_statement[&location].clone()
+    }
+
+    /// If the state after the statement is different from the provided, updates it and adds
+    /// the successor to the work queue.
+    fn update_state_after_statement(&mut self, mut location: mir::Location, state: IntervalState) {
+        if self.result.after_statement[&location] != state {
+            self.result.after_statement.insert(location, state);
+            let mir::BasicBlockData {
+                ref statements,
+                ref terminator,
+                ..
+            } = self.mir[location.block];
+            if location.statement_index + 1 == statements.len() {
+                // The next statement is terminator.
+                self.queue
+                    .push(WorkItem::ApplyTerminatorEffects(location.block));
+            } else if location.statement_index == statements.len() {
+                // We just updated the terminator, need to update all successors.
+                for successor in terminator.as_ref().unwrap().successors() {
+                    self.queue.push(WorkItem::MergeEffects(*successor));
+                }
+            } else {
+                location.statement_index += 1;
+                self.queue.push(WorkItem::ApplyStatementEffects(location));
+            }
+        }
+    }
+}
+
+fn operand_local(operand: &mir::Operand) -> Option<mir::Local> {
+    match operand {
+        mir::Operand::Copy(place) | mir::Operand::Move(place) => place.as_local(),
+        mir::Operand::Constant(_) => None,
+    }
+}
+
+impl Value {
+    /// The interval of the value.
+    fn resolve(self, state: &IntervalState) -> Interval {
+        match self {
+            Value::Offset(local, offset) => state.interval(local).add(&offset),
+            Value::Interval(interval) => interval,
+        }
+    }
+}
+
+/// Compute the intervals of and the relations between the integer local variables of the
+/// procedure at each program point.
+pub fn compute_intervals<'a, 'tcx: 'a>(
+    procedure: &'a Procedure<'a, 'tcx>,
+    bounds: IntegerBounds,
+) -> IntervalAnalysisResult {
+    let mut analysis = IntervalAnalysis::new(procedure, bounds);
+    analysis.initialize();
+    analysis.propagate_work_queue();
+    analysis.run();
+    analysis.result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(lower: Option<i128>, upper: Option<i128>) -> Interval {
+        Interval { lower, upper }
+    }
+
+    fn local(index: u32) -> mir::Local {
+        mir::Local::from_u32(index)
+    }
+
+    #[test]
+    fn test_interval_join() {
+        let joined = interval(Some(0), Some(2)).join(&interval(Some(5), Some(7)));
+        assert_eq!(joined, interval(Some(0), Some(7)));
+        let joined = interval(Some(0), Some(2)).join(&interval(None, Some(3)));
+        assert_eq!(joined, interval(None, Some(3)));
+        assert!(Interval::constant(1).join(&Interval::top()).is_top());
+    }
+
+    #[test]
+    fn test_interval_widen() {
+        // The upper bound is exceeded by the next iteration, the lower bound is stable.
+        let widened = Interval::constant(0).widen(&interval(Some(0), Some(1)));
+        assert_eq!(widened, interval(Some(0), None));
+        let widened = interval(Some(0), Some(5)).widen(&interval(Some(1), Some(4)));
+        assert_eq!(widened, interval(Some(0), Some(5)));
+        let widened = interval(Some(0), Some(5)).widen(&interval(Some(-1), None));
+        assert!(widened.is_top());
+    }
+
+    #[test]
+    fn test_interval_mul() {
+        let product = interval(Some(-2), Some(3)).mul(&interval(Some(4), Some(5)));
+        assert_eq!(product, interval(Some(-10), Some(15)));
+        let product = interval(Some(-2), Some(-1)).mul(&interval(Some(-3), Some(2)));
+        assert_eq!(product, interval(Some(-4), Some(6)));
+        assert!(interval(Some(0), None).mul(&Interval::constant(2)).is_top());
+        // Overflowing bounds are dropped.
+        assert!(interval(Some(0), Some(i128::MAX)).mul(&Interval::constant(2)).is_top());
+    }
+
+    #[test]
+    fn test_state_widen() {
+        let (i, n) = (local(1), local(2));
+        // `i == 0 && i <= n` on entry of the loop, `0 <= i <= 1 && i <= n` after an iteration.
+        let mut entry = IntervalState::new();
+        entry.set_interval(i, Interval::constant(0));
+        entry.add_difference(i, n, 0);
+        let mut next = IntervalState::new();
+        next.set_interval(i, interval(Some(0), Some(1)));
+        next.add_difference(i, n, 0);
+
+        let widened = entry.widen(&next);
+        assert_eq!(widened.interval(i), interval(Some(0), None));
+        assert_eq!(widened.difference_bound(i, n), Some(0));
+
+        // `i <= n + 1` does not satisfy `i <= n`, which is dropped.
+        let mut weaker = IntervalState::new();
+        weaker.set_interval(i, interval(Some(0), Some(1)));
+        weaker.add_difference(i, n, 1);
+        let widened = entry.widen(&weaker);
+        assert_eq!(widened.difference_bound(i, n), None);
+    }
+
+    #[test]
+    fn test_state_widen_unreachable() {
+        let mut state = IntervalState::new();
+        state.set_interval(local(1), Interval::constant(0));
+        assert_eq!(IntervalState::unreachable().widen(&state), state);
+        assert_eq!(state.widen(&IntervalState::unreachable()), state);
+    }
+}
@@ -8,4 +8,5 @@
 
 pub mod common;
 pub mod initialization;
+pub mod intervals;
 pub mod liveness;
@@ -8,7 +8,7 @@
 
 use rustc_hir::hir_id::HirId;
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::{self, TyCtxt};
+use rustc_middle::ty::{self, DefIdTree, TyCtxt};
 use std::path::PathBuf;
 
 use rustc_span::{Span, MultiSpan, symbol::Symbol};
@@ -104,40 +104,48 @@ impl<'tcx> Environment<'tcx> {
     //     self.state.session.span_err(sp, msg);
     // }
 
-    /// Emits an error message.
-    pub fn span_err_with_help_and_note<S: Into<MultiSpan> + Clone>(
+    /// Emits an error message, with help messages and notes. A note without span is shown
+    /// after the error.
+    pub fn span_err_with_help_and_notes<S: Into<MultiSpan>>(
         &self,
         sp: S,
         msg: &str,
-        help: &Option<String>,
-        note: &Option<(String, S)>
+        help: &[String],
+        notes: &[(String, Option<MultiSpan>)],
     ) {
         let mut diagnostic = self.tcx.sess.struct_err(msg);
         diagnostic.set_span(sp);
-        if let Some(help_msg) = help {
+        for help_msg in help {
             diagnostic.help(help_msg);
         }
-        if let Some((note_msg, note_sp)) = note {
-            diagnostic.span_note(note_sp.clone(), note_msg);
+        for (note_msg, opt_note_sp) in notes {
+            match opt_note_sp {
+                Some(note_sp) => diagnostic.span_note(note_sp.clone(), note_msg),
+                None => diagnostic.note(note_msg),
+            };
         }
         diagnostic.emit();
     }
 
-    /// Emits an error message.
-    pub fn span_warn_with_help_and_note<S: Into<MultiSpan> + Clone>(
+    /// Emits a warning message, with help messages and notes. A note without span is shown
+    /// after the warning.
+    pub fn span_warn_with_help_and_notes<S: Into<MultiSpan>>(
         &self,
         sp: S,
         msg: &str,
-        help: &Option<String>,
-        note: &Option<(String, S)>
+        help: &[String],
+        notes: &[(String, Option<MultiSpan>)],
     ) {
         let mut diagnostic = self.tcx.sess.struct_warn(msg);
         diagnostic.set_span(sp);
-        if let Some(help_msg) = help {
+        for help_msg in help {
             diagnostic.help(help_msg);
         }
-        if let Some((note_msg, note_sp)) = note {
-            diagnostic.span_note(note_sp.clone(), note_msg);
+        for (note_msg, opt_note_sp) in notes {
+            match opt_note_sp {
+                Some(note_sp) => diagnostic.span_note(note_sp.clone(), note_msg),
+                None => diagnostic.note(note_msg),
+            };
         }
         diagnostic.emit();
     }
@@ -181,6 +189,44 @@ impl<'tcx> Environment<'tcx> {
         crate::environment::collect_prusti_spec_visitor::contains_name(tcx.get_attrs(def_id), name)
     }
 
+    /// Returns the arguments of the `prusti::<name>(...)` attribute of the item or, if the item
+    /// does not have such an attribute, of its innermost parent (e.g. `impl` block or module)
+    /// that has one.
+    pub fn get_inherited_attribute_args(&self, def_id: DefId, name: &str) -> Option<Vec<String>> {
+        let tcx = self.tcx();
+        let mut current = Some(def_id);
+        while let Some(def_id) = current {
+            let args = crate::environment::collect_prusti_spec_visitor::read_args(
+                tcx.get_attrs(def_id),
+                name,
+            );
+            if args.is_some() {
+                return args;
+            }
+            current = tcx.parent(def_id);
+        }
+        None
+    }
+
+    /// Returns whether the item, or its innermost parent (e.g. `impl` block or module) that has
+    /// one of the `#[prusti::verify]` and `#[prusti::skip]` attributes, has been marked to be
+    /// verified (`Some(true)`) or skipped (`Some(false)`).
+    pub fn get_selection_attribute(&self, def_id: DefId) -> Option<bool> {
+        let tcx = self.tcx();
+        let mut current = Some(def_id);
+        while let Some(def_id) = current {
+            let attrs = tcx.get_attrs(def_id);
+            if crate::environment::collect_prusti_spec_visitor::contains_name(attrs, "verify") {
+                return Some(true);
+            }
+            if crate::environment::collect_prusti_spec_visitor::contains_name(attrs, "skip") {
+                return Some(false);
+            }
+            current = tcx.parent(def_id);
+        }
+        None
+    }
+
     /// Dump various information from the borrow checker.
     ///
     /// Mostly used for experiments and debugging.
@@ -240,15 +286,18 @@ impl<'tcx> Environment<'tcx> {
         self.tcx().associated_items(id).filter_by_name_unhygienic(name).next().cloned()
     }
 
-    // /// Get a trait method declaration by name for type.
-    // pub fn get_trait_method_decl_for_type(&self, typ: ty::Ty<'tcx>, trait_id: DefId, name: Symbol) -> Vec<ty::AssociatedItem> {
-    //     let mut result = Vec::new();
-    //     self.tcx().for_each_relevant_impl(trait_id, typ, |impl_id| {
-    //         let item = self.get_assoc_item(impl_id, name);
-    //         if let Some(inner) = item {
-    //             result.push(inner.clone());
-    //         }
-    //     });
-    //     result
-    // }
+    /// Get the implementations of a trait method for a type.
+    ///
+    /// Note that the result contains the methods of all impls that might apply to the type, which
+    /// are more than one if the impls are generic.
+    pub fn get_trait_method_decl_for_type(&self, typ: ty::Ty<'tcx>, trait_id: DefId, name: Symbol) -> Vec<ty::AssocItem> {
+        let mut result = Vec::new();
+        self.tcx().for_each_relevant_impl(trait_id, typ, |impl_id| {
+            let item = self.get_assoc_item(impl_id, name);
+            if let Some(inner) = item {
+                result.push(inner.clone());
+            }
+        });
+        result
+    }
 }
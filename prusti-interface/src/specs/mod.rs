@@ -51,6 +51,43 @@ impl<'tcx> SpecCollector<'tcx> {
     }
 }
 
+impl<'tcx> SpecCollector<'tcx> {
+    /// If the item is a specification item, i.e. a function generated by the specification
+    /// macros, start collecting its typed expressions.
+    fn enter_spec_item(&mut self, ident: rustc_span::symbol::Ident, attrs: &[ast::Attribute]) {
+        if has_spec_only_attr(attrs) {
+            assert!(
+                self.current_spec_item.is_none(),
+                "nested specification item?"
+            );
+            let fn_name = ident.name.to_ident_string();
+            let spec_type = if fn_name.starts_with("prusti_pre_item_") {
+                SpecType::Precondition
+            } else if fn_name.starts_with("prusti_post_item_") {
+                SpecType::Postcondition
+            } else if fn_name.starts_with("prusti_pred_item_") {
+                SpecType::Predicate
+            } else {
+                unreachable!();
+            };
+            let spec_item = SpecItem {
+                spec_id: read_attr("spec_id", attrs)
+                    .expect("missing spec_id on spec item")
+                    .try_into()
+                    .unwrap(),
+                spec_type: spec_type,
+                specification: deserialize_spec_from_attrs(attrs),
+            };
+            self.current_spec_item = Some(spec_item);
+        }
+    }
+    fn exit_spec_item(&mut self) {
+        if let Some(spec_item) = self.current_spec_item.take() {
+            self.spec_items.push(spec_item);
+        }
+    }
+}
+
 fn reconstruct_typed_assertion<'tcx>(
     assertion: JsonAssertion,
     typed_expressions: &HashMap<String, LocalDefId>,
@@ -143,34 +180,19 @@ impl<'tcx> intravisit::Visitor<'tcx> for SpecCollector<'tcx> {
         intravisit::NestedVisitorMap::All(map)
     }
     fn visit_item(&mut self, item: &'tcx rustc_hir::Item<'tcx>) {
-        if has_spec_only_attr(item.attrs) {
-            assert!(
-                self.current_spec_item.is_none(),
-                "nested specification item?"
-            );
-            let fn_name = item.ident.name.to_ident_string();
-            let spec_type = if fn_name.starts_with("prusti_pre_item_") {
-                SpecType::Precondition
-            } else if fn_name.starts_with("prusti_post_item_") {
-                SpecType::Postcondition
-            } else {
-                unreachable!();
-            };
-            let spec_item = SpecItem {
-                spec_id: read_attr("spec_id", item.attrs)
-                    .expect("missing spec_id on spec item")
-                    .try_into()
-                    .unwrap(),
-                spec_type: spec_type,
-                specification: deserialize_spec_from_attrs(item.attrs),
-            };
-            assert!(self.current_spec_item.is_none());
-            self.current_spec_item = Some(spec_item);
-        }
+        self.enter_spec_item(item.ident, item.attrs);
         intravisit::walk_item(self, item);
-        if let Some(spec_item) = self.current_spec_item.take() {
-            self.spec_items.push(spec_item);
-        }
+        self.exit_spec_item();
+    }
+    fn visit_trait_item(&mut self, trait_item: &'tcx rustc_hir::TraitItem<'tcx>) {
+        self.enter_spec_item(trait_item.ident, trait_item.attrs);
+        intravisit::walk_trait_item(self, trait_item);
+        self.exit_spec_item();
+    }
+    fn visit_impl_item(&mut self, impl_item: &'tcx rustc_hir::ImplItem<'tcx>) {
+        self.enter_spec_item(impl_item.ident, impl_item.attrs);
+        intravisit::walk_impl_item(self, impl_item);
+        self.exit_spec_item();
     }
     fn visit_fn(
         &mut self,
@@ -0,0 +1,508 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Cargo subcommand that verifies the packages of a workspace with Prusti.
+//!
+//! `cargo prusti` runs `cargo check` once for each selected workspace member, with this same
+//! executable as `RUSTC_WRAPPER`. In wrapper mode, the selected members are compiled by
+//! `prusti-rustc`, while all other crates (dependencies, build scripts, ...) are compiled by the
+//! plain `rustc`. The result of each verification is recorded in a temporary directory, from which
+//! a summary and the exit code are computed. The artifacts are written to the `prusti`
+//! subdirectory of the target directory, so that they don't interfere with those of the regular
+//! Cargo commands.
+
+extern crate serde_json;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+// The variables of the wrapper mode don't have the `PRUSTI_` prefix, which is reserved for the
+// configuration flags of Prusti.
+
+/// Set in wrapper mode: the directory in which the results of the verification are recorded.
+const RESULTS_DIR_VAR: &str = "CARGO_PRUSTI_RESULTS_DIR";
+/// Set in wrapper mode: the manifest directories of the packages to verify.
+const PACKAGES_VAR: &str = "CARGO_PRUSTI_PACKAGES";
+
+const USAGE: &str = "\
+Verify the packages of a Cargo workspace with Prusti
+
+USAGE:
+    cargo prusti [OPTIONS] [-- <CARGO ARGS>...]
+
+OPTIONS:
+    -p, --package <SPEC>...     Package(s) to verify
+        --workspace             Verify all packages in the workspace
+        --all                   Alias for --workspace
+        --features <FEATURES>   Space-separated list of features to activate
+        --all-features          Activate all available features
+        --no-default-features   Do not activate the `default` feature
+        --manifest-path <PATH>  Path to Cargo.toml
+        --prusti-<FLAG>[=<V>]   Set the Prusti configuration flag FLAG (e.g. --prusti-check-panics=false)
+    -h, --help                  Print help information
+
+All other arguments are forwarded to `cargo check`.";
+
+fn main() {
+    if env::var_os(RESULTS_DIR_VAR).is_some() {
+        process::exit(run_wrapper(env::args().skip(1).collect()));
+    }
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    // When invoked as `cargo prusti`, the first argument is the name of the subcommand.
+    if args.first().map(|arg| arg.as_str()) == Some("prusti") {
+        args.remove(0);
+    }
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    process::exit(run_cargo(options));
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    packages: Vec<String>,
+    workspace: bool,
+    manifest_path: Option<String>,
+    /// Arguments forwarded to each `cargo check` (features and unknown arguments).
+    cargo_args: Vec<String>,
+    /// Prusti configuration flags, as `(PRUSTI_<FLAG>, value)` environment variables.
+    prusti_flags: Vec<(String, String)>,
+    help: bool,
+}
+
+impl Options {
+    fn parse(args: Vec<String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.find('=') {
+                Some(pos) if arg.starts_with("--") => {
+                    (arg[..pos].to_string(), Some(arg[pos + 1..].to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            match name.as_str() {
+                "-h" | "--help" => options.help = true,
+                "-p" | "--package" => {
+                    let package = take_value(&name, inline_value, &mut args)?;
+                    options.packages.push(package);
+                }
+                "--workspace" | "--all" => options.workspace = true,
+                "--manifest-path" => {
+                    let manifest_path = take_value(&name, inline_value, &mut args)?;
+                    options.manifest_path = Some(manifest_path);
+                }
+                "--features" => {
+                    let features = take_value(&name, inline_value, &mut args)?;
+                    options.cargo_args.push("--features".to_string());
+                    options.cargo_args.push(features);
+                }
+                "--" => options.cargo_args.extend(args.by_ref()),
+                _ if name.starts_with("--prusti-") => {
+                    let flag = name["--prusti-".len()..].replace('-', "_").to_uppercase();
+                    if flag.is_empty() {
+                        return Err(format!("invalid Prusti flag '{}'", arg));
+                    }
+                    let value = inline_value.unwrap_or_else(|| "true".to_string());
+                    options.prusti_flags.push((format!("PRUSTI_{}", flag), value));
+                }
+                _ => options.cargo_args.push(arg),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// The value of an option, given either inline (`--option=value`) or as the next argument.
+fn take_value<I: Iterator<Item = String>>(
+    name: &str,
+    inline_value: Option<String>,
+    args: &mut I,
+) -> Result<String, String> {
+    inline_value
+        .or_else(|| args.next())
+        .ok_or_else(|| format!("the argument '{}' requires a value", name))
+}
+
+/// A member of the workspace.
+#[derive(Debug)]
+struct Package {
+    name: String,
+    manifest_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PackageStatus {
+    Verified,
+    Failed,
+    /// The package has not been verified, because one of its dependencies did not compile or
+    /// failed to verify.
+    NotVerified,
+}
+
+/// The members of a workspace and the directory in which Prusti writes its artifacts.
+#[derive(Debug)]
+struct Workspace {
+    members: Vec<Package>,
+    target_dir: PathBuf,
+}
+
+/// Load the members of the workspace with `cargo metadata`.
+fn load_workspace(manifest_path: &Option<String>) -> Result<Workspace, String> {
+    let mut cmd = Command::new(cargo());
+    cmd.args(&["metadata", "--no-deps", "--format-version", "1"]);
+    if let Some(manifest_path) = manifest_path {
+        cmd.args(&["--manifest-path", manifest_path]);
+    }
+    let output = cmd
+        .output()
+        .map_err(|err| format!("failed to run `cargo metadata`: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`cargo metadata` failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("failed to parse the output of `cargo metadata`: {}", err))?;
+    let target_dir = metadata["target_directory"]
+        .as_str()
+        .ok_or("unexpected output of `cargo metadata`")?;
+    let packages = metadata["packages"]
+        .as_array()
+        .ok_or("unexpected output of `cargo metadata`")?;
+    let members = packages
+        .iter()
+        .map(|package| {
+            let name = package["name"].as_str();
+            let manifest_path = package["manifest_path"].as_str();
+            match (name, manifest_path) {
+                (Some(name), Some(manifest_path)) => Ok(Package {
+                    name: name.to_string(),
+                    manifest_dir: Path::new(manifest_path).parent().unwrap().to_path_buf(),
+                }),
+                _ => Err("unexpected output of `cargo metadata`".to_string()),
+            }
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Workspace {
+        members,
+        target_dir: Path::new(target_dir).join("prusti"),
+    })
+}
+
+/// Select the packages to verify, following the rules of `cargo check`: the packages given with
+/// `--package`, all members with `--workspace`, and otherwise the package of the current
+/// directory (or all members, if the current directory is the root of a virtual workspace).
+fn select_packages(options: &Options, members: Vec<Package>) -> Result<Vec<Package>, String> {
+    if options.workspace {
+        return Ok(members);
+    }
+    if !options.packages.is_empty() {
+        for name in &options.packages {
+            if !members.iter().any(|package| &package.name == name) {
+                return Err(format!("package '{}' is not a member of the workspace", name));
+            }
+        }
+        return Ok(members
+            .into_iter()
+            .filter(|package| options.packages.contains(&package.name))
+            .collect());
+    }
+    let current_dir = match options.manifest_path {
+        Some(ref manifest_path) => Path::new(manifest_path).parent().unwrap().to_path_buf(),
+        None => env::current_dir().map_err(|err| err.to_string())?,
+    };
+    let current_dir = fs::canonicalize(&current_dir).unwrap_or(current_dir);
+    let current_package = members
+        .iter()
+        .filter(|package| current_dir.starts_with(&package.manifest_dir))
+        .max_by_key(|package| package.manifest_dir.components().count())
+        .map(|package| package.name.clone());
+    Ok(match current_package {
+        Some(name) => members.into_iter().filter(|p| p.name == name).collect(),
+        None => members,
+    })
+}
+
+fn cargo() -> String {
+    env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+/// Verify the selected packages, print a summary and return the exit code.
+fn run_cargo(options: Options) -> i32 {
+    let (packages, target_dir) = match load_workspace(&options.manifest_path).and_then(|workspace| {
+        let packages = select_packages(&options, workspace.members)?;
+        Ok((packages, workspace.target_dir))
+    }) {
+        Ok(result) => result,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return 101;
+        }
+    };
+
+    let results_dir = env::temp_dir().join(format!("cargo-prusti-{}", process::id()));
+    fs::create_dir_all(&results_dir).expect("failed to create a temporary directory");
+    let selected_dirs = env::join_paths(packages.iter().map(|p| &p.manifest_dir))
+        .expect("invalid manifest directory");
+    let current_exe = env::current_exe().expect("current executable path invalid");
+
+    let mut statuses = vec![];
+    for package in &packages {
+        // Verify the package, unless this already happened while verifying a dependent package.
+        let mut status = read_package_status(&results_dir, &package.name);
+        if status.is_none() {
+            let mut cmd = Command::new(cargo());
+            cmd.arg("check").args(&["--package", &package.name]);
+            if let Some(ref manifest_path) = options.manifest_path {
+                cmd.args(&["--manifest-path", manifest_path]);
+            }
+            cmd.arg("--target-dir").arg(&target_dir);
+            cmd.args(&options.cargo_args);
+            cmd.env("RUSTC_WRAPPER", &current_exe);
+            cmd.env(RESULTS_DIR_VAR, &results_dir);
+            cmd.env(PACKAGES_VAR, &selected_dirs);
+            cmd.env("PRUSTI_FULL_COMPILATION", "true");
+            for (name, value) in &options.prusti_flags {
+                cmd.env(name, value);
+            }
+            // Cargo considers the selected packages fresh if they have been checked before, so
+            // remove their artifacts to force a new verification.
+            clean_package(&options, &target_dir, package);
+            let exit_status = cmd.status().expect("could not run cargo");
+            status = read_package_status(&results_dir, &package.name);
+            if status.is_none() || (status == Some(PackageStatus::Verified) && !exit_status.success()) {
+                status = Some(PackageStatus::NotVerified);
+            }
+        }
+        statuses.push((package, status.unwrap()));
+    }
+    let _ = fs::remove_dir_all(&results_dir);
+
+    eprintln!("\nPrusti verification summary:");
+    for (package, status) in &statuses {
+        let status = match status {
+            PackageStatus::Verified => "verified",
+            PackageStatus::Failed => "failed",
+            PackageStatus::NotVerified => "not verified (a dependency failed to compile or verify)",
+        };
+        eprintln!("  {}: {}", package.name, status);
+    }
+    let verified_count = statuses
+        .iter()
+        .filter(|(_, status)| *status == PackageStatus::Verified)
+        .count();
+    eprintln!("{} of {} packages verified successfully", verified_count, statuses.len());
+
+    if verified_count == statuses.len() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Remove the artifacts of the package from the target directory of Prusti.
+fn clean_package(options: &Options, target_dir: &Path, package: &Package) {
+    let mut cmd = Command::new(cargo());
+    cmd.arg("clean").args(&["--package", &package.name]);
+    if let Some(ref manifest_path) = options.manifest_path {
+        cmd.args(&["--manifest-path", manifest_path]);
+    }
+    cmd.arg("--target-dir").arg(target_dir);
+    let _ = cmd.status();
+}
+
+/// The status of a package according to the results recorded by the wrapper, if any.
+fn read_package_status(results_dir: &Path, package_name: &str) -> Option<PackageStatus> {
+    let mut status = None;
+    for entry in fs::read_dir(results_dir.join(package_name)).ok()? {
+        let content = fs::read_to_string(entry.ok()?.path()).ok()?;
+        if content.trim() == "failure" {
+            return Some(PackageStatus::Failed);
+        }
+        status = Some(PackageStatus::Verified);
+    }
+    status
+}
+
+/// Run as `RUSTC_WRAPPER`: `args` are the path to `rustc` followed by its arguments.
+fn run_wrapper(args: Vec<String>) -> i32 {
+    let (rustc, rustc_args) = args.split_first().expect("missing path to rustc");
+    let crate_name = rustc_args
+        .iter()
+        .position(|arg| arg == "--crate-name")
+        .and_then(|pos| rustc_args.get(pos + 1))
+        .cloned();
+    let package_name = env::var("CARGO_PKG_NAME").ok();
+    let is_selected = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .and_then(|manifest_dir| {
+            let selected = env::var_os(PACKAGES_VAR)?;
+            Some(env::split_paths(&selected).any(|dir| dir == manifest_dir))
+        })
+        .unwrap_or(false);
+
+    let (package_name, crate_name) = match (package_name, crate_name) {
+        (Some(package_name), Some(crate_name))
+            if is_selected && crate_name != "build_script_build" =>
+        {
+            (package_name, crate_name)
+        }
+        // Dependencies, build scripts and queries of Cargo (e.g. `rustc -vV`)
+        _ => return exit_code(Command::new(rustc).args(rustc_args).status()),
+    };
+
+    let results_dir = PathBuf::from(env::var_os(RESULTS_DIR_VAR).unwrap()).join(&package_name);
+    fs::create_dir_all(&results_dir).expect("failed to create the results directory");
+    if read_package_status(results_dir.parent().unwrap(), &package_name)
+        == Some(PackageStatus::Failed)
+    {
+        eprintln!("Skipping the verification of '{}', which already failed", crate_name);
+        return 1;
+    }
+
+    let mut prusti_rustc_path = env::current_exe()
+        .expect("current executable path invalid")
+        .with_file_name("prusti-rustc");
+    if cfg!(windows) {
+        prusti_rustc_path.set_extension("exe");
+    }
+    let code = exit_code(Command::new(&prusti_rustc_path).args(rustc_args).status());
+    let result = if code == 0 { "success" } else { "failure" };
+    fs::write(results_dir.join(&crate_name), result)
+        .expect("failed to record the result of the verification");
+    code
+}
+
+fn exit_code(status: std::io::Result<process::ExitStatus>) -> i32 {
+    match status {
+        Ok(status) => status.code().unwrap_or(-1),
+        Err(err) => {
+            eprintln!("error: could not run the compiler: {}", err);
+            -1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn members() -> Vec<Package> {
+        vec![
+            Package {
+                name: "app".to_string(),
+                manifest_dir: PathBuf::from("/nonexistent/workspace/app"),
+            },
+            Package {
+                name: "lib".to_string(),
+                manifest_dir: PathBuf::from("/nonexistent/workspace/lib"),
+            },
+        ]
+    }
+
+    fn names(packages: &[Package]) -> Vec<&str> {
+        packages.iter().map(|package| package.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_packages_and_features() {
+        let options = Options::parse(args(&[
+            "-p", "app", "--package=lib", "--features", "a b", "--release",
+        ]))
+        .unwrap();
+        assert_eq!(options.packages, vec!["app", "lib"]);
+        assert_eq!(options.cargo_args, vec!["--features", "a b", "--release"]);
+        assert!(!options.workspace);
+        assert!(!options.help);
+    }
+
+    #[test]
+    fn parse_prusti_flags() {
+        let options = Options::parse(args(&[
+            "--prusti-check-panics=false",
+            "--prusti-check-binary-operations",
+        ]))
+        .unwrap();
+        assert_eq!(
+            options.prusti_flags,
+            vec![
+                ("PRUSTI_CHECK_PANICS".to_string(), "false".to_string()),
+                ("PRUSTI_CHECK_BINARY_OPERATIONS".to_string(), "true".to_string()),
+            ]
+        );
+        assert!(Options::parse(args(&["--prusti-"])).is_err());
+    }
+
+    #[test]
+    fn parse_forwarded_arguments() {
+        let options = Options::parse(args(&["--all", "--", "-p", "--prusti-x"])).unwrap();
+        assert!(options.workspace);
+        assert!(options.packages.is_empty());
+        assert!(options.prusti_flags.is_empty());
+        assert_eq!(options.cargo_args, vec!["-p", "--prusti-x"]);
+    }
+
+    #[test]
+    fn parse_missing_value() {
+        assert!(Options::parse(args(&["--package"])).is_err());
+        assert!(Options::parse(args(&["--manifest-path"])).is_err());
+    }
+
+    #[test]
+    fn select_workspace() {
+        let options = Options::parse(args(&["--workspace", "-p", "app"])).unwrap();
+        let packages = select_packages(&options, members()).unwrap();
+        assert_eq!(names(&packages), vec!["app", "lib"]);
+    }
+
+    #[test]
+    fn select_given_packages() {
+        let options = Options::parse(args(&["-p", "lib"])).unwrap();
+        let packages = select_packages(&options, members()).unwrap();
+        assert_eq!(names(&packages), vec!["lib"]);
+        let options = Options::parse(args(&["-p", "other"])).unwrap();
+        assert!(select_packages(&options, members()).is_err());
+    }
+
+    #[test]
+    fn select_current_package() {
+        let options = Options::parse(args(&[
+            "--manifest-path",
+            "/nonexistent/workspace/lib/Cargo.toml",
+        ]))
+        .unwrap();
+        let packages = select_packages(&options, members()).unwrap();
+        assert_eq!(names(&packages), vec!["lib"]);
+    }
+
+    #[test]
+    fn select_virtual_workspace_root() {
+        let options = Options::parse(args(&[
+            "--manifest-path",
+            "/nonexistent/workspace/Cargo.toml",
+        ]))
+        .unwrap();
+        let packages = select_packages(&options, members()).unwrap();
+        assert_eq!(names(&packages), vec!["app", "lib"]);
+    }
+}
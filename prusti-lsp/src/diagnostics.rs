@@ -0,0 +1,214 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Conversion of the diagnostics emitted by the compiler (`--error-format=json`), which include
+//! the errors reported by Prusti, to LSP diagnostics.
+
+use crate::report::to_position;
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Range,
+    Url,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A diagnostic of the compiler, in the JSON format of rustc.
+#[derive(Debug, Deserialize)]
+pub struct RustcDiagnostic {
+    message: String,
+    code: Option<RustcCode>,
+    level: String,
+    spans: Vec<RustcSpan>,
+    children: Vec<RustcDiagnostic>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: u64,
+    line_end: u64,
+    column_start: u64,
+    column_end: u64,
+    is_primary: bool,
+    label: Option<String>,
+}
+
+impl RustcSpan {
+    fn location(&self, base_dir: &Path) -> Option<(PathBuf, Location)> {
+        let path = base_dir.join(&self.file_name);
+        let uri = Url::from_file_path(&path).ok()?;
+        let range = Range::new(
+            to_position(self.line_start, self.column_start),
+            to_position(self.line_end, self.column_end),
+        );
+        Some((path, Location::new(uri, range)))
+    }
+}
+
+/// Parse a line of the output of the compiler, or of the JSON messages of Cargo, as a
+/// diagnostic.
+pub fn parse_diagnostic(line: &str) -> Option<RustcDiagnostic> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let value = if value.get("reason").is_some() {
+        // A message of Cargo, which wraps the diagnostics of the compiler.
+        if value["reason"] != "compiler-message" {
+            return None;
+        }
+        value["message"].clone()
+    } else {
+        value
+    };
+    serde_json::from_value(value).ok()
+}
+
+fn severity(level: &str) -> Option<DiagnosticSeverity> {
+    match level {
+        "error" | "error: internal compiler error" => Some(DiagnosticSeverity::Error),
+        "warning" => Some(DiagnosticSeverity::Warning),
+        "note" => Some(DiagnosticSeverity::Information),
+        "help" => Some(DiagnosticSeverity::Hint),
+        _ => None,
+    }
+}
+
+/// Convert the diagnostics of the compiler to LSP diagnostics, grouped by file. The file names
+/// of the diagnostics are relative to `base_dir`. Diagnostics without a primary span (e.g.
+/// "aborting due to previous error") are dropped.
+pub fn to_lsp_diagnostics(
+    diagnostics: &[RustcDiagnostic],
+    base_dir: &Path,
+) -> HashMap<PathBuf, Vec<Diagnostic>> {
+    let mut result: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+    for diagnostic in diagnostics {
+        let severity = match severity(&diagnostic.level) {
+            Some(severity) => severity,
+            None => continue,
+        };
+        let (path, location) = match diagnostic
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .and_then(|span| span.location(base_dir))
+        {
+            Some(primary) => primary,
+            None => continue,
+        };
+
+        let mut message = diagnostic.message.clone();
+        let mut related_information = vec![];
+        // The labels of the secondary spans.
+        for span in diagnostic.spans.iter().filter(|span| !span.is_primary) {
+            if let (Some(label), Some((_, location))) = (&span.label, span.location(base_dir)) {
+                related_information.push(DiagnosticRelatedInformation {
+                    location,
+                    message: label.clone(),
+                });
+            }
+        }
+        // The notes and helps that point at related code become related information, the
+        // others are appended to the message.
+        for child in &diagnostic.children {
+            let locations: Vec<_> = child
+                .spans
+                .iter()
+                .filter_map(|span| span.location(base_dir))
+                .collect();
+            if locations.is_empty() {
+                message.push_str(&format!("\n{}: {}", child.level, child.message));
+            }
+            for (_, location) in locations {
+                related_information.push(DiagnosticRelatedInformation {
+                    location,
+                    message: format!("{}: {}", child.level, child.message),
+                });
+            }
+        }
+
+        result.entry(path).or_insert_with(Vec::new).push(Diagnostic::new(
+            location.range,
+            Some(severity),
+            diagnostic
+                .code
+                .as_ref()
+                .map(|code| NumberOrString::String(code.code.clone())),
+            Some("prusti".to_string()),
+            message,
+            if related_information.is_empty() {
+                None
+            } else {
+                Some(related_information)
+            },
+            None,
+        ));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUSTC_ERROR: &str = r#"{"message":"[Prusti: verification error] precondition might not hold.","code":null,"level":"error","spans":[{"file_name":"src/lib.rs","line_start":14,"line_end":14,"column_start":9,"column_end":20,"is_primary":true,"label":null}],"children":[{"message":"the precondition of the called function is here","code":null,"level":"note","spans":[{"file_name":"src/lib.rs","line_start":6,"line_end":6,"column_start":12,"column_end":17,"is_primary":true,"label":null}],"children":[]},{"message":"the loop invariant is not checked","code":null,"level":"help","spans":[],"children":[]}]}"#;
+
+    #[test]
+    fn parse_rustc_diagnostic() {
+        let diagnostic = parse_diagnostic(RUSTC_ERROR).unwrap();
+        assert_eq!(diagnostic.level, "error");
+        assert_eq!(diagnostic.spans.len(), 1);
+        assert_eq!(diagnostic.spans[0].line_start, 14);
+        assert_eq!(diagnostic.children.len(), 2);
+    }
+
+    #[test]
+    fn parse_cargo_messages() {
+        let message = format!(
+            r#"{{"reason":"compiler-message","package_id":"app 0.1.0","message":{}}}"#,
+            RUSTC_ERROR
+        );
+        let diagnostic = parse_diagnostic(&message).unwrap();
+        assert_eq!(diagnostic.level, "error");
+        assert!(parse_diagnostic(r#"{"reason":"compiler-artifact","package_id":"app 0.1.0"}"#)
+            .is_none());
+        assert!(parse_diagnostic(r#"{"reason":"build-finished","success":true}"#).is_none());
+    }
+
+    #[test]
+    fn parse_invalid_lines() {
+        assert!(parse_diagnostic("error: aborting due to previous error").is_none());
+        assert!(parse_diagnostic("").is_none());
+        assert!(parse_diagnostic(r#"{"message":"missing fields"}"#).is_none());
+    }
+
+    #[test]
+    fn convert_to_lsp_diagnostics() {
+        let base_dir = Path::new("/nonexistent/app");
+        let diagnostics = vec![parse_diagnostic(RUSTC_ERROR).unwrap()];
+        let result = to_lsp_diagnostics(&diagnostics, base_dir);
+        let file_diagnostics = &result[&base_dir.join("src/lib.rs")];
+        assert_eq!(file_diagnostics.len(), 1);
+        let diagnostic = &file_diagnostics[0];
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::Error));
+        assert_eq!(diagnostic.range.start, to_position(14, 9));
+        assert_eq!(
+            diagnostic.message,
+            "[Prusti: verification error] precondition might not hold.\n\
+            help: the loop invariant is not checked"
+        );
+        let related_information = diagnostic.related_information.as_ref().unwrap();
+        assert_eq!(related_information.len(), 1);
+        assert_eq!(
+            related_information[0].message,
+            "note: the precondition of the called function is here"
+        );
+        assert_eq!(related_information[0].location.range.start, to_position(6, 12));
+    }
+}
@@ -0,0 +1,267 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A language server that verifies Rust code with Prusti when it is saved. See the README for
+//! the supported features.
+
+mod diagnostics;
+mod report;
+mod runner;
+mod server;
+mod worker;
+
+use crate::report::{SHOW_VIPER_ENCODING_COMMAND, VERIFY_FUNCTION_COMMAND};
+use crate::runner::{Job, Runner, Target};
+use crate::server::PrustiServerProcess;
+use crate::worker::{ClientSupport, State, Worker};
+use log::{debug, error, info, warn};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidOpenTextDocument, DidSaveTextDocument, Notification as LspNotification,
+};
+use lsp_types::request::{CodeLensRequest, ExecuteCommand, Request as LspRequest};
+use lsp_types::{
+    CodeLensOptions, ExecuteCommandOptions, SaveOptions, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, Url,
+};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+fn main() {
+    // The standard output is reserved for the protocol, so the logs go to the standard error.
+    env_logger::init();
+    if let Err(err) = run() {
+        error!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::None),
+            save: Some(SaveOptions::default().into()),
+            ..Default::default()
+        })),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                VERIFY_FUNCTION_COMMAND.to_string(),
+                SHOW_VIPER_ENCODING_COMMAND.to_string(),
+            ],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let initialize_params =
+        connection.initialize(serde_json::to_value(&server_capabilities())?)?;
+    let client_support = ClientSupport::from_initialize_params(&initialize_params);
+    debug!("Client support: {:?}", client_support);
+
+    let prusti_home = env::current_exe()?
+        .parent()
+        .expect("Failed to find Prusti's home")
+        .to_path_buf();
+    let work_dir = env::temp_dir().join(format!("prusti-lsp-{}", process::id()));
+    let server_log_dir = work_dir.join("server-log");
+    fs::create_dir_all(&server_log_dir)?;
+    let server = PrustiServerProcess::launch(&prusti_home, &server_log_dir)?;
+
+    let state = Arc::new(Mutex::new(State::default()));
+    let (job_sender, job_receiver) = mpsc::channel();
+    let worker = Worker::new(
+        Runner::new(
+            prusti_home,
+            work_dir.clone(),
+            server_log_dir,
+            server.address().to_string(),
+        ),
+        state.clone(),
+        connection.sender.clone(),
+        client_support,
+    );
+    let worker_thread = thread::spawn(move || worker.run(job_receiver));
+
+    main_loop(&connection, &state, &job_sender)?;
+
+    // Let the worker finish the current verification, which keeps the connection open.
+    drop(job_sender);
+    let _ = worker_thread.join();
+    drop(connection);
+    io_threads.join()?;
+    drop(server);
+    let _ = fs::remove_dir_all(&work_dir);
+    info!("Shut down");
+    Ok(())
+}
+
+fn main_loop(
+    connection: &Connection,
+    state: &Mutex<State>,
+    jobs: &Sender<Job>,
+) -> Result<()> {
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = handle_request(request, state, jobs);
+                connection.sender.send(response.into())?;
+            }
+            Message::Notification(notification) => handle_notification(notification, jobs),
+            // The responses to the requests of the worker are not used.
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(request: Request, state: &Mutex<State>, jobs: &Sender<Job>) -> Response {
+    let request = match request.extract::<lsp_types::CodeLensParams>(CodeLensRequest::METHOD) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let lenses: Vec<_> = match uri.to_file_path() {
+                Ok(path) => state
+                    .lock()
+                    .unwrap()
+                    .procedures
+                    .iter()
+                    .filter(|procedure| procedure.file == path)
+                    .flat_map(|procedure| procedure.code_lenses(uri.as_str()))
+                    .collect(),
+                Err(()) => vec![],
+            };
+            return Response::new_ok(id, lenses);
+        }
+        Err(request) => request,
+    };
+    let request =
+        match request.extract::<lsp_types::ExecuteCommandParams>(ExecuteCommand::METHOD) {
+            Ok((id, params)) => return execute_command(id, params, jobs),
+            Err(request) => request,
+        };
+    Response::new_err(
+        request.id,
+        ErrorCode::MethodNotFound as i32,
+        format!("unsupported request '{}'", request.method),
+    )
+}
+
+fn execute_command(
+    id: RequestId,
+    params: lsp_types::ExecuteCommandParams,
+    jobs: &Sender<Job>,
+) -> Response {
+    let arguments: Vec<&str> = params
+        .arguments
+        .iter()
+        .filter_map(|argument| argument.as_str())
+        .collect();
+    let (path, def_path) = match arguments.as_slice() {
+        [uri, def_path] => match Url::parse(uri).ok().and_then(|uri| uri.to_file_path().ok()) {
+            Some(path) => (path, def_path.to_string()),
+            None => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::InvalidParams as i32,
+                    format!("invalid document URI '{}'", uri),
+                )
+            }
+        },
+        _ => {
+            return Response::new_err(
+                id,
+                ErrorCode::InvalidParams as i32,
+                "expected the URI of a document and the def path of a function".to_string(),
+            )
+        }
+    };
+    let target = target_of(&path);
+    let job = match params.command.as_str() {
+        VERIFY_FUNCTION_COMMAND => Job::VerifyFunction(target, def_path),
+        SHOW_VIPER_ENCODING_COMMAND => Job::ShowViperEncoding(target, def_path),
+        command => {
+            return Response::new_err(
+                id,
+                ErrorCode::InvalidParams as i32,
+                format!("unknown command '{}'", command),
+            )
+        }
+    };
+    let _ = jobs.send(job);
+    // The results are published when the verification finishes.
+    Response::new_ok(id, serde_json::Value::Null)
+}
+
+fn handle_notification(notification: Notification, jobs: &Sender<Job>) {
+    let uri = match notification.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            match serde_json::from_value::<lsp_types::DidOpenTextDocumentParams>(
+                notification.params,
+            ) {
+                Ok(params) => params.text_document.uri,
+                Err(err) => {
+                    warn!("Invalid notification: {}", err);
+                    return;
+                }
+            }
+        }
+        DidSaveTextDocument::METHOD => {
+            match serde_json::from_value::<lsp_types::DidSaveTextDocumentParams>(
+                notification.params,
+            ) {
+                Ok(params) => params.text_document.uri,
+                Err(err) => {
+                    warn!("Invalid notification: {}", err);
+                    return;
+                }
+            }
+        }
+        _ => return,
+    };
+    let path = match uri.to_file_path() {
+        Ok(path) => path,
+        Err(()) => return,
+    };
+    let target = target_of(&path);
+    // A change of the configuration of a package requires to verify it again.
+    let is_relevant = match target {
+        Target::Package(_) => {
+            path.extension().map_or(false, |extension| extension == "rs")
+                || path.ends_with("Cargo.toml")
+                || path.ends_with("Prusti.toml")
+        }
+        Target::File(_) => path.extension().map_or(false, |extension| extension == "rs"),
+    };
+    if is_relevant {
+        let _ = jobs.send(Job::Verify(target));
+    }
+}
+
+/// The Cargo package that contains the file or, outside of Cargo packages, the file itself.
+fn target_of(path: &Path) -> Target {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(|dir| Target::Package(dir.to_path_buf()))
+        .unwrap_or_else(|| Target::File(PathBuf::from(path)))
+}
@@ -0,0 +1,219 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Reading of the JSON verification reports written by Prusti (see `JSON_REPORT_DIR`), from
+//! which the verification status of each function is shown as a code lens.
+
+use log::warn;
+use lsp_types::{CodeLens, Command, Position, Range};
+use serde::Deserialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The version of the schema of the reports that can be read.
+const REPORT_VERSION: u32 = 1;
+
+pub const VERIFY_FUNCTION_COMMAND: &str = "prusti.verifyFunction";
+pub const SHOW_VIPER_ENCODING_COMMAND: &str = "prusti.showViperEncoding";
+
+/// The subset of the report of a crate that is used by the language server.
+#[derive(Debug, Deserialize)]
+struct CrateReport {
+    version: u32,
+    procedures: Vec<ProcedureReport>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProcedureReport {
+    def_path: String,
+    name: String,
+    span: Option<SpanReport>,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpanReport {
+    file: String,
+    line_start: u64,
+    column_start: u64,
+    line_end: u64,
+    column_end: u64,
+}
+
+/// The verification status of a function.
+#[derive(Debug, Clone)]
+pub struct ProcedureStatus {
+    pub def_path: String,
+    pub name: String,
+    pub file: PathBuf,
+    pub range: Range,
+    pub status: String,
+}
+
+impl ProcedureStatus {
+    /// The code lenses of the function. `uri` is the URI of the document that contains it.
+    pub fn code_lenses(&self, uri: &str) -> Vec<CodeLens> {
+        let title = match self.status.as_str() {
+            "verified" => "✔ verified".to_string(),
+            "failed" => "✘ verification failed".to_string(),
+            "unsupported" => "unsupported by Prusti".to_string(),
+            "encoding_failed" => "✘ encoding failed".to_string(),
+            "trusted" => "trusted".to_string(),
+            "not_verified" => "not verified".to_string(),
+            "unchanged" => "✔ verified (unchanged)".to_string(),
+            status => status.replace('_', " "),
+        };
+        let arguments = Some(vec![json!(uri), json!(self.def_path)]);
+        // Code lenses are shown on the line of the start of their range.
+        let range = Range::new(self.range.start, self.range.start);
+        vec![
+            CodeLens {
+                range,
+                command: Some(Command::new(
+                    format!("Prusti: {} (verify again)", title),
+                    VERIFY_FUNCTION_COMMAND.to_string(),
+                    arguments.clone(),
+                )),
+                data: None,
+            },
+            CodeLens {
+                range,
+                command: Some(Command::new(
+                    "Show Viper encoding".to_string(),
+                    SHOW_VIPER_ENCODING_COMMAND.to_string(),
+                    arguments,
+                )),
+                data: None,
+            },
+        ]
+    }
+
+    /// Whether the function contains the given position of the given file.
+    pub fn contains(&self, file: &Path, position: Position) -> bool {
+        self.file == file && self.range.start <= position && position <= self.range.end
+    }
+}
+
+/// Read the statuses of the functions from the reports written to `report_dir`. The file names
+/// of the reports are relative to `base_dir`.
+pub fn read_reports(report_dir: &Path, base_dir: &Path) -> Vec<ProcedureStatus> {
+    let entries = match fs::read_dir(report_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    let mut statuses = vec![];
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().map_or(true, |extension| extension != "json") {
+            continue;
+        }
+        let report: CrateReport = match fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| serde_json::from_slice(&content).map_err(|err| err.to_string()))
+        {
+            Ok(report) => report,
+            Err(err) => {
+                warn!("Failed to read the report '{}': {}", path.display(), err);
+                continue;
+            }
+        };
+        if report.version != REPORT_VERSION {
+            warn!(
+                "Ignoring the report '{}', whose version {} is not supported",
+                path.display(),
+                report.version
+            );
+            continue;
+        }
+        for procedure in report.procedures {
+            if let Some(span) = procedure.span {
+                statuses.push(ProcedureStatus {
+                    def_path: procedure.def_path,
+                    name: procedure.name,
+                    file: base_dir.join(&span.file),
+                    range: Range::new(
+                        to_position(span.line_start, span.column_start),
+                        to_position(span.line_end, span.column_end),
+                    ),
+                    status: procedure.status,
+                });
+            }
+        }
+    }
+    statuses
+}
+
+/// Convert a 1-based line and column to a position.
+pub fn to_position(line: u64, column: u64) -> Position {
+    Position::new(line.saturating_sub(1), column.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for the reports of a test.
+    fn report_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prusti-lsp-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const REPORT: &str = r#"{
+        "version": 1,
+        "crate_name": "app",
+        "procedures": [
+            {
+                "def_path": "app::verified",
+                "name": "verified",
+                "span": {"file": "src/lib.rs", "line_start": 3, "column_start": 1, "line_end": 5, "column_end": 2},
+                "status": "verified"
+            },
+            {
+                "def_path": "app::external",
+                "name": "external",
+                "span": null,
+                "status": "failed"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn read_procedure_statuses() {
+        let dir = report_dir("statuses");
+        fs::write(dir.join("app.json"), REPORT).unwrap();
+        fs::write(dir.join("app.txt"), "not a report").unwrap();
+        let base_dir = Path::new("/nonexistent/app");
+        let statuses = read_reports(&dir, base_dir);
+        assert_eq!(statuses.len(), 1);
+        let status = &statuses[0];
+        assert_eq!(status.def_path, "app::verified");
+        assert_eq!(status.status, "verified");
+        assert_eq!(status.file, base_dir.join("src/lib.rs"));
+        assert_eq!(status.range, Range::new(Position::new(2, 0), Position::new(4, 1)));
+        assert!(status.contains(&base_dir.join("src/lib.rs"), Position::new(3, 4)));
+        assert!(!status.contains(&base_dir.join("src/lib.rs"), Position::new(5, 0)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn skip_invalid_reports() {
+        let dir = report_dir("invalid");
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        fs::write(dir.join("old.json"), REPORT.replace(r#""version": 1"#, r#""version": 0"#))
+            .unwrap();
+        assert!(read_reports(&dir, Path::new("/nonexistent/app")).is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_report_dir() {
+        let dir = std::env::temp_dir().join("prusti-lsp-nonexistent-report-dir");
+        assert!(read_reports(&dir, Path::new("/nonexistent/app")).is_empty());
+    }
+}
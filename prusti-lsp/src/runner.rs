@@ -0,0 +1,188 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Verification runs, which invoke `cargo-prusti` or `prusti-rustc` and collect their
+//! diagnostics, reports and Viper programs.
+
+use crate::diagnostics::{parse_diagnostic, RustcDiagnostic};
+use crate::report::{read_reports, ProcedureStatus};
+use crate::server::{executable_name, VIPER_PROGRAM_NAMESPACE};
+use log::{debug, info};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+/// What a verification run verifies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The Cargo package with the given manifest directory, verified with `cargo-prusti`.
+    Package(PathBuf),
+    /// A file outside of any Cargo package, verified on its own with `prusti-rustc`.
+    File(PathBuf),
+}
+
+impl Target {
+    /// The directory in which the driver runs, to which the file names in its output are
+    /// relative.
+    pub fn base_dir(&self) -> &Path {
+        match self {
+            Target::Package(dir) => dir,
+            Target::File(file) => file.parent().unwrap_or_else(|| Path::new(".")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Job {
+    /// Verify the whole target.
+    Verify(Target),
+    /// Verify a single function of the target, identified by its def path.
+    VerifyFunction(Target, String),
+    /// Verify a single function of the target and show the Viper program that encodes it.
+    ShowViperEncoding(Target, String),
+}
+
+impl Job {
+    pub fn target(&self) -> &Target {
+        match self {
+            Job::Verify(target)
+            | Job::VerifyFunction(target, _)
+            | Job::ShowViperEncoding(target, _) => target,
+        }
+    }
+
+    /// The function to verify, if the job does not verify the whole target.
+    pub fn function(&self) -> Option<&str> {
+        match self {
+            Job::Verify(_) => None,
+            Job::VerifyFunction(_, def_path) | Job::ShowViperEncoding(_, def_path) => {
+                Some(def_path)
+            }
+        }
+    }
+}
+
+/// The outcome of a verification run.
+pub struct RunOutput {
+    pub diagnostics: Vec<RustcDiagnostic>,
+    pub procedures: Vec<ProcedureStatus>,
+    /// The Viper programs that have been verified during the run.
+    pub viper_programs: Vec<PathBuf>,
+}
+
+pub struct Runner {
+    /// The directory that contains the Prusti binaries.
+    prusti_home: PathBuf,
+    /// The directory in which the reports, configuration files and temporary outputs of the
+    /// runs are written.
+    work_dir: PathBuf,
+    /// The directory to which `prusti-server` dumps the Viper programs.
+    server_log_dir: PathBuf,
+    server_address: String,
+}
+
+impl Runner {
+    pub fn new(
+        prusti_home: PathBuf,
+        work_dir: PathBuf,
+        server_log_dir: PathBuf,
+        server_address: String,
+    ) -> Self {
+        Runner {
+            prusti_home,
+            work_dir,
+            server_log_dir,
+            server_address,
+        }
+    }
+
+    pub fn run(&self, job: &Job) -> io::Result<RunOutput> {
+        let target = job.target();
+        let report_dir = self.work_dir.join("reports");
+        let out_dir = self.work_dir.join("out");
+        let _ = fs::remove_dir_all(&report_dir);
+        fs::create_dir_all(&report_dir)?;
+        fs::create_dir_all(&out_dir)?;
+
+        let mut cmd = match target {
+            Target::Package(_) => {
+                let mut cmd =
+                    Command::new(self.prusti_home.join(executable_name("cargo-prusti")));
+                cmd.args(&["--message-format=json", "--color=never"]);
+                cmd
+            }
+            Target::File(file) => {
+                let mut cmd =
+                    Command::new(self.prusti_home.join(executable_name("prusti-rustc")));
+                cmd.args(&["--error-format=json", "--color=never", "--edition=2018"])
+                    .args(&["--crate-type=lib", "--out-dir"])
+                    .arg(&out_dir)
+                    .arg(file);
+                cmd
+            }
+        };
+        cmd.current_dir(target.base_dir())
+            .env("PRUSTI_SERVER_ADDRESS", &self.server_address)
+            .env("PRUSTI_JSON_REPORT_DIR", &report_dir)
+            .stdin(Stdio::null());
+        if let Some(def_path) = job.function() {
+            // Lists can be given only in configuration files.
+            let config_path = self.work_dir.join("Prusti.toml");
+            fs::write(
+                &config_path,
+                format!(
+                    "ENABLE_WHITELIST = true\nWHITELIST = [{:?}]\n",
+                    def_path
+                ),
+            )?;
+            cmd.env("PRUSTI_CONFIG", &config_path)
+                // The function must be verified again, even if it did not change.
+                .env("PRUSTI_INCREMENTAL_VERIFICATION", "false");
+        }
+
+        info!("Running {:?}", cmd);
+        let start = SystemTime::now();
+        let output = cmd.output()?;
+        debug!("The driver exited with {}", output.status);
+
+        // `cargo-prusti` prints the messages of Cargo on the standard output, `prusti-rustc` the
+        // diagnostics on the standard error.
+        let diagnostics = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+            .filter_map(parse_diagnostic)
+            .collect();
+        let procedures = read_reports(&report_dir, target.base_dir());
+        let viper_programs = self.viper_programs_since(start);
+        Ok(RunOutput {
+            diagnostics,
+            procedures,
+            viper_programs,
+        })
+    }
+
+    /// The Viper programs dumped by `prusti-server` since the given time.
+    fn viper_programs_since(&self, start: SystemTime) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(self.server_log_dir.join(VIPER_PROGRAM_NAMESPACE)) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        let mut programs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map_or(false, |modified| modified >= start)
+            })
+            .map(|entry| entry.path())
+            .collect();
+        programs.sort();
+        programs
+    }
+}
@@ -0,0 +1,94 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The `prusti-server` process that keeps the JVM and the verifiers warm across verifications.
+
+use log::{info, warn};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+/// The namespace (i.e. subdirectory of the log directory) in which `prusti-server` dumps the
+/// verified Viper programs.
+pub const VIPER_PROGRAM_NAMESPACE: &str = "viper_program";
+
+pub struct PrustiServerProcess {
+    child: Child,
+    address: String,
+}
+
+impl PrustiServerProcess {
+    /// Launch `prusti-server` on a free port. The server dumps the Viper programs that it
+    /// verifies to `log_dir`.
+    pub fn launch(prusti_home: &Path, log_dir: &Path) -> io::Result<Self> {
+        let mut child = Command::new(prusti_home.join(executable_name("prusti-server")))
+            .args(&["--port", "0"])
+            .env("PRUSTI_DUMP_VIPER_PROGRAM", "true")
+            .env("PRUSTI_LOG_DIR", log_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            // The standard output of the language server is reserved for the protocol.
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let port = loop {
+            match lines.next() {
+                Some(line) => {
+                    let line = line?;
+                    if let Some(port) = line.strip_prefix("port: ") {
+                        break port.trim().to_string();
+                    }
+                    info!("prusti-server: {}", line);
+                }
+                None => {
+                    let _ = child.wait();
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "prusti-server exited before reporting its port",
+                    ));
+                }
+            }
+        };
+        // Keep draining the output, so that the server never blocks on a full pipe.
+        thread::spawn(move || {
+            for line in lines {
+                match line {
+                    Ok(line) => info!("prusti-server: {}", line),
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let address = format!("localhost:{}", port);
+        info!("prusti-server is listening on {}", address);
+        Ok(PrustiServerProcess { child, address })
+    }
+
+    /// The address to pass to the driver with `PRUSTI_SERVER_ADDRESS`.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+impl Drop for PrustiServerProcess {
+    fn drop(&mut self) {
+        if let Err(err) = self.child.kill() {
+            warn!("Failed to stop prusti-server: {}", err);
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// The name of the executable of a Prusti binary on the current platform.
+pub fn executable_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,244 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The thread that runs the verifications, one at a time, and publishes their results.
+
+use crate::diagnostics::to_lsp_diagnostics;
+use crate::report::ProcedureStatus;
+use crate::runner::{Job, RunOutput, Runner, Target};
+use crossbeam_channel::Sender;
+use log::{error, info};
+use lsp_server::{Message, Notification, Request, RequestId};
+use lsp_types::notification::{LogMessage, PublishDiagnostics, ShowMessage};
+use lsp_types::{
+    Diagnostic, LogMessageParams, MessageType, PublishDiagnosticsParams, ShowMessageParams, Url,
+};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+
+/// The results of the verifications, shared with the thread that answers the requests.
+#[derive(Default)]
+pub struct State {
+    pub procedures: Vec<ProcedureStatus>,
+    pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+}
+
+/// The features of the client that the server uses, if supported.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientSupport {
+    /// `workspace/codeLens/refresh`
+    pub code_lens_refresh: bool,
+    /// `window/showDocument`
+    pub show_document: bool,
+}
+
+impl ClientSupport {
+    pub fn from_initialize_params(params: &serde_json::Value) -> Self {
+        let capabilities = &params["capabilities"];
+        ClientSupport {
+            code_lens_refresh: capabilities["workspace"]["codeLens"]["refreshSupport"]
+                .as_bool()
+                .unwrap_or(false),
+            show_document: capabilities["window"]["showDocument"]["support"]
+                .as_bool()
+                .unwrap_or(false),
+        }
+    }
+}
+
+pub struct Worker {
+    runner: Runner,
+    state: Arc<Mutex<State>>,
+    sender: Sender<Message>,
+    client_support: ClientSupport,
+    next_request_id: AtomicU64,
+}
+
+impl Worker {
+    pub fn new(
+        runner: Runner,
+        state: Arc<Mutex<State>>,
+        sender: Sender<Message>,
+        client_support: ClientSupport,
+    ) -> Self {
+        Worker {
+            runner,
+            state,
+            sender,
+            client_support,
+            next_request_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Run the jobs until all the senders of the channel are dropped. The jobs that have been
+    /// queued while running a verification are run only once.
+    pub fn run(self, jobs: Receiver<Job>) {
+        while let Ok(job) = jobs.recv() {
+            let mut pending = vec![job];
+            pending.extend(jobs.try_iter());
+            let mut seen = HashSet::new();
+            pending.retain(|job| seen.insert(job.clone()));
+            for job in pending {
+                self.run_job(&job);
+            }
+        }
+    }
+
+    fn run_job(&self, job: &Job) {
+        info!("Running {:?}", job);
+        let output = match self.runner.run(job) {
+            Ok(output) => output,
+            Err(err) => {
+                error!("Failed to run Prusti: {}", err);
+                self.notify::<ShowMessage>(ShowMessageParams {
+                    typ: MessageType::Error,
+                    message: format!("Failed to run Prusti: {}", err),
+                });
+                return;
+            }
+        };
+        let viper_programs = output.viper_programs.clone();
+        self.update_state(job, output);
+        self.notify::<LogMessage>(LogMessageParams {
+            typ: MessageType::Info,
+            message: format!("Prusti: finished {}", describe(job)),
+        });
+        if self.client_support.code_lens_refresh {
+            self.request("workspace/codeLens/refresh", json!(null));
+        }
+        if let Job::ShowViperEncoding(..) = job {
+            self.show_viper_programs(&viper_programs);
+        }
+    }
+
+    /// Record the results of the run and publish the diagnostics of the files that changed.
+    fn update_state(&self, job: &Job, output: RunOutput) {
+        let target = job.target();
+        let mut new_diagnostics = to_lsp_diagnostics(&output.diagnostics, target.base_dir());
+        let mut state = self.state.lock().unwrap();
+
+        let mut changed_files: HashSet<PathBuf> = new_diagnostics.keys().cloned().collect();
+        match job.function() {
+            None => {
+                for (file, diagnostics) in state.diagnostics.iter_mut() {
+                    if covers(target, file) && !diagnostics.is_empty() {
+                        diagnostics.clear();
+                        changed_files.insert(file.clone());
+                    }
+                }
+                state.procedures.retain(|procedure| !covers(target, &procedure.file));
+            }
+            Some(def_path) => {
+                // Only the diagnostics of the function are replaced; the other diagnostics of
+                // the run (e.g. warnings of the compiler) have already been reported.
+                let old_status = state
+                    .procedures
+                    .iter()
+                    .find(|procedure| {
+                        procedure.def_path == def_path && covers(target, &procedure.file)
+                    })
+                    .cloned();
+                if let Some(old_status) = old_status {
+                    for (file, diagnostics) in new_diagnostics.iter_mut() {
+                        diagnostics.retain(|diagnostic| {
+                            old_status.contains(file, diagnostic.range.start)
+                        });
+                    }
+                    if let Some(diagnostics) = state.diagnostics.get_mut(&old_status.file) {
+                        diagnostics.retain(|diagnostic| {
+                            !old_status.contains(&old_status.file, diagnostic.range.start)
+                        });
+                        changed_files.insert(old_status.file.clone());
+                    }
+                }
+                state.procedures.retain(|procedure| {
+                    procedure.def_path != def_path || !covers(target, &procedure.file)
+                });
+            }
+        }
+        for (file, diagnostics) in new_diagnostics {
+            state
+                .diagnostics
+                .entry(file)
+                .or_insert_with(Vec::new)
+                .extend(diagnostics);
+        }
+        state.procedures.extend(output.procedures);
+
+        for file in changed_files {
+            if let Ok(uri) = Url::from_file_path(&file) {
+                let diagnostics = state.diagnostics.get(&file).cloned().unwrap_or_default();
+                self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+                    uri,
+                    diagnostics,
+                    None,
+                ));
+            }
+        }
+    }
+
+    fn show_viper_programs(&self, programs: &[PathBuf]) {
+        if programs.is_empty() {
+            self.notify::<ShowMessage>(ShowMessageParams {
+                typ: MessageType::Warning,
+                message: "Prusti did not verify any Viper program".to_string(),
+            });
+            return;
+        }
+        for program in programs {
+            let uri = match Url::from_file_path(program) {
+                Ok(uri) => uri,
+                Err(()) => continue,
+            };
+            if self.client_support.show_document {
+                self.request("window/showDocument", json!({ "uri": uri, "takeFocus": true }));
+            } else {
+                self.notify::<ShowMessage>(ShowMessageParams {
+                    typ: MessageType::Info,
+                    message: format!("Viper encoding: {}", program.display()),
+                });
+            }
+        }
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        // The connection is closed only when the server exits.
+        let _ = self.sender.send(notification.into());
+    }
+
+    /// Send a request to the client, whose response is ignored.
+    fn request(&self, method: &str, params: serde_json::Value) {
+        let id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+        let request = Request::new(
+            RequestId::from(format!("prusti-{}", id)),
+            method.to_string(),
+            params,
+        );
+        let _ = self.sender.send(request.into());
+    }
+}
+
+/// Whether the results of a run on the target cover the given file.
+fn covers(target: &Target, file: &Path) -> bool {
+    match target {
+        Target::Package(root) => file.starts_with(root),
+        Target::File(target_file) => file == target_file,
+    }
+}
+
+fn describe(job: &Job) -> String {
+    match job {
+        Job::Verify(Target::Package(root)) => format!("verification of {}", root.display()),
+        Job::Verify(Target::File(file)) => format!("verification of {}", file.display()),
+        Job::VerifyFunction(_, def_path) => format!("verification of {}", def_path),
+        Job::ShowViperEncoding(_, def_path) => format!("encoding of {}", def_path),
+    }
+}
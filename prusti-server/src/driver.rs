@@ -7,10 +7,14 @@
 extern crate clap;
 extern crate env_logger;
 extern crate log;
+extern crate prusti_common;
 extern crate prusti_server;
+extern crate viper;
 
 use clap::{App, Arg};
-use prusti_server::ServerSideService;
+use prusti_common::verification_context::VerifierBuilder;
+use prusti_server::{load_request, replay_request, ServerSideService};
+use viper::VerificationResult;
 
 fn main() {
     env_logger::init_from_env(
@@ -23,12 +27,26 @@ fn main() {
                 .short("p")
                 .long("port")
                 .help("Sets the port on which to listen for incoming verification requests. Pass 0 to get a free one assigned by the OS.")
-                .required(true)
+                .required_unless("replay")
                 .takes_value(true)
                 .value_name("PORT"),
         )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .help("Verifies the requests recorded with RECORD_VERIFICATION_REQUESTS, prints the results and exits.")
+                .conflicts_with("port")
+                .takes_value(true)
+                .multiple(true)
+                .value_name("FILE"),
+        )
         .get_matches();
 
+    if let Some(files) = matches.values_of("replay") {
+        let all_succeeded = replay(files.collect());
+        std::process::exit(if all_succeeded { 0 } else { 1 });
+    }
+
     let port = matches
         .value_of("port")
         .unwrap()
@@ -38,3 +56,40 @@ fn main() {
     let service = ServerSideService::new();
     service.listen_on_port(port);
 }
+
+/// Replays the recorded requests, returning whether all of them verified successfully.
+fn replay(files: Vec<&str>) -> bool {
+    let verifier_builder = VerifierBuilder::new();
+    let mut all_succeeded = true;
+    for file in files {
+        let request = match load_request(file) {
+            Ok(request) => request,
+            Err(message) => {
+                eprintln!("{}", message);
+                all_succeeded = false;
+                continue;
+            }
+        };
+        let program_name = request.program_name.clone();
+        match replay_request(&verifier_builder, request) {
+            VerificationResult::Success() => {
+                println!("{} ({}): verification succeeded", file, program_name);
+            }
+            VerificationResult::Failure(errors) => {
+                println!("{} ({}): verification failed", file, program_name);
+                for error in errors {
+                    println!("  {} ({})", error.message, error.full_id);
+                }
+                all_succeeded = false;
+            }
+            VerificationResult::ConsistencyErrors(errors) => {
+                println!("{} ({}): consistency errors", file, program_name);
+                for error in errors {
+                    println!("  {}", error);
+                }
+                all_succeeded = false;
+            }
+        }
+    }
+    all_succeeded
+}
@@ -13,16 +13,19 @@ extern crate bincode;
 extern crate futures;
 extern crate num_cpus;
 extern crate prusti_common;
+extern crate serde_json;
 extern crate tokio;
 #[macro_use]
 extern crate serde_derive;
 
+mod request_recording;
 mod service;
 mod verifier_runner;
 mod verifier_thread;
 
 use futures::Future;
 use prusti_common::{verification_context::VerifierBuilder, verification_service::*, Stopwatch};
+pub use request_recording::*;
 pub use service::*;
 use std::{
     collections::VecDeque,
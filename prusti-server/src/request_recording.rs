@@ -0,0 +1,50 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Recording and replaying of verification requests, to reproduce the verification of a crate
+//! without the crate and the Rust toolchain that produced it.
+
+use prusti_common::{
+    report::log, verification_context::VerifierBuilder, verification_service::VerificationRequest,
+};
+use serde_json;
+use std::{fs, path::Path};
+use verifier_runner::VerifierRunner;
+use viper::VerificationResult;
+
+/// Saves the request as JSON to `<LOG_DIR>/verification_requests/<program name>.json`, from where
+/// it can be replayed with `prusti-server --replay <file>`.
+pub fn record_request(request: &VerificationRequest) {
+    let namespace = "verification_requests";
+    let filename = format!("{}.json", request.program_name);
+    info!(
+        "Recording verification request to '{}/{}'",
+        namespace, filename
+    );
+    log::report_with_writer(namespace, filename, |writer| {
+        serde_json::to_writer(writer, request)
+            .unwrap_or_else(|e| panic!("Failed to record the verification request: {}", e))
+    });
+}
+
+/// Loads a request saved by `record_request`.
+pub fn load_request<P: AsRef<Path>>(path: P) -> Result<VerificationRequest, String> {
+    let path = path.as_ref();
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+    serde_json::from_reader(file)
+        .map_err(|e| format!("Failed to load the request '{}': {}", path.display(), e))
+}
+
+/// Verifies a loaded request with a local verifier.
+pub fn replay_request(
+    verifier_builder: &VerifierBuilder,
+    request: VerificationRequest,
+) -> VerificationResult {
+    VerifierRunner::with_runner(verifier_builder, &request.backend_config, |runner| {
+        runner.verify(request.program, &request.program_name)
+    })
+}
@@ -9,15 +9,15 @@ use prusti_common::{
     report::log,
     verification_context::*,
     verification_service::ViperBackendConfig,
-    vir::{Program, ToViper},
+    vir::{Program, ToViper, ToViperSyntax},
     Stopwatch,
 };
-use viper::{self, VerificationResult};
+use std::sync::mpsc;
+use viper::{self, VerificationMessage, VerificationResult};
 
 pub struct VerifierRunner<'v> {
     verifier: viper::Verifier<'v, viper::state::Started>,
     ast_factory: viper::AstFactory<'v>,
-    ast_utils: viper::AstUtils<'v>,
 }
 
 impl<'v> VerifierRunner<'v> {
@@ -51,7 +51,6 @@ impl<'v> VerifierRunner<'v> {
         Self {
             verifier: context.new_viper_verifier(backend_config),
             ast_factory: context.new_ast_factory(),
-            ast_utils: context.new_ast_utils(),
         }
     }
 
@@ -60,17 +59,41 @@ impl<'v> VerifierRunner<'v> {
         let viper_program = program.to_viper(&self.ast_factory);
         if config::dump_viper_program() {
             stopwatch.start_next("dumping viper program");
-            self.dump(viper_program, program_name);
+            self.dump(&program, program_name);
         }
 
         stopwatch.start_next("verification");
-        self.verifier.verify(viper_program)
+        let (sender, receiver) = mpsc::channel();
+        let result = self.verifier.verify_with_messages(viper_program, Some(sender));
+        for message in receiver.try_iter() {
+            match message {
+                VerificationMessage::EntitySuccess {
+                    entity_name,
+                    verification_time,
+                    ..
+                } => debug!("Verified '{}' in {} ms", entity_name, verification_time),
+                VerificationMessage::EntityFailure {
+                    entity_name,
+                    verification_time,
+                    errors,
+                    ..
+                } => debug!(
+                    "Failed to verify '{}' in {} ms ({} errors)",
+                    entity_name,
+                    verification_time,
+                    errors.len()
+                ),
+                _ => trace!("Viper message: {:?}", message),
+            }
+        }
+        result
     }
 
-    fn dump(&self, program: viper::Program, program_name: &str) {
+    /// Dumps the program in Viper syntax, without using the JVM.
+    fn dump(&self, program: &Program, program_name: &str) {
         let namespace = "viper_program";
         let filename = format!("{}.vpr", program_name);
         info!("Dumping Viper program to '{}/{}'", namespace, filename);
-        log::report(namespace, filename, self.ast_utils.pretty_print(program));
+        log::report(namespace, filename, program.to_viper_syntax());
     }
 }
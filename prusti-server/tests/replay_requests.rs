@@ -0,0 +1,42 @@
+extern crate prusti_common;
+extern crate prusti_server;
+extern crate serde_json;
+extern crate viper;
+
+use prusti_common::{
+    verification_context::VerifierBuilder, verification_service::VerificationRequest, vir::*,
+};
+use prusti_server::{load_request, replay_request};
+use std::{env, fs};
+use viper::VerificationResult;
+
+#[test]
+fn replay_saved_request() {
+    let request = VerificationRequest {
+        program: Program {
+            domains: vec![],
+            fields: vec![Field::new("val_int", Type::Int)],
+            builtin_methods: vec![],
+            methods: vec![],
+            functions: vec![],
+            viper_predicates: vec![],
+        },
+        program_name: "saved".to_string(),
+        backend_config: Default::default(),
+    };
+    let path = env::temp_dir().join("prusti-server-saved-request.json");
+    fs::write(&path, serde_json::to_string(&request).unwrap()).unwrap();
+
+    let loaded = load_request(&path).unwrap();
+    assert_eq!(loaded.program_name, request.program_name);
+    assert_eq!(loaded.backend_config, request.backend_config);
+
+    let result = replay_request(&VerifierBuilder::new(), loaded);
+    assert_eq!(result, VerificationResult::Success());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn load_missing_request() {
+    assert!(load_request("does-not-exist.json").is_err());
+}
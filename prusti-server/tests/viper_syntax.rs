@@ -0,0 +1,114 @@
+extern crate prusti_common;
+extern crate viper;
+
+use prusti_common::{
+    verification_context::VerifierBuilder,
+    vir::*,
+};
+use std::path::Path;
+
+fn build_program() -> Program {
+    let ref_type = Type::TypedRef("i32".to_string());
+    let val_field = Field::new("val_int", Type::Int);
+
+    let x = LocalVar::new("x", Type::Int);
+    let function = Function {
+        name: "inc".to_string(),
+        formal_args: vec![x.clone()],
+        return_type: Type::Int,
+        pres: vec![Expr::le_cmp(0.into(), x.clone().into())],
+        posts: vec![Expr::lt_cmp(x.clone().into(), LocalVar::new("__result", Type::Int).into())],
+        body: Some(Expr::add(x.clone().into(), 1.into())),
+    };
+
+    // Not built with `conjoin`, whose trailing `true` would be simplified by the JVM.
+    let this = Predicate::construct_this(ref_type.clone());
+    let this_val = Expr::from(this.clone()).field(val_field.clone());
+    let predicate = Predicate::Struct(StructPredicate {
+        name: "i32".to_string(),
+        this,
+        body: Some(Expr::and(
+            Expr::acc_permission(this_val.clone(), PermAmount::Write),
+            Expr::le_cmp(0.into(), this_val),
+        )),
+    });
+
+    let r = LocalVar::new("r", ref_type.clone());
+    let r_val = Expr::from(r.clone()).field(val_field.clone());
+    let mut method = CfgMethod::new("m".to_string(), 0, vec![r.clone()], vec![x.clone()], vec![]);
+    let start = method.add_block(
+        "start",
+        vec![],
+        vec![
+            Stmt::Inhale(
+                Expr::acc_permission(r_val.clone(), PermAmount::Write),
+                FoldingBehaviour::None,
+            ),
+            Stmt::Assign(r_val.clone(), x.clone().into(), AssignKind::Copy),
+        ],
+    );
+    let positive = method.add_block(
+        "positive",
+        vec![Expr::lt_cmp(0.into(), x.clone().into())],
+        vec![Stmt::Fold(
+            "i32".to_string(),
+            vec![r.clone().into()],
+            PermAmount::Write,
+            None,
+            Position::default(),
+        )],
+    );
+    let other = method.add_block(
+        "other",
+        vec![],
+        vec![Stmt::Assert(
+            Expr::le_cmp(r_val.clone(), 0.into()),
+            FoldingBehaviour::None,
+            Position::new(1, 1, 1),
+        )],
+    );
+    method.set_successor(
+        start,
+        Successor::GotoSwitch(
+            vec![(Expr::lt_cmp(0.into(), x.clone().into()), positive)],
+            other,
+        ),
+    );
+    method.set_successor(positive, Successor::Return);
+    method.set_successor(other, Successor::Return);
+
+    Program {
+        domains: vec![],
+        fields: vec![val_field],
+        builtin_methods: vec![BodylessMethod {
+            name: "builtin$havoc_int".to_string(),
+            formal_args: vec![],
+            formal_returns: vec![LocalVar::new("ret", Type::Int)],
+        }],
+        methods: vec![method],
+        functions: vec![function],
+        viper_predicates: vec![predicate],
+    }
+}
+
+#[test]
+fn native_printer_agrees_with_jvm_printer() {
+    let program = build_program();
+    let native = program.to_viper_syntax();
+
+    let verifier_builder = VerifierBuilder::new();
+    let context = verifier_builder.new_verification_context();
+    let ast_factory = context.new_ast_factory();
+    let ast_utils = context.new_ast_utils();
+    let jvm = ast_utils.pretty_print(program.to_viper(&ast_factory));
+
+    // The printers may place brackets differently, so the native output is parsed back and
+    // printed by the JVM printer. Any difference in the structure of the program remains.
+    let parser = context.new_program_parser();
+    let parsed = parser
+        .parse(&native, Path::new("native.vpr"))
+        .unwrap_or_else(|messages| panic!("native:\n{}\nerrors: {:?}", native, messages));
+    let reprinted = ast_utils.pretty_print(parsed);
+
+    assert_eq!(reprinted, jvm, "native:\n{}\njvm:\n{}", native, jvm);
+}
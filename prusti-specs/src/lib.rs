@@ -2,7 +2,8 @@
 #![feature(box_patterns)]
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
 
 use specifications::untyped;
@@ -19,36 +20,130 @@ macro_rules! handle_result {
     };
 }
 
-pub fn requires(attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    let item: syn::ItemFn = handle_result!(syn::parse2(tokens));
-    let mut rewriter = rewriter::AstRewriter::new();
-    let spec_id = rewriter.generate_spec_id();
-    let spec_id_str = spec_id.to_string();
-    let assertion = handle_result!(rewriter.parse_assertion(spec_id, attr));
-    let spec_item =
-        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Precondition, spec_id, assertion, &item));
-    quote! {
-        #spec_item
-        #[prusti::pre_spec_id_ref = #spec_id_str]
-        #item
+/// The kinds of specification attributes that generate specification items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SpecAttributeKind {
+    Requires,
+    Ensures,
+    AfterExpiry,
+    AfterExpiryIf,
+}
+
+/// A function item, a method of an `impl` block, or a method declaration of a trait, which may
+/// have no body.
+struct AnyFnItem {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    sig: syn::Signature,
+    block: Option<syn::Block>,
+}
+
+impl Parse for AnyFnItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig = input.parse()?;
+        let block = if input.peek(syn::Token![;]) {
+            input.parse::<syn::Token![;]>()?;
+            None
+        } else {
+            Some(input.parse()?)
+        };
+        Ok(AnyFnItem { attrs, vis, sig, block })
     }
 }
 
-pub fn ensures(attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    let item: syn::ItemFn = handle_result!(syn::parse2(tokens));
+impl ToTokens for AnyFnItem {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let AnyFnItem { attrs, vis, sig, block } = self;
+        let block = match block {
+            Some(block) => quote! { #block },
+            None => quote! { ; },
+        };
+        tokens.extend(quote! {
+            #(#attrs)*
+            #vis #sig #block
+        });
+    }
+}
+
+/// Generate the specification items of the attribute `kind(attr)` of the function with
+/// signature `sig`, and the attribute that refers to them.
+fn generate_spec_items(
+    kind: SpecAttributeKind,
+    attr: TokenStream,
+    sig: &syn::Signature,
+) -> syn::Result<(Vec<syn::Item>, syn::Attribute)> {
     let mut rewriter = rewriter::AstRewriter::new();
-    let spec_id = rewriter.generate_spec_id();
-    let spec_id_str = spec_id.to_string();
-    let assertion = handle_result!(rewriter.parse_assertion(spec_id, attr));
-    let spec_item =
-        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Postcondition, spec_id, assertion, &item));
+    match kind {
+        SpecAttributeKind::Requires => {
+            let spec_id = rewriter.generate_spec_id();
+            let spec_id_str = spec_id.to_string();
+            let assertion = rewriter.parse_assertion(spec_id, attr)?;
+            let spec_item = rewriter.generate_spec_item_fn(
+                rewriter::SpecItemType::Precondition, spec_id, assertion, sig)?;
+            Ok((vec![spec_item], syn::parse_quote! { #[prusti::pre_spec_id_ref = #spec_id_str] }))
+        }
+        SpecAttributeKind::Ensures => {
+            let spec_id = rewriter.generate_spec_id();
+            let spec_id_str = spec_id.to_string();
+            let assertion = rewriter.parse_assertion(spec_id, attr)?;
+            let spec_item = rewriter.generate_spec_item_fn(
+                rewriter::SpecItemType::Postcondition, spec_id, assertion, sig)?;
+            Ok((vec![spec_item], syn::parse_quote! { #[prusti::post_spec_id_ref = #spec_id_str] }))
+        }
+        SpecAttributeKind::AfterExpiry => {
+            let spec_id_rhs = rewriter.generate_spec_id();
+            let spec_id_rhs_str = format!(":{}", spec_id_rhs);
+            let pledge = rewriter.parse_pledge(None, spec_id_rhs, attr)?;
+            check_is_result(&pledge.reference)?;
+            assert!(pledge.lhs.is_none(), "after_expiry with lhs?");
+            let spec_item_rhs = rewriter.generate_spec_item_fn(
+                rewriter::SpecItemType::Postcondition, spec_id_rhs, pledge.rhs, sig)?;
+            Ok((vec![spec_item_rhs], syn::parse_quote! { #[prusti::pledge_spec_id_ref = #spec_id_rhs_str] }))
+        }
+        SpecAttributeKind::AfterExpiryIf => {
+            let spec_id_lhs = rewriter.generate_spec_id();
+            let spec_id_rhs = rewriter.generate_spec_id();
+            let spec_id_str = format!("{}:{}", spec_id_lhs, spec_id_rhs);
+            let pledge = rewriter.parse_pledge(Some(spec_id_lhs), spec_id_rhs, attr)?;
+            check_is_result(&pledge.reference)?;
+            let spec_item_lhs = rewriter.generate_spec_item_fn(
+                rewriter::SpecItemType::Postcondition, spec_id_lhs, pledge.lhs.unwrap(), sig)?;
+            let spec_item_rhs = rewriter.generate_spec_item_fn(
+                rewriter::SpecItemType::Postcondition, spec_id_rhs, pledge.rhs, sig)?;
+            Ok((vec![spec_item_lhs, spec_item_rhs], syn::parse_quote! { #[prusti::pledge_spec_id_ref = #spec_id_str] }))
+        }
+    }
+}
+
+/// Rewrite a function that has the specification attribute `kind(attr)`. The specification
+/// items are emitted next to the function, which works for functions, for methods of inherent
+/// `impl` blocks and for method declarations of traits. The methods of trait implementations
+/// cannot have specifications, because a trait implementation cannot contain the specification
+/// items.
+fn rewrite_spec_attribute(
+    kind: SpecAttributeKind,
+    attr: TokenStream,
+    tokens: TokenStream,
+) -> TokenStream {
+    let item: AnyFnItem = handle_result!(syn::parse2(tokens));
+    let (spec_items, spec_attr) = handle_result!(generate_spec_items(kind, attr, &item.sig));
     quote! {
-        #spec_item
-        #[prusti::post_spec_id_ref = #spec_id_str]
+        #(#spec_items)*
+        #spec_attr
         #item
     }
 }
 
+pub fn requires(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_spec_attribute(SpecAttributeKind::Requires, attr, tokens)
+}
+
+pub fn ensures(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_spec_attribute(SpecAttributeKind::Ensures, attr, tokens)
+}
+
 /// Check if the given expression is identifier `result`.
 fn check_is_result(reference: &Option<untyped::Expression>) -> syn::Result<()> {
     if let Some(untyped::Expression { expr, ..}) = reference {
@@ -67,40 +162,11 @@ fn check_is_result(reference: &Option<untyped::Expression>) -> syn::Result<()> {
 }
 
 pub fn after_expiry(attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    let item: syn::ItemFn = handle_result!(syn::parse2(tokens));
-    let mut rewriter = rewriter::AstRewriter::new();
-    let spec_id_rhs = rewriter.generate_spec_id();
-    let spec_id_rhs_str = format!(":{}", spec_id_rhs);
-    let pledge = handle_result!(rewriter.parse_pledge(None, spec_id_rhs, attr));
-    handle_result!(check_is_result(&pledge.reference));
-    assert!(pledge.lhs.is_none(), "after_expiry with lhs?");
-    let spec_item_rhs =
-        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Postcondition, spec_id_rhs, pledge.rhs, &item));
-    quote! {
-        #spec_item_rhs
-        #[prusti::pledge_spec_id_ref = #spec_id_rhs_str]
-        #item
-    }
+    rewrite_spec_attribute(SpecAttributeKind::AfterExpiry, attr, tokens)
 }
 
 pub fn after_expiry_if(attr: TokenStream, tokens: TokenStream) -> TokenStream {
-    let item: syn::ItemFn = handle_result!(syn::parse2(tokens));
-    let mut rewriter = rewriter::AstRewriter::new();
-    let spec_id_lhs = rewriter.generate_spec_id();
-    let spec_id_rhs = rewriter.generate_spec_id();
-    let spec_id_str = format!("{}:{}", spec_id_lhs, spec_id_rhs);
-    let pledge = handle_result!(rewriter.parse_pledge(Some(spec_id_lhs), spec_id_rhs, attr));
-    handle_result!(check_is_result(&pledge.reference));
-    let spec_item_lhs =
-        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Postcondition, spec_id_lhs, pledge.lhs.unwrap(), &item));
-    let spec_item_rhs =
-        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Postcondition, spec_id_rhs, pledge.rhs, &item));
-    quote! {
-        #spec_item_lhs
-        #spec_item_rhs
-        #[prusti::pledge_spec_id_ref = #spec_id_str]
-        #item
-    }
+    rewrite_spec_attribute(SpecAttributeKind::AfterExpiryIf, attr, tokens)
 }
 
 pub fn pure(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
@@ -117,6 +183,41 @@ pub fn trusted(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     }
 }
 
+pub fn skip(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #[prusti::skip]
+        #tokens
+    }
+}
+
+pub fn verify(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #[prusti::verify]
+        #tokens
+    }
+}
+
+pub fn timeout(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #[prusti::timeout(#attr)]
+        #tokens
+    }
+}
+
+pub fn backend(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #[prusti::backend(#attr)]
+        #tokens
+    }
+}
+
+pub fn verifier_args(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #[prusti::verifier_args(#attr)]
+        #tokens
+    }
+}
+
 pub fn invariant(tokens: TokenStream) -> TokenStream {
     let mut rewriter = rewriter::AstRewriter::new();
     let spec_id = rewriter.generate_spec_id();
@@ -128,3 +229,67 @@ pub fn invariant(tokens: TokenStream) -> TokenStream {
         }
     }
 }
+
+/// A function whose body is an assertion, which is not a valid Rust block in general.
+struct PredicateFn {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    sig: syn::Signature,
+    body: TokenStream,
+}
+
+impl Parse for PredicateFn {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let body = content.parse()?;
+        Ok(PredicateFn { attrs, vis, sig, body })
+    }
+}
+
+/// Check if the predicate returns `bool`.
+fn check_returns_bool(sig: &syn::Signature) -> syn::Result<()> {
+    if let syn::ReturnType::Type(_, box syn::Type::Path(syn::TypePath { qself: None, path })) =
+        &sig.output
+    {
+        if path.is_ident("bool") {
+            return Ok(());
+        }
+    }
+    Err(syn::Error::new(
+        sig.span(),
+        "the return type of a predicate must be `bool`".to_string(),
+    ))
+}
+
+pub fn predicate(tokens: TokenStream) -> TokenStream {
+    let predicate: PredicateFn = handle_result!(syn::parse2(tokens));
+    handle_result!(check_returns_bool(&predicate.sig));
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    let assertion = handle_result!(rewriter.parse_assertion(spec_id, predicate.body));
+    let item = syn::ItemFn {
+        attrs: Vec::new(),
+        vis: predicate.vis,
+        sig: predicate.sig,
+        block: box syn::parse_quote! {{
+            unreachable!("predicates cannot be executed")
+        }},
+    };
+    let spec_item =
+        handle_result!(rewriter.generate_spec_item_fn(rewriter::SpecItemType::Predicate, spec_id, assertion, &item.sig));
+    let attrs = predicate.attrs;
+    quote! {
+        #spec_item
+        #(#attrs)*
+        #[allow(unused_variables, dead_code)]
+        #[prusti::pure]
+        #[prusti::trusted]
+        #[prusti::pred_spec_id_ref = #spec_id_str]
+        #item
+    }
+}
@@ -13,6 +13,7 @@ pub(crate) struct AstRewriter {
 pub enum SpecItemType {
     Precondition,
     Postcondition,
+    Predicate,
 }
 
 impl std::fmt::Display for SpecItemType {
@@ -20,6 +21,7 @@ impl std::fmt::Display for SpecItemType {
         match self {
             SpecItemType::Precondition => write!(f, "pre"),
             SpecItemType::Postcondition => write!(f, "post"),
+            SpecItemType::Predicate => write!(f, "pred"),
         }
 
     }
@@ -52,10 +54,10 @@ impl AstRewriter {
     ) -> syn::Result<untyped::Pledge> {
         untyped::Pledge::parse(tokens, spec_id_lhs, spec_id_rhs, &mut self.expr_id_generator)
     }
-    /// Check whether function `item` contains a parameter called `keyword`. If
-    /// yes, return its span.
-    fn check_contains_keyword_in_params(&self, item: &syn::ItemFn, keyword: &str) -> Option<Span> {
-        for param in &item.sig.inputs {
+    /// Check whether the function with signature `sig` contains a parameter called `keyword`.
+    /// If yes, return its span.
+    fn check_contains_keyword_in_params(&self, sig: &syn::Signature, keyword: &str) -> Option<Span> {
+        for param in &sig.inputs {
             match param {
                 syn::FnArg::Typed(syn::PatType {
                     pat: box syn::Pat::Ident(syn::PatIdent { ident, .. }),
@@ -70,8 +72,8 @@ impl AstRewriter {
         }
         None
     }
-    fn generate_result_arg(&self, item: &syn::ItemFn) -> syn::FnArg {
-        let output_ty = match &item.sig.output {
+    fn generate_result_arg(&self, sig: &syn::Signature) -> syn::FnArg {
+        let output_ty = match &sig.output {
             syn::ReturnType::Default => syn::parse_quote!{ () },
             syn::ReturnType::Type(_, ty) => ty.clone(),
         };
@@ -79,31 +81,30 @@ impl AstRewriter {
             syn::PatType {
                 attrs: Vec::new(),
                 pat: box syn::parse_quote! { result },
-                colon_token: syn::Token![:](item.sig.output.span()),
+                colon_token: syn::Token![:](sig.output.span()),
                 ty: output_ty,
             }
         );
         fn_arg
     }
-    /// Generate a dummy function for checking the given precondition or postcondition.
-    ///
-    /// `spec_type` should be either `"pre"` or `"post"`.
+    /// Generate a dummy function for checking the given precondition, postcondition or body of
+    /// a predicate of the function with signature `sig`.
     pub fn generate_spec_item_fn(
         &mut self,
         spec_type: SpecItemType,
         spec_id: untyped::SpecificationId,
         assertion: untyped::Assertion,
-        item: &syn::ItemFn,
+        sig: &syn::Signature,
     ) -> syn::Result<syn::Item> {
-        if let Some(span) = self.check_contains_keyword_in_params(item, "result") {
+        if let Some(span) = self.check_contains_keyword_in_params(sig, "result") {
             return Err(syn::Error::new(
                 span,
                 "it is not allowed to use the keyword `result` as a function argument".to_string(),
             ));
         }
         let item_name = syn::Ident::new(
-            &format!("prusti_{}_item_{}_{}", spec_type, item.sig.ident, spec_id),
-            item.span(),
+            &format!("prusti_{}_item_{}_{}", spec_type, sig.ident, spec_id),
+            sig.span(),
         );
         let mut statements = TokenStream::new();
         assertion.encode_type_check(&mut statements);
@@ -117,10 +118,10 @@ impl AstRewriter {
                 #statements
             }
         };
-        spec_item.sig.generics = item.sig.generics.clone();
-        spec_item.sig.inputs = item.sig.inputs.clone();
+        spec_item.sig.generics = sig.generics.clone();
+        spec_item.sig.inputs = sig.inputs.clone();
         if spec_type == SpecItemType::Postcondition {
-            let fn_arg = self.generate_result_arg(item);
+            let fn_arg = self.generate_result_arg(sig);
             spec_item.sig.inputs.push(fn_arg);
         }
         Ok(syn::Item::Fn(spec_item))
@@ -17,6 +17,8 @@ pub enum SpecType {
     Postcondition,
     /// Loop invariant or struct invariant
     Invariant,
+    /// Body of a predicate.
+    Predicate,
 }
 
 #[derive(Debug)]
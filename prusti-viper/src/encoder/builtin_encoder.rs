@@ -19,6 +19,106 @@ pub enum BuiltinFunctionKind {
     Unreachable(vir::Type),
     /// type
     Undefined(vir::Type),
+    /// operation, type of the operands
+    BitOperation(BitOperationKind, IntegerKind),
+}
+
+/// A bitwise operation on integers that has no exact encoding in Viper.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum BitOperationKind {
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+impl BitOperationKind {
+    fn name(&self) -> &'static str {
+        match self {
+            BitOperationKind::And => "bitand",
+            BitOperationKind::Or => "bitor",
+            BitOperationKind::Xor => "bitxor",
+            BitOperationKind::Shl => "shl",
+            BitOperationKind::Shr => "shr",
+        }
+    }
+}
+
+/// The representation of a Rust integer type.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct IntegerKind {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl IntegerKind {
+    pub fn name(&self) -> String {
+        format!("{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+
+    /// `2^exp`
+    pub fn pow2(exp: u32) -> vir::Expr {
+        if exp < 63 {
+            (1i64 << exp).into()
+        } else if exp < 128 {
+            (1u128 << exp).into()
+        } else {
+            assert_eq!(exp, 128);
+            vir::Expr::Const(
+                vir::Const::BigInt("340282366920938463463374607431768211456".to_string()),
+                vir::Position::default(),
+            )
+        }
+    }
+
+    /// The number of values of the type, i.e. `2^bits`.
+    pub fn modulus(&self) -> vir::Expr {
+        Self::pow2(self.bits)
+    }
+
+    pub fn min_value(&self) -> vir::Expr {
+        if self.signed {
+            (std::i128::MIN >> (128 - self.bits)).into()
+        } else {
+            0.into()
+        }
+    }
+
+    pub fn max_value(&self) -> vir::Expr {
+        if self.signed {
+            (std::i128::MAX >> (128 - self.bits)).into()
+        } else {
+            (std::u128::MAX >> (128 - self.bits)).into()
+        }
+    }
+
+    /// Whether all the values of `other` are values of `self`.
+    pub fn includes(&self, other: &IntegerKind) -> bool {
+        if self.signed == other.signed {
+            other.bits <= self.bits
+        } else {
+            self.signed && other.bits < self.bits
+        }
+    }
+
+    /// `min <= expr && expr <= max`
+    pub fn contains(&self, expr: vir::Expr) -> vir::Expr {
+        vir::Expr::and(
+            vir::Expr::le_cmp(self.min_value(), expr.clone()),
+            vir::Expr::le_cmp(expr, self.max_value()),
+        )
+    }
+
+    /// Encode the wrap-around of a mathematical integer to the range of the type (i.e. the
+    /// semantics of `wrapping_*` operations).
+    pub fn wrap(&self, expr: vir::Expr) -> vir::Expr {
+        // Viper's modulo is Euclidean: the result is always non-negative.
+        vir::Expr::add(
+            vir::Expr::modulo(vir::Expr::sub(expr, self.min_value()), self.modulus()),
+            self.min_value(),
+        )
+    }
 }
 
 pub struct BuiltinEncoder {}
@@ -63,6 +163,9 @@ impl BuiltinEncoder {
             BuiltinFunctionKind::Undefined(vir::Type::Bool) => format!("builtin$undef_bool"),
             BuiltinFunctionKind::Undefined(vir::Type::TypedRef(_)) => format!("builtin$undef_ref"),
             BuiltinFunctionKind::Undefined(vir::Type::Domain(_)) => format!("builtin$undef_doman"),
+            BuiltinFunctionKind::BitOperation(op, int_kind) => {
+                format!("builtin${}_{}", op.name(), int_kind.name())
+            }
         }
     }
 
@@ -86,6 +189,154 @@ impl BuiltinEncoder {
                 posts: vec![],
                 body: None,
             },
+            BuiltinFunctionKind::BitOperation(op, int_kind) => vir::Function {
+                name: fn_name,
+                formal_args: vec![
+                    vir::LocalVar::new("left", vir::Type::Int),
+                    vir::LocalVar::new("right", vir::Type::Int),
+                ],
+                return_type: vir::Type::Int,
+                pres: vec![],
+                posts: self.encode_bit_operation_posts(op, int_kind),
+                body: None,
+            },
+        }
+    }
+
+    /// The postconditions of the uninterpreted function that encodes a bitwise operation.
+    ///
+    /// Since the function has no body, the postconditions are assumed: each of them must be a
+    /// property of the actual operation, otherwise the encoding is unsound. All properties are
+    /// guarded by the operands being in the range of the type (and, for shifts, by the shift
+    /// amount being less than the number of bits).
+    fn encode_bit_operation_posts(
+        &self,
+        op: BitOperationKind,
+        int_kind: IntegerKind,
+    ) -> Vec<vir::Expr> {
+        let left: vir::Expr = vir::LocalVar::new("left", vir::Type::Int).into();
+        let right: vir::Expr = vir::LocalVar::new("right", vir::Type::Int).into();
+        let result: vir::Expr = vir::LocalVar::new("__result", vir::Type::Int).into();
+        let zero: vir::Expr = 0.into();
+        let minus_one: vir::Expr = (-1).into();
+        let non_negative = |expr: &vir::Expr| vir::Expr::ge_cmp(expr.clone(), 0.into());
+        let negative = |expr: &vir::Expr| vir::Expr::lt_cmp(expr.clone(), 0.into());
+
+        let mut properties = vec![int_kind.contains(result.clone())];
+        let guard = match op {
+            BitOperationKind::And | BitOperationKind::Or | BitOperationKind::Xor => {
+                vir::Expr::and(
+                    int_kind.contains(left.clone()),
+                    int_kind.contains(right.clone()),
+                )
+            }
+            BitOperationKind::Shl | BitOperationKind::Shr => vir::Expr::and(
+                int_kind.contains(left.clone()),
+                vir::Expr::and(
+                    vir::Expr::le_cmp(0.into(), right.clone()),
+                    vir::Expr::lt_cmp(right.clone(), (int_kind.bits as i64).into()),
+                ),
+            ),
+        };
+        match op {
+            BitOperationKind::And => {
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(left.clone(), right.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(result.clone(), zero.clone()),
+                ));
+                // All bits set
+                let all_ones = if int_kind.signed {
+                    minus_one.clone()
+                } else {
+                    int_kind.max_value()
+                };
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), all_ones),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::or(non_negative(&left), non_negative(&right)),
+                    non_negative(&result),
+                ));
+                properties.push(vir::Expr::implies(
+                    non_negative(&left),
+                    vir::Expr::le_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    non_negative(&right),
+                    vir::Expr::le_cmp(result.clone(), right.clone()),
+                ));
+            }
+            BitOperationKind::Or => {
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(left.clone(), right.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::or(negative(&left), negative(&right)),
+                    negative(&result),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::and(non_negative(&left), non_negative(&right)),
+                    vir::Expr::and(
+                        vir::Expr::ge_cmp(result.clone(), left.clone()),
+                        vir::Expr::ge_cmp(result.clone(), right.clone()),
+                    ),
+                ));
+            }
+            BitOperationKind::Xor => {
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(left.clone(), right.clone()),
+                    vir::Expr::eq_cmp(result.clone(), zero.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                // The sign bit of the result
+                properties.push(vir::Expr::eq_cmp(
+                    negative(&result),
+                    vir::Expr::ne_cmp(negative(&left), negative(&right)),
+                ));
+            }
+            BitOperationKind::Shl => {
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+            }
+            BitOperationKind::Shr => {
+                properties.push(vir::Expr::implies(
+                    vir::Expr::eq_cmp(right.clone(), zero.clone()),
+                    vir::Expr::eq_cmp(result.clone(), left.clone()),
+                ));
+                properties.push(vir::Expr::implies(
+                    non_negative(&left),
+                    vir::Expr::and(
+                        non_negative(&result),
+                        vir::Expr::le_cmp(result.clone(), left.clone()),
+                    ),
+                ));
+                properties.push(vir::Expr::implies(
+                    negative(&left),
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(left.clone(), result.clone()),
+                        negative(&result),
+                    ),
+                ));
+            }
         }
+        properties
+            .into_iter()
+            .map(|property| vir::Expr::implies(guard.clone(), property))
+            .collect()
     }
 }
@@ -16,12 +16,16 @@ use crate::encoder::procedure_encoder::ProcedureEncoder;
 use crate::encoder::pure_function_encoder::PureFunctionEncoder;
 use crate::encoder::stub_function_encoder::StubFunctionEncoder;
 use crate::encoder::spec_encoder::SpecEncoder;
+use crate::encoder::seq_encoder;
 use crate::encoder::snapshot_encoder::{Snapshot, SnapshotEncoder};
+use crate::encoder::std_encoder::{self, vec_elem_type};
+use crate::encoder::str_encoder::{self, is_bytes_type, is_str_type, StrMethodKind};
 use crate::encoder::type_encoder::{
     compute_discriminant_values, compute_discriminant_bounds, TypeEncoder};
 use prusti_common::vir;
 use prusti_common::vir::WithIdentifier;
 use prusti_common::config;
+use prusti_common::Stopwatch;
 // use prusti_interface::constants::PRUSTI_SPEC_ATTR;
 use prusti_interface::data::ProcedureDefId;
 use prusti_interface::environment::Environment;
@@ -38,17 +42,19 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
 // use rustc::mir::interpret::GlobalId;
 use rustc_middle::ty;
+use rustc_middle::ty::TypeFoldable;
 use std::cell::{RefCell, RefMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
-use std::mem;
 // use syntax::ast;
 use rustc_ast::ast;
 // use viper;
 use crate::encoder::stub_procedure_encoder::StubProcedureEncoder;
+use crate::encoder::trait_method_encoder::TraitMethodEncoder;
 use std::ops::AddAssign;
 use ::log::info;
 use std::convert::TryInto;
+use std::time::Duration;
 
 /// A reference into a specification.
 ///
@@ -58,6 +64,7 @@ enum SpecIdRef {
     Precondition(SpecificationId),
     Postcondition(SpecificationId),
     Pledge { lhs: Option<SpecificationId>, rhs: SpecificationId },
+    Predicate(SpecificationId),
 }
 
 const SNAPSHOT_MIRROR_DOMAIN: &str = "$SnapshotMirrors$";
@@ -75,6 +82,8 @@ pub struct Encoder<'v, 'tcx: 'v> {
     /// Stub pure functions. Generated when an impure Rust function is invoked
     /// where a pure function is required.
     stub_pure_functions: RefCell<HashMap<(ProcedureDefId, String), vir::Function>>,
+    /// Body-less pure functions that encode the methods of trait bounds, indexed by name.
+    trait_method_functions: RefCell<HashMap<String, vir::Function>>,
     type_predicate_names: RefCell<HashMap<ty::TyKind<'tcx>, String>>,
     type_invariant_names: RefCell<HashMap<ty::TyKind<'tcx>, String>>,
     type_tag_names: RefCell<HashMap<ty::TyKind<'tcx>, String>>,
@@ -104,6 +113,20 @@ pub struct Encoder<'v, 'tcx: 'v> {
     vir_program_before_viper_writer: RefCell<Box<Write>>,
     pub typaram_repl: RefCell<Vec<HashMap<ty::Ty<'tcx>, ty::Ty<'tcx>>>>,
     encoding_errors_counter: RefCell<usize>,
+    /// The encoding errors, with the procedure whose encoding reported them.
+    encoding_errors: RefCell<Vec<(Option<ProcedureDefId>, EncodingError)>>,
+    /// The time spent encoding each procedure of the encoding queue.
+    encoding_durations: RefCell<HashMap<ProcedureDefId, Duration>>,
+    /// For each encoded item (procedure, pure function or trait method function), the items
+    /// (callees, pure functions and types) whose specification or definition has been directly
+    /// used by its encoding.
+    dependencies: RefCell<HashMap<DefId, HashSet<DefId>>>,
+    /// For each encoded item, the callees without specification, whose calls have been encoded
+    /// with the empty contract.
+    unspecified_callees: RefCell<HashMap<DefId, HashSet<ProcedureDefId>>>,
+    /// The items whose encoding is in progress, innermost last. Dependencies are recorded for
+    /// the innermost one, because the encodings of pure functions are cached and shared.
+    dependency_owners: RefCell<Vec<DefId>>,
 }
 
 impl<'v, 'tcx> Encoder<'v, 'tcx> {
@@ -138,6 +161,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             pure_function_bodies: RefCell::new(HashMap::new()),
             pure_functions: RefCell::new(HashMap::new()),
             stub_pure_functions: RefCell::new(HashMap::new()),
+            trait_method_functions: RefCell::new(HashMap::new()),
             type_predicate_names: RefCell::new(HashMap::new()),
             type_invariant_names: RefCell::new(HashMap::new()),
             type_tag_names: RefCell::new(HashMap::new()),
@@ -157,6 +181,11 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             type_snapshots: RefCell::new(HashMap::new()),
             snap_mirror_funcs: RefCell::new(HashMap::new()),
             encoding_errors_counter: RefCell::new(0),
+            encoding_errors: RefCell::new(vec![]),
+            encoding_durations: RefCell::new(HashMap::new()),
+            dependencies: RefCell::new(HashMap::new()),
+            unspecified_callees: RefCell::new(HashMap::new()),
+            dependency_owners: RefCell::new(Vec::new()),
         }
     }
 
@@ -217,8 +246,22 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         }
     }
 
+    /// The names of the Viper methods that encode the given procedures.
+    pub fn get_procedure_method_names(&self, procedures: &[ProcedureDefId]) -> HashSet<String> {
+        let procedure_methods = self.procedures.borrow();
+        procedures
+            .iter()
+            .filter_map(|proc_id| procedure_methods.get(proc_id))
+            .map(|method| method.name())
+            .collect()
+    }
+
     pub(in crate::encoder) fn register_encoding_error(&self, encoding_error: EncodingError) {
         debug!("Encoding error: {:?}", encoding_error);
+        let current_procedure = self.error_manager.borrow().current_procedure();
+        self.encoding_errors
+            .borrow_mut()
+            .push((current_procedure, encoding_error.clone()));
         let prusti_error: PrustiError = encoding_error.into();
         if prusti_error.is_error() {
             self.encoding_errors_counter.borrow_mut().add_assign(1);
@@ -230,6 +273,70 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         *self.encoding_errors_counter.borrow()
     }
 
+    /// The encoding errors reported so far, with the procedure whose encoding reported them.
+    pub fn get_encoding_errors(&self) -> Vec<(Option<ProcedureDefId>, EncodingError)> {
+        self.encoding_errors.borrow().clone()
+    }
+
+    /// The time spent encoding the given procedure, if it has been encoded.
+    pub fn get_encoding_duration(&self, proc_def_id: ProcedureDefId) -> Option<Duration> {
+        self.encoding_durations.borrow().get(&proc_def_id).cloned()
+    }
+
+    /// Encode an item with `encode`, recording the dependencies of its encoding under `def_id`.
+    fn with_dependency_owner<R>(&self, def_id: DefId, encode: impl FnOnce() -> R) -> R {
+        self.dependency_owners.borrow_mut().push(def_id);
+        let result = encode();
+        self.dependency_owners.borrow_mut().pop();
+        result
+    }
+
+    /// Record that the encoding of the item that is currently being encoded depends on the
+    /// given item.
+    fn register_dependency(&self, def_id: DefId) {
+        if let Some(&owner) = self.dependency_owners.borrow().last() {
+            if owner != def_id {
+                self.dependencies
+                    .borrow_mut()
+                    .entry(owner)
+                    .or_insert_with(HashSet::new)
+                    .insert(def_id);
+            }
+        }
+    }
+
+    /// Record that the item that is currently being encoded calls a procedure without
+    /// specification.
+    fn register_unspecified_callee(&self, callee_def_id: ProcedureDefId) {
+        if let Some(&owner) = self.dependency_owners.borrow().last() {
+            self.unspecified_callees
+                .borrow_mut()
+                .entry(owner)
+                .or_insert_with(HashSet::new)
+                .insert(callee_def_id);
+        }
+    }
+
+    /// The callees without specification that are called by the encoding of the given item
+    /// (not transitively, see `incremental::transitive_dependencies`).
+    pub fn get_unspecified_callees(&self, def_id: DefId) -> HashSet<ProcedureDefId> {
+        self.unspecified_callees
+            .borrow()
+            .get(&def_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The items on which the encoding of the given item directly depends (not transitively,
+    /// see `incremental::transitive_dependencies`).
+    pub fn get_dependencies(&self, def_id: DefId) -> HashSet<DefId> {
+        self.dependencies
+            .borrow()
+            .get(&def_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn get_used_viper_domains(&self) -> Vec<vir::Domain> {
         let mirrors: Vec<_> = self
             .snap_mirror_funcs
@@ -253,6 +360,18 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 type_vars: vec![],
             });
         }
+        let uses_strings = self.fields.borrow().contains_key(&str_encoder::str_value_field().name);
+        if uses_strings {
+            domains.push(str_encoder::encode_str_domain());
+        }
+        for elem_type in &[vir::Type::Int, vir::Type::Bool] {
+            // The values of strings are sequences of integers.
+            let uses_seqs = (uses_strings && *elem_type == vir::Type::Int)
+                || self.fields.borrow().contains_key(&std_encoder::seq_value_field(elem_type).name);
+            if uses_seqs {
+                domains.push(seq_encoder::encode_seq_domain(elem_type));
+            }
+        }
         domains.sort_by_key(|d| d.get_identifier());
         domains
     }
@@ -274,6 +393,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         for function in self.stub_pure_functions.borrow().values() {
             functions.push(function.clone());
         }
+        for function in self.trait_method_functions.borrow().values() {
+            functions.push(function.clone());
+        }
         for function in self.type_invariants.borrow().values() {
             functions.push(function.clone());
         }
@@ -449,6 +571,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                                     let rhs_spec_id = value.next().unwrap().to_string().try_into().unwrap();
                                     Some(SpecIdRef::Pledge{ lhs: lhs_spec_id, rhs: rhs_spec_id })
                                 },
+                                "pred_spec_id_ref" => Some(SpecIdRef::Predicate(to_spec_id(attr))),
                                 x => unimplemented!("x: {:}", x),
                             }
                         } else {
@@ -494,12 +617,36 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                             rhs: self.spec().get(&rhs).unwrap().as_assertion().clone(),
                         })
                     }
+                    SpecIdRef::Predicate(_) => {
+                        // The body of a predicate is not part of its contract.
+                    }
                 }
             }
             Some(typed::SpecificationSet::Procedure(typed::ProcedureSpecification::new(pres, posts, pledges)))
         }
     }
 
+    /// Returns the assertion that defines the predicate `def_id`, or `None` if `def_id` is not a
+    /// predicate.
+    pub fn get_predicate_body(&self, def_id: DefId) -> Option<typed::Assertion<'tcx>> {
+        if !def_id.is_local() {
+            return None;
+        }
+        self.get_opt_spec_id(def_id)
+            .into_iter()
+            .find_map(|spec_id_ref| match spec_id_ref {
+                SpecIdRef::Predicate(spec_id) => {
+                    Some(self.spec().get(&spec_id).unwrap().as_assertion().clone())
+                }
+                _ => None,
+            })
+    }
+
+    /// Is the procedure with the `def_id` a predicate, i.e. a specification-only pure function?
+    pub fn is_predicate(&self, def_id: DefId) -> bool {
+        self.env.has_attribute_name(def_id, "pred_spec_id_ref")
+    }
+
     fn get_procedure_contract(&self, proc_def_id: ProcedureDefId) -> ProcedureContractMirDef<'tcx> {
         let opt_fun_spec = self.get_spec_by_def_id(proc_def_id);
         let fun_spec = match opt_fun_spec {
@@ -529,11 +676,13 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         args: &Vec<places::Local>,
         target: places::Local,
     ) -> ProcedureContract<'tcx> {
+        self.register_dependency(proc_def_id);
         // get specification on trait declaration method or inherent impl
         let fun_spec = if let Some(spec) = self.get_spec_by_def_id(proc_def_id) {
             spec.clone()
         } else {
             debug!("Procedure {:?} has no specification", proc_def_id);
+            self.register_unspecified_callee(proc_def_id);
             typed::SpecificationSet::Procedure(typed::ProcedureSpecification::empty())
         };
 
@@ -583,7 +732,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     pub fn encode_value_expr(&self, base: vir::Expr, ty: ty::Ty<'tcx>) -> vir::Expr {
         match ty.kind {
             ty::TyKind::Adt(_, _)
-            | ty::TyKind::Tuple(_) => {
+            | ty::TyKind::Tuple(_)
+                if !is_str_type(self.env.tcx(), ty) && vec_elem_type(self.env.tcx(), ty).is_none() =>
+            {
                 base // don't use a field for tuples and ADTs
             }
             _ => {
@@ -593,6 +744,54 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         }
     }
 
+    /// Encodes the value of a string, given the value `value` of an expression of type `ty`,
+    /// which is either a string, a byte slice or a (possibly nested) reference to one.
+    pub fn encode_str_value(&self, value: vir::Expr, ty: ty::Ty<'tcx>) -> vir::Expr {
+        match ty.kind {
+            ty::TyKind::Ref(_, target_ty, _) => {
+                let target_value = self.encode_value_expr(value, target_ty);
+                self.encode_str_value(target_value, target_ty)
+            }
+            _ => {
+                assert!(is_bytes_type(self.env.tcx(), ty), "{:?} is not a string", ty);
+                value
+            }
+        }
+    }
+
+    /// Encodes the arguments of a string method, given the values of the Rust arguments.
+    /// Strings are replaced by their bytes and a range by its bounds.
+    pub fn encode_str_method_args(
+        &self,
+        method: StrMethodKind,
+        arg_values: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+    ) -> Vec<vir::Expr> {
+        let str_value = |index: usize| self.encode_str_value(arg_values[index].clone(), arg_tys[index]);
+        match method {
+            StrMethodKind::New => vec![],
+            StrMethodKind::Len | StrMethodKind::IsEmpty | StrMethodKind::View => vec![str_value(0)],
+            StrMethodKind::Slice => {
+                let range_ty = arg_tys[1];
+                let (start, end) = match range_ty.kind {
+                    ty::TyKind::Adt(adt_def, subst) => {
+                        let tcx = self.env.tcx();
+                        let fields = &adt_def.non_enum_variant().fields;
+                        let field_ty = fields[0].ty(tcx, subst);
+                        let encode_bound = |name: &str| {
+                            let field = self.encode_struct_field(name, field_ty);
+                            self.encode_value_expr(arg_values[1].clone().field(field), field_ty)
+                        };
+                        (encode_bound("start"), encode_bound("end"))
+                    }
+                    ref x => unreachable!("{:?}", x),
+                };
+                vec![str_value(0), start, end]
+            }
+            _ => vec![str_value(0), str_value(1)],
+        }
+    }
+
     pub fn encode_value_field(&self, ty: ty::Ty<'tcx>) -> vir::Field {
         let type_encoder = TypeEncoder::new(self, ty);
         let field = type_encoder.encode_value_field();
@@ -647,52 +846,11 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         field
     }
 
-    pub fn encode_discriminant_func_app(
-        &self,
-        place: vir::Expr,
-        adt_def: &ty::AdtDef,
-    ) -> vir::Expr {
-        // let typ = place.get_type().clone();
-        // let mut name = typ.name();
-        // name.push_str("$$discriminant$$");
-        // let self_local_var = vir::LocalVar::new("self", typ);
-        // self.type_discriminant_funcs
-        //     .borrow_mut()
-        //     .entry(name.clone())
-        //     .or_insert_with(|| {
-        //         let predicate_name = place.get_type().name();
-        //         let precondition = vir::Expr::predicate_access_predicate(
-        //             predicate_name,
-        //             self_local_var.clone().into(),
-        //             vir::PermAmount::Read,
-        //         );
-        //         let result = vir::LocalVar::new("__result", vir::Type::Int);
-        //         let postcondition = compute_discriminant_bounds(
-        //             adt_def, self.env.tcx(), &result.into());
-        //         let discr_field = self.encode_discriminant_field();
-        //         let self_local_var_expr: vir::Expr = self_local_var.clone().into();
-        //         let function = vir::Function {
-        //             name: name.clone(),
-        //             formal_args: vec![self_local_var.clone()],
-        //             return_type: vir::Type::Int,
-        //             pres: vec![precondition],
-        //             posts: vec![postcondition],
-        //             body: Some(self_local_var_expr.field(discr_field)),
-        //         };
-        //         let final_function = foldunfold::add_folding_unfolding_to_function(
-        //             function,
-        //             self.get_used_viper_predicates_map(),
-        //         );
-        //         final_function
-        //     });
-        // vir::Expr::FuncApp(
-        //     name,
-        //     vec![place],
-        //     vec![self_local_var],
-        //     vir::Type::Int,
-        //     vir::Position::default(),
-        // )
-        unimplemented!();
+    /// Encodes the discriminant of the enumeration stored in `place`, which is the discriminant
+    /// field of the predicate of the enumeration. The same field is used in pure functions.
+    /// Accessing it requires the predicate of `place` to be unfolded.
+    pub fn encode_discriminant_place(&self, place: vir::Expr) -> vir::Expr {
+        place.field(self.encode_discriminant_field())
     }
 
     fn encode_memory_eq_tuple(
@@ -1068,6 +1226,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
     }
 
     pub fn encode_type_predicate_use(&self, ty: ty::Ty<'tcx>) -> Result<String, ErrorCtxt> {
+        if let ty::TyKind::Adt(adt_def, _) = ty.kind {
+            self.register_dependency(adt_def.did);
+        }
         if !self.type_predicate_names.borrow().contains_key(&ty.kind) {
             let type_encoder = TypeEncoder::new(self, ty);
             let result = type_encoder.encode_predicate_use()?;
@@ -1202,6 +1363,25 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         self.type_tags.borrow()[&tag_name].clone()
     }
 
+    /// Encodes the bytes of a string constant, i.e. a constant of type `&str`.
+    pub fn encode_str_const_expr(
+        &self,
+        value: &ty::ConstKind<'tcx>,
+        span: rustc_span::Span,
+    ) -> Result<vir::Expr, EncodingError> {
+        trace!("encode_str_const_expr {:?}", value);
+        match value {
+            ty::ConstKind::Value(mir::interpret::ConstValue::Slice { data, start, end }) => {
+                let bytes = data.inspect_with_undef_and_ptr_outside_interpreter(*start..*end);
+                Ok(str_encoder::encode_str_literal(bytes))
+            }
+            _ => Err(EncodingError::unsupported(
+                format!("string constants of the form '{:?}' are not supported", value),
+                span,
+            )),
+        }
+    }
+
     pub fn encode_const_expr(&self, ty: &ty::TyS<'tcx>, value: &ty::ConstKind<'tcx>) -> vir::Expr {
         trace!("encode_const_expr {:?}", value);
         let scalar_value = match value {
@@ -1258,8 +1438,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             ty::TyKind::Int(ast::IntTy::Isize) => {
                 match scalar_value {
                     mir::interpret::Scalar::Raw { data, .. } => {
-                        let isize_bits = mem::size_of::<isize>() * 8;
-                        let val: isize = with_sign(data, isize_bits.try_into().unwrap()).try_into().unwrap();
+                        // The value has the size of a pointer of the target, not of the host.
+                        let isize_bits = config::target_pointer_width();
+                        let val: i128 = with_sign(data, isize_bits.into());
                         val.into()
                     },
                     _ => unimplemented!(),
@@ -1281,7 +1462,8 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             ty::TyKind::Uint(ast::UintTy::Usize) => {
                 match scalar_value {
                     mir::interpret::Scalar::Raw { data, .. } => {
-                        let val: usize = data.try_into().unwrap();
+                        let usize_bits = config::target_pointer_width();
+                        let val: u128 = data & (u128::MAX >> (128 - usize_bits));
                         val.into()
                     },
                     _ => unimplemented!(),
@@ -1425,7 +1607,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             let procedure = self.env.get_procedure(proc_def_id);
             let pure_function_encoder =
                 PureFunctionEncoder::new(self, proc_def_id, procedure.get_mir(), false);
-            let function = if self.is_trusted(proc_def_id) {
+            let function = if let Some(predicate_body) = self.get_predicate_body(proc_def_id) {
+                pure_function_encoder.encode_predicate_function(&predicate_body)
+            } else if self.is_trusted(proc_def_id) {
                 pure_function_encoder.encode_bodyless_function()
             } else {
                 let pure_function = pure_function_encoder.encode_function();
@@ -1568,7 +1752,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         &self,
         proc_def_id: ProcedureDefId,
     ) -> (String, vir::Type) {
-        let procedure = self.env.get_procedure(proc_def_id);
+        self.register_dependency(proc_def_id);
 
         assert!(
             self.env.has_attribute_name(proc_def_id, "pure"),
@@ -1576,6 +1760,16 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             proc_def_id
         );
 
+        if self.is_bodyless_trait_method(proc_def_id) {
+            // Use the implementation of the method, if it is known.
+            return match self.find_pure_trait_method_impl(proc_def_id) {
+                Some(impl_def_id) => self.encode_pure_function_use(impl_def_id),
+                None => self.encode_trait_method_function_use(proc_def_id),
+            };
+        }
+
+        let procedure = self.env.get_procedure(proc_def_id);
+
         let pure_function_encoder =
             PureFunctionEncoder::new(self, proc_def_id, procedure.get_mir(), false);
 
@@ -1607,11 +1801,112 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 },
                 vir::Type::Bool
             )
+        } else if self.is_type_parameter(arg_ty) {
+            // The equality of a type parameter is only known through its trait bound.
+            self.encode_trait_method_function_use(proc_def_id)
         } else {
             self.encode_stub_pure_function_use(proc_def_id)
         }
     }
 
+    /// Whether the type (or the type it references) is a type parameter that is not replaced
+    /// by a concrete type in the current context.
+    pub fn is_type_parameter(&self, ty: ty::Ty<'tcx>) -> bool {
+        let ty = match ty.builtin_deref(true) {
+            Some(ty::TypeAndMut { ty, .. }) => ty,
+            None => ty,
+        };
+        match self.resolve_typaram(ty).kind {
+            ty::TyKind::Param(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the procedure is a method declared in a trait without a default implementation.
+    pub fn is_bodyless_trait_method(&self, proc_def_id: ProcedureDefId) -> bool {
+        let tcx = self.env.tcx();
+        tcx.trait_of_item(proc_def_id).is_some() && !tcx.is_mir_available(proc_def_id)
+    }
+
+    /// Find the pure implementation of a trait method for the current `Self` type, if the type
+    /// is concrete and its implementation of the trait can be determined: either there is a
+    /// single candidate implementation, or exactly one of them is for `Self` itself.
+    fn find_pure_trait_method_impl(&self, proc_def_id: ProcedureDefId) -> Option<ProcedureDefId> {
+        let tcx = self.env.tcx();
+        let trait_id = tcx.trait_of_item(proc_def_id)?;
+        let self_ty = self.resolve_typaram(
+            ty::List::identity_for_item(tcx, proc_def_id).type_at(0)
+        );
+        if self_ty.needs_subst() {
+            return None;
+        }
+        let impls = self.env.get_trait_method_decl_for_type(
+            self_ty,
+            trait_id,
+            tcx.item_name(proc_def_id),
+        );
+        let item = match impls.as_slice() {
+            [item] => item,
+            _ => {
+                // The candidates are the implementations that may apply to `self_ty`, e.g.
+                // `impl Trait for Vec<u8>` and `impl Trait for Vec<i32>` for `Vec<u8>`.
+                let self_ty = tcx.erase_regions(&self_ty);
+                let mut exact_impls = impls.iter().filter(|item| {
+                    tcx.impl_trait_ref(item.container.id()).map_or(false, |trait_ref| {
+                        tcx.erase_regions(&trait_ref.self_ty()) == self_ty
+                    })
+                });
+                match (exact_impls.next(), exact_impls.next()) {
+                    (Some(item), None) => item,
+                    _ => return None,
+                }
+            }
+        };
+        if self.env.has_attribute_name(item.def_id, "pure") && tcx.is_mir_available(item.def_id) {
+            Some(item.def_id)
+        } else {
+            None
+        }
+    }
+
+    /// Encode the use (call) of a trait method that has no body, returning the name of the
+    /// function and its type.
+    ///
+    /// The function is body-less and has the contract of the trait declaration.
+    pub fn encode_trait_method_function_use(
+        &self,
+        proc_def_id: ProcedureDefId,
+    ) -> (String, vir::Type) {
+        self.register_dependency(proc_def_id);
+        let encoder = TraitMethodEncoder::new(self, proc_def_id);
+        let function_name = encoder.encode_function_name();
+        if !self.trait_method_functions.borrow().contains_key(&function_name) {
+            // Insert a placeholder first, to break cycles between contracts.
+            let placeholder = vir::Function {
+                name: function_name.clone(),
+                formal_args: vec![],
+                return_type: encoder.encode_function_return_type(),
+                pres: vec![],
+                posts: vec![],
+                body: None,
+            };
+            self.trait_method_functions
+                .borrow_mut()
+                .insert(function_name.clone(), placeholder);
+            let function = self
+                .with_dependency_owner(proc_def_id, || encoder.encode_function())
+                .unwrap_or_else(|error| {
+                    self.register_encoding_error(error);
+                    encoder.encode_stub_function()
+                });
+            self.log_vir_program_before_viper(function.to_string());
+            self.trait_method_functions
+                .borrow_mut()
+                .insert(function_name.clone(), function);
+        }
+        (function_name, encoder.encode_function_return_type())
+    }
+
     /// Encode the use (call) of a stub pure function, returning the name of the
     /// function and its type.
     ///
@@ -1621,6 +1916,7 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         &self,
         proc_def_id: ProcedureDefId,
     ) -> (String, vir::Type) {
+        self.register_dependency(proc_def_id);
         let procedure = self.env.get_procedure(proc_def_id);
         let encoder = StubFunctionEncoder::new(self, proc_def_id, procedure.get_mir());
 
@@ -1661,7 +1957,11 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                 "Encoding: {} from {:?} ({})",
                 proc_name, proc_span, proc_def_path
             );
+            self.error_manager().set_current_procedure(Some(proc_def_id));
+            let stopwatch =
+                Stopwatch::start_debug("prusti-viper", format!("encoding of {}", proc_name));
             let is_pure_function = self.env.has_attribute_name(proc_def_id, "pure");
+            self.dependency_owners.borrow_mut().push(proc_def_id);
             if is_pure_function {
                 self.encode_pure_function_def(proc_def_id, substs);
             } else {
@@ -1678,6 +1978,14 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     }
                 }
             }
+            self.dependency_owners.borrow_mut().pop();
+            let duration = stopwatch.finish();
+            *self
+                .encoding_durations
+                .borrow_mut()
+                .entry(proc_def_id)
+                .or_insert_with(Duration::default) += duration;
+            self.error_manager().set_current_procedure(None);
         }
     }
 
@@ -5,13 +5,25 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use prusti_common::vir::Position;
+use prusti_interface::data::ProcedureDefId;
 use std::collections::HashMap;
+use rustc_middle::mir;
 use rustc_span::source_map::SourceMap;
 use rustc_span::MultiSpan;
 use viper::VerificationError;
 use crate::encoder::errors::PrustiError;
 use log::debug;
 
+/// Which kinds of panics are checked, i.e. whose absence is verified. The encoders initialize
+/// it from the configuration flags of the kinds of panics.
+#[derive(Clone, Copy, Debug)]
+pub struct CheckedPanics {
+    pub index_bounds: bool,
+    pub division_by_zero: bool,
+    pub unwrap: bool,
+    pub overflows: bool,
+}
+
 /// The cause of a panic!()
 #[derive(Clone, Debug)]
 pub enum PanicCause {
@@ -25,6 +37,142 @@ pub enum PanicCause {
     Unreachable,
     /// Caused by an unimplemented!()
     Unimplemented,
+    /// Caused by slicing a string out of bounds or not at a char boundary
+    StrSlicing,
+    /// Caused by unwrapping a `None` or `Err` value
+    Unwrap,
+    /// Caused by indexing a vector out of bounds
+    IndexOutOfBounds,
+}
+
+impl PanicCause {
+    /// Whether the absence of panics with this cause should be checked.
+    pub fn is_checked(&self, checked: CheckedPanics) -> bool {
+        match self {
+            PanicCause::StrSlicing | PanicCause::IndexOutOfBounds => checked.index_bounds,
+            PanicCause::Unwrap => checked.unwrap,
+            _ => true,
+        }
+    }
+}
+
+/// The cause of the failure of an `assert` Rust terminator, i.e. of a check inserted by the
+/// compiler
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssertCause {
+    /// Indexing an array or a slice out of bounds
+    BoundsCheck,
+    /// Dividing by zero
+    DivisionByZero,
+    /// Computing the remainder of a division by zero
+    RemainderByZero,
+    /// An arithmetic operation whose result does not fit in its type
+    Overflow(mir::BinOp),
+    /// Negating the minimum value of a signed integer type
+    NegationOverflow,
+    /// Any other check (e.g. resuming a completed generator)
+    /// Arguments: the message of the Rust assertion
+    Other(String),
+}
+
+impl AssertCause {
+    pub fn new<O>(kind: &mir::AssertKind<O>) -> Self {
+        match kind {
+            mir::AssertKind::BoundsCheck { .. } => AssertCause::BoundsCheck,
+            mir::AssertKind::DivisionByZero { .. } => AssertCause::DivisionByZero,
+            mir::AssertKind::RemainderByZero { .. } => AssertCause::RemainderByZero,
+            mir::AssertKind::Overflow(op, ..) => AssertCause::Overflow(*op),
+            mir::AssertKind::OverflowNeg { .. } => AssertCause::NegationOverflow,
+            _ => AssertCause::Other(kind.description().to_string()),
+        }
+    }
+
+    /// Whether the checks with this cause should be verified.
+    pub fn is_checked(&self, checked: CheckedPanics) -> bool {
+        match self {
+            AssertCause::BoundsCheck => checked.index_bounds,
+            AssertCause::DivisionByZero | AssertCause::RemainderByZero => checked.division_by_zero,
+            AssertCause::Overflow(_) | AssertCause::NegationOverflow => checked.overflows,
+            AssertCause::Other(_) => true,
+        }
+    }
+
+    /// The message of the error reported when the check might fail.
+    fn message(&self) -> String {
+        match self {
+            AssertCause::BoundsCheck => "the index might be out of bounds".to_string(),
+            AssertCause::DivisionByZero => "the divisor might be zero".to_string(),
+            AssertCause::RemainderByZero => {
+                "the divisor of the remainder operation might be zero".to_string()
+            }
+            AssertCause::Overflow(op) => {
+                let operation = match op {
+                    mir::BinOp::Add => "addition",
+                    mir::BinOp::Sub => "subtraction",
+                    mir::BinOp::Mul => "multiplication",
+                    mir::BinOp::Div => "division",
+                    mir::BinOp::Rem => "remainder operation",
+                    _ => "operation",
+                };
+                format!("the {} might overflow", operation)
+            }
+            AssertCause::NegationOverflow => "the negation might overflow".to_string(),
+            AssertCause::Other(message) => format!("assertion might fail with \"{}\"", message),
+        }
+    }
+
+    /// A help text that explains how to fix the error, if there is one.
+    fn help(&self) -> Option<&'static str> {
+        match self {
+            AssertCause::BoundsCheck => Some(
+                "consider adding a precondition or a check that the index is smaller than the \
+                length",
+            ),
+            AssertCause::DivisionByZero | AssertCause::RemainderByZero => Some(
+                "consider adding a precondition or a check that the divisor is not zero",
+            ),
+            AssertCause::Overflow(mir::BinOp::Div) | AssertCause::Overflow(mir::BinOp::Rem) => {
+                Some("the minimum value of a signed integer type cannot be divided by -1")
+            }
+            AssertCause::Overflow(_) => Some(
+                "consider adding a precondition or a check that the result fits in the type of \
+                the operands",
+            ),
+            AssertCause::NegationOverflow => {
+                Some("the minimum value of a signed integer type cannot be negated")
+            }
+            AssertCause::Other(_) => None,
+        }
+    }
+
+    fn to_error(&self, error_span: MultiSpan, opt_cause_span: Option<&MultiSpan>) -> PrustiError {
+        let error = PrustiError::verification(self.message(), error_span)
+            .set_failing_assertion(opt_cause_span);
+        match self.help() {
+            Some(help) => error.add_help(help),
+            None => error,
+        }
+    }
+}
+
+/// The inconsistency that a vacuity check reveals when its `assert false` verifies
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VacuityCheck {
+    /// The precondition of the procedure is unsatisfiable
+    Precondition,
+    /// The loop invariant is inconsistent
+    LoopInvariant,
+    /// The branch of a conditional is unreachable
+    Branch,
+}
+
+/// The integer operation that an overflow check is about
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowCheck {
+    /// A cast with `as` whose destination type might not contain the value
+    Cast,
+    /// A shift whose amount might not be smaller than the number of bits of the shifted value
+    ShiftAmount,
 }
 
 /// In case of verification error, this enum will contain additional information
@@ -47,9 +195,11 @@ pub enum ErrorCtxt {
     /// A Viper `assert expr` that asserts the functional specification of a loop invariant `expr`
     AssertLoopInvariantOnEntry,
     AssertLoopInvariantAfterIteration,
+    /// A Viper `assert expr` that asserts the loop invariant `expr` inferred by Prusti
+    AssertInferredLoopInvariant,
     /// A Viper `assert false` that encodes the failure (panic) of an `assert` Rust terminator
-    /// Arguments: the message of the Rust assertion
-    AssertTerminator(String),
+    /// Arguments: the cause of the failure
+    AssertTerminator(AssertCause),
     /// A Viper `assert false` that encodes an `abort` Rust terminator
     AbortTerminator,
     /// A Viper `assert false` that encodes an `unreachable` Rust terminator
@@ -68,8 +218,8 @@ pub enum ErrorCtxt {
     PureFunctionPostconditionValueRangeOfResult,
     /// A Viper function with `false` precondition that encodes the failure (panic) of an
     /// `assert` Rust terminator in a Rust pure function.
-    /// Arguments: the message of the Rust assertion
-    PureFunctionAssertTerminator(String),
+    /// Arguments: the cause of the failure
+    PureFunctionAssertTerminator(AssertCause),
     /// A generic expression
     GenericExpression,
     /// A generic statement
@@ -90,6 +240,83 @@ pub enum ErrorCtxt {
     AssertMethodPostconditionStrengthening(MultiSpan),
     /// A Viper `assert false` that encodes an unsupported feature
     Unsupported(String, String),
+    /// A Viper `assert expr` (or, in a pure function, a function call with precondition `expr`)
+    /// that checks that an integer cast or shift does not overflow
+    OverflowCheck(OverflowCheck),
+    /// A Viper `assert false`, reached nondeterministically, that is expected to fail unless the
+    /// program point is unreachable
+    VacuityCheck(VacuityCheck),
+}
+
+impl ErrorCtxt {
+    /// The error context of the check of an `assert` Rust terminator, in a procedure or in a
+    /// pure function.
+    pub fn assert_terminator<O>(kind: &mir::AssertKind<O>, in_pure_function: bool) -> Self {
+        match AssertCause::new(kind) {
+            AssertCause::Overflow(mir::BinOp::Shl) | AssertCause::Overflow(mir::BinOp::Shr) => {
+                ErrorCtxt::OverflowCheck(OverflowCheck::ShiftAmount)
+            }
+            cause if in_pure_function => ErrorCtxt::PureFunctionAssertTerminator(cause),
+            cause => ErrorCtxt::AssertTerminator(cause),
+        }
+    }
+
+    /// Whether the check with this error context should be verified, according to the kinds of
+    /// panics that are checked. The `check_panics` flag is not considered.
+    pub fn is_checked(&self, checked: CheckedPanics) -> bool {
+        match self {
+            ErrorCtxt::Panic(cause) | ErrorCtxt::PanicInPureFunction(cause) => {
+                cause.is_checked(checked)
+            }
+            ErrorCtxt::AssertTerminator(cause) | ErrorCtxt::PureFunctionAssertTerminator(cause) => {
+                cause.is_checked(checked)
+            }
+            ErrorCtxt::OverflowCheck(_) => checked.overflows,
+            _ => true,
+        }
+    }
+
+    /// A stable, human-readable name of the kind of the error context, without its arguments.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ErrorCtxt::Panic(_) => "Panic",
+            ErrorCtxt::ExhaleMethodPrecondition => "ExhaleMethodPrecondition",
+            ErrorCtxt::AssertMethodPostcondition => "AssertMethodPostcondition",
+            ErrorCtxt::AssertMethodPostconditionTypeInvariants => {
+                "AssertMethodPostconditionTypeInvariants"
+            }
+            ErrorCtxt::ExhaleMethodPostcondition => "ExhaleMethodPostcondition",
+            ErrorCtxt::ExhaleLoopInvariantOnEntry => "ExhaleLoopInvariantOnEntry",
+            ErrorCtxt::ExhaleLoopInvariantAfterIteration => "ExhaleLoopInvariantAfterIteration",
+            ErrorCtxt::AssertLoopInvariantOnEntry => "AssertLoopInvariantOnEntry",
+            ErrorCtxt::AssertLoopInvariantAfterIteration => "AssertLoopInvariantAfterIteration",
+            ErrorCtxt::AssertInferredLoopInvariant => "AssertInferredLoopInvariant",
+            ErrorCtxt::AssertTerminator(_) => "AssertTerminator",
+            ErrorCtxt::AbortTerminator => "AbortTerminator",
+            ErrorCtxt::UnreachableTerminator => "UnreachableTerminator",
+            ErrorCtxt::Unexpected => "Unexpected",
+            ErrorCtxt::PureFunctionDefinition => "PureFunctionDefinition",
+            ErrorCtxt::PureFunctionCall => "PureFunctionCall",
+            ErrorCtxt::StubPureFunctionCall => "StubPureFunctionCall",
+            ErrorCtxt::PureFunctionPostconditionValueRangeOfResult => {
+                "PureFunctionPostconditionValueRangeOfResult"
+            }
+            ErrorCtxt::PureFunctionAssertTerminator(_) => "PureFunctionAssertTerminator",
+            ErrorCtxt::GenericExpression => "GenericExpression",
+            ErrorCtxt::GenericStatement => "GenericStatement",
+            ErrorCtxt::PackageMagicWandForPostcondition => "PackageMagicWandForPostcondition",
+            ErrorCtxt::ApplyMagicWandOnExpiry => "ApplyMagicWandOnExpiry",
+            ErrorCtxt::DivergingCallInPureFunction => "DivergingCallInPureFunction",
+            ErrorCtxt::PanicInPureFunction(_) => "PanicInPureFunction",
+            ErrorCtxt::AssertMethodPreconditionWeakening(_) => "AssertMethodPreconditionWeakening",
+            ErrorCtxt::AssertMethodPostconditionStrengthening(_) => {
+                "AssertMethodPostconditionStrengthening"
+            }
+            ErrorCtxt::Unsupported(_, _) => "Unsupported",
+            ErrorCtxt::OverflowCheck(_) => "OverflowCheck",
+            ErrorCtxt::VacuityCheck(_) => "VacuityCheck",
+        }
+    }
 }
 
 /// The error manager
@@ -98,6 +325,12 @@ pub struct ErrorManager<'tcx> {
     codemap: &'tcx SourceMap,
     source_span: HashMap<u64, MultiSpan>,
     error_contexts: HashMap<u64, ErrorCtxt>,
+    /// Notes that point at code related to the errors reported at a position (e.g. the contract
+    /// of the callee, or the enclosing loop).
+    related_notes: HashMap<u64, Vec<(String, MultiSpan)>>,
+    /// The procedure that was being encoded when a position was registered.
+    procedures: HashMap<u64, ProcedureDefId>,
+    current_procedure: Option<ProcedureDefId>,
     next_pos_id: u64,
 }
 
@@ -108,10 +341,22 @@ impl<'tcx> ErrorManager<'tcx>
             codemap,
             source_span: HashMap::new(),
             error_contexts: HashMap::new(),
+            related_notes: HashMap::new(),
+            procedures: HashMap::new(),
+            current_procedure: None,
             next_pos_id: 1,
         }
     }
 
+    /// Set the procedure to which the positions registered from now on belong.
+    pub fn set_current_procedure(&mut self, proc_def_id: Option<ProcedureDefId>) {
+        self.current_procedure = proc_def_id;
+    }
+
+    pub fn current_procedure(&self) -> Option<ProcedureDefId> {
+        self.current_procedure
+    }
+
     pub fn register<T: Into<MultiSpan>>(&mut self, span: T, error_ctxt: ErrorCtxt) -> Position {
         let pos = self.register_span(span);
         self.register_error(&pos, error_ctxt);
@@ -139,6 +384,9 @@ impl<'tcx> ErrorManager<'tcx>
             Position::new(0, 0, pos_id)
         };
         self.source_span.insert(pos_id, span);
+        if let Some(proc_def_id) = self.current_procedure {
+            self.procedures.insert(pos_id, proc_def_id);
+        }
         pos
     }
 
@@ -147,6 +395,68 @@ impl<'tcx> ErrorManager<'tcx>
         self.error_contexts.insert(pos.id(), error_ctxt);
     }
 
+    /// Attach to the errors reported at the given position a note that points at related code.
+    pub fn add_related_note<S: ToString, T: Into<MultiSpan>>(
+        &mut self,
+        pos: &Position,
+        note: S,
+        span: T,
+    ) {
+        let span = span.into();
+        if span.primary_span().is_none() {
+            return;
+        }
+        debug!("Register related note at: {:?}", pos.id());
+        self.related_notes
+            .entry(pos.id())
+            .or_insert_with(Vec::new)
+            .push((note.to_string(), span));
+    }
+
+    /// The procedure whose encoding contains the offending node of the verification error.
+    pub fn get_procedure(&self, ver_error: &VerificationError) -> Option<ProcedureDefId> {
+        let pos_id: u64 = ver_error.pos_id.as_ref()?.parse().ok()?;
+        self.procedures.get(&pos_id).cloned()
+    }
+
+    /// The error context registered for the offending node of the verification error.
+    pub fn get_error_ctxt(&self, ver_error: &VerificationError) -> Option<&ErrorCtxt> {
+        let pos_id: u64 = ver_error.pos_id.as_ref()?.parse().ok()?;
+        self.error_contexts.get(&pos_id)
+    }
+
+    /// The vacuity checks registered so far, with the procedure that contains them.
+    pub fn get_vacuity_checks(&self) -> Vec<(u64, Option<ProcedureDefId>, VacuityCheck)> {
+        let mut checks: Vec<_> = self
+            .error_contexts
+            .iter()
+            .filter_map(|(&pos_id, error_ctxt)| match error_ctxt {
+                ErrorCtxt::VacuityCheck(check) => {
+                    Some((pos_id, self.procedures.get(&pos_id).cloned(), *check))
+                }
+                _ => None,
+            })
+            .collect();
+        checks.sort_by_key(|&(pos_id, _, _)| pos_id);
+        checks
+    }
+
+    /// The warning to report for a vacuity check whose `assert false` verified.
+    pub fn translate_vacuity_check(&self, pos_id: u64, check: VacuityCheck) -> PrustiError {
+        let span = self.source_span.get(&pos_id).cloned().unwrap_or_else(MultiSpan::new);
+        match check {
+            VacuityCheck::Precondition => {
+                PrustiError::vacuity("the precondition is unsatisfiable", span)
+                    .add_help("the function can never be called, and its body is verified vacuously")
+            }
+            VacuityCheck::LoopInvariant => {
+                PrustiError::vacuity("the loop invariant is inconsistent", span)
+                    .add_help("the code after the loop invariant is verified vacuously")
+            }
+            VacuityCheck::Branch => PrustiError::vacuity("this branch is unreachable", span),
+        }
+    }
+
     pub fn translate_verification_error(&self, ver_error: &VerificationError) -> PrustiError {
         debug!("Verification error: {:?}", ver_error);
         let opt_pos_id: Option<u64> = match ver_error.pos_id {
@@ -214,7 +524,7 @@ impl<'tcx> ErrorManager<'tcx>
                             ver_error.full_id, pos_id, ver_error.message
                         ),
                         error_span
-                    ).set_help(
+                    ).add_help(
                         "This could be caused by too small assertion timeout. \
                         Try increasing it by setting the configuration parameter \
                         ASSERT_TIMEOUT to a larger value."
@@ -227,7 +537,7 @@ impl<'tcx> ErrorManager<'tcx>
                             ver_error.full_id, ver_error.message
                         ),
                         error_span
-                    ).set_help(
+                    ).add_help(
                         "This could be caused by too small assertion timeout. \
                         Try increasing it by setting the configuration parameter \
                         ASSERT_TIMEOUT to a larger value."
@@ -236,7 +546,12 @@ impl<'tcx> ErrorManager<'tcx>
             }
         };
 
-        match (ver_error.full_id.as_str(), error_ctxt) {
+        let related_notes = opt_pos_id
+            .and_then(|pos_id| self.related_notes.get(&pos_id))
+            .cloned()
+            .unwrap_or_default();
+
+        let prusti_error = match (ver_error.full_id.as_str(), error_ctxt) {
             ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::Unknown)) => {
                 PrustiError::verification("statement might panic", error_span)
                     .set_failing_assertion(opt_cause_span)
@@ -262,8 +577,38 @@ impl<'tcx> ErrorManager<'tcx>
                     .set_failing_assertion(opt_cause_span)
             }
 
-            ("assert.failed:assertion.false", ErrorCtxt::AssertTerminator(ref message)) => {
-                PrustiError::verification(format!("assertion might fail with \"{}\"", message), error_span)
+            ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::StrSlicing)) => {
+                PrustiError::verification(
+                    "the string might be sliced out of bounds or not at a char boundary",
+                    error_span,
+                ).set_failing_assertion(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::Unwrap)) => {
+                PrustiError::verification(
+                    "unwrap might be called on a None or Err value",
+                    error_span,
+                ).set_failing_assertion(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::IndexOutOfBounds)) => {
+                PrustiError::verification("the index might be out of bounds", error_span)
+                    .set_failing_assertion(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertTerminator(ref cause)) => {
+                cause.to_error(error_span, opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::OverflowCheck(OverflowCheck::Cast)) |
+            ("application.precondition:assertion.false", ErrorCtxt::OverflowCheck(OverflowCheck::Cast)) => {
+                PrustiError::verification("the cast may truncate the value", error_span)
+                    .set_failing_assertion(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::OverflowCheck(OverflowCheck::ShiftAmount)) |
+            ("application.precondition:assertion.false", ErrorCtxt::OverflowCheck(OverflowCheck::ShiftAmount)) => {
+                PrustiError::verification("the shift amount may exceed the bit width", error_span)
                     .set_failing_assertion(opt_cause_span)
             }
 
@@ -277,7 +622,7 @@ impl<'tcx> ErrorManager<'tcx>
                     "unreachable code might be reachable",
                     error_span
                 ).set_failing_assertion(opt_cause_span)
-                    .set_help("This might be a bug in the Rust compiler.")
+                    .add_help("This might be a bug in the Rust compiler.")
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition) => {
@@ -285,6 +630,13 @@ impl<'tcx> ErrorManager<'tcx>
                     .set_failing_assertion(opt_cause_span)
             }
 
+            ("exhale.failed:insufficient.permission", ErrorCtxt::ExhaleMethodPrecondition) => {
+                PrustiError::verification(
+                    "the permission required by the function call might not be held.",
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
             ("fold.failed:assertion.false", ErrorCtxt::ExhaleMethodPrecondition) => {
                 PrustiError::verification(
                     "implicit type invariant expected by the function call might not hold.",
@@ -294,12 +646,12 @@ impl<'tcx> ErrorManager<'tcx>
 
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleMethodPostcondition) => {
                 PrustiError::verification("postcondition might not hold.", error_span)
-                    .push_primary_span(opt_cause_span)
+                    .set_failing_part(opt_cause_span)
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantOnEntry) => {
                 PrustiError::verification("loop invariant might not hold in the first loop iteration.", error_span)
-                    .push_primary_span(opt_cause_span)
+                    .set_failing_part(opt_cause_span)
             }
 
             ("fold.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantOnEntry) => {
@@ -311,21 +663,31 @@ impl<'tcx> ErrorManager<'tcx>
 
             ("assert.failed:assertion.false", ErrorCtxt::AssertLoopInvariantOnEntry) => {
                 PrustiError::verification("loop invariant might not hold in the first loop iteration.", error_span)
-                    .push_primary_span(opt_cause_span)
+                    .set_failing_part(opt_cause_span)
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::ExhaleLoopInvariantAfterIteration) => {
                 PrustiError::verification(
                     "loop invariant might not hold after a loop iteration that preserves the loop condition.",
                     error_span
-                ).push_primary_span(opt_cause_span)
+                ).set_failing_part(opt_cause_span)
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::AssertLoopInvariantAfterIteration) => {
                 PrustiError::verification(
                     "loop invariant might not hold after a loop iteration that preserves the loop condition.",
                     error_span
-                ).push_primary_span(opt_cause_span)
+                ).set_failing_part(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertInferredLoopInvariant) => {
+                PrustiError::internal("the inferred loop invariant might not hold.", error_span)
+                    .set_failing_assertion(opt_cause_span)
+                    .add_help(
+                        "This is a bug in the inference of loop invariants. As a workaround, \
+                        disable it by setting the configuration parameter INFER_LOOP_INVARIANTS \
+                        to false."
+                    )
             }
 
             ("application.precondition:assertion.false", ErrorCtxt::PureFunctionCall) => {
@@ -340,7 +702,7 @@ impl<'tcx> ErrorManager<'tcx>
                     "use of impure function might be reachable.",
                     error_span
                 ).set_failing_assertion(opt_cause_span)
-                    .set_help("Functions called from assertions should be marked as pure.")
+                    .add_help("Functions called from assertions should be marked as pure.")
             }
 
             ("package.failed:assertion.false", ErrorCtxt::PackageMagicWandForPostcondition) => {
@@ -417,13 +779,8 @@ impl<'tcx> ErrorManager<'tcx>
 
             (
                 "application.precondition:assertion.false",
-                ErrorCtxt::PureFunctionAssertTerminator(ref message),
-            ) => {
-                PrustiError::verification(
-                    format!("assertion might fail with \"{}\"", message),
-                    error_span
-                ).set_failing_assertion(opt_cause_span)
-            },
+                ErrorCtxt::PureFunctionAssertTerminator(ref cause),
+            ) => cause.to_error(error_span, opt_cause_span),
 
             ("apply.failed:assertion.false", ErrorCtxt::ApplyMagicWandOnExpiry) => {
                 PrustiError::verification("obligation might not hold on borrow expiry", error_span)
@@ -432,7 +789,7 @@ impl<'tcx> ErrorManager<'tcx>
 
             ("assert.failed:assertion.false", ErrorCtxt::AssertMethodPostcondition) => {
                 PrustiError::verification(format!("postcondition might not hold."), error_span)
-                    .push_primary_span(opt_cause_span)
+                    .set_failing_part(opt_cause_span)
             }
 
             (
@@ -457,14 +814,14 @@ impl<'tcx> ErrorManager<'tcx>
                 PrustiError::verification(format!("the method's precondition may not be a valid weakening of the trait's precondition."), error_span)
                     //.push_primary_span(opt_cause_span)
                     .push_primary_span(Some(&impl_span))
-                    .set_help("The trait's precondition should imply the implemented method's precondition.")
+                    .add_help("The trait's precondition should imply the implemented method's precondition.")
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::AssertMethodPostconditionStrengthening(impl_span)) => {
                 PrustiError::verification(format!("the method's postcondition may not be a valid strengthening of the trait's postcondition."), error_span)
                     //.push_primary_span(opt_cause_span)
                     .push_primary_span(Some(&impl_span))
-                    .set_help("The implemented method's postcondition should imply the trait's postcondition.")
+                    .add_help("The implemented method's postcondition should imply the trait's postcondition.")
             }
 
             ("assert.failed:assertion.false", ErrorCtxt::Unsupported(ref reason, ref help)) => {
@@ -472,7 +829,7 @@ impl<'tcx> ErrorManager<'tcx>
                     format!("an unsupported Rust feature might be reachable: {}.", reason),
                     error_span
                 ).set_failing_assertion(opt_cause_span)
-                .set_help(help)
+                .add_help(help)
             }
 
             (full_err_id, ErrorCtxt::Unexpected) => {
@@ -484,7 +841,7 @@ impl<'tcx> ErrorManager<'tcx>
                     error_span,
                 ).set_failing_assertion(
                     opt_cause_span
-                ).set_help(
+                ).add_help(
                     "This could be caused by too small assertion timeout. \
                     Try increasing it by setting the configuration parameter \
                     ASSERT_TIMEOUT to a larger value."
@@ -504,12 +861,16 @@ impl<'tcx> ErrorManager<'tcx>
                     error_span,
                 ).set_failing_assertion(
                     opt_cause_span
-                ).set_help(
+                ).add_help(
                     "This could be caused by too small assertion timeout. \
                     Try increasing it by setting the configuration parameter \
                     ASSERT_TIMEOUT to a larger value."
                 )
             }
-        }
+        };
+
+        related_notes.into_iter().fold(prusti_error, |error, (note, span)| {
+            error.add_note(note, Some(span))
+        })
     }
 }
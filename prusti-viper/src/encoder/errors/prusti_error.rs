@@ -21,8 +21,9 @@ pub struct PrustiError {
     is_error: bool,
     message: String,
     span: MultiSpan,
-    help: Option<String>,
-    note: Option<(String, MultiSpan)>,
+    help: Vec<String>,
+    /// Notes, optionally with the span of the related code.
+    notes: Vec<(String, Option<MultiSpan>)>,
 }
 
 impl PrustiError {
@@ -32,8 +33,8 @@ impl PrustiError {
             is_error: true,
             message,
             span,
-            help: None,
-            note: None,
+            help: vec![],
+            notes: vec![],
         }
     }
 
@@ -59,6 +60,18 @@ impl PrustiError {
         error
     }
 
+    /// Report a part of the verified Rust code that is verified vacuously (e.g. because of an
+    /// unsatisfiable precondition). This is always a warning.
+    pub fn vacuity<S: ToString>(message: S, span: MultiSpan) -> Self {
+        check_message(message.to_string());
+        let mut error = PrustiError::new(
+            format!("[Prusti: vacuity] {}", message.to_string()),
+            span
+        );
+        error.set_warning();
+        error
+    }
+
     /// Report an incorrect usage of Prusti (e.g. call an impure function in a contract)
     pub fn incorrect<S: ToString>(message: S, span: MultiSpan) -> Self {
         check_message(message.to_string());
@@ -86,26 +99,40 @@ impl PrustiError {
         self.is_error
     }
 
-    pub fn set_help<S: ToString>(mut self, message: S) -> Self {
-        self.help = Some(message.to_string());
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> &MultiSpan {
+        &self.span
+    }
+
+    pub fn add_help<S: ToString>(mut self, message: S) -> Self {
+        self.help.push(message.to_string());
+        self
+    }
+
+    /// Add a note, optionally pointing at related code.
+    pub fn add_note<S: ToString>(mut self, message: S, opt_span: Option<MultiSpan>) -> Self {
+        self.notes.push((message.to_string(), opt_span));
         self
     }
 
     /// Report the encoding error using the compiler's interface
     pub fn emit(self, env: &Environment) {
         if self.is_error {
-            env.span_err_with_help_and_note(
+            env.span_err_with_help_and_notes(
                 self.span,
                 &self.message,
                 &self.help,
-                &self.note,
+                &self.notes,
             );
         } else {
-            env.span_warn_with_help_and_note(
+            env.span_warn_with_help_and_notes(
                 self.span,
                 &self.message,
                 &self.help,
-                &self.note,
+                &self.notes,
             );
         }
     }
@@ -115,7 +142,21 @@ impl PrustiError {
     /// Note: this is a noop if `opt_span` is None
     pub fn set_failing_assertion(mut self, opt_span: Option<&MultiSpan>) -> Self {
         if let Some(span) = opt_span {
-            self.note = Some(("the failing assertion is here".to_string(), span.clone()));
+            self.notes.push(("the failing assertion is here".to_string(), Some(span.clone())));
+        }
+        self
+    }
+
+    /// Report the failing part of a specification as the error span, and show the whole
+    /// specification (the original error span) in a note.
+    ///
+    /// Note: this is a noop if `opt_span` is None or if it is the original error span
+    pub fn set_failing_part(mut self, opt_span: Option<&MultiSpan>) -> Self {
+        if let Some(span) = opt_span {
+            if *span != self.span {
+                let full_span = std::mem::replace(&mut self.span, span.clone());
+                self.notes.push(("the full specification is here".to_string(), Some(full_span)));
+            }
         }
         self
     }
@@ -125,8 +166,8 @@ impl PrustiError {
     /// Note: this is a noop if `opt_span` is None
     pub fn push_primary_span(mut self, opt_span: Option<&MultiSpan>) -> Self {
         if let Some(span) = opt_span {
-            self.note = Some(("the error originates here".to_string(), self.span));
-            self.span = span.clone();
+            let original_span = std::mem::replace(&mut self.span, span.clone());
+            self.notes.push(("the error originates here".to_string(), Some(original_span)));
         }
         self
     }
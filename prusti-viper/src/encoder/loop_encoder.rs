@@ -4,9 +4,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use prusti_common::config;
 use prusti_interface::environment::mir_analyses::initialization::{
     compute_definitely_initialized, DefinitelyInitializedAnalysisResult,
 };
+use prusti_interface::environment::mir_analyses::intervals::{
+    compute_intervals, Fact, IntegerBounds, IntervalAnalysisResult,
+};
 use prusti_interface::environment::place_set::PlaceSet;
 use prusti_interface::environment::{BasicBlockIndex, PermissionForest, ProcedureLoops, Procedure};
 use prusti_interface::utils;
@@ -21,6 +25,8 @@ pub struct LoopEncoder<'p, 'tcx: 'p> {
     procedure: &'p Procedure<'p, 'tcx>,
     tcx: ty::TyCtxt<'tcx>,
     initialization: DefinitelyInitializedAnalysisResult<'tcx>,
+    /// The intervals of the integer local variables, if loop invariants should be inferred.
+    intervals: Option<IntervalAnalysisResult>,
 }
 
 impl<'p, 'tcx: 'p> LoopEncoder<'p, 'tcx> {
@@ -36,6 +42,19 @@ impl<'p, 'tcx: 'p> LoopEncoder<'p, 'tcx> {
                 tcx,
                 tcx.hir().def_path(procedure.get_id().expect_local())
             ),
+            intervals: if config::infer_loop_invariants() {
+                // Only the bounds of the integer types that are encoded can be used.
+                let bounds = if config::check_binary_operations() {
+                    IntegerBounds::All
+                } else if config::encode_unsigned_num_constraint() {
+                    IntegerBounds::Unsigned
+                } else {
+                    IntegerBounds::None
+                };
+                Some(compute_intervals(procedure, bounds))
+            } else {
+                None
+            },
         }
     }
 
@@ -130,6 +149,37 @@ impl<'p, 'tcx: 'p> LoopEncoder<'p, 'tcx> {
             .any(|def_init_place| utils::is_prefix(place, def_init_place))
     }
 
+    /// The facts about the integer local variables that are inferred to hold where the loop
+    /// invariant holds, i.e. on the edge from `loop_inv` to the next block of the loop body.
+    /// Returns nothing if the inference of loop invariants is disabled.
+    pub fn get_inferred_loop_invariant(
+        &self,
+        loop_head: BasicBlockIndex,
+        loop_inv: BasicBlockIndex,
+    ) -> Vec<Fact> {
+        let intervals = match self.intervals {
+            Some(ref intervals) => intervals,
+            None => return vec![],
+        };
+        let loop_body: Vec<BasicBlockIndex> = self
+            .loops()
+            .get_loop_body(loop_head)
+            .iter()
+            .filter(|&&bb| !self.procedure.is_spec_block(bb))
+            .cloned()
+            .collect();
+        let after_inv_block = loop_body
+            .iter()
+            .position(|&bb| bb == loop_inv)
+            .and_then(|position| loop_body.get(position + 1));
+        match after_inv_block {
+            // The state before the block joins the states of all its incoming edges, so it
+            // over-approximates the state on the edge from `loop_inv`.
+            Some(&bb) => intervals.get_before_block(bb).facts(),
+            None => vec![],
+        }
+    }
+
     /// Return the block at whose end the loop invariant holds
     pub fn get_loop_invariant_block(
         &self,
@@ -4,8 +4,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::encoder::builtin_encoder::BuiltinFunctionKind;
-use crate::encoder::errors::ErrorCtxt;
+use crate::encoder::builtin_encoder::{BitOperationKind, BuiltinFunctionKind, IntegerKind};
+use crate::encoder::errors::{EncodingError, ErrorCtxt};
 use crate::encoder::Encoder;
 use prusti_common::vir;
 use prusti_common::config;
@@ -441,15 +441,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         self.encoder.encode_value_type(ty)
     }
 
+    /// Encode a binary operation on operands of type `ty`. The `span` is used to report
+    /// unsupported operations.
     pub fn encode_bin_op_expr(
         &self,
         op: mir::BinOp,
         left: vir::Expr,
         right: vir::Expr,
         ty: ty::Ty<'tcx>,
-    ) -> vir::Expr {
+        span: Span,
+    ) -> Result<vir::Expr, EncodingError> {
         let is_bool = ty.kind == ty::TyKind::Bool;
-        match op {
+        Ok(match op {
             mir::BinOp::Eq => vir::Expr::eq_cmp(left, right),
             mir::BinOp::Ne => vir::Expr::ne_cmp(left, right),
             mir::BinOp::Gt => vir::Expr::gt_cmp(left, right),
@@ -464,13 +467,117 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
             mir::BinOp::BitAnd if is_bool => vir::Expr::and(left, right),
             mir::BinOp::BitOr if is_bool => vir::Expr::or(left, right),
             mir::BinOp::BitXor if is_bool => vir::Expr::xor(left, right),
-            x => unimplemented!("{:?}", x),
+            mir::BinOp::BitAnd => {
+                self.encode_bit_op_expr(BitOperationKind::And, left, right, ty, span)?
+            }
+            mir::BinOp::BitOr => {
+                self.encode_bit_op_expr(BitOperationKind::Or, left, right, ty, span)?
+            }
+            mir::BinOp::BitXor => {
+                self.encode_bit_op_expr(BitOperationKind::Xor, left, right, ty, span)?
+            }
+            mir::BinOp::Shl => {
+                self.encode_bit_op_expr(BitOperationKind::Shl, left, right, ty, span)?
+            }
+            mir::BinOp::Shr => {
+                self.encode_bit_op_expr(BitOperationKind::Shr, left, right, ty, span)?
+            }
+            mir::BinOp::Offset => {
+                return Err(EncodingError::unsupported(
+                    "pointer offsets are not supported",
+                    span,
+                ));
+            }
+        })
+    }
+
+    /// Returns the number of bits and the signedness of an integer type.
+    pub fn encode_integer_kind(ty: ty::Ty<'tcx>) -> Option<IntegerKind> {
+        let pointer_bits = config::target_pointer_width();
+        let (bits, signed) = match ty.kind {
+            ty::TyKind::Int(ast::IntTy::I8) => (8, true),
+            ty::TyKind::Int(ast::IntTy::I16) => (16, true),
+            ty::TyKind::Int(ast::IntTy::I32) => (32, true),
+            ty::TyKind::Int(ast::IntTy::I64) => (64, true),
+            ty::TyKind::Int(ast::IntTy::I128) => (128, true),
+            ty::TyKind::Int(ast::IntTy::Isize) => (pointer_bits, true),
+            ty::TyKind::Uint(ast::UintTy::U8) => (8, false),
+            ty::TyKind::Uint(ast::UintTy::U16) => (16, false),
+            ty::TyKind::Uint(ast::UintTy::U32) => (32, false),
+            ty::TyKind::Uint(ast::UintTy::U64) => (64, false),
+            ty::TyKind::Uint(ast::UintTy::U128) => (128, false),
+            ty::TyKind::Uint(ast::UintTy::Usize) => (pointer_bits, false),
+            _ => return None,
+        };
+        Some(IntegerKind { bits, signed })
+    }
+
+    /// Encode a bitwise operation on integers of type `ty`.
+    ///
+    /// Shifts by a constant amount are encoded exactly, using arithmetic operations. All other
+    /// operations are encoded as calls of uninterpreted functions, whose postconditions state
+    /// some properties of the operation.
+    fn encode_bit_op_expr(
+        &self,
+        op: BitOperationKind,
+        left: vir::Expr,
+        right: vir::Expr,
+        ty: ty::Ty<'tcx>,
+        span: Span,
+    ) -> Result<vir::Expr, EncodingError> {
+        let int_kind = Self::encode_integer_kind(ty).ok_or_else(|| {
+            EncodingError::unsupported(
+                format!("bitwise operations on values of type '{}' are not supported", ty),
+                span,
+            )
+        })?;
+        let shift_amount = match right {
+            vir::Expr::Const(vir::Const::Int(value), _) => Some(value as i128),
+            vir::Expr::Const(vir::Const::BigInt(ref value), _) => value.parse::<i128>().ok(),
+            _ => None,
         }
+        .filter(|&amount| 0 <= amount && amount < int_kind.bits as i128);
+        Ok(match (op, shift_amount) {
+            (BitOperationKind::Shl, Some(amount)) => {
+                // Bits shifted out of the range of the type are discarded.
+                int_kind.wrap(vir::Expr::mul(left, IntegerKind::pow2(amount as u32)))
+            }
+            (BitOperationKind::Shr, Some(amount)) => {
+                // Viper's division rounds towards negative infinity (for a positive divisor), like
+                // an arithmetic right shift.
+                vir::Expr::div(left, IntegerKind::pow2(amount as u32))
+            }
+            _ => {
+                let function_name = self
+                    .encoder
+                    .encode_builtin_function_use(BuiltinFunctionKind::BitOperation(op, int_kind));
+                vir::Expr::func_app(
+                    function_name,
+                    vec![left, right],
+                    vec![
+                        vir::LocalVar::new("left", vir::Type::Int),
+                        vir::LocalVar::new("right", vir::Type::Int),
+                    ],
+                    vir::Type::Int,
+                    vir::Position::default(),
+                )
+            }
+        })
     }
 
-    pub fn encode_unary_op_expr(&self, op: mir::UnOp, expr: vir::Expr) -> vir::Expr {
+    pub fn encode_unary_op_expr(
+        &self,
+        op: mir::UnOp,
+        expr: vir::Expr,
+        ty: ty::Ty<'tcx>,
+    ) -> vir::Expr {
         match op {
-            mir::UnOp::Not => vir::Expr::not(expr),
+            mir::UnOp::Not => match Self::encode_integer_kind(ty) {
+                // Bitwise negation
+                Some(int_kind) if !int_kind.signed => vir::Expr::sub(int_kind.max_value(), expr),
+                Some(_) => vir::Expr::sub(vir::Expr::minus(expr), 1.into()),
+                None => vir::Expr::not(expr),
+            },
             mir::UnOp::Neg => vir::Expr::minus(expr),
         }
     }
@@ -486,73 +593,36 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         if !op.is_checkable() || !config::check_binary_operations() {
             false.into()
         } else {
-            let result = self.encode_bin_op_expr(op, left.clone(), right.clone(), ty);
+            let int_kind = match Self::encode_integer_kind(ty) {
+                Some(int_kind) => int_kind,
+                None => {
+                    debug!(
+                        "Encoding of bin op check '{:?}' is incomplete for type {:?}",
+                        op, ty
+                    );
+                    return false.into();
+                }
+            };
 
             match op {
-                mir::BinOp::Add | mir::BinOp::Mul | mir::BinOp::Sub => match ty.kind {
-                    // Unsigned
-                    ty::TyKind::Uint(ast::UintTy::U8) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u8::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u8::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U16) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u16::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u16::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U32) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u32::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u32::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U64) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u64::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u64::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::U128) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::u128::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::u128::MAX.into()),
-                    ),
-                    ty::TyKind::Uint(ast::UintTy::Usize) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::usize::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::usize::MAX.into()),
-                    ),
-                    // Signed
-                    ty::TyKind::Int(ast::IntTy::I8) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i8::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i8::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I16) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i16::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i16::MIN.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I32) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i32::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i32::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I64) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i64::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i64::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::I128) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::i128::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::i128::MAX.into()),
-                    ),
-                    ty::TyKind::Int(ast::IntTy::Isize) => vir::Expr::or(
-                        vir::Expr::lt_cmp(result.clone(), std::isize::MIN.into()),
-                        vir::Expr::gt_cmp(result, std::isize::MAX.into()),
-                    ),
-
-                    _ => {
-                        debug!(
-                            "Encoding of bin op check '{:?}' is incomplete for type {:?}",
-                            op, ty
-                        );
-                        false.into()
-                    }
-                },
+                mir::BinOp::Add | mir::BinOp::Mul | mir::BinOp::Sub => {
+                    let result = match op {
+                        mir::BinOp::Add => vir::Expr::add(left, right),
+                        mir::BinOp::Sub => vir::Expr::sub(left, right),
+                        _ => vir::Expr::mul(left, right),
+                    };
+                    vir::Expr::or(
+                        vir::Expr::lt_cmp(result.clone(), int_kind.min_value()),
+                        vir::Expr::gt_cmp(result, int_kind.max_value()),
+                    )
+                }
 
                 mir::BinOp::Shl | mir::BinOp::Shr => {
-                    debug!("Encoding of bin op check '{:?}' is incomplete", op);
-                    false.into()
+                    // The shift amount must be less than the number of bits of `left`.
+                    vir::Expr::or(
+                        vir::Expr::lt_cmp(right.clone(), 0.into()),
+                        vir::Expr::ge_cmp(right, (int_kind.bits as i64).into()),
+                    )
                 }
 
                 _ => unreachable!("{:?}", op),
@@ -560,146 +630,75 @@ impl<'p, 'v: 'p, 'tcx: 'v> MirEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// The kind of the integer values of `ty` that can be cast with `as`. A `char` is cast as
+    /// its code point, which is a `u32`.
+    fn encode_cast_integer_kind(ty: ty::Ty<'tcx>) -> Option<IntegerKind> {
+        match ty.kind {
+            ty::TyKind::Char => Some(IntegerKind { bits: 32, signed: false }),
+            _ => Self::encode_integer_kind(ty),
+        }
+    }
+
+    /// Encode the value of `operand as dst_ty`. A value that does not fit in the destination
+    /// type wraps around, as in Rust. The `span` is used to report unsupported casts.
     pub fn encode_cast_expr(
         &self,
         operand: &mir::Operand<'tcx>,
         dst_ty: ty::Ty<'tcx>,
-    ) -> vir::Expr {
+        span: Span,
+    ) -> Result<vir::Expr, EncodingError> {
         let src_ty = self.get_operand_ty(operand);
+        let encoded_val = self.encode_operand_expr(operand);
+        let dst_kind = Self::encode_integer_kind(dst_ty);
 
-        let encoded_val = match (&src_ty.kind, &dst_ty.kind) {
-            (ty::TyKind::Int(ast::IntTy::I8), ty::TyKind::Int(ast::IntTy::I8))
-            | (ty::TyKind::Int(ast::IntTy::I8), ty::TyKind::Int(ast::IntTy::I16))
-            | (ty::TyKind::Int(ast::IntTy::I8), ty::TyKind::Int(ast::IntTy::I32))
-            | (ty::TyKind::Int(ast::IntTy::I8), ty::TyKind::Int(ast::IntTy::I64))
-            | (
-                ty::TyKind::Int(ast::IntTy::I8),
-                ty::TyKind::Int(ast::IntTy::I128),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I16),
-                ty::TyKind::Int(ast::IntTy::I16),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I16),
-                ty::TyKind::Int(ast::IntTy::I32),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I16),
-                ty::TyKind::Int(ast::IntTy::I64),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I16),
-                ty::TyKind::Int(ast::IntTy::I128),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I32),
-                ty::TyKind::Int(ast::IntTy::I32),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I32),
-                ty::TyKind::Int(ast::IntTy::I64),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I32),
-                ty::TyKind::Int(ast::IntTy::I128),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I64),
-                ty::TyKind::Int(ast::IntTy::I64),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I64),
-                ty::TyKind::Int(ast::IntTy::I128),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::I128),
-                ty::TyKind::Int(ast::IntTy::I128),
-            )
-            | (
-                ty::TyKind::Int(ast::IntTy::Isize),
-                ty::TyKind::Int(ast::IntTy::Isize),
-            )
-            | (ty::TyKind::Char, ty::TyKind::Char)
-            | (ty::TyKind::Char, ty::TyKind::Uint(ast::UintTy::U8))
-            | (ty::TyKind::Char, ty::TyKind::Uint(ast::UintTy::U16))
-            | (ty::TyKind::Char, ty::TyKind::Uint(ast::UintTy::U32))
-            | (ty::TyKind::Char, ty::TyKind::Uint(ast::UintTy::U64))
-            | (ty::TyKind::Char, ty::TyKind::Uint(ast::UintTy::U128))
-            | (ty::TyKind::Uint(ast::UintTy::U8), ty::TyKind::Char)
-            | (
-                ty::TyKind::Uint(ast::UintTy::U8),
-                ty::TyKind::Uint(ast::UintTy::U8),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U8),
-                ty::TyKind::Uint(ast::UintTy::U16),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U8),
-                ty::TyKind::Uint(ast::UintTy::U32),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U8),
-                ty::TyKind::Uint(ast::UintTy::U64),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U8),
-                ty::TyKind::Uint(ast::UintTy::U128),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U16),
-                ty::TyKind::Uint(ast::UintTy::U16),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U16),
-                ty::TyKind::Uint(ast::UintTy::U32),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U16),
-                ty::TyKind::Uint(ast::UintTy::U64),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U16),
-                ty::TyKind::Uint(ast::UintTy::U128),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U32),
-                ty::TyKind::Uint(ast::UintTy::U32),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U32),
-                ty::TyKind::Uint(ast::UintTy::U64),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U32),
-                ty::TyKind::Uint(ast::UintTy::U128),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U64),
-                ty::TyKind::Uint(ast::UintTy::U64),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U64),
-                ty::TyKind::Uint(ast::UintTy::U128),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::U128),
-                ty::TyKind::Uint(ast::UintTy::U128),
-            )
-            | (
-                ty::TyKind::Uint(ast::UintTy::Usize),
-                ty::TyKind::Uint(ast::UintTy::Usize),
-            ) => self.encode_operand_expr(operand),
-
-            _ => unimplemented!(
-                "unimplemented cast from type '{:?}' to type '{:?}'",
-                src_ty,
-                dst_ty
-            ),
-        };
+        Ok(match (&src_ty.kind, &dst_ty.kind) {
+            (ty::TyKind::Bool, _) if dst_kind.is_some() => {
+                vir::Expr::ite(encoded_val, 1.into(), 0.into())
+            }
+            (ty::TyKind::Char, ty::TyKind::Char)
+            | (ty::TyKind::Uint(ast::UintTy::U8), ty::TyKind::Char) => encoded_val,
+            _ => match (Self::encode_cast_integer_kind(src_ty), dst_kind) {
+                (Some(src_kind), Some(dst_kind)) => {
+                    if dst_kind.includes(&src_kind) {
+                        encoded_val
+                    } else {
+                        vir::Expr::ite(
+                            dst_kind.contains(encoded_val.clone()),
+                            encoded_val.clone(),
+                            dst_kind.wrap(encoded_val),
+                        )
+                    }
+                }
+                _ => {
+                    return Err(EncodingError::unsupported(
+                        format!(
+                            "casts from type '{}' to type '{}' are not supported",
+                            src_ty, dst_ty
+                        ),
+                        span,
+                    ));
+                }
+            },
+        })
+    }
 
-        encoded_val
+    /// Encode the condition under which `operand as dst_ty` preserves the value of the operand.
+    /// Returns `None` if the cast cannot change the value or if overflows are not checked.
+    pub fn encode_cast_check(
+        &self,
+        operand: &mir::Operand<'tcx>,
+        dst_ty: ty::Ty<'tcx>,
+    ) -> Option<vir::Expr> {
+        if !config::check_binary_operations() {
+            return None;
+        }
+        let src_kind = Self::encode_cast_integer_kind(self.get_operand_ty(operand))?;
+        let dst_kind = Self::encode_integer_kind(dst_ty)?;
+        if dst_kind.includes(&src_kind) {
+            None
+        } else {
+            Some(dst_kind.contains(self.encode_operand_expr(operand)))
+        }
     }
 
     pub fn encode_operand_place(&self, operand: &mir::Operand<'tcx>) -> Option<vir::Expr> {
@@ -9,7 +9,7 @@ pub use self::{encoder::Encoder, errors::PrustiError};
 mod borrows;
 mod builtin_encoder;
 mod encoder;
-mod errors;
+pub(crate) mod errors;
 mod foldunfold;
 mod initialisation;
 mod loop_encoder;
@@ -20,10 +20,14 @@ mod optimizer;
 mod places;
 mod procedure_encoder;
 mod pure_function_encoder;
+mod seq_encoder;
 mod snapshot_encoder;
 mod snapshot_spec_patcher;
 mod spec_encoder;
+mod std_encoder;
+mod str_encoder;
 mod stub_function_encoder;
 mod stub_procedure_encoder;
+mod trait_method_encoder;
 mod type_encoder;
 // mod utils;
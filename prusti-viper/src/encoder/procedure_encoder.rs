@@ -6,7 +6,10 @@
 
 use crate::encoder::borrows::ProcedureContract;
 use crate::encoder::builtin_encoder::BuiltinMethodKind;
+use crate::encoder::errors::CheckedPanics;
 use crate::encoder::errors::PanicCause;
+use crate::encoder::errors::VacuityCheck;
+use crate::encoder::errors::OverflowCheck;
 use crate::encoder::errors::{EncodingError, ErrorCtxt};
 use crate::encoder::foldunfold;
 use crate::encoder::initialisation::InitInfo;
@@ -18,9 +21,11 @@ use crate::encoder::optimizer;
 use crate::encoder::places::{Local, LocalVariableManager, Place};
 use crate::encoder::Encoder;
 use crate::encoder::snapshot_spec_patcher::SnapshotSpecPatcher;
+use crate::encoder::std_encoder::{is_identity_call, StdMethodEncoder};
+use crate::encoder::str_encoder::{is_bytes_value_type, is_str_reference, StrMethodKind};
 use prusti_common::{
     config,
-    report::log,
+    report::{log, user},
     utils::to_string::ToString,
     vir::{
         self,
@@ -39,6 +44,7 @@ use prusti_interface::{
             LoanPlaces, PoloniusInfo, PoloniusInfoError, ReborrowingDAG, ReborrowingDAGNode,
             ReborrowingKind, ReborrowingZombity,
         },
+        mir_analyses::intervals::Fact,
         BasicBlockIndex, PermissionKind, Procedure,
     },
 };
@@ -75,6 +81,8 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     auxiliary_local_vars: HashMap<String, vir::Type>,
     mir_encoder: MirEncoder<'p, 'v, 'tcx>,
     check_panics: bool,
+    checked_panics: CheckedPanics,
+    check_vacuity: bool,
     check_fold_unfold_state: bool,
     polonius_info: Option<PoloniusInfo<'p, 'tcx>>,
     procedure_contract: Option<ProcedureContract<'tcx>>,
@@ -101,6 +109,14 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     old_ghost_vars: HashMap<String, vir::Type>,
     /// For each loop head, the block at whose end the loop invariant holds
     cached_loop_invariant_block: HashMap<BasicBlockIndex, BasicBlockIndex>,
+    /// For each local variable that holds a copy of a raw pointer, the place from which the
+    /// pointer was copied. The permission of the target is given back to this place after the
+    /// copy has been used (e.g. passed to a function).
+    raw_pointer_origins: HashMap<vir::Expr, vir::Expr>,
+    /// The positions of the vacuity checks of the loop invariants (by loop head) and of the
+    /// branches (by target block). They are shared by all the copies of a MIR block in the
+    /// encoding, so that a check is reported only if it verifies in all of them.
+    vacuity_check_positions: HashMap<(VacuityCheck, BasicBlockIndex), vir::Position>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -121,6 +137,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }
         };
 
+        if let Some(span) = find_mutable_trait_object_cast(mir) {
+            return Err(EncodingError::unsupported(
+                "casts of mutable references to trait objects are not supported",
+                span,
+            ));
+        }
+
+        if let Some(span) = find_raw_pointer_from_reference(mir, tcx) {
+            return Err(EncodingError::unsupported(
+                "creating raw pointers from references is not supported",
+                span,
+            ));
+        }
+
         let cfg_method = vir::CfgMethod::new(
             // method name
             encoder.encode_item_name(def_id),
@@ -145,6 +175,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             auxiliary_local_vars: HashMap::new(),
             mir_encoder: mir_encoder,
             check_panics: config::check_panics(),
+            checked_panics: CheckedPanics {
+                index_bounds: config::check_index_bounds(),
+                division_by_zero: config::check_division_by_zero(),
+                unwrap: config::check_unwrap(),
+                overflows: config::check_overflows(),
+            },
+            check_vacuity: config::check_vacuity(),
             check_fold_unfold_state: config::check_foldunfold_state(),
             polonius_info: None,
             procedure_contract: None,
@@ -159,6 +196,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             old_to_ghost_var: HashMap::new(),
             old_ghost_vars: HashMap::new(),
             cached_loop_invariant_block: HashMap::new(),
+            raw_pointer_origins: HashMap::new(),
+            vacuity_check_positions: HashMap::new(),
         })
     }
 
@@ -942,6 +981,29 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 complete_resolution = false;
             }
         }
+
+        // Check that each branch of a conditional is reachable
+        let is_switch = matches!(
+            self.mir[bbi].terminator().kind,
+            TerminatorKind::SwitchInt { .. }
+        );
+        if self.check_vacuity && is_switch {
+            let mut checked_targets = HashSet::new();
+            for &target in &mir_targets {
+                let edge_block = match targets_map.get(&target) {
+                    Some(&edge_block) => edge_block,
+                    None => continue,
+                };
+                if !checked_targets.insert(target) || self.leads_to_panic(target) {
+                    continue;
+                }
+                let span = self.mir_encoder.get_span_of_basic_block(target);
+                let pos = self.get_vacuity_check_pos(VacuityCheck::Branch, target, span);
+                let stmts = self.encode_vacuity_check(pos);
+                self.cfg_method.add_stmts(edge_block, stmts);
+            }
+        }
+
         let unresolved_edges = if complete_resolution {
             // Resolve successor and return the edge blocks
             let curr_successor =
@@ -987,6 +1049,66 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(())
     }
 
+    /// Encode a vacuity check: an `assert false` that is reached nondeterministically, and that
+    /// verifies only if the current program point is unreachable.
+    fn encode_vacuity_check(&mut self, pos: vir::Position) -> Vec<vir::Stmt> {
+        let guard_var = self.cfg_method.add_fresh_local_var(vir::Type::Bool);
+        vec![
+            vir::Stmt::comment("Vacuity check"),
+            vir::Stmt::If(
+                vir::Expr::local(guard_var),
+                vec![vir::Stmt::Assert(false.into(), vir::FoldingBehaviour::Stmt, pos)],
+                vec![],
+            ),
+        ]
+    }
+
+    /// Returns the position of the vacuity check of the given kind for the given MIR block,
+    /// registering it with the given span the first time.
+    fn get_vacuity_check_pos<T: Into<MultiSpan>>(
+        &mut self,
+        check: VacuityCheck,
+        bbi: BasicBlockIndex,
+        span: T,
+    ) -> vir::Position {
+        if let Some(&pos) = self.vacuity_check_positions.get(&(check, bbi)) {
+            return pos;
+        }
+        let pos = self
+            .encoder
+            .error_manager()
+            .register(span, ErrorCtxt::VacuityCheck(check));
+        self.vacuity_check_positions.insert((check, bbi), pos);
+        pos
+    }
+
+    /// Whether all the executions that reach the block end in a panic, an abort or an
+    /// `unreachable` terminator. Such a block is meant to be unreachable, so it is not subject to
+    /// vacuity checks.
+    fn leads_to_panic(&self, bbi: BasicBlockIndex) -> bool {
+        let mut curr_bb = bbi;
+        let mut visited = HashSet::new();
+        while visited.insert(curr_bb) {
+            let bb_data = &self.mir[curr_bb];
+            if bb_data.is_cleanup || self.procedure.is_panic_block(curr_bb) {
+                return true;
+            }
+            match bb_data.terminator().kind {
+                TerminatorKind::Abort
+                | TerminatorKind::Unreachable
+                | TerminatorKind::Call {
+                    destination: None, ..
+                } => return true,
+                _ => {}
+            }
+            match self.procedure.successors(curr_bb).as_slice() {
+                [next_bb] => curr_bb = *next_bb,
+                _ => return false,
+            }
+        }
+        false
+    }
+
     /// Encode the statements of the block
     fn encode_block_statements(
         &mut self,
@@ -1043,7 +1165,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let index = location.statement_index;
         if index < bb_data.statements.len() {
             let mir_stmt = &bb_data.statements[index];
-            let stmts = self.encode_statement(mir_stmt, location);
+            let stmts = self.encode_statement(mir_stmt, location)?;
             Ok((stmts, None))
         } else {
             let mir_term = bb_data.terminator();
@@ -1056,7 +1178,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         &mut self,
         stmt: &mir::Statement<'tcx>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+    ) -> Result<Vec<vir::Stmt>> {
         debug!(
             "Encode statement '{:?}', span: {:?}",
             stmt.kind, stmt.source_info.span
@@ -1077,13 +1199,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let (encoded_lhs, ty, _) = self.mir_encoder.encode_place(lhs).unwrap(); // will panic if attempting to encode unsupported type
                 match rhs {
                     &mir::Rvalue::Use(ref operand) => {
-                        self.encode_assign_operand(&encoded_lhs, operand, location)
+                        self.encode_assign_operand(&encoded_lhs, operand, location)?
                     }
                     &mir::Rvalue::Aggregate(ref aggregate, ref operands) => self
-                        .encode_assign_aggregate(&encoded_lhs, ty, aggregate, operands, location),
-                    &mir::Rvalue::BinaryOp(op, ref left, ref right) => {
-                        self.encode_assign_binary_op(op, left, right, encoded_lhs, ty, location)
-                    }
+                        .encode_assign_aggregate(&encoded_lhs, ty, aggregate, operands, location)?,
+                    &mir::Rvalue::BinaryOp(op, ref left, ref right) => self.encode_assign_binary_op(
+                        op,
+                        left,
+                        right,
+                        encoded_lhs,
+                        ty,
+                        location,
+                        stmt.source_info.span,
+                    )?,
                     &mir::Rvalue::CheckedBinaryOp(op, ref left, ref right) => self
                         .encode_assign_checked_binary_op(
                             op,
@@ -1092,7 +1220,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             encoded_lhs,
                             ty,
                             location,
-                        ),
+                            stmt.source_info.span,
+                        )?,
                     &mir::Rvalue::UnaryOp(op, ref operand) => {
                         self.encode_assign_unary_op(op, operand, encoded_lhs, ty, location)
                     }
@@ -1106,7 +1235,24 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         self.encode_assign_ref(mir_borrow_kind, place, location, encoded_lhs, ty)
                     }
                     &mir::Rvalue::Cast(mir::CastKind::Misc, ref operand, dst_ty) => {
-                        self.encode_cast(operand, dst_ty, encoded_lhs, ty, location)
+                        let span = stmt.source_info.span;
+                        self.encode_cast(operand, dst_ty, encoded_lhs, ty, location, span)?
+                    }
+                    &mir::Rvalue::Cast(
+                        mir::CastKind::Pointer(ty::adjustment::PointerCast::Unsize),
+                        ref operand,
+                        dst_ty,
+                    ) if is_trait_object_pointer(dst_ty) => {
+                        let span = stmt.source_info.span;
+                        self.encode_trait_object_cast(operand, dst_ty, encoded_lhs, location, span)
+                    }
+                    &mir::Rvalue::Cast(
+                        mir::CastKind::Pointer(ty::adjustment::PointerCast::MutToConstPointer),
+                        ref operand,
+                        _,
+                    ) => {
+                        // `*mut T` and `*const T` have the same encoding.
+                        self.encode_assign_operand(&encoded_lhs, operand, location)?
                     }
                     ref rhs => {
                         unimplemented!("encoding of '{:?}'", rhs);
@@ -1117,7 +1263,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             ref x => unimplemented!("{:?}", x),
         };
         stmts.extend(encoding_stmts);
-        stmts
+        Ok(stmts
             .into_iter()
             .map(|s| {
                 let expr_pos = self
@@ -1130,7 +1276,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     .register(stmt.source_info.span, ErrorCtxt::GenericStatement);
                 s.set_default_expr_pos(expr_pos).set_default_pos(stmt_pos)
             })
-            .collect()
+            .collect())
     }
 
     /// Translate a borrowed place to a place that is currently usable
@@ -1419,7 +1565,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         _mir_dag: &ReborrowingDAG,
         loan: facts::Loan,
         node: &ReborrowingDAGNode,
-        _location: mir::Location,
+        location: mir::Location,
     ) -> vir::borrows::Node {
         let mut stmts: Vec<vir::Stmt> = Vec::new();
 
@@ -1492,6 +1638,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             self.mir.source_info(loan_location).span, // the source of the ref
             ErrorCtxt::ApplyMagicWandOnExpiry,
         );
+        let pledge_spans = self.get_pledge_spans(&contract);
+        {
+            let mut error_manager = self.encoder.error_manager();
+            error_manager.add_related_note(
+                &pos,
+                "the borrow expires here",
+                self.mir.source_info(location).span,
+            );
+            error_manager.add_related_note(
+                &pos,
+                "the pledge of the called function is here",
+                MultiSpan::from_spans(pledge_spans),
+            );
+        }
         // Inhale the magic wand.
         let magic_wand = vir::Expr::MagicWand(
             box lhs.clone(),
@@ -1759,7 +1919,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             } => {
                 // will panic if attempting to encode unsupported type
                 let (encoded_lhs, _, _) = self.mir_encoder.encode_place(lhs).unwrap();
-                stmts.extend(self.encode_assign_operand(&encoded_lhs, value, location));
+                stmts.extend(self.encode_assign_operand(&encoded_lhs, value, location)?);
                 (stmts, MirSuccessor::Goto(target))
             }
 
@@ -1877,12 +2037,13 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             // Something else called panic!()
                             PanicCause::Unknown
                         };
+                        let is_checked = panic_cause.is_checked(self.checked_panics);
                         let pos = self
                             .encoder
                             .error_manager()
                             .register(term.source_info.span, ErrorCtxt::Panic(panic_cause));
 
-                        if self.check_panics {
+                        if self.check_panics && is_checked {
                             stmts.push(vir::Stmt::comment(format!(
                                 "Rust panic - {}",
                                 panic_message
@@ -1922,7 +2083,71 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         stmts.extend(self.encode_havoc_and_allocation(&box_content));
 
                         // Initialize `box_content`
-                        stmts.extend(self.encode_assign_operand(&box_content, &args[0], location));
+                        stmts.extend(self.encode_assign_operand(&box_content, &args[0], location)?);
+                    }
+
+                    "std::ptr::read" | "core::ptr::read" => {
+                        // args[0]: pointer to the value to be read
+                        assert_eq!(args.len(), 1);
+                        debug!("Encoding call of ptr::read");
+
+                        let &(ref target_place, _) = destination.as_ref().unwrap();
+                        // will panic if attempting to encode unsupported type
+                        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place).unwrap();
+                        let src = self.encode_raw_pointer_target(&args[0]);
+                        stmts.extend(self.encode_copy2(src, dst, dest_ty, location));
+                        stmts.extend(self.encode_restore_raw_pointer_permissions(args, None));
+                    }
+
+                    "std::ptr::write" | "core::ptr::write" => {
+                        // args[0]: pointer to the location to be overwritten
+                        // args[1]: the value to be written
+                        assert_eq!(args.len(), 2);
+                        debug!("Encoding call of ptr::write");
+
+                        let target = self.encode_raw_pointer_target(&args[0]);
+                        stmts.extend(self.encode_assign_operand(&target, &args[1], location)?);
+                        stmts.extend(self.encode_restore_raw_pointer_permissions(&args[..1], None));
+                    }
+
+                    "<std::boxed::Box<T>>::into_raw" | "std::boxed::Box::<T>::into_raw" |
+                    "<std::boxed::Box<T>>::from_raw" | "std::boxed::Box::<T>::from_raw" => {
+                        // This moves the content of a box to a raw pointer or vice versa,
+                        // together with its permission.
+                        // args[0]: the box or the raw pointer
+                        assert_eq!(args.len(), 1);
+                        debug!("Encoding call of {}", full_func_proc_name);
+
+                        let &(ref target_place, _) = destination.as_ref().unwrap();
+                        // will panic if attempting to encode unsupported type
+                        let (dst, dest_ty, _) = self.mir_encoder.encode_place(target_place).unwrap();
+                        let src = self.mir_encoder.encode_operand_place(&args[0]).unwrap();
+                        let target_ty = dest_ty.builtin_deref(true).unwrap().ty;
+                        let field = self.encoder.encode_dereference_field(target_ty);
+                        stmts.extend(
+                            self.prepare_assign_target(
+                                dst.clone(),
+                                field.clone(),
+                                location,
+                                vir::AssignKind::Move,
+                            )
+                        );
+                        stmts.push(vir::Stmt::Assign(
+                            dst.field(field.clone()),
+                            src.field(field),
+                            vir::AssignKind::Move,
+                        ));
+                    }
+
+                    name if self.is_identity_call(name, args, destination) => {
+                        // The call returns its argument unchanged, so it is a move.
+                        // args[0]: the value to be returned
+                        debug!("Encoding call of {} as a move", name);
+
+                        let &(ref target_place, _) = destination.as_ref().unwrap();
+                        // will panic if attempting to encode unsupported type
+                        let (dst, _, _) = self.mir_encoder.encode_place(target_place).unwrap();
+                        stmts.extend(self.encode_assign_operand(&dst, &args[0], location)?);
                     }
 
                     "std::cmp::PartialEq::eq" |
@@ -1959,9 +2184,51 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         );
                     }
 
+                    "std::cmp::PartialOrd::lt" | "core::cmp::PartialOrd::lt" |
+                    "std::cmp::PartialOrd::le" | "core::cmp::PartialOrd::le" |
+                    "std::cmp::PartialOrd::gt" | "core::cmp::PartialOrd::gt" |
+                    "std::cmp::PartialOrd::ge" | "core::cmp::PartialOrd::ge"
+                        if self.encoder.is_type_parameter(self.mir_encoder.get_operand_ty(&args[0])) => {
+                        debug_assert!(args.len() == 2);
+                        debug!("Encoding call of PartialOrd on a type parameter");
+
+                        stmts.extend(
+                            self.encode_trait_method_call(
+                                def_id,
+                                location,
+                                term.source_info.span,
+                                args,
+                                destination,
+                            )
+                        );
+                    }
+
+                    name if integer_arithmetic_method(name).is_some() => {
+                        debug_assert!(args.len() == 2);
+                        debug!("Encoding call of integer arithmetic method {}", name);
+                        let (wrapping, bin_op) = integer_arithmetic_method(name).unwrap();
+
+                        stmts.extend(
+                            self.encode_integer_arithmetic_call(
+                                location,
+                                term.source_info.span,
+                                args,
+                                destination,
+                                bin_op,
+                                wrapping,
+                            )?
+                        );
+                    }
+
                     _ => {
                         let is_pure_function =
                             self.encoder.env().has_attribute_name(def_id, "pure");
+                        if self.encoder.is_predicate(def_id) {
+                            return Err(EncodingError::incorrect(
+                                "using predicate from non-specification code is not allowed",
+                                term.source_info.span,
+                            ));
+                        }
                         if is_pure_function {
                             let (function_name, _) = self.encoder.encode_pure_function_use(def_id);
                             debug!("Encoding pure function call '{}'", function_name);
@@ -2042,14 +2309,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     "Rust assertion: {}",
                     msg.description()
                 )));
-                if self.check_panics {
+                let error_ctxt = ErrorCtxt::assert_terminator(msg, false);
+                if self.check_panics && error_ctxt.is_checked(self.checked_panics) {
                     stmts.push(vir::Stmt::Assert(
                         viper_guard,
                         vir::FoldingBehaviour::Stmt,
-                        self.encoder.error_manager().register(
-                            term.source_info.span,
-                            ErrorCtxt::AssertTerminator(msg.description().to_string()),
-                        ),
+                        self.encoder.error_manager().register(term.source_info.span, error_ctxt),
                     ));
                 } else {
                     stmts.push(vir::Stmt::comment("This assertion will not be checked"));
@@ -2086,6 +2351,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 .encoder
                 .error_manager()
                 .register(call_site_span, ErrorCtxt::PureFunctionCall);
+            self.add_loop_context_notes(&pos, location);
 
             let lhs = self.mir_encoder.encode_operand_expr(&args[0]);
             let rhs = self.mir_encoder.encode_operand_expr(&args[1]);
@@ -2108,6 +2374,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             self.encode_transfer_args_permissions(location, args,  &mut stmts, label);
 
             stmts
+        } else if self.encoder.is_type_parameter(arg_ty) {
+            // the equality of a type parameter is only known through its trait bound
+            self.encode_trait_method_call(
+                called_def_id,
+                location,
+                call_site_span,
+                args,
+                destination,
+            )
         } else {
             // the equality check involves some unsupported feature;
             // treat it as any other function
@@ -2121,6 +2396,103 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// Encode a call of a trait method that has no body (e.g. a comparison of values of a type
+    /// parameter) as a call of the corresponding body-less pure function.
+    fn encode_trait_method_call(
+        &mut self,
+        called_def_id: ProcedureDefId,
+        location: mir::Location,
+        call_site_span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+    ) -> Vec<vir::Stmt> {
+        let (function_name, return_type) =
+            self.encoder.encode_trait_method_function_use(called_def_id);
+        let arg_exprs = args
+            .iter()
+            .map(|operand| self.mir_encoder.encode_operand_expr(operand))
+            .collect();
+        self.encode_specified_pure_function_call(
+            location,
+            call_site_span,
+            args,
+            destination,
+            function_name,
+            arg_exprs,
+            return_type,
+        )
+    }
+
+    /// Encode a call of `wrapping_{add,sub,mul}` (if `wrapping` is true) or of
+    /// `checked_{add,sub,mul,div,rem}` on an integer (see `integer_arithmetic_method`).
+    fn encode_integer_arithmetic_call(
+        &mut self,
+        location: mir::Location,
+        span: Span,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+        bin_op: mir::BinOp,
+        wrapping: bool,
+    ) -> Result<Vec<vir::Stmt>> {
+        let arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+        let int_kind = MirEncoder::encode_integer_kind(arg_ty).unwrap();
+        let lhs = self.mir_encoder.encode_operand_expr(&args[0]);
+        let rhs = self.mir_encoder.encode_operand_expr(&args[1]);
+        let is_division = bin_op == mir::BinOp::Div || bin_op == mir::BinOp::Rem;
+        let divisor_is_zero = vir::Expr::eq_cmp(rhs.clone(), 0.into());
+        let result = self.mir_encoder.encode_bin_op_expr(bin_op, lhs, rhs, arg_ty, span)?;
+
+        let inhaled_expr = if wrapping {
+            let target_value = self.encode_pure_function_call_lhs_value(destination);
+            vir::Expr::eq_cmp(target_value, int_kind.wrap(result))
+        } else {
+            // The result is an `Option`: `Some(result)` if there is no overflow, `None` otherwise.
+            let &(ref target, _) = destination.as_ref().unwrap();
+            let (target_place, target_ty, _) = self.mir_encoder.encode_place(target).unwrap();
+            let (adt_def, subst) = match target_ty.kind {
+                ty::TyKind::Adt(adt_def, subst) => (adt_def, subst),
+                ref x => unreachable!("{:?}", x),
+            };
+            let tcx = self.encoder.env().tcx();
+            let some_variant = adt_def.variants.iter().find(|v| v.ident.as_str() == "Some").unwrap();
+            let some_field = &some_variant.fields[0];
+            let some_field_ty = some_field.ty(tcx, subst);
+            let encoded_field = self.encoder.encode_struct_field(&some_field.ident.as_str(), some_field_ty);
+            let some_value = target_place
+                .clone()
+                .variant(&some_variant.ident.as_str())
+                .field(encoded_field)
+                .field(self.encoder.encode_value_field(some_field_ty));
+            // The checked division and remainder also return `None` if the divisor is zero.
+            let no_overflow = if is_division {
+                vir::Expr::and(
+                    vir::Expr::not(divisor_is_zero),
+                    int_kind.contains(result.clone()),
+                )
+            } else {
+                int_kind.contains(result.clone())
+            };
+            let discriminant = self.encoder.encode_discriminant_place(target_place);
+            vir::Expr::and(
+                vir::Expr::eq_cmp(
+                    discriminant,
+                    vir::Expr::ite(no_overflow.clone(), 1.into(), 0.into()),
+                ),
+                vir::Expr::implies(no_overflow, vir::Expr::eq_cmp(some_value, result)),
+            )
+        };
+
+        let (mut stmts, label) = self.encode_pure_function_call_site(
+            location,
+            destination,
+            inhaled_expr
+        );
+
+        self.encode_transfer_args_permissions(location, args, &mut stmts, label);
+
+        Ok(stmts)
+    }
+
     /// Encode an edge of the MIR graph
     fn encode_edge_block(
         &mut self,
@@ -2182,7 +2554,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let mut const_arg_vars: HashSet<vir::Expr> = HashSet::new();
         let mut type_invs: HashMap<String, vir::Function> = HashMap::new();
         let mut constant_args = Vec::new();
+        let mut str_constant_facts = Vec::new();
         let mut arg_tys = Vec::new();
+        let mut fake_arg_places = Vec::new();
 
         for operand in args.iter() {
             let arg_ty = self.mir_encoder.get_operand_ty(operand);
@@ -2191,6 +2565,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             fake_vars.push(fake_arg.clone());
             let encoded_local = self.encode_prusti_local(fake_arg);
             let fake_arg_place = vir::Expr::local(encoded_local);
+            fake_arg_places.push(fake_arg_place.clone());
             debug!("fake_arg: {:?} {}", fake_arg, fake_arg_place);
             let inv_name = self.encoder.encode_type_invariant_use(arg_ty);
             let arg_inv = self.encoder.encode_type_invariant_def(arg_ty);
@@ -2200,6 +2575,26 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     debug!("fake_arg: {} {}", fake_arg_place, place);
                     fake_exprs.insert(fake_arg_place, place.into());
                 }
+                None if is_str_reference(self.encoder.env().tcx(), arg_ty) => {
+                    // We have a string literal, whose bytes are known once it is allocated.
+                    constant_args.push(fake_arg_place.clone());
+                    let literal = self.encode_str_constant_operand(operand, call_site_span)?;
+                    let arg_value = self.encoder.encode_value_expr(fake_arg_place.clone(), arg_ty);
+                    str_constant_facts.push(vir::Expr::eq_cmp(
+                        self.encoder.encode_str_value(arg_value, arg_ty),
+                        literal,
+                    ));
+                    if self.loop_encoder.get_loop_depth(location.block) > 0 {
+                        return Err(EncodingError::unsupported(
+                            format!(
+                                "please use a local variable as argument for function '{}', not a \
+                                string literal, when calling the function from a loop",
+                                full_func_proc_name
+                            ),
+                            call_site_span,
+                        ));
+                    }
+                }
                 None => {
                     // We have a constant.
                     constant_args.push(fake_arg_place.clone());
@@ -2298,6 +2693,61 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         for constant_arg in &constant_args {
             stmts.extend(self.encode_havoc_and_allocation(constant_arg));
         }
+        for fact in str_constant_facts {
+            stmts.push(vir::Stmt::Inhale(fact, vir::FoldingBehaviour::Expr));
+        }
+
+        // The methods of `Vec`, `Option` and `Result` are not verified either.
+        let target_ty = destination
+            .as_ref()
+            .map(|(target_place, _)| self.mir_encoder.encode_place(target_place).unwrap().1);
+        let std_method = target_ty.and_then(|return_ty| {
+            StdMethodEncoder::new(self.encoder, full_func_proc_name, arg_tys.clone(), return_ty)
+        });
+        if let Some(ref std_method) = std_method {
+            let std_args = self.encode_std_method_call_args(&fake_arg_places, &arg_tys, None);
+            if let Some((condition, cause)) = std_method.encode_precondition(&std_args) {
+                if self.check_panics && cause.is_checked(self.checked_panics) {
+                    let pos = self
+                        .encoder
+                        .error_manager()
+                        .register(call_site_span, ErrorCtxt::Panic(cause));
+                    stmts.push(vir::Stmt::Assert(
+                        replace_fake_exprs(condition),
+                        vir::FoldingBehaviour::Expr,
+                        pos,
+                    ));
+                }
+            }
+        }
+
+        // The methods of strings are not verified, their behaviour is encoded natively.
+        let str_method = StrMethodKind::from_call(
+            self.encoder.env().tcx(),
+            full_func_proc_name,
+            &arg_tys,
+        );
+        if let Some(method) = str_method {
+            let str_args = self.encode_str_method_call_args(
+                method,
+                &fake_arg_places,
+                &arg_tys,
+                None,
+            );
+            if let Some(condition) = method.encode_precondition(&str_args) {
+                if self.check_panics && PanicCause::StrSlicing.is_checked(self.checked_panics) {
+                    let pos = self.encoder.error_manager().register(
+                        call_site_span,
+                        ErrorCtxt::Panic(PanicCause::StrSlicing),
+                    );
+                    stmts.push(vir::Stmt::Assert(
+                        replace_fake_exprs(condition),
+                        vir::FoldingBehaviour::Expr,
+                        pos,
+                    ));
+                }
+            }
+        }
 
         // Encode precondition.
         let (
@@ -2312,6 +2762,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .encoder
             .error_manager()
             .register(call_site_span, ErrorCtxt::ExhaleMethodPrecondition);
+        let precondition_spans: Vec<Span> = procedure_contract
+            .functional_precondition()
+            .iter()
+            .flat_map(|ts| typed::Spanned::get_spans(ts, self.encoder.env().tcx()))
+            .collect();
+        self.encoder.error_manager().add_related_note(
+            &pos,
+            "the precondition of the called function is here",
+            MultiSpan::from_spans(precondition_spans),
+        );
+        self.add_loop_context_notes(&pos, location);
         stmts.push(vir::Stmt::Assert(
             replace_fake_exprs(pre_func_spec),
             vir::FoldingBehaviour::Stmt, // TODO: Should be Expr.
@@ -2419,11 +2880,49 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             replace_fake_exprs(post_func_spec),
             vir::FoldingBehaviour::Expr,
         ));
+        if let Some(method) = str_method {
+            let target = target_ty.map(|ty| {
+                let target_place = vir::Expr::local(self.encode_prusti_local(fake_target_local));
+                (target_place, ty)
+            });
+            let fact = self.encode_str_method_call_post(
+                method,
+                &fake_arg_places,
+                &arg_tys,
+                target,
+                &pre_label,
+            );
+            if let Some(fact) = fact {
+                stmts.push(vir::Stmt::Inhale(
+                    replace_fake_exprs(fact),
+                    vir::FoldingBehaviour::Expr,
+                ));
+            }
+        }
+        if let Some(ref std_method) = std_method {
+            let target_place = vir::Expr::local(self.encode_prusti_local(fake_target_local));
+            let facts = self.encode_std_method_call_post(
+                std_method,
+                &fake_arg_places,
+                &arg_tys,
+                target_place,
+                &pre_label,
+            );
+            for fact in facts {
+                stmts.push(vir::Stmt::Inhale(
+                    replace_fake_exprs(fact),
+                    vir::FoldingBehaviour::Expr,
+                ));
+            }
+        }
 
         // Exhale the permissions that were moved into magic wands.
         assert!(!pos.is_default());
         stmts.push(vir::Stmt::Exhale(pre_mandatory_perm_spec, pos));
 
+        // Give back the permissions returned through raw pointers.
+        stmts.extend(self.encode_restore_raw_pointer_permissions(args, Some(&pre_label)));
+
         // Emit the label and magic wands
         stmts.push(vir::Stmt::Label(post_label.clone()));
 
@@ -2435,6 +2934,167 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(stmts)
     }
 
+    /// Encodes the string literal of a constant operand.
+    fn encode_str_constant_operand(
+        &self,
+        operand: &mir::Operand<'tcx>,
+        span: Span,
+    ) -> Result<vir::Expr> {
+        match operand {
+            mir::Operand::Constant(box mir::Constant { literal: ty::Const { val, .. }, .. }) => {
+                self.encoder.encode_str_const_expr(val, span)
+            }
+            x => unreachable!("{:?}", x),
+        }
+    }
+
+    /// Encodes the arguments of a natively encoded string method from the places of the
+    /// arguments of the call. If `pre_label` is given, the arguments that are not shared
+    /// references are evaluated in the state before the call.
+    fn encode_str_method_call_args(
+        &self,
+        method: StrMethodKind,
+        arg_places: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+        pre_label: Option<&str>,
+    ) -> Vec<vir::Expr> {
+        let arg_values: Vec<vir::Expr> = arg_places
+            .iter()
+            .zip(arg_tys)
+            .map(|(arg_place, &arg_ty)| self.encoder.encode_value_expr(arg_place.clone(), arg_ty))
+            .collect();
+        let str_args = self.encoder.encode_str_method_args(method, &arg_values, arg_tys);
+        match pre_label {
+            Some(label) => self.encode_args_in_pre_state(str_args, arg_places, arg_tys, label),
+            None => str_args,
+        }
+    }
+
+    /// Evaluates the expressions that mention an argument of a call in the state before the
+    /// call, unless the argument is a shared reference. Only the targets of shared references
+    /// are known to be unchanged by the call.
+    fn encode_args_in_pre_state(
+        &self,
+        exprs: Vec<vir::Expr>,
+        arg_places: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+        pre_label: &str,
+    ) -> Vec<vir::Expr> {
+        let changed_args: Vec<&vir::Expr> = arg_places
+            .iter()
+            .zip(arg_tys)
+            .filter(|(_, arg_ty)| match arg_ty.kind {
+                ty::TyKind::Ref(_, _, Mutability::Not) => false,
+                _ => true,
+            })
+            .map(|(arg_place, _)| arg_place)
+            .collect();
+        exprs
+            .into_iter()
+            .map(|expr| {
+                if changed_args.iter().any(|arg_place| expr.find(arg_place)) {
+                    expr.old(pre_label)
+                } else {
+                    expr
+                }
+            })
+            .collect()
+    }
+
+    /// Encodes the values of the arguments of a natively encoded method of `Vec`, `Option` or
+    /// `Result`. If `pre_label` is given, the arguments are evaluated like in
+    /// `encode_str_method_call_args`.
+    fn encode_std_method_call_args(
+        &self,
+        arg_places: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+        pre_label: Option<&str>,
+    ) -> Vec<vir::Expr> {
+        let arg_values: Vec<vir::Expr> = arg_places
+            .iter()
+            .zip(arg_tys)
+            .map(|(arg_place, &arg_ty)| self.encoder.encode_value_expr(arg_place.clone(), arg_ty))
+            .collect();
+        match pre_label {
+            Some(label) => self.encode_args_in_pre_state(arg_values, arg_places, arg_tys, label),
+            None => arg_values,
+        }
+    }
+
+    /// Encodes what is known after a call of a natively encoded method of `Vec`, `Option` or
+    /// `Result`: the value of the result and the new value of the vector passed by mutable
+    /// reference.
+    fn encode_std_method_call_post(
+        &self,
+        std_method: &StdMethodEncoder<'_, 'v, 'tcx>,
+        arg_places: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+        target_place: vir::Expr,
+        pre_label: &str,
+    ) -> Vec<vir::Expr> {
+        let mut facts = vec![];
+        let old_args = self.encode_std_method_call_args(arg_places, arg_tys, Some(pre_label));
+        if let Some(new_self) = std_method.encode_updated_self(&old_args) {
+            let args = self.encode_std_method_call_args(arg_places, arg_tys, None);
+            let self_place = std_method.encode_self_place(&args);
+            facts.push(vir::Expr::eq_cmp(self_place, new_self));
+        }
+        for fact in std_method.encode_result(&old_args, target_place) {
+            facts.push(vir::Expr::implies(
+                fact.guard,
+                vir::Expr::eq_cmp(fact.place, fact.value),
+            ));
+        }
+        facts
+    }
+
+    /// Encodes what is known after a call of a natively encoded string method: the value of the
+    /// result or the new value of the string passed by mutable reference.
+    fn encode_str_method_call_post(
+        &self,
+        method: StrMethodKind,
+        arg_places: &[vir::Expr],
+        arg_tys: &[ty::Ty<'tcx>],
+        target: Option<(vir::Expr, ty::Ty<'tcx>)>,
+        pre_label: &str,
+    ) -> Option<vir::Expr> {
+        let tcx = self.encoder.env().tcx();
+        let str_args =
+            self.encode_str_method_call_args(method, arg_places, arg_tys, Some(pre_label));
+        if let Some(new_self) = method.encode_updated_self(&str_args) {
+            let self_value = self.encoder.encode_value_expr(arg_places[0].clone(), arg_tys[0]);
+            let self_str = self.encoder.encode_str_value(self_value, arg_tys[0]);
+            return Some(vir::Expr::eq_cmp(self_str, new_self));
+        }
+        let result = method.encode_result(&str_args)?;
+        let (target_place, target_ty) = target?;
+        let target_value = self.encoder.encode_value_expr(target_place, target_ty);
+        let target_value = if is_bytes_value_type(tcx, target_ty) {
+            self.encoder.encode_str_value(target_value, target_ty)
+        } else {
+            target_value
+        };
+        Some(vir::Expr::eq_cmp(target_value, result))
+    }
+
+    /// Whether the call of `name` returns its argument unchanged.
+    fn is_identity_call(
+        &self,
+        name: &str,
+        args: &[mir::Operand<'tcx>],
+        destination: &Option<(mir::Place<'tcx>, BasicBlockIndex)>,
+    ) -> bool {
+        let target_ty = match destination {
+            Some((target_place, _)) => self.mir_encoder.encode_place(target_place).unwrap().1,
+            None => return false,
+        };
+        let arg_tys: Vec<_> = args
+            .iter()
+            .map(|operand| self.mir_encoder.get_operand_ty(operand))
+            .collect();
+        is_identity_call(self.encoder.env().tcx(), name, &arg_tys, target_ty)
+    }
+
     fn encode_pure_function_call(
         &mut self,
         location: mir::Location,
@@ -2489,6 +3149,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .encoder
             .error_manager()
             .register(call_site_span, ErrorCtxt::PureFunctionCall);
+        self.add_loop_context_notes(&pos, location);
 
         let func_call = vir::Expr::func_app(
             function_name,
@@ -2595,13 +3256,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             let operand_ty = self.mir_encoder.get_operand_ty(operand);
             let operand_place = self.mir_encoder.encode_operand_place(operand);
             match (operand_place, &operand_ty.kind) {
-                (
-                    Some(ref place),
-                    ty::TyKind::RawPtr(ty::TypeAndMut {
-                        ty: ref inner_ty, ..
-                    }),
-                )
-                | (Some(ref place), ty::TyKind::Ref(_, ref inner_ty, _)) => {
+                (Some(ref place), ty::TyKind::Ref(_, ref inner_ty, _)) => {
                     let ref_field = self.encoder.encode_dereference_field(inner_ty);
                     let ref_place = place.clone().field(ref_field);
                     stmts.extend(self.encode_transfer_permissions(
@@ -2645,11 +3300,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
     /// Encode permissions that are implicitly carried by the given local variable.
     fn encode_local_variable_permission(&self, local: Local) -> vir::Expr {
         match self.locals.get_type(local).kind {
-            ty::TyKind::RawPtr(ty::TypeAndMut {
-                ref ty,
-                mutbl: mutability,
-            })
-            | ty::TyKind::Ref(_, ref ty, mutability) => {
+            ty::TyKind::Ref(_, ref ty, mutability) => {
                 // Use unfolded references.
                 let encoded_local = self.encode_prusti_local(local);
                 let field = self.encoder.encode_dereference_field(ty);
@@ -2663,6 +3314,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     vir::Expr::pred_permission(place, perm_amount).unwrap(),
                 )
             }
+            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
+                // Use unfolded raw pointers. The permission to access the target is not implied
+                // by the type and has to be given by the specification (see `acc` and `own`).
+                let encoded_local = self.encode_prusti_local(local);
+                let field = self.encoder.encode_dereference_field(ty);
+                let place = vir::Expr::from(encoded_local).field(field);
+                vir::Expr::acc_permission(place, vir::PermAmount::Write)
+            }
             _ => self
                 .mir_encoder
                 .encode_place_predicate_permission(
@@ -2765,6 +3424,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 None,
                 ErrorCtxt::GenericExpression,
             );
+            let (accesses, value) = split_access_predicates(value);
+            type_spec.extend(accesses);
             func_spec.push(value);
         }
         let precondition_weakening = precondition_weakening.map(|pw| {
@@ -2834,6 +3495,21 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             start_cfg_block,
             vir::Stmt::Inhale(func_spec, vir::FoldingBehaviour::Expr),
         );
+        // Check that the precondition is satisfiable
+        let precondition_spans: Vec<_> = self
+            .procedure_contract()
+            .functional_precondition()
+            .iter()
+            .flat_map(|ts| typed::Spanned::get_spans(ts, self.encoder.env().tcx()))
+            .collect();
+        if self.check_vacuity && !precondition_spans.is_empty() {
+            let pos = self.encoder.error_manager().register(
+                MultiSpan::from_spans(precondition_spans),
+                ErrorCtxt::VacuityCheck(VacuityCheck::Precondition),
+            );
+            let stmts = self.encode_vacuity_check(pos);
+            self.cfg_method.add_stmts(start_cfg_block, stmts);
+        }
         self.cfg_method.add_stmt(
             start_cfg_block,
             vir::Stmt::Label(PRECONDITION_LABEL.to_string()),
@@ -3113,6 +3789,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             );
             func_spec_spans.extend(typed::Spanned::get_spans(typed_assertion, self.encoder.env().tcx()));
             assertion = self.wrap_arguments_into_old(assertion, pre_label, contract, &encoded_args);
+            let (accesses, assertion) = split_access_predicates(assertion);
+            type_spec.extend(accesses);
             func_spec.push(assertion);
         }
         let func_spec_pos = self.encoder.error_manager().register_span(func_spec_spans);
@@ -3236,6 +3914,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 .encoder
                 .error_manager()
                 .register(self.mir.span, ErrorCtxt::PackageMagicWandForPostcondition);
+            let pledge_spans = self.get_pledge_spans(self.procedure_contract());
+            self.encoder.error_manager().add_related_note(
+                &pos,
+                "the pledge is here",
+                MultiSpan::from_spans(pledge_spans),
+            );
 
             let blocker = mir::RETURN_PLACE;
             // TODO: Check if it really is always start and not the mid point.
@@ -3399,19 +4083,22 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let encoded_arg: vir::Expr = self.encode_prusti_local(arg).into();
                 let (encoded_deref, ..) = self.mir_encoder.encode_deref(encoded_arg.clone(), ty);
 
-                // Fold argument.
-                let deref_pred = self
-                    .mir_encoder
-                    .encode_place_predicate_permission(
-                        encoded_deref.clone(),
-                        vir::PermAmount::Write,
-                    )
-                    .unwrap();
-                for stmt in self
-                    .encode_obtain(deref_pred, type_inv_pos)
-                    .drain(..)
-                {
-                    self.cfg_method.add_stmt(return_cfg_block, stmt);
+                // Fold argument. The target of a raw pointer is folded only if the
+                // postcondition gives back its permission.
+                if !ty.is_unsafe_ptr() {
+                    let deref_pred = self
+                        .mir_encoder
+                        .encode_place_predicate_permission(
+                            encoded_deref.clone(),
+                            vir::PermAmount::Write,
+                        )
+                        .unwrap();
+                    for stmt in self
+                        .encode_obtain(deref_pred, type_inv_pos)
+                        .drain(..)
+                    {
+                        self.cfg_method.add_stmt(return_cfg_block, stmt);
+                    }
                 }
 
                 // Transfer permissions.
@@ -3473,10 +4160,20 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             );
         }
         // Assert functional specification of postcondition
+        let postcondition_spans: Vec<_> = contract
+            .functional_postcondition()
+            .iter()
+            .flat_map(|ts| typed::Spanned::get_spans(ts, self.encoder.env().tcx()))
+            .collect();
+        let func_span = if postcondition_spans.is_empty() {
+            MultiSpan::from_span(self.mir.span)
+        } else {
+            MultiSpan::from_spans(postcondition_spans)
+        };
         let func_pos = self
             .encoder
             .error_manager()
-            .register(self.mir.span, ErrorCtxt::AssertMethodPostcondition);
+            .register(func_span, ErrorCtxt::AssertMethodPostcondition);
         let patched_func_spec = self.replace_old_places_with_ghost_vars(None, func_spec);
         self.cfg_method.add_stmt(
             return_cfg_block,
@@ -3661,6 +4358,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                 ref x => unreachable!("{:?}", x),
                             }
                         }
+                        // The target of a raw pointer is not owned by the pointer.
+                        let owns_target = !ty.is_unsafe_ptr();
                         match ty.kind {
                             ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, mutbl })
                             | ty::TyKind::Ref(_, ref ty, mutbl) => {
@@ -3684,6 +4383,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                                     ));
                                 }
                                 if def_init
+                                    && owns_target
                                     && !(mutbl == Mutability::Not && drop_read_references)
                                 {
                                     permissions.push(
@@ -3831,6 +4531,91 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         (encoded_specs, MultiSpan::from_spans(encoded_spec_spans))
     }
 
+    /// The facts about integer local variables that are inferred to hold where the loop
+    /// invariant holds (see `config::infer_loop_invariants`). Only the facts about local
+    /// variables whose values are framed by the loop invariant are kept; the other local
+    /// variables are either not used by the loop or not initialized.
+    fn get_inferred_loop_invariant(
+        &self,
+        loop_head: BasicBlockIndex,
+        loop_inv_block: BasicBlockIndex,
+    ) -> Vec<Fact> {
+        let facts = self
+            .loop_encoder
+            .get_inferred_loop_invariant(loop_head, loop_inv_block);
+        if facts.is_empty() {
+            return facts;
+        }
+        let permissions_forest = self
+            .loop_encoder
+            .compute_loop_invariant(loop_head, loop_inv_block);
+        let framed_locals: HashSet<mir::Local> = permissions_forest
+            .get_trees()
+            .iter()
+            .flat_map(|tree| tree.get_permissions())
+            .filter(|(kind, place)| {
+                (*kind == PermissionKind::ReadSubtree || *kind == PermissionKind::WriteSubtree)
+                    && self.loop_encoder.is_definitely_initialised(place, loop_head)
+            })
+            .filter_map(|(_, place)| place.as_local())
+            .filter(|&local| self.mir.local_decls[local].ty.is_integral())
+            .collect();
+        facts
+            .into_iter()
+            .filter(|fact| fact.locals().iter().all(|local| framed_locals.contains(local)))
+            .collect()
+    }
+
+    fn encode_inferred_loop_invariant(&self, facts: &[Fact]) -> vir::Expr {
+        let value = |local: mir::Local| {
+            // will panic if attempting to encode unsupported type
+            let var = self.mir_encoder.encode_local(local).unwrap();
+            self.encoder
+                .encode_value_expr(var.into(), self.mir.local_decls[local].ty)
+        };
+        facts
+            .iter()
+            .map(|fact| match *fact {
+                Fact::Value(x, value_x) => vir::Expr::eq_cmp(value(x), value_x.into()),
+                Fact::LowerBound(x, lower) => vir::Expr::ge_cmp(value(x), lower.into()),
+                Fact::UpperBound(x, upper) => vir::Expr::le_cmp(value(x), upper.into()),
+                Fact::Equality(x, y) => vir::Expr::eq_cmp(value(x), value(y)),
+                Fact::Difference(x, y, -1) => vir::Expr::lt_cmp(value(x), value(y)),
+                Fact::Difference(x, y, 0) => vir::Expr::le_cmp(value(x), value(y)),
+                Fact::Difference(x, y, c) => {
+                    vir::Expr::le_cmp(value(x), vir::Expr::add(value(y), c.into()))
+                }
+            })
+            .conjoin()
+    }
+
+    fn print_inferred_loop_invariant(&self, loop_head: BasicBlockIndex, facts: &[Fact]) {
+        let mut names = HashMap::new();
+        for info in &self.mir.var_debug_info {
+            if let Some(local) = info.place.as_local() {
+                names.insert(local, info.name.to_string());
+            }
+        }
+        let name = |local: mir::Local| {
+            names
+                .get(&local)
+                .cloned()
+                .unwrap_or_else(|| format!("{:?}", local))
+        };
+        let mut message = format!(
+            "Inferred loop invariant of the loop at {:?} in {}:",
+            self.get_loop_span(loop_head),
+            self.encoder.env().get_item_def_path(self.proc_def_id),
+        );
+        if facts.is_empty() {
+            message.push_str("\n  true");
+        }
+        for fact in facts {
+            message.push_str(&format!("\n  {}", fact.to_string_with(&name)));
+        }
+        user::message(message);
+    }
+
     fn encode_loop_invariant_exhale_stmts(
         &mut self,
         loop_head: BasicBlockIndex,
@@ -3851,6 +4636,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             self.encode_loop_invariant_permissions(loop_head, loop_inv_block, true);
         let (func_spec, func_spec_span) =
             self.encode_loop_invariant_specs(loop_head, loop_inv_block);
+        let inferred_facts = self.get_inferred_loop_invariant(loop_head, loop_inv_block);
+        if !after_loop_iteration && config::print_inferred_loop_invariants() {
+            self.print_inferred_loop_invariant(loop_head, &inferred_facts);
+        }
 
         // TODO: use different positions, and generate different error messages, for the exhale
         // before the loop and after the loop body
@@ -3899,6 +4688,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             vir::FoldingBehaviour::Expr,
             assert_pos,
         ));
+        if !inferred_facts.is_empty() {
+            let inferred_pos = self.encoder.error_manager().register(
+                self.get_loop_span(loop_head),
+                ErrorCtxt::AssertInferredLoopInvariant,
+            );
+            stmts.push(vir::Stmt::Assert(
+                self.encode_inferred_loop_invariant(&inferred_facts),
+                vir::FoldingBehaviour::Expr,
+                inferred_pos,
+            ));
+        }
         let equalities_expr = equalities.into_iter().conjoin();
         stmts.push(vir::Stmt::Assert(
             equalities_expr,
@@ -3923,8 +4723,10 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         );
         let (permissions, equalities) =
             self.encode_loop_invariant_permissions(loop_head, loop_inv_block, true);
-        let (func_spec, _func_spec_span) =
+        let (func_spec, func_spec_span) =
             self.encode_loop_invariant_specs(loop_head, loop_inv_block);
+        let has_func_spec = !func_spec.is_empty();
+        let inferred_facts = self.get_inferred_loop_invariant(loop_head, loop_inv_block);
 
         let permission_expr = permissions.into_iter().conjoin();
         let equality_expr = equalities.into_iter().conjoin();
@@ -3945,6 +4747,21 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             func_spec.into_iter().conjoin(),
             vir::FoldingBehaviour::Expr,
         ));
+        if !inferred_facts.is_empty() {
+            stmts.push(vir::Stmt::Inhale(
+                self.encode_inferred_loop_invariant(&inferred_facts),
+                vir::FoldingBehaviour::Expr,
+            ));
+        }
+        // Check that the loop invariant is consistent
+        if self.check_vacuity && has_func_spec {
+            let pos = self.get_vacuity_check_pos(
+                VacuityCheck::LoopInvariant,
+                loop_head,
+                func_spec_span,
+            );
+            stmts.extend(self.encode_vacuity_check(pos));
+        }
         stmts
     }
 
@@ -4087,7 +4904,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         lhs: &vir::Expr,
         operand: &mir::Operand<'tcx>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+    ) -> Result<Vec<vir::Stmt>> {
         trace!(
             "[enter] encode_assign_operand(lhs={}, operand={:?}, location={:?})",
             lhs, operand, location
@@ -4097,6 +4914,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let (src, ty, _) = self.mir_encoder.encode_place(place).unwrap(); // will panic if attempting to encode unsupported type
                 let mut stmts = match ty.kind {
                     ty::TyKind::RawPtr(..) | ty::TyKind::Ref(..) => {
+                        if ty.is_unsafe_ptr() {
+                            self.record_raw_pointer_origin(lhs, &src);
+                        }
                         // Reborrow.
                         let field = self.encoder.encode_value_field(ty);
                         let mut alloc_stmts = self.prepare_assign_target(
@@ -4132,7 +4952,24 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             mir::Operand::Copy(ref place) => {
                 let (src, ty, _) = self.mir_encoder.encode_place(place).unwrap(); // will panic if attempting to encode unsupported type
 
-                let mut stmts = if self.mir_encoder.is_reference(ty) {
+                let mut stmts = if ty.is_unsafe_ptr() {
+                    // Copying a raw pointer does not create a loan. The permission of the
+                    // target moves to the copy, and is given back when the copy is used.
+                    self.record_raw_pointer_origin(lhs, &src);
+                    let field = self.encoder.encode_value_field(ty);
+                    let mut stmts = self.prepare_assign_target(
+                        lhs.clone(),
+                        field.clone(),
+                        location,
+                        vir::AssignKind::Move,
+                    );
+                    stmts.push(vir::Stmt::Assign(
+                        lhs.clone().field(field.clone()),
+                        src.field(field),
+                        vir::AssignKind::Move,
+                    ));
+                    stmts
+                } else if self.mir_encoder.is_reference(ty) {
                     let loan = self.polonius_info().get_loan_at_location(location);
                     let ref_field = self.encoder.encode_value_field(ty);
                     let mut stmts = self.prepare_assign_target(
@@ -4173,6 +5010,26 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                     // Since we have a ZST, we do not need to do anything to
                     // encode it.
                     Vec::new()
+                } else if is_str_reference(self.encoder.env().tcx(), ty) {
+                    // A string literal: allocate a fresh string with the bytes of the literal.
+                    let field = self.encoder.encode_value_field(ty);
+                    let mut stmts = self.prepare_assign_target(
+                        lhs.clone(),
+                        field.clone(),
+                        location,
+                        vir::AssignKind::Move,
+                    );
+                    let target = lhs.clone().field(field);
+                    stmts.extend(self.encode_havoc_and_allocation(&target));
+                    let span = self.mir.source_info(location).span;
+                    stmts.push(vir::Stmt::Inhale(
+                        vir::Expr::eq_cmp(
+                            self.encoder.encode_str_value(target, ty),
+                            self.encoder.encode_str_const_expr(val, span)?,
+                        ),
+                        vir::FoldingBehaviour::Expr,
+                    ));
+                    stmts
                 } else {
                     // We expect to have a constant of a primitive type here.
                     let field = self.encoder.encode_value_field(ty);
@@ -4233,7 +5090,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             location,
             vir::stmts_to_str(&stmts)
         );
-        stmts
+        Ok(stmts)
     }
 
     fn encode_assign_binary_op(
@@ -4244,7 +5101,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         encoded_lhs: vir::Expr,
         ty: ty::Ty<'tcx>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+        span: Span,
+    ) -> Result<Vec<vir::Stmt>> {
         trace!(
             "[enter] encode_assign_binary_op(op={:?}, left={:?}, right={:?})",
             op,
@@ -4255,8 +5113,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         let encoded_right = self.mir_encoder.encode_operand_expr(right);
         let encoded_value =
             self.mir_encoder
-                .encode_bin_op_expr(op, encoded_left, encoded_right, ty);
-        self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location)
+                .encode_bin_op_expr(op, encoded_left, encoded_right, ty, span)?;
+        Ok(self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location))
     }
 
     fn encode_copy_value_assign(
@@ -4278,7 +5136,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         encoded_lhs: vir::Expr,
         ty: ty::Ty<'tcx>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+        span: Span,
+    ) -> Result<Vec<vir::Stmt>> {
         trace!(
             "[enter] encode_assign_checked_binary_op(op={:?}, left={:?}, right={:?})",
             op,
@@ -4297,7 +5156,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             encoded_left.clone(),
             encoded_right.clone(),
             operand_ty.expect_ty(),
-        );
+            span,
+        )?;
         let encoded_check =
             self.mir_encoder
                 .encode_bin_op_check(op, encoded_left, encoded_right, operand_ty.expect_ty());
@@ -4357,7 +5217,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             encoded_check,
             vir::AssignKind::Copy,
         ));
-        stmts
+        Ok(stmts)
     }
 
     fn encode_assign_unary_op(
@@ -4374,7 +5234,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             operand
         );
         let encoded_val = self.mir_encoder.encode_operand_expr(operand);
-        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val);
+        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val, ty);
         // Initialize `lhs.field`
         self.encode_copy_value_assign(encoded_lhs, encoded_value, ty, location)
     }
@@ -4435,9 +5295,8 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 // Note: in our encoding an enumeration with just one variant has
                 // no discriminant
                 if num_variants > 1 {
-                    let encoded_rhs = self.encoder.encode_discriminant_func_app(
+                    let encoded_rhs = self.encoder.encode_discriminant_place(
                         self.translate_maybe_borrowed_place(location, encoded_src),
-                        adt_def,
                     );
                     self.encode_copy_value_assign(encoded_lhs.clone(), encoded_rhs, ty, location)
                 } else {
@@ -4509,14 +5368,137 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         encoded_lhs: vir::Expr,
         ty: ty::Ty<'tcx>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+        span: Span,
+    ) -> Result<Vec<vir::Stmt>> {
         trace!(
             "[enter] encode_cast(operand={:?}, dst_ty={:?})",
             operand,
             dst_ty
         );
-        let encoded_val = self.mir_encoder.encode_cast_expr(operand, dst_ty);
-        self.encode_copy_value_assign(encoded_lhs, encoded_val, ty, location)
+        let mut stmts = vec![];
+        let error_ctxt = ErrorCtxt::OverflowCheck(OverflowCheck::Cast);
+        if self.check_panics && error_ctxt.is_checked(self.checked_panics) {
+            if let Some(check) = self.mir_encoder.encode_cast_check(operand, dst_ty) {
+                stmts.push(vir::Stmt::Assert(
+                    check,
+                    vir::FoldingBehaviour::Stmt,
+                    self.encoder.error_manager().register(span, error_ctxt),
+                ));
+            }
+        }
+        let encoded_val = self.mir_encoder.encode_cast_expr(operand, dst_ty, span)?;
+        stmts.extend(self.encode_copy_value_assign(encoded_lhs, encoded_val, ty, location));
+        Ok(stmts)
+    }
+
+    /// Encode the creation of a trait object (`&dyn Trait` or `Box<dyn Trait>`) from a pointer
+    /// to a value of a concrete type.
+    ///
+    /// The trait object is encoded as a fresh instance of the abstract predicate of `dyn Trait`,
+    /// because it can only be accessed through the methods of the trait. A moved box is
+    /// consumed, so the permission of its content is exhaled. The target of a reference cannot
+    /// change through a shared trait object, so its permission is kept.
+    ///
+    /// Casts of mutable references to trait objects are not supported, and are rejected by
+    /// `find_mutable_trait_object_cast` before the procedure is encoded.
+    fn encode_trait_object_cast(
+        &mut self,
+        operand: &mir::Operand<'tcx>,
+        dst_ty: ty::Ty<'tcx>,
+        encoded_lhs: vir::Expr,
+        location: mir::Location,
+        span: Span,
+    ) -> Vec<vir::Stmt> {
+        trace!(
+            "[enter] encode_trait_object_cast(operand={:?}, dst_ty={:?})",
+            operand,
+            dst_ty
+        );
+        let trait_object_ty = dst_ty.builtin_deref(true).unwrap().ty;
+        let ref_field = self.encoder.encode_dereference_field(trait_object_ty);
+        let trait_object = encoded_lhs.clone().field(ref_field.clone());
+        let mut stmts = self.prepare_assign_target(
+            encoded_lhs,
+            ref_field,
+            location,
+            vir::AssignKind::Move,
+        );
+        stmts.extend(self.encode_havoc_and_allocation(&trait_object));
+        if let mir::Operand::Move(ref place) = operand {
+            // will panic if attempting to encode unsupported type
+            let (src, src_ty, _) = self.mir_encoder.encode_place(place).unwrap();
+            let pos = self.encoder.error_manager().register(span, ErrorCtxt::Unexpected);
+            match src_ty.kind {
+                ty::TyKind::Ref(..) => {}
+                _ => {
+                    let permission = self.mir_encoder
+                        .encode_place_predicate_permission(src, vir::PermAmount::Write)
+                        .unwrap();
+                    stmts.push(vir::Stmt::Exhale(permission, pos));
+                }
+            }
+        }
+        stmts
+    }
+
+    /// Encode the target `*ptr` of the raw pointer passed as `operand`.
+    fn encode_raw_pointer_target(&self, operand: &mir::Operand<'tcx>) -> vir::Expr {
+        let operand_ty = self.mir_encoder.get_operand_ty(operand);
+        let target_ty = operand_ty.builtin_deref(true).unwrap().ty;
+        let field = self.encoder.encode_dereference_field(target_ty);
+        // will panic if attempting to encode unsupported type
+        self.mir_encoder
+            .encode_operand_place(operand)
+            .unwrap()
+            .field(field)
+    }
+
+    /// Remember that the local variable `lhs` holds a copy of the raw pointer stored in `src`.
+    fn record_raw_pointer_origin(&mut self, lhs: &vir::Expr, src: &vir::Expr) {
+        if lhs.is_local() {
+            let origin = self
+                .raw_pointer_origins
+                .get(src)
+                .cloned()
+                .unwrap_or_else(|| src.clone());
+            self.raw_pointer_origins.insert(lhs.clone(), origin);
+        }
+    }
+
+    /// Give back the permission of the targets of the raw pointers passed as `args` to the
+    /// places from which the pointers were copied.
+    ///
+    /// `label` is the label of the state before a function call, if the permissions were
+    /// returned by the postcondition of the called function.
+    fn encode_restore_raw_pointer_permissions(
+        &self,
+        args: &[mir::Operand<'tcx>],
+        label: Option<&str>,
+    ) -> Vec<vir::Stmt> {
+        let mut stmts = vec![];
+        for operand in args {
+            let operand_ty = self.mir_encoder.get_operand_ty(operand);
+            if let ty::TyKind::RawPtr(ty::TypeAndMut { ty: target_ty, .. }) = operand_ty.kind {
+                let place = match self.mir_encoder.encode_operand_place(operand) {
+                    Some(place) => place,
+                    None => continue,
+                };
+                if let Some(origin) = self.raw_pointer_origins.get(&place) {
+                    let field = self.encoder.encode_dereference_field(target_ty);
+                    let target = place.clone().field(field.clone());
+                    let target = match label {
+                        Some(label) => target.old(label),
+                        None => target,
+                    };
+                    stmts.push(vir::Stmt::TransferPerm(
+                        target,
+                        origin.clone().field(field),
+                        true,
+                    ));
+                }
+            }
+        }
+        stmts
     }
 
     pub fn get_auxiliary_local_var(&mut self, suffix: &str, vir_type: vir::Type) -> vir::LocalVar {
@@ -4725,7 +5707,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         aggregate: &mir::AggregateKind<'tcx>,
         operands: &Vec<mir::Operand<'tcx>>,
         location: mir::Location,
-    ) -> Vec<vir::Stmt> {
+    ) -> Result<Vec<vir::Stmt>> {
         debug!(
             "[enter] encode_assign_aggregate({:?}, {:?})",
             aggregate, operands
@@ -4748,9 +5730,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         &dst.clone().field(encoded_field),
                         operand,
                         location,
-                    ));
+                    )?);
                 }
-                stmts
+                Ok(stmts)
             }
 
             &mir::AggregateKind::Adt(adt_def, variant_index, subst, _, _) => {
@@ -4779,9 +5761,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                             .into()
                     };
                     // dst was havocked, so it is safe to assume the equality here.
-                    let discriminant = self
-                        .encoder
-                        .encode_discriminant_func_app(dst.clone(), adt_def);
+                    let discriminant = self.encoder.encode_discriminant_place(dst.clone());
                     stmts.push(vir::Stmt::Inhale(
                         vir::Expr::eq_cmp(discriminant, discr_value),
                         vir::FoldingBehaviour::Stmt,
@@ -4799,16 +5779,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         &dst_base.clone().field(encoded_field),
                         operand,
                         location,
-                    ));
+                    )?);
                 }
-                stmts
+                Ok(stmts)
             }
 
             &mir::AggregateKind::Closure(def_id, _substs) => {
                 assert!(self.encoder.is_spec_closure(def_id), "closure: {:?}", def_id);
                 // Specification only. Just ignore in the encoding.
                 // FIXME: Filtering of specification blocks is broken, so we need to handle this here.
-                Vec::new()
+                Ok(Vec::new())
             }
 
             ref x => unimplemented!("{:?}", x),
@@ -4834,6 +5814,44 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         &self.label_after_location[&location]
     }
 
+    /// Attach to the errors reported at `pos` the notes that point at the loop that encloses
+    /// `location`, if any, and at its invariant.
+    fn add_loop_context_notes(&self, pos: &vir::Position, location: mir::Location) {
+        let loop_head = match self.loop_encoder.get_loop_head(location.block) {
+            Some(loop_head) => loop_head,
+            None => return,
+        };
+        let loop_span = self.get_loop_span(loop_head);
+        let invariant_spans: Vec<Span> = self
+            .get_loop_spec_blocks(loop_head)
+            .into_iter()
+            .map(|bbi| self.mir_encoder.get_span_of_basic_block(bbi))
+            .collect();
+        let mut error_manager = self.encoder.error_manager();
+        error_manager.add_related_note(pos, "the enclosing loop is here", loop_span);
+        error_manager.add_related_note(
+            pos,
+            "the loop invariant assumed here",
+            MultiSpan::from_spans(invariant_spans),
+        );
+    }
+
+    /// The spans of the pledges of a contract.
+    fn get_pledge_spans(&self, contract: &ProcedureContract<'tcx>) -> Vec<Span> {
+        let tcx = self.encoder.env().tcx();
+        contract
+            .pledges()
+            .iter()
+            .flat_map(|pledge| {
+                let mut spans = typed::Spanned::get_spans(&pledge.rhs, tcx);
+                if let Some(ref lhs) = pledge.lhs {
+                    spans.extend(typed::Spanned::get_spans(lhs, tcx));
+                }
+                spans
+            })
+            .collect()
+    }
+
     fn get_loop_span(&self, loop_head: mir::BasicBlock) -> Span {
         let loop_info = self.loop_encoder.loops();
         debug_assert!(loop_info.is_loop_head(loop_head));
@@ -4851,3 +5869,112 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 fn convert_loans_to_borrows(loans: &Vec<facts::Loan>) -> Vec<Borrow> {
     loans.iter().map(|l| l.into()).collect()
 }
+
+/// Recognizes the `wrapping_{add,sub,mul}` and `checked_{add,sub,mul,div,rem}` methods of the
+/// integer types. Returns whether the operation wraps around, and the corresponding binary
+/// operation.
+///
+/// The other methods (e.g. `wrapping_div`, which panics on a zero divisor, the shifts, `pow`,
+/// and the `overflowing_` and `saturating_` variants) are not recognized: their calls are
+/// encoded like those of any other function without specification.
+fn integer_arithmetic_method(full_func_proc_name: &str) -> Option<(bool, mir::BinOp)> {
+    let method_name = ["core::num::<impl ", "std::num::<impl "]
+        .iter()
+        .find(|prefix| full_func_proc_name.starts_with(*prefix))
+        .and_then(|_| full_func_proc_name.rsplit("::").next())?;
+    match method_name {
+        "wrapping_add" => Some((true, mir::BinOp::Add)),
+        "wrapping_sub" => Some((true, mir::BinOp::Sub)),
+        "wrapping_mul" => Some((true, mir::BinOp::Mul)),
+        "checked_add" => Some((false, mir::BinOp::Add)),
+        "checked_sub" => Some((false, mir::BinOp::Sub)),
+        "checked_mul" => Some((false, mir::BinOp::Mul)),
+        "checked_div" => Some((false, mir::BinOp::Div)),
+        "checked_rem" => Some((false, mir::BinOp::Rem)),
+        _ => None,
+    }
+}
+
+/// Whether the type is a reference or a box pointing to a trait object.
+fn is_trait_object_pointer(ty: ty::Ty) -> bool {
+    match ty.builtin_deref(true) {
+        Some(ty::TypeAndMut { ty: pointee_ty, .. }) => pointee_ty.is_trait(),
+        None => false,
+    }
+}
+
+/// Returns the span of the first cast of a mutable reference to a trait object.
+///
+/// The values modified through such a trait object can not be related back to the original
+/// value when the reference expires.
+fn find_mutable_trait_object_cast(mir: &mir::Body) -> Option<Span> {
+    for bb_data in mir.basic_blocks().iter() {
+        for stmt in &bb_data.statements {
+            if let mir::StatementKind::Assign(box (
+                _,
+                mir::Rvalue::Cast(
+                    mir::CastKind::Pointer(ty::adjustment::PointerCast::Unsize),
+                    _,
+                    dst_ty,
+                ),
+            )) = stmt.kind
+            {
+                if let ty::TyKind::Ref(_, pointee_ty, Mutability::Mut) = dst_ty.kind {
+                    if pointee_ty.is_trait() {
+                        return Some(stmt.source_info.span);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns the span of a statement that creates a raw pointer from a reference, if any.
+///
+/// The permission of the target of such a raw pointer would be shared with the reference.
+fn find_raw_pointer_from_reference<'tcx>(
+    mir: &mir::Body<'tcx>,
+    tcx: ty::TyCtxt<'tcx>,
+) -> Option<Span> {
+    for bb_data in mir.basic_blocks().iter() {
+        for stmt in &bb_data.statements {
+            if let mir::StatementKind::Assign(box (_, ref rvalue)) = stmt.kind {
+                match rvalue {
+                    mir::Rvalue::AddressOf(..) => return Some(stmt.source_info.span),
+                    mir::Rvalue::Cast(_, operand, dst_ty)
+                        if dst_ty.is_unsafe_ptr() && operand.ty(mir, tcx).is_region_ptr() =>
+                    {
+                        return Some(stmt.source_info.span)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Split the top-level conjuncts of a specification that are access predicates (e.g. obtained
+/// from `acc(ptr)`) from the rest of the specification.
+///
+/// The access predicates are exhaled and inhaled together with the permissions of the types,
+/// while the rest of the specification is only asserted or assumed.
+fn split_access_predicates(spec: vir::Expr) -> (Vec<vir::Expr>, vir::Expr) {
+    match spec {
+        vir::Expr::PredicateAccessPredicate(..) | vir::Expr::FieldAccessPredicate(..) => {
+            (vec![spec], true.into())
+        }
+        vir::Expr::BinOp(vir::BinOpKind::And, box left, box right, pos) => {
+            let (mut accesses, left) = split_access_predicates(left);
+            let (right_accesses, right) = split_access_predicates(right);
+            accesses.extend(right_accesses);
+            if accesses.is_empty() {
+                (accesses, vir::Expr::BinOp(vir::BinOpKind::And, box left, box right, pos))
+            } else {
+                (accesses, vir::Expr::and(left, right))
+            }
+        }
+        _ => (vec![], spec),
+    }
+}
@@ -6,6 +6,8 @@
 
 use crate::encoder::borrows::{compute_procedure_contract, ProcedureContract};
 use crate::encoder::builtin_encoder::BuiltinFunctionKind;
+use crate::encoder::errors::CheckedPanics;
+use crate::encoder::errors::OverflowCheck;
 use crate::encoder::errors::PanicCause;
 use crate::encoder::errors::{EncodingError, ErrorCtxt};
 use crate::encoder::foldunfold;
@@ -16,6 +18,10 @@ use crate::encoder::mir_interpreter::{
 };
 use crate::encoder::Encoder;
 use crate::encoder::snapshot_spec_patcher::SnapshotSpecPatcher;
+use crate::encoder::std_encoder::{is_identity_call, StdMethodEncoder, StdMethodKind};
+use crate::encoder::str_encoder::{
+    self, is_byte_slice_type, is_bytes_value_type, is_str_reference, StrMethodKind,
+};
 use prusti_common::vir;
 use prusti_common::vir::ExprIterator;
 use prusti_common::config;
@@ -120,6 +126,30 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionEncoder<'p, 'v, 'tcx> {
         self.encode_function_given_body(None)
     }
 
+    /// Encode a predicate, whose body is the given specification assertion.
+    pub fn encode_predicate_function(&self, predicate_body: &typed::Assertion<'tcx>) -> vir::Function {
+        let function_name = self.encode_function_name();
+        debug!("Encode predicate {}", function_name);
+
+        let encoded_args: Vec<vir::Expr> = self
+            .mir
+            .args_iter()
+            .map(|local| self.encode_local(local).into())
+            .collect();
+        let body_expr = self.encoder.encode_assertion(
+            predicate_body,
+            &self.mir,
+            &"",
+            &encoded_args,
+            None,
+            true,
+            None,
+            ErrorCtxt::GenericExpression,
+        );
+        let body_expr = SnapshotSpecPatcher::new(self.encoder).patch_spec(body_expr);
+        self.encode_function_given_body(Some(body_expr))
+    }
+
     // Private
 
     fn encode_function_given_body(&self, body: Option<vir::Expr>) -> vir::Function {
@@ -381,6 +411,8 @@ pub(super) struct PureFunctionBackwardInterpreter<'p, 'v: 'p, 'tcx: 'v> {
     /// when to a undefined function calls. This distinction allows overflow checks to be checked
     /// on the caller side and assumed on the definition side.
     is_encoding_assertion: bool,
+    /// The kinds of panics whose absence is checked.
+    checked_panics: CheckedPanics,
 }
 
 /// XXX: This encoding works backward, but there is the risk of generating expressions whose length
@@ -400,9 +432,118 @@ impl<'p, 'v: 'p, 'tcx: 'v> PureFunctionBackwardInterpreter<'p, 'v, 'tcx> {
             mir_encoder: MirEncoder::new_with_namespace(encoder, mir, def_id, namespace.clone()),
             namespace,
             is_encoding_assertion,
+            checked_panics: CheckedPanics {
+                index_bounds: config::check_index_bounds(),
+                division_by_zero: config::check_division_by_zero(),
+                unwrap: config::check_unwrap(),
+                overflows: config::check_overflows(),
+            },
+        }
+    }
+
+    /// Encodes the result of a failing check: `false` in an assertion, and an undefined value
+    /// in a pure function, whose precondition makes the caller check that it is unreachable.
+    fn encode_failure_expr(&self, pos: vir::Position) -> vir::Expr {
+        if self.is_encoding_assertion {
+            false.into()
+        } else {
+            let encoded_type = self.encoder.encode_value_or_ref_type(self.mir.return_ty());
+            let function_name = self
+                .encoder
+                .encode_builtin_function_use(BuiltinFunctionKind::Unreachable(
+                    encoded_type.clone(),
+                ));
+            vir::Expr::func_app(function_name, vec![], vec![], encoded_type, pos)
+        }
+    }
+
+    /// Returns the operation that corresponds to a call of `PartialEq::{eq,ne}` or
+    /// `PartialOrd::{lt,le,gt,ge}`, if the compared values are of a primitive type.
+    fn encode_primitive_comparison_op(
+        &self,
+        func_proc_name: &str,
+        first_arg: &mir::Operand<'tcx>,
+    ) -> Option<mir::BinOp> {
+        let op = match func_proc_name {
+            "std::cmp::PartialEq::eq" => mir::BinOp::Eq,
+            "std::cmp::PartialEq::ne" => mir::BinOp::Ne,
+            "std::cmp::PartialOrd::lt" => mir::BinOp::Lt,
+            "std::cmp::PartialOrd::le" => mir::BinOp::Le,
+            "std::cmp::PartialOrd::gt" => mir::BinOp::Gt,
+            "std::cmp::PartialOrd::ge" => mir::BinOp::Ge,
+            _ => return None,
+        };
+        let arg_ty = self.mir_encoder.get_operand_ty(first_arg);
+        let cmp_ty = self.encoder.resolve_typaram(arg_ty.builtin_deref(true)?.ty);
+        match cmp_ty.kind {
+            ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {
+                Some(op)
+            }
+            _ => None,
         }
     }
 
+    /// Encodes an argument of a natively encoded string method, returning its value and the
+    /// type of the value. The value of a string literal is its bytes, of type `str`.
+    fn encode_str_method_arg(
+        &self,
+        arg: &mir::Operand<'tcx>,
+        arg_ty: ty::Ty<'tcx>,
+        span: rustc_span::Span,
+    ) -> Result<(vir::Expr, ty::Ty<'tcx>), EncodingError> {
+        let tcx = self.encoder.env().tcx();
+        match arg {
+            mir::Operand::Constant(box mir::Constant { literal: ty::Const { val, .. }, .. })
+                if is_str_reference(tcx, arg_ty) =>
+            {
+                Ok((self.encoder.encode_str_const_expr(val, span)?, tcx.types.str_))
+            }
+            _ => Ok((self.mir_encoder.encode_operand_expr(arg), arg_ty)),
+        }
+    }
+
+    /// Encodes the arguments of a call of a natively encoded string method (see
+    /// `Encoder::encode_str_method_args`).
+    fn encode_str_method_call_args(
+        &self,
+        method: StrMethodKind,
+        args: &[mir::Operand<'tcx>],
+        arg_tys: &[ty::Ty<'tcx>],
+        span: rustc_span::Span,
+    ) -> Result<Vec<vir::Expr>, EncodingError> {
+        let mut arg_values = vec![];
+        let mut value_tys = vec![];
+        for (arg, &arg_ty) in args.iter().zip(arg_tys) {
+            let (arg_value, value_ty) = self.encode_str_method_arg(arg, arg_ty, span)?;
+            arg_values.push(arg_value);
+            value_tys.push(value_ty);
+        }
+        Ok(self.encoder.encode_str_method_args(method, &arg_values, &value_tys))
+    }
+
+    /// Encodes the byte `s[i]` of a place `s[i]` where `s` is a byte slice, e.g. the result of
+    /// `as_bytes()`.
+    fn encode_byte_place(&self, place: &mir::Place<'tcx>) -> Option<vir::Expr> {
+        let (last_elem, base_projection) = place.projection.split_last()?;
+        let index = match last_elem {
+            mir::ProjectionElem::Index(index) => *index,
+            _ => return None,
+        };
+        let base_place = mir::Place {
+            local: place.local,
+            projection: self.encoder.env().tcx().intern_place_elems(base_projection),
+        };
+        let (encoded_base, base_ty, _) = self.mir_encoder.encode_place(&base_place).ok()?;
+        if !is_byte_slice_type(base_ty) {
+            return None;
+        }
+        let encoded_index = self.mir_encoder.eval_place(&index.into());
+        Some(str_encoder::byte(
+            self.encoder.encode_value_expr(encoded_base, base_ty),
+            encoded_index,
+        ))
+    }
+
     pub(super) fn mir_encoder(&self) -> &MirEncoder<'p, 'v, 'tcx> {
         &self.mir_encoder
     }
@@ -631,10 +772,30 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     let (ref lhs_place, target_block) = destination.as_ref().unwrap();
                     let (encoded_lhs, ty, _) = self.mir_encoder.encode_place(lhs_place).unwrap(); // will panic if attempting to encode unsupported type
                     let lhs_value = self.encoder.encode_value_expr(encoded_lhs.clone(), ty);
-                    let encoded_args: Vec<vir::Expr> = args
+                    let arg_tys: Vec<ty::Ty<'tcx>> = args
                         .iter()
-                        .map(|arg| self.mir_encoder.encode_operand_expr(arg))
+                        .map(|arg| self.encoder.resolve_typaram(self.mir_encoder.get_operand_ty(arg)))
                         .collect();
+                    let str_method = StrMethodKind::from_call(
+                        self.encoder.env().tcx(),
+                        full_func_proc_name,
+                        &arg_tys,
+                    );
+                    let std_method = StdMethodEncoder::new(
+                        self.encoder,
+                        full_func_proc_name,
+                        arg_tys.clone(),
+                        ty,
+                    );
+                    let encoded_args: Vec<vir::Expr> = if str_method.is_some() {
+                        // String literals have no value of their reference type, the arguments
+                        // are encoded by `encode_str_method_arg`.
+                        vec![]
+                    } else {
+                        args.iter()
+                            .map(|arg| self.mir_encoder.encode_operand_expr(arg))
+                            .collect()
+                    };
 
                     match full_func_proc_name {
                         "prusti_contracts::old" => {
@@ -658,11 +819,134 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             state.substitute_value(&lhs_value, encoded_rhs);
                             state
                         }
+
+                        "prusti_contracts::acc" | "prusti_contracts::own" => {
+                            trace!("Encoding access predicate {:?}", args[0]);
+                            assert_eq!(args.len(), 1);
+                            // `encoded_args[0]` is the address stored in the raw pointer, so we
+                            // need the full permission of the predicate of the target.
+                            let encoded_rhs = vir::Expr::pred_permission(
+                                encoded_args[0].clone(),
+                                vir::PermAmount::Write,
+                            )
+                            .unwrap();
+                            let mut state = states[&target_block].clone();
+                            state.substitute_value(&lhs_value, encoded_rhs);
+                            state
+                        }
+
+                        // natively encoded methods of strings
+                        _ if str_method.is_some() => {
+                            trace!("Encoding string method {:?}", func_proc_name);
+                            let method = str_method.unwrap();
+                            let mut state = states[&target_block].clone();
+                            let span = term.source_info.span;
+                            let encoded_result = self
+                                .encode_str_method_call_args(method, args, &arg_tys, span)
+                                .map(|str_args| method.encode_result(&str_args));
+                            match encoded_result {
+                                Ok(Some(encoded_rhs)) => {
+                                    let tcx = self.encoder.env().tcx();
+                                    let lhs_str_value =
+                                        if is_bytes_value_type(tcx, ty) {
+                                            self.encoder.encode_str_value(lhs_value.clone(), ty)
+                                        } else {
+                                            lhs_value.clone()
+                                        };
+                                    state.substitute_value(&lhs_str_value, encoded_rhs);
+                                }
+                                Err(error) => self.encoder.register_encoding_error(error),
+                                Ok(None) => {
+                                    // The method mutates a string.
+                                    self.encoder.register_encoding_error(EncodingError::incorrect(
+                                        format!(
+                                            "use of impure function {:?} in assertion is not allowed",
+                                            func_proc_name
+                                        ),
+                                        term.source_info.span,
+                                    ));
+                                }
+                            }
+                            state
+                        }
+
+                        // calls that return their argument unchanged
+                        name if is_identity_call(self.encoder.env().tcx(), name, &arg_tys, ty) => {
+                            trace!("Encoding identity call {:?}", func_proc_name);
+                            let mut state = states[&target_block].clone();
+                            state.substitute_value(&lhs_value, encoded_args[0].clone());
+                            state
+                        }
+
+                        // natively encoded methods of `Vec`, `Option` and `Result`
+                        _ if std_method.is_some() => {
+                            trace!("Encoding std method {:?}", func_proc_name);
+                            let std_method = std_method.unwrap();
+                            let mut state = states[&target_block].clone();
+                            match std_method.method() {
+                                StdMethodKind::VecPush
+                                | StdMethodKind::VecPop
+                                | StdMethodKind::VecIndexMut => {
+                                    // The method mutates a vector.
+                                    self.encoder.register_encoding_error(EncodingError::incorrect(
+                                        format!(
+                                            "use of impure function {:?} in assertion is not allowed",
+                                            func_proc_name
+                                        ),
+                                        term.source_info.span,
+                                    ));
+                                }
+                                _ => {
+                                    // A guarded fact is about a payload that can only be read
+                                    // when the guard holds, so the guard can be ignored.
+                                    for fact in std_method.encode_result(&encoded_args, encoded_lhs.clone()) {
+                                        state.substitute_value(&fact.place, fact.value);
+                                    }
+                                }
+                            }
+                            state
+                        }
+
+                        // comparison of primitive values, e.g. in the instantiation of a generic
+                        // specification
+                        _ if self.encode_primitive_comparison_op(func_proc_name, &args[0]).is_some() => {
+                            trace!("Encoding primitive comparison {:?}", func_proc_name);
+                            assert_eq!(args.len(), 2);
+                            let op = self
+                                .encode_primitive_comparison_op(func_proc_name, &args[0])
+                                .unwrap();
+                            let arg_ty = self.mir_encoder.get_operand_ty(&args[0]);
+                            let cmp_ty = self.encoder.resolve_typaram(arg_ty.builtin_deref(true).unwrap().ty);
+                            let value_field = self.encoder.encode_value_field(cmp_ty);
+                            let encoded_rhs = self.mir_encoder.encode_bin_op_expr(
+                                op,
+                                encoded_args[0].clone().field(value_field.clone()),
+                                encoded_args[1].clone().field(value_field),
+                                cmp_ty,
+                                term.source_info.span,
+                            );
+                            let mut state = states[&target_block].clone();
+                            match encoded_rhs {
+                                Ok(encoded_rhs) => state.substitute_value(&lhs_value, encoded_rhs),
+                                Err(error) => self.encoder.register_encoding_error(error),
+                            }
+                            state
+                        }
+
                         // simple function call
                         _ => {
                             let mut is_cmp_call = false;
                             let is_pure_function =
                                 self.encoder.env().has_attribute_name(def_id, "pure");
+                            if is_pure_function
+                                && !self.is_encoding_assertion
+                                && self.encoder.is_predicate(def_id)
+                            {
+                                self.encoder.register_encoding_error(EncodingError::incorrect(
+                                    "using predicate from non-specification code is not allowed",
+                                    term.source_info.span,
+                                ));
+                            }
                             let (function_name, return_type) = if is_pure_function {
                                 self.encoder.encode_pure_function_use(def_id)
                             } else {
@@ -679,6 +963,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                                         is_cmp_call = true;
                                         self.encoder.encode_cmp_pure_function_use(def_id, arg_ty, false)
                                     }
+                                    "std::cmp::PartialOrd::lt"
+                                    | "std::cmp::PartialOrd::le"
+                                    | "std::cmp::PartialOrd::gt"
+                                    | "std::cmp::PartialOrd::ge" => {
+                                        // The ordering of a type parameter is only known
+                                        // through its trait bound.
+                                        is_cmp_call = true;
+                                        self.encoder.encode_trait_method_function_use(def_id)
+                                    }
                                     _ => {
                                         self.encoder.encode_stub_pure_function_use(def_id)
                                     }
@@ -810,11 +1103,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
 
                         _ => ErrorCtxt::DivergingCallInPureFunction,
                     };
+                    let is_checked = error_ctxt.is_checked(self.checked_panics);
                     let pos = self
                         .encoder
                         .error_manager()
                         .register(term.source_info.span, error_ctxt);
-                    MultiExprBackwardInterpreterState::new_single(unreachable_expr(pos))
+                    if is_checked {
+                        MultiExprBackwardInterpreterState::new_single(unreachable_expr(pos))
+                    } else {
+                        // The absence of this kind of panic is not checked
+                        MultiExprBackwardInterpreterState::new_single(undef_expr(pos))
+                    }
                 };
 
                 // FIXME; hideous monstrosity...
@@ -844,10 +1143,15 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     vir::Expr::not(cond_val)
                 };
 
-                let pos = self.encoder.error_manager().register(
-                    term.source_info.span,
-                    ErrorCtxt::PureFunctionAssertTerminator(msg.description().to_string()),
-                );
+                let error_ctxt = ErrorCtxt::assert_terminator(msg, true);
+                if !error_ctxt.is_checked(self.checked_panics) {
+                    // The assertion is assumed to hold
+                    return states[target].clone();
+                }
+                let pos = self
+                    .encoder
+                    .error_manager()
+                    .register(term.source_info.span, error_ctxt);
 
                 MultiExprBackwardInterpreterState::new(
                     states[target]
@@ -923,6 +1227,43 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                 };
 
                 match rhs {
+                    &mir::Rvalue::Use(mir::Operand::Constant(box mir::Constant {
+                        literal: ty::Const { ty: const_ty, val },
+                        ..
+                    })) if is_str_reference(self.encoder.env().tcx(), const_ty) => {
+                        let lhs_value = opt_lhs_value_place.unwrap();
+                        match self.encoder.encode_str_const_expr(val, stmt.source_info.span) {
+                            Ok(encoded_rhs) => state.substitute_value(
+                                &self.encoder.encode_str_value(lhs_value, ty),
+                                encoded_rhs,
+                            ),
+                            Err(error) => self.encoder.register_encoding_error(error),
+                        }
+                    }
+
+                    &mir::Rvalue::Use(mir::Operand::Copy(ref place))
+                    | &mir::Rvalue::Use(mir::Operand::Move(ref place))
+                        if self.encode_byte_place(place).is_some() =>
+                    {
+                        let encoded_rhs = self.encode_byte_place(place).unwrap();
+                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_rhs);
+                    }
+
+                    &mir::Rvalue::Len(ref place) => {
+                        // will panic if attempting to encode unsupported type
+                        let (encoded_place, place_ty, _) = self.mir_encoder.encode_place(place).unwrap();
+                        if is_byte_slice_type(place_ty) {
+                            let encoded_value = self.encoder.encode_value_expr(encoded_place, place_ty);
+                            let encoded_rhs = str_encoder::len(encoded_value);
+                            state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_rhs);
+                        } else {
+                            self.encoder.register_encoding_error(EncodingError::unsupported(
+                                format!("the length of values of type '{}' is not supported", place_ty),
+                                stmt.source_info.span,
+                            ));
+                        }
+                    }
+
                     &mir::Rvalue::Use(ref operand) => {
                         let opt_encoded_rhs = self.mir_encoder.encode_operand_place(operand);
 
@@ -1026,10 +1367,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             encoded_left,
                             encoded_right,
                             ty,
+                            stmt.source_info.span,
                         );
 
                         // Substitute a place of a value with an expression
-                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_value);
+                        match encoded_value {
+                            Ok(encoded_value) => {
+                                state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_value)
+                            }
+                            Err(error) => self.encoder.register_encoding_error(error),
+                        }
                     }
 
                     &mir::Rvalue::CheckedBinaryOp(op, ref left, ref right) => {
@@ -1047,6 +1394,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             encoded_left.clone(),
                             encoded_right.clone(),
                             operand_ty.expect_ty(),
+                            stmt.source_info.span,
                         );
                         let encoded_check = self.mir_encoder.encode_bin_op_check(
                             op,
@@ -1079,13 +1427,18 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                             .field(check_field_value);
 
                         // Substitute a place of a value with an expression
-                        state.substitute_value(&lhs_value, encoded_value);
+                        match encoded_value {
+                            Ok(encoded_value) => state.substitute_value(&lhs_value, encoded_value),
+                            Err(error) => self.encoder.register_encoding_error(error),
+                        }
                         state.substitute_value(&lhs_check, encoded_check);
                     }
 
                     &mir::Rvalue::UnaryOp(op, ref operand) => {
                         let encoded_val = self.mir_encoder.encode_operand_expr(operand);
-                        let encoded_value = self.mir_encoder.encode_unary_op_expr(op, encoded_val);
+                        let operand_ty = self.mir_encoder.get_operand_ty(operand);
+                        let encoded_value =
+                            self.mir_encoder.encode_unary_op_expr(op, encoded_val, operand_ty);
 
                         // Substitute a place of a value with an expression
                         state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_value);
@@ -1154,10 +1507,46 @@ impl<'p, 'v: 'p, 'tcx: 'v> BackwardMirInterpreter<'tcx>
                     }
 
                     &mir::Rvalue::Cast(mir::CastKind::Misc, ref operand, dst_ty) => {
-                        let encoded_val = self.mir_encoder.encode_cast_expr(operand, dst_ty);
+                        let encoded_val = self.mir_encoder.encode_cast_expr(
+                            operand,
+                            dst_ty,
+                            stmt.source_info.span,
+                        );
 
                         // Substitute a place of a value with an expression
-                        state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_val);
+                        match encoded_val {
+                            Ok(encoded_val) => {
+                                state.substitute_value(&opt_lhs_value_place.unwrap(), encoded_val)
+                            }
+                            Err(error) => self.encoder.register_encoding_error(error),
+                        }
+
+                        let error_ctxt = ErrorCtxt::OverflowCheck(OverflowCheck::Cast);
+                        let opt_check = if error_ctxt.is_checked(self.checked_panics) {
+                            self.mir_encoder.encode_cast_check(operand, dst_ty)
+                        } else {
+                            None
+                        };
+                        if let Some(check) = opt_check {
+                            let pos = self
+                                .encoder
+                                .error_manager()
+                                .register(stmt.source_info.span, error_ctxt);
+                            let failure_result = self.encode_failure_expr(pos);
+                            *state = MultiExprBackwardInterpreterState::new(
+                                state
+                                    .exprs()
+                                    .iter()
+                                    .map(|expr| {
+                                        vir::Expr::ite(
+                                            check.clone(),
+                                            expr.clone(),
+                                            failure_result.clone(),
+                                        )
+                                    })
+                                    .collect(),
+                            );
+                        }
                     }
 
                     ref rhs => {
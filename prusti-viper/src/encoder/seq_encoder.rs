@@ -0,0 +1,170 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The `Seq$Int` and `Seq$Bool` domains of sequences, which model strings and vectors.
+
+use prusti_common::vir;
+use prusti_common::vir::ExprIterator;
+
+fn seq_domain_name(elem_type: &vir::Type) -> String {
+    format!("Seq${}", elem_type)
+}
+
+/// The type of sequences of values of type `elem_type`.
+pub fn seq_type(elem_type: &vir::Type) -> vir::Type {
+    vir::Type::Domain(seq_domain_name(elem_type))
+}
+
+/// A function of the `Seq` domains.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum SeqFunctionKind {
+    /// The empty sequence.
+    Empty,
+    /// A sequence with an element appended at the end.
+    Push,
+    /// A sequence without its last element.
+    Pop,
+    /// The number of elements.
+    Len,
+    /// The element at a given index.
+    Get,
+}
+
+impl SeqFunctionKind {
+    fn domain_func(&self, elem_type: &vir::Type) -> vir::DomainFunc {
+        let seq = seq_type(elem_type);
+        let (name, arg_types, return_type) = match self {
+            SeqFunctionKind::Empty => ("empty", vec![], seq.clone()),
+            SeqFunctionKind::Push => ("push", vec![seq.clone(), elem_type.clone()], seq.clone()),
+            SeqFunctionKind::Pop => ("pop", vec![seq.clone()], seq.clone()),
+            SeqFunctionKind::Len => ("len", vec![seq.clone()], vir::Type::Int),
+            SeqFunctionKind::Get => ("get", vec![seq.clone(), vir::Type::Int], elem_type.clone()),
+        };
+        vir::DomainFunc {
+            name: format!("seq${}${}", elem_type, name),
+            formal_args: arg_types
+                .into_iter()
+                .enumerate()
+                .map(|(index, typ)| vir::LocalVar::new(format!("_{}", index), typ))
+                .collect(),
+            return_type,
+            unique: false,
+            domain_name: seq_domain_name(elem_type),
+        }
+    }
+
+    pub fn apply(&self, elem_type: &vir::Type, args: Vec<vir::Expr>) -> vir::Expr {
+        let domain_func = self.domain_func(elem_type);
+        assert_eq!(args.len(), domain_func.formal_args.len());
+        vir::Expr::DomainFuncApp(domain_func, args, vir::Position::default())
+    }
+}
+
+pub fn encode_seq_domain(elem_type: &vir::Type) -> vir::Domain {
+    let len = |s: vir::Expr| SeqFunctionKind::Len.apply(elem_type, vec![s]);
+    let get = |s: vir::Expr, i: vir::Expr| SeqFunctionKind::Get.apply(elem_type, vec![s, i]);
+    let axiom = |name: &str, expr: vir::Expr| vir::DomainAxiom {
+        name: format!("seq${}${}", elem_type, name),
+        expr,
+        domain_name: seq_domain_name(elem_type),
+    };
+
+    let s_var = vir::LocalVar::new("s", seq_type(elem_type));
+    let v_var = vir::LocalVar::new("v", elem_type.clone());
+    let i_var = vir::LocalVar::new("i", vir::Type::Int);
+    let s: vir::Expr = s_var.clone().into();
+    let v: vir::Expr = v_var.clone().into();
+    let i: vir::Expr = i_var.clone().into();
+    let push = SeqFunctionKind::Push.apply(elem_type, vec![s.clone(), v.clone()]);
+    let pop = SeqFunctionKind::Pop.apply(elem_type, vec![s.clone()]);
+
+    let axioms = vec![
+        axiom(
+            "len_empty",
+            vir::Expr::eq_cmp(len(SeqFunctionKind::Empty.apply(elem_type, vec![])), 0.into()),
+        ),
+        axiom(
+            "len_non_negative",
+            vir::Expr::forall(
+                vec![s_var.clone()],
+                vec![vir::Trigger::new(vec![len(s.clone())])],
+                vir::Expr::le_cmp(0.into(), len(s.clone())),
+            ),
+        ),
+        axiom(
+            "push",
+            vir::Expr::forall(
+                vec![s_var.clone(), v_var.clone()],
+                vec![vir::Trigger::new(vec![push.clone()])],
+                vec![
+                    vir::Expr::eq_cmp(len(push.clone()), vir::Expr::add(len(s.clone()), 1.into())),
+                    vir::Expr::eq_cmp(get(push.clone(), len(s.clone())), v.clone()),
+                    vir::Expr::eq_cmp(
+                        SeqFunctionKind::Pop.apply(elem_type, vec![push.clone()]),
+                        s.clone(),
+                    ),
+                ]
+                .into_iter()
+                .conjoin(),
+            ),
+        ),
+        axiom(
+            "push_get",
+            vir::Expr::forall(
+                vec![s_var.clone(), v_var, i_var.clone()],
+                vec![vir::Trigger::new(vec![get(push.clone(), i.clone())])],
+                vir::Expr::implies(
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(0.into(), i.clone()),
+                        vir::Expr::lt_cmp(i.clone(), len(s.clone())),
+                    ),
+                    vir::Expr::eq_cmp(get(push, i.clone()), get(s.clone(), i.clone())),
+                ),
+            ),
+        ),
+        axiom(
+            "pop",
+            vir::Expr::forall(
+                vec![s_var.clone()],
+                vec![vir::Trigger::new(vec![pop.clone()])],
+                vir::Expr::implies(
+                    vir::Expr::lt_cmp(0.into(), len(s.clone())),
+                    vir::Expr::eq_cmp(len(pop.clone()), vir::Expr::sub(len(s.clone()), 1.into())),
+                ),
+            ),
+        ),
+        axiom(
+            "pop_get",
+            vir::Expr::forall(
+                vec![s_var, i_var],
+                vec![vir::Trigger::new(vec![get(pop.clone(), i.clone())])],
+                vir::Expr::implies(
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(0.into(), i.clone()),
+                        vir::Expr::lt_cmp(i.clone(), vir::Expr::sub(len(s.clone()), 1.into())),
+                    ),
+                    vir::Expr::eq_cmp(get(pop, i.clone()), get(s, i)),
+                ),
+            ),
+        ),
+    ];
+
+    vir::Domain {
+        name: seq_domain_name(elem_type),
+        functions: vec![
+            SeqFunctionKind::Empty,
+            SeqFunctionKind::Push,
+            SeqFunctionKind::Pop,
+            SeqFunctionKind::Len,
+            SeqFunctionKind::Get,
+        ]
+        .iter()
+        .map(|function| function.domain_func(elem_type))
+        .collect(),
+        axioms,
+        type_vars: vec![],
+    }
+}
@@ -79,6 +79,27 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
         }
     }
 
+    /// Used to encode the specification of a function that has no MIR body, e.g. a trait method
+    /// without default implementation.
+    pub fn new_without_mir(
+        encoder: &'p Encoder<'v, 'tcx>,
+        target_args: &'p [vir::Expr],
+        target_return: Option<&'p vir::Expr>,
+        targets_are_values: bool,
+    ) -> Self {
+        trace!("SpecEncoder constructor without MIR");
+
+        SpecEncoder {
+            encoder,
+            mir: None,
+            target_label: &"",
+            target_args,
+            target_return,
+            targets_are_values,
+            stop_at_bbi: None,
+        }
+    }
+
 //     fn encode_hir_field(&self, base_place: vir::Expr, field_expr: &hir::Expr) -> vir::Expr {
 //         trace!("encode_hir_field: {:?}", field_expr);
 //         assert!(match field_expr.node {
@@ -335,18 +356,21 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
     }
 
     /// Encode a specification item as a single expression.
+    ///
+    /// Each conjunct, implication consequent and quantifier body gets its own position, so that
+    /// the failing part of a specification can be reported precisely.
     pub fn encode_assertion(&self, assertion: &typed::Assertion<'tcx>) -> vir::Expr {
         trace!("encode_assertion {:?}", assertion);
         match assertion.kind {
             box typed::AssertionKind::Expr(ref assertion_expr) => self.encode_expression(assertion_expr),
             box typed::AssertionKind::And(ref assertions) => assertions
                 .iter()
-                .map(|x| self.encode_assertion(x))
+                .map(|x| self.encode_assertion_part(x))
                 .collect::<Vec<vir::Expr>>()
                 .into_iter()
                 .conjoin(),
             box typed::AssertionKind::Implies(ref lhs, ref rhs) => {
-                vir::Expr::implies(self.encode_assertion(lhs), self.encode_assertion(rhs))
+                vir::Expr::implies(self.encode_assertion(lhs), self.encode_assertion_part(rhs))
             }
             box typed::AssertionKind::TypeCond(ref vars, ref assertion) => {
                 let enc = |ty: ty::Ty<'tcx>| -> vir::Expr {
@@ -356,7 +380,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
                 };
                 let typecond =
                     vir::Expr::eq_cmp(enc(vars.vars[0].1), enc(vars.vars[1].1));
-                vir::Expr::implies(typecond, self.encode_assertion(assertion))
+                vir::Expr::implies(typecond, self.encode_assertion_part(assertion))
             }
             box typed::AssertionKind::ForAll(ref vars, ref trigger_set, ref body) => vir::Expr::forall(
                 vars.vars.iter().map(|(arg, ty)| self.encode_forall_arg(*arg, ty)).collect(),
@@ -365,11 +389,21 @@ impl<'p, 'v: 'p, 'tcx: 'v> SpecEncoder<'p, 'v, 'tcx> {
                     .iter()
                     .map(|x| self.encode_trigger(x))
                     .collect(),
-                self.encode_assertion(body),
+                self.encode_assertion_part(body),
             ),
         }
     }
 
+    /// Encode a part of a specification item, registering the position of its spans.
+    fn encode_assertion_part(&self, assertion: &typed::Assertion<'tcx>) -> vir::Expr {
+        let spans = typed::Spanned::get_spans(assertion, self.encoder.env().tcx());
+        self.encode_assertion(assertion).set_default_pos(
+            self.encoder
+                .error_manager()
+                .register(spans, ErrorCtxt::GenericExpression),
+        )
+    }
+
     fn encode_expression(&self, assertion_expr: &typed::Expression) -> vir::Expr {
         debug!("encode_expression {:?}", assertion_expr);
         let tcx = self.encoder.env().tcx();
@@ -0,0 +1,540 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Built-in models of `Vec<T>`, `Option<T>` and `Result<T, E>`.
+//!
+//! A vector whose elements are integers, chars or booleans is modelled as a sequence of the
+//! `Seq$Int` or `Seq$Bool` domain. The methods of `Option` and `Result` are modelled by their
+//! effect on the discriminant and, when it is of a primitive type, on the payload.
+//!
+//! Calls of any other method, e.g. `v.get(i)`, `v.iter()` or `x.map(f)`, are encoded like calls
+//! of functions without a specification.
+
+use crate::encoder::errors::PanicCause;
+use crate::encoder::seq_encoder::{seq_type, SeqFunctionKind};
+use crate::encoder::type_encoder::compute_discriminant_values;
+use crate::encoder::Encoder;
+use crate::utils::is_item;
+use prusti_common::vir;
+use rustc_middle::ty;
+
+fn is_primitive(ty: ty::Ty) -> bool {
+    match ty.kind {
+        ty::TyKind::Bool | ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => true,
+        _ => false,
+    }
+}
+
+/// The type of the elements of `ty`, if it is a vector modelled as a sequence.
+///
+/// Only vectors of integers, chars and booleans are modelled; other vectors are opaque. The
+/// model covers `new`, `with_capacity`, `len`, `is_empty`, `push`, `pop`, `v[i]` and `&mut v[i]`.
+/// The methods of slices, such as `get` and `iter`, are reached through `Deref` and are not
+/// modelled, and a write through `&mut v[i]` is not reflected in the sequence: the vector is
+/// unknown once the reference expires.
+pub fn vec_elem_type<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> Option<ty::Ty<'tcx>> {
+    match ty.kind {
+        ty::TyKind::Adt(adt_def, subst)
+            if is_item(tcx, adt_def.did, &["std::vec::Vec", "alloc::vec::Vec"]) =>
+        {
+            let elem_ty = subst.type_at(0);
+            if is_primitive(elem_ty) {
+                Some(elem_ty)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The type of the values stored in a sequence of elements of type `elem_ty`.
+pub fn seq_elem_type(elem_ty: ty::Ty) -> vir::Type {
+    match elem_ty.kind {
+        ty::TyKind::Bool => vir::Type::Bool,
+        _ => vir::Type::Int,
+    }
+}
+
+/// The field that stores the value of a vector.
+pub fn seq_value_field(elem_type: &vir::Type) -> vir::Field {
+    vir::Field::new(format!("val_seq${}", elem_type), seq_type(elem_type))
+}
+
+/// A method of `Vec`, `Option` or `Result` whose behaviour is encoded natively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StdMethodKind {
+    /// `Vec::new()` and `Vec::with_capacity(n)`
+    VecNew,
+    /// `v.len()`
+    VecLen,
+    /// `v.is_empty()`
+    VecIsEmpty,
+    /// `v.push(x)`
+    VecPush,
+    /// `v.pop()`
+    VecPop,
+    /// `v[i]`
+    VecIndex,
+    /// `&mut v[i]`
+    VecIndexMut,
+    /// `x.is_some()`, `x.is_none()`, `x.is_ok()` and `x.is_err()`, with the name of the variant
+    IsVariant(&'static str),
+    /// `x.unwrap()` and `x.expect(msg)`
+    Unwrap,
+    /// `x.unwrap_or(default)`
+    UnwrapOr,
+    /// `x.ok_or(err)`
+    OkOr,
+    /// `Try::into_result(x)` for an `Option`, used by the `?` operator
+    IntoResult,
+    /// `Try::from_error(err)`, used by the `?` operator
+    FromError,
+    /// `Try::from_ok(x)`
+    FromOk,
+}
+
+/// The name of the variant of `Option` or `Result` that holds a value.
+fn success_variant(enum_name: &str) -> &'static str {
+    if enum_name.ends_with("Option") {
+        "Some"
+    } else {
+        "Ok"
+    }
+}
+
+/// The name of the variant of `Option` or `Result` that does not hold a value.
+fn failure_variant(enum_name: &str) -> &'static str {
+    if enum_name.ends_with("Option") {
+        "None"
+    } else {
+        "Err"
+    }
+}
+
+/// The path of `Option` or `Result`, if `ty` is one of them.
+fn std_enum_name<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> Option<String> {
+    match ty.kind {
+        ty::TyKind::Adt(adt_def, _) => {
+            let path = tcx.def_path_str(adt_def.did);
+            match path.as_str() {
+                "std::option::Option" | "core::option::Option" | "std::result::Result"
+                | "core::result::Result" => Some(path),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn peel_refs(ty: ty::Ty) -> ty::Ty {
+    match ty.kind {
+        ty::TyKind::Ref(_, target_ty, _) => peel_refs(target_ty),
+        _ => ty,
+    }
+}
+
+impl StdMethodKind {
+    /// Recognise a call of the method `name`.
+    fn from_call<'tcx>(
+        tcx: ty::TyCtxt<'tcx>,
+        name: &str,
+        arg_tys: &[ty::Ty<'tcx>],
+        return_ty: ty::Ty<'tcx>,
+    ) -> Option<Self> {
+        let self_ty = arg_tys.get(0).map(|&arg_ty| peel_refs(arg_ty));
+        let is_vec = |ty: Option<ty::Ty<'tcx>>| ty.and_then(|ty| vec_elem_type(tcx, ty)).is_some();
+        let is_enum = |ty: Option<ty::Ty<'tcx>>| ty.and_then(|ty| std_enum_name(tcx, ty)).is_some();
+        let method_name = |prefixes: &[&str]| {
+            prefixes
+                .iter()
+                .filter(|prefix| name.starts_with(*prefix))
+                .map(|prefix| name[prefix.len()..].to_string())
+                .next()
+        };
+        if let Some(method) = method_name(&[
+            "std::vec::Vec::<T>::",
+            "<std::vec::Vec<T>>::",
+            "alloc::vec::Vec::<T>::",
+            "<alloc::vec::Vec<T>>::",
+        ]) {
+            return match method.as_str() {
+                "new" | "with_capacity" if is_vec(Some(return_ty)) => Some(StdMethodKind::VecNew),
+                "len" if is_vec(self_ty) => Some(StdMethodKind::VecLen),
+                "is_empty" if is_vec(self_ty) => Some(StdMethodKind::VecIsEmpty),
+                "push" if is_vec(self_ty) => Some(StdMethodKind::VecPush),
+                "pop" if is_vec(self_ty) => Some(StdMethodKind::VecPop),
+                _ => None,
+            };
+        }
+        if let Some(method) = method_name(&[
+            "std::option::Option::<T>::",
+            "<std::option::Option<T>>::",
+            "core::option::Option::<T>::",
+            "<core::option::Option<T>>::",
+            "std::result::Result::<T, E>::",
+            "<std::result::Result<T, E>>::",
+            "core::result::Result::<T, E>::",
+            "<core::result::Result<T, E>>::",
+        ]) {
+            if !is_enum(self_ty) {
+                return None;
+            }
+            return match method.as_str() {
+                "is_some" => Some(StdMethodKind::IsVariant("Some")),
+                "is_none" => Some(StdMethodKind::IsVariant("None")),
+                "is_ok" => Some(StdMethodKind::IsVariant("Ok")),
+                "is_err" => Some(StdMethodKind::IsVariant("Err")),
+                "unwrap" | "expect" => Some(StdMethodKind::Unwrap),
+                "unwrap_or" => Some(StdMethodKind::UnwrapOr),
+                "ok_or" => Some(StdMethodKind::OkOr),
+                _ => None,
+            };
+        }
+        match name {
+            "std::ops::Index::index" | "core::ops::Index::index" if is_vec(self_ty) => {
+                Some(StdMethodKind::VecIndex)
+            }
+            "std::ops::IndexMut::index_mut" | "core::ops::IndexMut::index_mut"
+                if is_vec(self_ty) => Some(StdMethodKind::VecIndexMut),
+            "std::ops::Try::into_result" | "core::ops::Try::into_result"
+                if self_ty
+                    .and_then(|ty| std_enum_name(tcx, ty))
+                    .map_or(false, |enum_name| success_variant(&enum_name) == "Some") =>
+            {
+                Some(StdMethodKind::IntoResult)
+            }
+            "std::ops::Try::from_error" | "core::ops::Try::from_error"
+                if is_enum(Some(return_ty)) => Some(StdMethodKind::FromError),
+            "std::ops::Try::from_ok" | "core::ops::Try::from_ok"
+                if is_enum(Some(return_ty)) => Some(StdMethodKind::FromOk),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a call of `name` returns its argument unchanged, e.g. `From::from` with the same
+/// source and target types or `Try::into_result` of a `Result`. Such calls are encoded as moves.
+pub fn is_identity_call<'tcx>(
+    tcx: ty::TyCtxt<'tcx>,
+    name: &str,
+    arg_tys: &[ty::Ty<'tcx>],
+    return_ty: ty::Ty<'tcx>,
+) -> bool {
+    match name {
+        "std::convert::From::from" | "core::convert::From::from"
+        | "std::convert::Into::into" | "core::convert::Into::into" => {
+            arg_tys.len() == 1 && arg_tys[0] == return_ty
+        }
+        "std::ops::Try::into_result" | "core::ops::Try::into_result" => {
+            arg_tys.len() == 1
+                && arg_tys[0] == return_ty
+                && std_enum_name(tcx, return_ty).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// A fact about the result of a call: `guard ==> place == value`.
+pub struct ResultFact {
+    pub guard: vir::Expr,
+    pub place: vir::Expr,
+    pub value: vir::Expr,
+}
+
+/// Encodes a call of a method of `Vec`, `Option` or `Result` whose behaviour is modelled natively.
+///
+/// The arguments are given by their values, as encoded by `Encoder::encode_value_expr`; for
+/// example, the address of the target for a reference and the place for an `Option`.
+pub struct StdMethodEncoder<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    method: StdMethodKind,
+    arg_tys: Vec<ty::Ty<'tcx>>,
+    return_ty: ty::Ty<'tcx>,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> StdMethodEncoder<'p, 'v, 'tcx> {
+    pub fn new(
+        encoder: &'p Encoder<'v, 'tcx>,
+        name: &str,
+        arg_tys: Vec<ty::Ty<'tcx>>,
+        return_ty: ty::Ty<'tcx>,
+    ) -> Option<Self> {
+        let tcx = encoder.env().tcx();
+        StdMethodKind::from_call(tcx, name, &arg_tys, return_ty).map(|method| StdMethodEncoder {
+            encoder,
+            method,
+            arg_tys,
+            return_ty,
+        })
+    }
+
+    pub fn method(&self) -> StdMethodKind {
+        self.method
+    }
+
+    /// The value stored behind the references of an argument.
+    fn encode_target_value(&self, value: vir::Expr, ty: ty::Ty<'tcx>) -> (vir::Expr, ty::Ty<'tcx>) {
+        match ty.kind {
+            ty::TyKind::Ref(_, target_ty, _) => {
+                let target_value = self.encoder.encode_value_expr(value, target_ty);
+                self.encode_target_value(target_value, target_ty)
+            }
+            _ => (value, ty),
+        }
+    }
+
+    fn encode_seq_arg(&self, args: &[vir::Expr], index: usize) -> (vir::Expr, vir::Type) {
+        let (seq, vec_ty) = self.encode_target_value(args[index].clone(), self.arg_tys[index]);
+        let elem_ty = vec_elem_type(self.encoder.env().tcx(), vec_ty).unwrap();
+        (seq, seq_elem_type(elem_ty))
+    }
+
+    fn enum_variant(
+        &self,
+        enum_ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> (&'tcx ty::AdtDef, usize, ty::subst::SubstsRef<'tcx>) {
+        match enum_ty.kind {
+            ty::TyKind::Adt(adt_def, subst) => {
+                let index = adt_def
+                    .variants
+                    .iter()
+                    .position(|variant| variant.ident.as_str() == variant_name)
+                    .unwrap();
+                (adt_def, index, subst)
+            }
+            ref x => unreachable!("{:?}", x),
+        }
+    }
+
+    /// `discriminant(place) == discriminant of variant_name`
+    fn encode_is_variant(
+        &self,
+        place: vir::Expr,
+        enum_ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> vir::Expr {
+        let (adt_def, index, _) = self.enum_variant(enum_ty, variant_name);
+        let tcx = self.encoder.env().tcx();
+        let discriminant = compute_discriminant_values(adt_def, tcx)[index];
+        vir::Expr::eq_cmp(
+            self.encoder.encode_discriminant_place(place),
+            discriminant.into(),
+        )
+    }
+
+    fn encode_discriminant(&self, place: vir::Expr, enum_ty: ty::Ty<'tcx>) -> vir::Expr {
+        match enum_ty.kind {
+            ty::TyKind::Adt(..) => self.encoder.encode_discriminant_place(place),
+            ref x => unreachable!("{:?}", x),
+        }
+    }
+
+    fn encode_variant_discriminant(&self, enum_ty: ty::Ty<'tcx>, variant_name: &str) -> vir::Expr {
+        let (adt_def, index, _) = self.enum_variant(enum_ty, variant_name);
+        compute_discriminant_values(adt_def, self.encoder.env().tcx())[index].into()
+    }
+
+    /// The value of the payload of a variant, if it is of a primitive type.
+    fn encode_payload(
+        &self,
+        place: vir::Expr,
+        enum_ty: ty::Ty<'tcx>,
+        variant_name: &str,
+    ) -> Option<vir::Expr> {
+        let (adt_def, index, subst) = self.enum_variant(enum_ty, variant_name);
+        let field = adt_def.variants.iter().nth(index).unwrap().fields.get(0)?;
+        let field_ty = field.ty(self.encoder.env().tcx(), subst);
+        if !is_primitive(field_ty) {
+            return None;
+        }
+        let encoded_field = self.encoder.encode_struct_field(&field.ident.as_str(), field_ty);
+        let payload = place.variant(variant_name).field(encoded_field);
+        Some(self.encoder.encode_value_expr(payload, field_ty))
+    }
+
+    fn enum_name(&self, enum_ty: ty::Ty<'tcx>) -> String {
+        std_enum_name(self.encoder.env().tcx(), enum_ty).unwrap()
+    }
+
+    /// The condition under which the call does not panic, with the cause of the panic.
+    pub fn encode_precondition(&self, args: &[vir::Expr]) -> Option<(vir::Expr, PanicCause)> {
+        match self.method {
+            StdMethodKind::VecIndex | StdMethodKind::VecIndexMut => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                let len = SeqFunctionKind::Len.apply(&elem_type, vec![seq]);
+                let condition = vir::Expr::and(
+                    vir::Expr::le_cmp(0.into(), args[1].clone()),
+                    vir::Expr::lt_cmp(args[1].clone(), len),
+                );
+                Some((condition, PanicCause::IndexOutOfBounds))
+            }
+            StdMethodKind::Unwrap => {
+                let enum_ty = self.arg_tys[0];
+                let variant = success_variant(&self.enum_name(enum_ty));
+                let condition = self.encode_is_variant(args[0].clone(), enum_ty, variant);
+                Some((condition, PanicCause::Unwrap))
+            }
+            _ => None,
+        }
+    }
+
+    /// The facts about the result of the call, given the place of the result and the values of
+    /// the arguments before the call.
+    pub fn encode_result(&self, args: &[vir::Expr], target: vir::Expr) -> Vec<ResultFact> {
+        let fact = |place: vir::Expr, value: vir::Expr| ResultFact {
+            guard: true.into(),
+            place,
+            value,
+        };
+        let return_ty = self.return_ty;
+        let target_value = self.encoder.encode_value_expr(target.clone(), return_ty);
+        match self.method {
+            StdMethodKind::VecNew => {
+                let elem_type = seq_elem_type(
+                    vec_elem_type(self.encoder.env().tcx(), return_ty).unwrap(),
+                );
+                let empty = SeqFunctionKind::Empty.apply(&elem_type, vec![]);
+                vec![fact(target_value, empty)]
+            }
+            StdMethodKind::VecLen => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                vec![fact(target_value, SeqFunctionKind::Len.apply(&elem_type, vec![seq]))]
+            }
+            StdMethodKind::VecIsEmpty => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                let len = SeqFunctionKind::Len.apply(&elem_type, vec![seq]);
+                vec![fact(target_value, vir::Expr::eq_cmp(len, 0.into()))]
+            }
+            StdMethodKind::VecIndex | StdMethodKind::VecIndexMut => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                let (elem, _) = self.encode_target_value(target_value, return_ty);
+                let value = SeqFunctionKind::Get.apply(&elem_type, vec![seq, args[1].clone()]);
+                vec![fact(elem, value)]
+            }
+            StdMethodKind::VecPop => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                let len = SeqFunctionKind::Len.apply(&elem_type, vec![seq.clone()]);
+                let is_empty = vir::Expr::eq_cmp(len.clone(), 0.into());
+                let mut facts = vec![fact(
+                    self.encode_discriminant(target.clone(), return_ty),
+                    vir::Expr::ite(
+                        is_empty.clone(),
+                        self.encode_variant_discriminant(return_ty, "None"),
+                        self.encode_variant_discriminant(return_ty, "Some"),
+                    ),
+                )];
+                if let Some(payload) = self.encode_payload(target, return_ty, "Some") {
+                    let last = vir::Expr::sub(len, 1.into());
+                    facts.push(ResultFact {
+                        guard: vir::Expr::not(is_empty),
+                        place: payload,
+                        value: SeqFunctionKind::Get.apply(&elem_type, vec![seq, last]),
+                    });
+                }
+                facts
+            }
+            StdMethodKind::VecPush => vec![],
+            StdMethodKind::IsVariant(variant) => {
+                let (place, enum_ty) = self.encode_target_value(args[0].clone(), self.arg_tys[0]);
+                vec![fact(target_value, self.encode_is_variant(place, enum_ty, variant))]
+            }
+            StdMethodKind::Unwrap | StdMethodKind::UnwrapOr => {
+                let enum_ty = self.arg_tys[0];
+                let variant = success_variant(&self.enum_name(enum_ty));
+                let payload = match self.encode_payload(args[0].clone(), enum_ty, variant) {
+                    Some(payload) => payload,
+                    None => return vec![],
+                };
+                let value = if self.method == StdMethodKind::Unwrap {
+                    payload
+                } else {
+                    vir::Expr::ite(
+                        self.encode_is_variant(args[0].clone(), enum_ty, variant),
+                        payload,
+                        args[1].clone(),
+                    )
+                };
+                vec![fact(target_value, value)]
+            }
+            StdMethodKind::OkOr | StdMethodKind::IntoResult => {
+                let option_ty = self.arg_tys[0];
+                let is_some = self.encode_is_variant(args[0].clone(), option_ty, "Some");
+                let mut facts = vec![fact(
+                    self.encode_discriminant(target.clone(), return_ty),
+                    vir::Expr::ite(
+                        is_some.clone(),
+                        self.encode_variant_discriminant(return_ty, "Ok"),
+                        self.encode_variant_discriminant(return_ty, "Err"),
+                    ),
+                )];
+                let some_payload = self.encode_payload(args[0].clone(), option_ty, "Some");
+                let ok_payload = self.encode_payload(target.clone(), return_ty, "Ok");
+                if let (Some(some_payload), Some(ok_payload)) = (some_payload, ok_payload) {
+                    facts.push(ResultFact {
+                        guard: is_some.clone(),
+                        place: ok_payload,
+                        value: some_payload,
+                    });
+                }
+                if self.method == StdMethodKind::OkOr {
+                    if let Some(err_payload) = self.encode_payload(target, return_ty, "Err") {
+                        facts.push(ResultFact {
+                            guard: vir::Expr::not(is_some),
+                            place: err_payload,
+                            value: args[1].clone(),
+                        });
+                    }
+                }
+                facts
+            }
+            StdMethodKind::FromError | StdMethodKind::FromOk => {
+                let enum_name = self.enum_name(return_ty);
+                let variant = if self.method == StdMethodKind::FromOk {
+                    success_variant(&enum_name)
+                } else {
+                    failure_variant(&enum_name)
+                };
+                let mut facts = vec![fact(
+                    self.encode_discriminant(target.clone(), return_ty),
+                    self.encode_variant_discriminant(return_ty, variant),
+                )];
+                if let Some(payload) = self.encode_payload(target, return_ty, variant) {
+                    facts.push(fact(payload, args[0].clone()));
+                }
+                facts
+            }
+        }
+    }
+
+    /// The new value of the vector passed by mutable reference, given the values of the
+    /// arguments before the call.
+    pub fn encode_updated_self(&self, args: &[vir::Expr]) -> Option<vir::Expr> {
+        match self.method {
+            StdMethodKind::VecPush => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                Some(SeqFunctionKind::Push.apply(&elem_type, vec![seq, args[1].clone()]))
+            }
+            StdMethodKind::VecPop => {
+                let (seq, elem_type) = self.encode_seq_arg(args, 0);
+                let len = SeqFunctionKind::Len.apply(&elem_type, vec![seq.clone()]);
+                Some(vir::Expr::ite(
+                    vir::Expr::eq_cmp(len, 0.into()),
+                    seq.clone(),
+                    SeqFunctionKind::Pop.apply(&elem_type, vec![seq]),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// The place of the vector passed by mutable reference, given the values of the arguments
+    /// after the call.
+    pub fn encode_self_place(&self, args: &[vir::Expr]) -> vir::Expr {
+        self.encode_seq_arg(args, 0).0
+    }
+}
@@ -0,0 +1,464 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Encoding of string slices (`str`) and `String` as sequences of bytes.
+//!
+//! The value of a string is a sequence of the `Seq$Int` domain (see `seq_encoder`), whose
+//! elements are the bytes of the UTF-8 encoding of the string, so `len` is the number of bytes
+//! and not the number of characters. The byte slice (`[u8]`) returned by `s.as_bytes()` is a
+//! view of the same sequence. The `Str` domain adds the operations on strings that vectors do
+//! not have.
+
+use crate::encoder::seq_encoder::{seq_type, SeqFunctionKind};
+use crate::utils::is_item;
+use prusti_common::vir;
+use prusti_common::vir::ExprIterator;
+use rustc_ast::ast;
+use rustc_middle::ty;
+
+pub const STR_DOMAIN_NAME: &str = "Str";
+
+/// The type of the values of strings: sequences of bytes.
+pub fn str_type() -> vir::Type {
+    seq_type(&vir::Type::Int)
+}
+
+/// The field that stores the value of a string.
+pub fn str_value_field() -> vir::Field {
+    vir::Field::new("val_str", str_type())
+}
+
+/// Whether `ty` is a string, i.e. `str` or `String`.
+pub fn is_str_type<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+    match ty.kind {
+        ty::TyKind::Str => true,
+        ty::TyKind::Adt(adt_def, _) => is_item(tcx, adt_def.did, &["std::string::String"]),
+        _ => false,
+    }
+}
+
+/// Whether `ty` is the byte slice `[u8]`.
+pub fn is_byte_slice_type(ty: ty::Ty) -> bool {
+    match ty.kind {
+        ty::TyKind::Slice(elem_ty) => elem_ty.kind == ty::TyKind::Uint(ast::UintTy::U8),
+        _ => false,
+    }
+}
+
+/// Whether values of type `ty` are encoded like strings, i.e. `ty` is a string or a byte slice.
+pub fn is_bytes_type<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+    is_str_type(tcx, ty) || is_byte_slice_type(ty)
+}
+
+/// Whether `ty` is a (possibly nested) reference to a string.
+pub fn is_str_reference<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+    match ty.kind {
+        ty::TyKind::Ref(_, target_ty, _) => {
+            is_str_type(tcx, target_ty) || is_str_reference(tcx, target_ty)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `ty` is encoded like a string or is a (possibly nested) reference to such a type.
+pub fn is_bytes_value_type<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+    match ty.kind {
+        ty::TyKind::Ref(_, target_ty, _) => is_bytes_value_type(tcx, target_ty),
+        _ => is_bytes_type(tcx, ty),
+    }
+}
+
+/// A function of the `Str` domain.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum StrFunctionKind {
+    /// The concatenation of two strings.
+    Concat,
+    /// The bytes between two indices.
+    Slice,
+    /// Whether two strings have the same bytes.
+    Equals,
+    /// Whether a string starts with the bytes of another one.
+    StartsWith,
+}
+
+impl StrFunctionKind {
+    fn name(&self) -> &'static str {
+        match self {
+            StrFunctionKind::Concat => "str$concat",
+            StrFunctionKind::Slice => "str$slice",
+            StrFunctionKind::Equals => "str$equals",
+            StrFunctionKind::StartsWith => "str$starts_with",
+        }
+    }
+
+    fn arg_types(&self) -> Vec<vir::Type> {
+        match self {
+            StrFunctionKind::Concat | StrFunctionKind::Equals | StrFunctionKind::StartsWith => {
+                vec![str_type(), str_type()]
+            }
+            StrFunctionKind::Slice => vec![str_type(), vir::Type::Int, vir::Type::Int],
+        }
+    }
+
+    fn return_type(&self) -> vir::Type {
+        match self {
+            StrFunctionKind::Concat | StrFunctionKind::Slice => str_type(),
+            StrFunctionKind::Equals | StrFunctionKind::StartsWith => vir::Type::Bool,
+        }
+    }
+
+    fn domain_func(&self) -> vir::DomainFunc {
+        vir::DomainFunc {
+            name: self.name().to_string(),
+            formal_args: self
+                .arg_types()
+                .into_iter()
+                .enumerate()
+                .map(|(index, typ)| vir::LocalVar::new(format!("_{}", index), typ))
+                .collect(),
+            return_type: self.return_type(),
+            unique: false,
+            domain_name: STR_DOMAIN_NAME.to_string(),
+        }
+    }
+
+    pub fn apply(&self, args: Vec<vir::Expr>) -> vir::Expr {
+        assert_eq!(args.len(), self.arg_types().len());
+        vir::Expr::DomainFuncApp(self.domain_func(), args, vir::Position::default())
+    }
+}
+
+/// The number of bytes of a string or a byte slice `s`.
+pub fn len(s: vir::Expr) -> vir::Expr {
+    SeqFunctionKind::Len.apply(&vir::Type::Int, vec![s])
+}
+
+/// The byte at `index` of a string or a byte slice `s`, e.g. `s.as_bytes()[index]`.
+pub fn byte(s: vir::Expr, index: vir::Expr) -> vir::Expr {
+    SeqFunctionKind::Get.apply(&vir::Type::Int, vec![s, index])
+}
+
+/// The value of a string literal.
+pub fn encode_str_literal(bytes: &[u8]) -> vir::Expr {
+    bytes.iter().fold(
+        SeqFunctionKind::Empty.apply(&vir::Type::Int, vec![]),
+        |s, &b| SeqFunctionKind::Push.apply(&vir::Type::Int, vec![s, (b as u32).into()]),
+    )
+}
+
+/// Whether `index` is on a char boundary of `s`, i.e. it is either the length of the string or
+/// the index of a byte that is not a continuation byte (`0b10xx_xxxx`) of the UTF-8 encoding.
+fn encode_is_char_boundary(s: vir::Expr, index: vir::Expr) -> vir::Expr {
+    let is_continuation_byte = vir::Expr::and(
+        vir::Expr::le_cmp(128.into(), byte(s.clone(), index.clone())),
+        vir::Expr::lt_cmp(byte(s.clone(), index.clone()), 192.into()),
+    );
+    vir::Expr::or(
+        vir::Expr::eq_cmp(index.clone(), len(s.clone())),
+        vir::Expr::and(
+            vir::Expr::and(
+                vir::Expr::le_cmp(0.into(), index.clone()),
+                vir::Expr::lt_cmp(index, len(s)),
+            ),
+            vir::Expr::not(is_continuation_byte),
+        ),
+    )
+}
+
+fn var(name: &str, typ: vir::Type) -> (vir::LocalVar, vir::Expr) {
+    let local = vir::LocalVar::new(name, typ);
+    (local.clone(), local.into())
+}
+
+fn axiom(name: &str, expr: vir::Expr) -> vir::DomainAxiom {
+    vir::DomainAxiom {
+        name: format!("str${}", name),
+        expr,
+        domain_name: STR_DOMAIN_NAME.to_string(),
+    }
+}
+
+/// `0 <= index && index < upper`
+fn in_range(index: vir::Expr, upper: vir::Expr) -> vir::Expr {
+    vir::Expr::and(
+        vir::Expr::le_cmp(0.into(), index.clone()),
+        vir::Expr::lt_cmp(index, upper),
+    )
+}
+
+pub fn encode_str_domain() -> vir::Domain {
+    let (s_var, s) = var("s", str_type());
+    let (t_var, t) = var("t", str_type());
+    let (i_var, i) = var("i", vir::Type::Int);
+    let (lo_var, lo) = var("lo", vir::Type::Int);
+    let (hi_var, hi) = var("hi", vir::Type::Int);
+
+    let concat = StrFunctionKind::Concat.apply(vec![s.clone(), t.clone()]);
+    let slice = StrFunctionKind::Slice.apply(vec![s.clone(), lo.clone(), hi.clone()]);
+    let equals = StrFunctionKind::Equals.apply(vec![s.clone(), t.clone()]);
+    let starts_with = StrFunctionKind::StartsWith.apply(vec![s.clone(), t.clone()]);
+
+    // The bytes of `s` and `t` are the same up to `upper`.
+    let same_bytes = |upper: vir::Expr| {
+        vir::Expr::forall(
+            vec![i_var.clone()],
+            vec![
+                vir::Trigger::new(vec![byte(s.clone(), i.clone())]),
+                vir::Trigger::new(vec![byte(t.clone(), i.clone())]),
+            ],
+            vir::Expr::implies(
+                in_range(i.clone(), upper),
+                vir::Expr::eq_cmp(byte(s.clone(), i.clone()), byte(t.clone(), i.clone())),
+            ),
+        )
+    };
+    let slice_is_valid = vec![
+        vir::Expr::le_cmp(0.into(), lo.clone()),
+        vir::Expr::le_cmp(lo.clone(), hi.clone()),
+        vir::Expr::le_cmp(hi.clone(), len(s.clone())),
+    ]
+    .into_iter()
+    .conjoin();
+
+    let axioms = vec![
+        axiom(
+            "concat",
+            vir::Expr::forall(
+                vec![s_var.clone(), t_var.clone()],
+                vec![vir::Trigger::new(vec![concat.clone()])],
+                vir::Expr::eq_cmp(
+                    len(concat.clone()),
+                    vir::Expr::add(len(s.clone()), len(t.clone())),
+                ),
+            ),
+        ),
+        axiom(
+            "concat_bytes",
+            vir::Expr::forall(
+                vec![s_var.clone(), t_var.clone(), i_var.clone()],
+                vec![vir::Trigger::new(vec![byte(concat.clone(), i.clone())])],
+                vir::Expr::eq_cmp(
+                    byte(concat.clone(), i.clone()),
+                    vir::Expr::ite(
+                        vir::Expr::lt_cmp(i.clone(), len(s.clone())),
+                        byte(s.clone(), i.clone()),
+                        byte(t.clone(), vir::Expr::sub(i.clone(), len(s.clone()))),
+                    ),
+                ),
+            ),
+        ),
+        axiom(
+            "slice",
+            vir::Expr::forall(
+                vec![s_var.clone(), lo_var.clone(), hi_var.clone()],
+                vec![vir::Trigger::new(vec![slice.clone()])],
+                vir::Expr::implies(
+                    slice_is_valid.clone(),
+                    vir::Expr::eq_cmp(len(slice.clone()), vir::Expr::sub(hi.clone(), lo.clone())),
+                ),
+            ),
+        ),
+        axiom(
+            "slice_bytes",
+            vir::Expr::forall(
+                vec![s_var.clone(), lo_var.clone(), hi_var.clone(), i_var.clone()],
+                vec![vir::Trigger::new(vec![byte(slice.clone(), i.clone())])],
+                vir::Expr::implies(
+                    vir::Expr::and(
+                        slice_is_valid,
+                        in_range(i.clone(), vir::Expr::sub(hi.clone(), lo.clone())),
+                    ),
+                    vir::Expr::eq_cmp(
+                        byte(slice, i.clone()),
+                        byte(s.clone(), vir::Expr::add(lo, i.clone())),
+                    ),
+                ),
+            ),
+        ),
+        axiom(
+            "equals",
+            vir::Expr::forall(
+                vec![s_var.clone(), t_var.clone()],
+                vec![vir::Trigger::new(vec![equals.clone()])],
+                vir::Expr::eq_cmp(
+                    equals,
+                    vir::Expr::and(
+                        vir::Expr::eq_cmp(len(s.clone()), len(t.clone())),
+                        same_bytes(len(s.clone())),
+                    ),
+                ),
+            ),
+        ),
+        axiom(
+            "starts_with",
+            vir::Expr::forall(
+                vec![s_var, t_var],
+                vec![vir::Trigger::new(vec![starts_with.clone()])],
+                vir::Expr::eq_cmp(
+                    starts_with,
+                    vir::Expr::and(
+                        vir::Expr::le_cmp(len(t.clone()), len(s.clone())),
+                        same_bytes(len(t.clone())),
+                    ),
+                ),
+            ),
+        ),
+    ];
+
+    vir::Domain {
+        name: STR_DOMAIN_NAME.to_string(),
+        functions: vec![
+            StrFunctionKind::Concat,
+            StrFunctionKind::Slice,
+            StrFunctionKind::Equals,
+            StrFunctionKind::StartsWith,
+        ]
+        .iter()
+        .map(|function| function.domain_func())
+        .collect(),
+        axioms,
+        type_vars: vec![],
+    }
+}
+
+/// A method of `str` or `String` whose behaviour is encoded natively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrMethodKind {
+    /// `s.len()`
+    Len,
+    /// `s.is_empty()`
+    IsEmpty,
+    /// `s.starts_with(t)` with a string pattern
+    StartsWith,
+    /// `s == t`
+    Eq,
+    /// `s != t`
+    Ne,
+    /// A method that returns the same bytes in another form, e.g. `s.as_bytes()`, `s.as_str()`
+    /// or `s.to_string()`.
+    View,
+    /// `String::new()`
+    New,
+    /// `s + t`
+    Concat,
+    /// `s.push_str(t)`
+    PushStr,
+    /// `&s[lo..hi]`
+    Slice,
+}
+
+impl StrMethodKind {
+    /// Recognise a call of the method `name` with arguments of types `arg_tys`.
+    pub fn from_call<'tcx>(
+        tcx: ty::TyCtxt<'tcx>,
+        name: &str,
+        arg_tys: &[ty::Ty<'tcx>],
+    ) -> Option<Self> {
+        let is_str_arg = |index: usize| {
+            arg_tys
+                .get(index)
+                .map(|&arg_ty| is_str_reference(tcx, arg_ty))
+                .unwrap_or(false)
+        };
+        let method_name = [
+            "core::str::<impl str>::",
+            "std::str::<impl str>::",
+            "std::string::String::",
+            "alloc::string::String::",
+        ]
+        .iter()
+        .filter(|prefix| name.starts_with(*prefix))
+        .map(|prefix| &name[prefix.len()..])
+        .next();
+        if let Some(method_name) = method_name {
+            return match method_name {
+                "len" if is_str_arg(0) => Some(StrMethodKind::Len),
+                "is_empty" if is_str_arg(0) => Some(StrMethodKind::IsEmpty),
+                "as_bytes" | "as_str" if is_str_arg(0) => Some(StrMethodKind::View),
+                "starts_with" if is_str_arg(0) && is_str_arg(1) => Some(StrMethodKind::StartsWith),
+                "new" if arg_tys.is_empty() => Some(StrMethodKind::New),
+                "push_str" if is_str_arg(0) && is_str_arg(1) => Some(StrMethodKind::PushStr),
+                _ => None,
+            };
+        }
+        match name {
+            "std::cmp::PartialEq::eq" | "core::cmp::PartialEq::eq"
+                if is_str_arg(0) && is_str_arg(1) => Some(StrMethodKind::Eq),
+            "std::cmp::PartialEq::ne" | "core::cmp::PartialEq::ne"
+                if is_str_arg(0) && is_str_arg(1) => Some(StrMethodKind::Ne),
+            "std::ops::Deref::deref" | "core::ops::Deref::deref"
+            | "std::clone::Clone::clone" | "core::clone::Clone::clone"
+            | "std::string::ToString::to_string" | "alloc::string::ToString::to_string"
+                if is_str_arg(0) => Some(StrMethodKind::View),
+            "std::convert::From::from" | "core::convert::From::from"
+                if arg_tys.len() == 1 && is_str_arg(0) => Some(StrMethodKind::View),
+            "std::ops::Add::add" | "core::ops::Add::add"
+                if arg_tys.len() == 2 && is_str_type(tcx, arg_tys[0]) && is_str_arg(1) =>
+            {
+                Some(StrMethodKind::Concat)
+            }
+            "std::ops::Index::index" | "core::ops::Index::index" if is_str_arg(0) => {
+                match arg_tys[1].kind {
+                    ty::TyKind::Adt(adt_def, _)
+                        if is_item(tcx, adt_def.did, &["std::ops::Range", "core::ops::Range"]) =>
+                    {
+                        Some(StrMethodKind::Slice)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The condition under which the method does not panic.
+    ///
+    /// `args` are the values of the arguments, where the range of a slicing is given by two
+    /// integers.
+    pub fn encode_precondition(&self, args: &[vir::Expr]) -> Option<vir::Expr> {
+        match self {
+            StrMethodKind::Slice => {
+                let (s, lo, hi) = (&args[0], &args[1], &args[2]);
+                Some(
+                    vec![
+                        vir::Expr::le_cmp(lo.clone(), hi.clone()),
+                        vir::Expr::le_cmp(hi.clone(), len(s.clone())),
+                        encode_is_char_boundary(s.clone(), lo.clone()),
+                        encode_is_char_boundary(s.clone(), hi.clone()),
+                    ]
+                    .into_iter()
+                    .conjoin(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// The value returned by the method, if any.
+    pub fn encode_result(&self, args: &[vir::Expr]) -> Option<vir::Expr> {
+        let args = args.to_vec();
+        match self {
+            StrMethodKind::Len => Some(len(args[0].clone())),
+            StrMethodKind::IsEmpty => Some(vir::Expr::eq_cmp(len(args[0].clone()), 0.into())),
+            StrMethodKind::StartsWith => Some(StrFunctionKind::StartsWith.apply(args)),
+            StrMethodKind::Eq => Some(StrFunctionKind::Equals.apply(args)),
+            StrMethodKind::Ne => Some(vir::Expr::not(StrFunctionKind::Equals.apply(args))),
+            StrMethodKind::View => Some(args[0].clone()),
+            StrMethodKind::New => Some(SeqFunctionKind::Empty.apply(&vir::Type::Int, args)),
+            StrMethodKind::Concat => Some(StrFunctionKind::Concat.apply(args)),
+            StrMethodKind::Slice => Some(StrFunctionKind::Slice.apply(args)),
+            StrMethodKind::PushStr => None,
+        }
+    }
+
+    /// The new value of the string passed by mutable reference as first argument, if any.
+    pub fn encode_updated_self(&self, args: &[vir::Expr]) -> Option<vir::Expr> {
+        match self {
+            StrMethodKind::PushStr => Some(StrFunctionKind::Concat.apply(args.to_vec())),
+            _ => None,
+        }
+    }
+}
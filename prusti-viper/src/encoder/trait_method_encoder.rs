@@ -0,0 +1,183 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::encoder::borrows::compute_procedure_contract;
+use crate::encoder::errors::{EncodingError, ErrorCtxt};
+use crate::encoder::foldunfold;
+use crate::encoder::mir_encoder::{FakeMirEncoder, PlaceEncoder};
+use crate::encoder::snapshot_spec_patcher::SnapshotSpecPatcher;
+use crate::encoder::spec_encoder::SpecEncoder;
+use crate::encoder::Encoder;
+use prusti_common::vir;
+use prusti_interface::specs::typed;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir;
+use rustc_middle::ty;
+use log::{debug, trace};
+
+/// Encodes a trait method that has no body (e.g. `PartialOrd::lt` or a `#[pure]` method declared
+/// in a trait) as a body-less pure function, whose contract is the one of the trait declaration.
+///
+/// The function is specific to the type that implements the trait (the `Self` type), which is
+/// usually a type parameter bounded by the trait. This allows generic code to use the methods of
+/// its trait bounds in specifications.
+pub struct TraitMethodEncoder<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    proc_def_id: DefId,
+    self_ty: ty::Ty<'tcx>,
+    arg_tys: Vec<ty::Ty<'tcx>>,
+    return_ty: ty::Ty<'tcx>,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> TraitMethodEncoder<'p, 'v, 'tcx> {
+    pub fn new(encoder: &'p Encoder<'v, 'tcx>, proc_def_id: DefId) -> Self {
+        trace!("TraitMethodEncoder constructor: {:?}", proc_def_id);
+        let tcx = encoder.env().tcx();
+        let self_ty = ty::List::identity_for_item(tcx, proc_def_id).type_at(0);
+        // The encoding does not depend on regions, so the late-bound ones can be erased.
+        let fn_sig = tcx.erase_late_bound_regions(&tcx.fn_sig(proc_def_id));
+        let arg_tys = fn_sig
+            .inputs()
+            .iter()
+            .map(|arg_ty| encoder.resolve_typaram(arg_ty))
+            .collect();
+        let return_ty = encoder.resolve_typaram(fn_sig.output());
+        TraitMethodEncoder {
+            encoder,
+            proc_def_id,
+            self_ty: encoder.resolve_typaram(self_ty),
+            arg_tys,
+            return_ty,
+        }
+    }
+
+    pub fn encode_function_name(&self) -> String {
+        format!(
+            "{}${}",
+            self.encoder.encode_item_name(self.proc_def_id),
+            // will panic if attempting to encode unsupported type
+            self.encoder.encode_type_predicate_use(self.self_ty).unwrap()
+        )
+    }
+
+    pub fn encode_function_return_type(&self) -> vir::Type {
+        self.encoder.encode_value_type(self.return_ty)
+    }
+
+    fn encode_formal_args(&self) -> Vec<vir::LocalVar> {
+        let mir_encoder = FakeMirEncoder::new(self.encoder, self.arg_tys.clone(), None);
+        self.arg_tys
+            .iter()
+            .enumerate()
+            .map(|(index, &arg_ty)| {
+                let local = mir::Local::from_usize(index + 1);
+                vir::LocalVar::new(
+                    mir_encoder.encode_local_var_name(local),
+                    self.encoder.encode_value_or_ref_type(arg_ty),
+                )
+            })
+            .collect()
+    }
+
+    /// Encode a function with a `false` precondition, to be used when the contract of the
+    /// method cannot be encoded.
+    pub fn encode_stub_function(&self) -> vir::Function {
+        vir::Function {
+            name: self.encode_function_name(),
+            formal_args: self.encode_formal_args(),
+            return_type: self.encode_function_return_type(),
+            pres: vec![false.into()],
+            posts: vec![],
+            body: None,
+        }
+    }
+
+    pub fn encode_function(&self) -> Result<vir::Function, EncodingError> {
+        let function_name = self.encode_function_name();
+        debug!("Encode trait method function {}", function_name);
+
+        let formal_args = self.encode_formal_args();
+        let return_type = self.encode_function_return_type();
+        let encoded_args: Vec<vir::Expr> = formal_args.iter().cloned().map(|arg| arg.into()).collect();
+        let encoded_return: vir::Expr = vir::LocalVar::new("__result", return_type.clone()).into();
+
+        let fun_spec = match self.encoder.get_spec_by_def_id(self.proc_def_id) {
+            Some(fun_spec) => fun_spec.clone(),
+            None => {
+                debug!("Procedure {:?} has no specification", self.proc_def_id);
+                typed::SpecificationSet::Procedure(typed::ProcedureSpecification::empty())
+            }
+        };
+        let tymap = self.encoder.current_tymap();
+        let contract = compute_procedure_contract(
+            self.proc_def_id,
+            self.encoder.env().tcx(),
+            fun_spec,
+            Some(&tymap),
+        )
+        .to_def_site_contract();
+
+        // The arguments passed by reference have to be framed.
+        let mut pres: Vec<vir::Expr> = formal_args
+            .iter()
+            .filter(|arg| arg.typ.is_ref())
+            .flat_map(|arg| {
+                vir::Expr::pred_permission(arg.clone().into(), vir::PermAmount::Read)
+            })
+            .collect();
+        pres.extend(contract.functional_precondition().iter().map(|assertion| {
+            self.encode_assertion(assertion, &encoded_args, None)
+        }));
+        let posts: Vec<vir::Expr> = contract
+            .functional_postcondition()
+            .iter()
+            .map(|assertion| self.encode_assertion(assertion, &encoded_args, Some(&encoded_return)))
+            .collect();
+
+        let function = vir::Function {
+            name: function_name,
+            formal_args,
+            return_type,
+            pres,
+            posts,
+            body: None,
+        };
+
+        self.encoder
+            .log_vir_program_before_foldunfold(function.to_string());
+
+        // Add folding/unfolding
+        foldunfold::add_folding_unfolding_to_function(
+            function,
+            self.encoder.get_used_viper_predicates_map(),
+        )
+        .map_err(|foldunfold_error| {
+            EncodingError::internal(
+                format!(
+                    "generating fold-unfold Viper expressions failed ({:?})",
+                    foldunfold_error
+                ),
+                self.encoder.env().tcx().def_span(self.proc_def_id),
+            )
+        })
+    }
+
+    fn encode_assertion(
+        &self,
+        assertion: &typed::Assertion<'tcx>,
+        encoded_args: &[vir::Expr],
+        encoded_return: Option<&vir::Expr>,
+    ) -> vir::Expr {
+        let spec_encoder =
+            SpecEncoder::new_without_mir(self.encoder, encoded_args, encoded_return, true);
+        let pos = self.encoder.error_manager().register(
+            typed::Spanned::get_spans(assertion, self.encoder.env().tcx()),
+            ErrorCtxt::GenericExpression,
+        );
+        let encoded_assertion = spec_encoder.encode_assertion(assertion).set_default_pos(pos);
+        SnapshotSpecPatcher::new(self.encoder).patch_spec(encoded_assertion)
+    }
+}
@@ -5,7 +5,11 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::encoder::foldunfold;
+use crate::encoder::mir_encoder::MirEncoder;
 use crate::encoder::spec_encoder::SpecEncoder;
+use crate::encoder::seq_encoder;
+use crate::encoder::std_encoder::{self, vec_elem_type};
+use crate::encoder::str_encoder::{self, is_byte_slice_type, is_bytes_type};
 // use crate::encoder::utils::range_extract;
 // use crate::encoder::utils::PlusOne;
 use crate::encoder::Encoder;
@@ -37,18 +41,32 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
         TypeEncoder { encoder, ty }
     }
 
+    /// Is this type a string or a byte slice, whose value is a sequence of bytes?
+    fn is_str(&self) -> bool {
+        is_bytes_type(self.encoder.env().tcx(), self.ty)
+    }
+
+    /// The type of the values of the elements, if this type is a vector modelled as a sequence.
+    fn seq_elem_type(&self) -> Option<vir::Type> {
+        vec_elem_type(self.encoder.env().tcx(), self.ty).map(std_encoder::seq_elem_type)
+    }
+
     /// Is this type supported?
     fn is_supported_type(&self, ty: ty::Ty<'tcx>) -> bool {
         match ty.kind {
+            ty::TyKind::Str => true,
+            ty::TyKind::Slice(_) => is_byte_slice_type(ty),
             ty::TyKind::Bool
             | ty::TyKind::Int(_)
             | ty::TyKind::Uint(_)
             | ty::TyKind::Char
             | ty::TyKind::Ref(_, _, _)
+            | ty::TyKind::RawPtr(_)
             | ty::TyKind::Adt(_, _)
             | ty::TyKind::Tuple(_)
             | ty::TyKind::Never
-            | ty::TyKind::Param(_) => true,
+            | ty::TyKind::Param(_)
+            | ty::TyKind::Dynamic(..) => true,
             _ => false,
         }
     }
@@ -106,13 +124,20 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
     pub fn encode_value_type(self) -> vir::Type {
         debug!("Encode value type '{:?}'", self.ty);
         match self.ty.kind {
+            _ if self.is_str() => str_encoder::str_type(),
+
+            _ if self.seq_elem_type().is_some() => {
+                seq_encoder::seq_type(&self.seq_elem_type().unwrap())
+            }
+
             ty::TyKind::Bool => vir::Type::Bool,
 
             ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {
                 vir::Type::Int
             }
 
-            ty::TyKind::Ref(_, ref ty, _) => {
+            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. })
+            | ty::TyKind::Ref(_, ref ty, _) => {
                 // will panic if attempting to encode unsupported type
                 let type_name = self.encoder.encode_type_predicate_use(ty).unwrap();
                 vir::Type::TypedRef(type_name)
@@ -127,10 +152,6 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 }
             },
 
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
-                unimplemented!("Raw pointers are unsupported. (ty={:?})", ty);
-            }
-
             ref x => unimplemented!("{:?}", x),
         }
     }
@@ -141,6 +162,12 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
     pub fn encode_value_or_ref_type(self) -> vir::Type {
         debug!("Encode ref value type '{:?}'", self.ty);
         match self.ty.kind {
+            // Strings and vectors have no snapshot, their value is stored in a field.
+            ty::TyKind::Adt(_, _) if self.is_str() || self.seq_elem_type().is_some() => {
+                let type_name = self.encoder.encode_type_predicate_use(self.ty).ok().unwrap();
+                vir::Type::TypedRef(type_name)
+            }
+
             ty::TyKind::Adt(_, _)
             | ty::TyKind::Tuple(_) => {
                 let snapshot = self.encoder.encode_snapshot(&self.ty);
@@ -159,13 +186,20 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
     pub fn encode_value_field(self) -> vir::Field {
         trace!("Encode value field for type '{:?}'", self.ty);
         match self.ty.kind {
+            _ if self.is_str() => str_encoder::str_value_field(),
+
+            _ if self.seq_elem_type().is_some() => {
+                std_encoder::seq_value_field(&self.seq_elem_type().unwrap())
+            }
+
             ty::TyKind::Bool => vir::Field::new("val_bool", vir::Type::Bool),
 
             ty::TyKind::Int(_) | ty::TyKind::Uint(_) | ty::TyKind::Char => {
                 vir::Field::new("val_int", vir::Type::Int)
             }
 
-            ty::TyKind::Ref(_, ref ty, _) => {
+            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. })
+            | ty::TyKind::Ref(_, ref ty, _) => {
                 // will panic if attempting to encode unsupported type
                 let type_name = self.encoder.encode_type_predicate_use(ty).unwrap();
                 vir::Field::new("val_ref", vir::Type::TypedRef(type_name))
@@ -180,10 +214,6 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 vir::Field::new("val_ref", vir::Type::TypedRef(type_name))
             }
 
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
-                unimplemented!("Raw pointers are unsupported. (ty={:?})", ty);
-            }
-
             ref x => unimplemented!("{:?}", x),
         }
     }
@@ -197,7 +227,10 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                     ast::IntTy::I32 => (std::i32::MIN.into(), std::i32::MAX.into()),
                     ast::IntTy::I64 => (std::i64::MIN.into(), std::i64::MAX.into()),
                     ast::IntTy::I128 => (std::i128::MIN.into(), std::i128::MAX.into()),
-                    ast::IntTy::Isize => (std::isize::MIN.into(), std::isize::MAX.into()),
+                    ast::IntTy::Isize => {
+                        let int_kind = MirEncoder::encode_integer_kind(self.ty).unwrap();
+                        (int_kind.min_value(), int_kind.max_value())
+                    }
                 };
                 Some(bounds)
             }
@@ -208,7 +241,10 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                     ast::UintTy::U32 => (0.into(), std::u32::MAX.into()),
                     ast::UintTy::U64 => (0.into(), std::u64::MAX.into()),
                     ast::UintTy::U128 => (0.into(), std::u128::MAX.into()),
-                    ast::UintTy::Usize => (0.into(), std::usize::MAX.into()),
+                    ast::UintTy::Usize => {
+                        let int_kind = MirEncoder::encode_integer_kind(self.ty).unwrap();
+                        (int_kind.min_value(), int_kind.max_value())
+                    }
                 };
                 Some(bounds)
             }
@@ -217,6 +253,7 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 Some((0.into(), 0xFFFFFFFFu32.into()))
             }
             ty::TyKind::Bool | ty::TyKind::Ref(_, _, _) => None,
+            _ if self.is_str() || self.seq_elem_type().is_some() => None,
             ref x => unreachable!("{:?}", x),
         }
     }
@@ -239,6 +276,14 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
         let typ = vir::Type::TypedRef(predicate_name.clone());
 
         match self.ty.kind {
+            // The bytes of a string and the elements of a vector are stored in a single field.
+            _ if self.is_str() || self.seq_elem_type().is_some() => vec![vir::Predicate::new_primitive_value(
+                typ,
+                self.encoder.encode_value_field(self.ty),
+                None,
+                false,
+            )],
+
             ty::TyKind::Bool => vec![vir::Predicate::new_primitive_value(
                 typ,
                 self.encoder.encode_value_field(self.ty),
@@ -265,8 +310,15 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 )]
             }
 
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
-                unimplemented!("Raw pointers are unsupported. (ty={:?})", ty);
+            ty::TyKind::RawPtr(_) => {
+                // A raw pointer does not own its target: the permission to access the target
+                // has to be stated explicitly in the specifications, using `acc` or `own`.
+                vec![vir::Predicate::new_primitive_value(
+                    typ,
+                    self.encoder.encode_value_field(self.ty),
+                    None,
+                    false,
+                )]
             }
 
             ty::TyKind::Ref(_, ref ty, _) => vec![vir::Predicate::new_struct(
@@ -382,6 +434,12 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 vec![vir::Predicate::new_abstract(typ)]
             }
 
+            ty::TyKind::Dynamic(..) => {
+                // The concrete type of a trait object is unknown, so its content can only be
+                // accessed through the methods of the trait.
+                vec![vir::Predicate::new_abstract(typ)]
+            }
+
             ref ty_variant => {
                 debug!("Encoding of type '{:?}' is incomplete", ty_variant);
                 vec![vir::Predicate::new_abstract(typ)]
@@ -411,11 +469,14 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
 
             ty::TyKind::Char => "char".to_string(),
 
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. })
-            | ty::TyKind::Ref(_, ref ty, _) => {
+            ty::TyKind::Ref(_, ref ty, _) => {
                 format!("ref${}", self.encoder.encode_type_predicate_use(ty)?)
             }
 
+            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. }) => {
+                format!("ptr${}", self.encoder.encode_type_predicate_use(ty)?)
+            }
+
             ty::TyKind::Adt(adt_def, subst) => {
                 let mut composed_name = vec![self.encoder.encode_item_name(adt_def.did)];
                 composed_name.push("_beg_".to_string()); // makes generics "less fragile"
@@ -486,6 +547,26 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                 format!("__TYPARAM__${}$__", param_ty.name.as_str())
             }
 
+            ty::TyKind::Dynamic(preds, _) => {
+                match preds.principal() {
+                    Some(principal) => {
+                        let trait_ref = principal.skip_binder();
+                        let mut composed_name = vec![
+                            "dyn".to_string(),
+                            self.encoder.encode_item_name(trait_ref.def_id),
+                        ];
+                        for kind in trait_ref.substs.iter() {
+                            if let ty::subst::GenericArgKind::Type(ty) = kind.unpack() {
+                                composed_name.push(self.encoder.encode_type_predicate_use(ty)?)
+                            }
+                        }
+                        composed_name.join("$")
+                    }
+                    // Only auto traits, e.g. `dyn Send`
+                    None => "dyn$auto".to_string(),
+                }
+            }
+
             ref x => {
                 debug!("Unimplemented! {:?}", x);
                 return Err(ErrorCtxt::Panic(Unimplemented));
@@ -505,8 +586,9 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
         let invariant_name = self.encoder.encode_type_invariant_use(self.ty);
 
         let field_invariants = match self.ty.kind {
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. })
-            | ty::TyKind::Ref(_, ref ty, _) => {
+            _ if self.is_str() || self.seq_elem_type().is_some() => Some(vec![]),
+
+            ty::TyKind::Ref(_, ref ty, _) => {
                 let elem_field = self.encoder.encode_dereference_field(ty);
                 let elem_loc = vir::Expr::from(self_local_var.clone()).field(elem_field);
                 Some(vec![self.encoder.encode_invariant_func_app(ty, elem_loc)])
@@ -611,8 +693,7 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
         };
 
         let precondition = match self.ty.kind {
-            ty::TyKind::RawPtr(ty::TypeAndMut { ref ty, .. })
-            | ty::TyKind::Ref(_, ref ty, _) => {
+            ty::TyKind::Ref(_, ref ty, _) => {
                 // This is a reference, so we need to have it already unfolded.
                 let elem_field = self.encoder.encode_dereference_field(ty);
                 let elem_loc = vir::Expr::from(self_local_var.clone()).field(elem_field);
@@ -0,0 +1,435 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Incremental verification.
+//!
+//! For each procedure that has been verified successfully we persist (under `LOG_DIR`) its
+//! fingerprint, together with the fingerprints of the items its encoding depended on (callee
+//! contracts, pure functions and types). In the next run, a procedure whose fingerprint and
+//! whose dependencies' fingerprints did not change is neither encoded nor verified again.
+//!
+//! Fingerprints are computed from the source code of the item, its specification, its MIR, its
+//! verifier settings (see `VerifierSettings`) and its `#[skip]`/`#[verify]` selection, together
+//! with the Prusti configuration and version. The dependencies of a procedure are closed
+//! transitively, so that changing e.g. a callee of a pure function also causes a
+//! re-verification. Fingerprints are conservative: some changes (e.g. moving a closure) cause a
+//! re-verification even if the semantics of the procedure did not change.
+
+use crate::encoder::Encoder;
+use crate::verifier_settings::VerifierSettings;
+use prusti_common::config;
+use prusti_interface::data::ProcedureDefId;
+use prusti_interface::environment::Environment;
+use prusti_interface::specs::typed::{self, Spanned};
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::mir;
+use rustc_middle::ty;
+use serde::{Deserialize, Serialize};
+use siphasher::sip::SipHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use ::log::{debug, error};
+
+/// The persisted dependency graph of the procedures of a crate.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    /// The procedures that have been verified successfully, indexed by def path.
+    procedures: HashMap<String, ProcedureEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ProcedureEntry {
+    fingerprint: u64,
+    /// The fingerprints of the transitive dependencies, indexed by def path.
+    dependencies: BTreeMap<String, u64>,
+}
+
+impl ProcedureEntry {
+    /// Whether the fingerprints of the procedure and of its dependencies are the recorded ones.
+    /// `dependency_fingerprint` returns `None` for items that no longer exist.
+    fn is_unchanged<F>(
+        &self,
+        def_path: &str,
+        fingerprint: u64,
+        mut dependency_fingerprint: F,
+    ) -> bool
+    where
+        F: FnMut(&str) -> Option<u64>,
+    {
+        if self.fingerprint != fingerprint {
+            debug!("Procedure {} changed", def_path);
+            return false;
+        }
+        for (dependency, &fingerprint) in &self.dependencies {
+            if dependency_fingerprint(dependency) != Some(fingerprint) {
+                debug!("Dependency {} of procedure {} changed", dependency, def_path);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The items reachable from `root` (excluding `root` itself, unless it is part of a cycle) by
+/// following `direct_dependencies`.
+pub fn transitive_dependencies<T, F>(root: T, mut direct_dependencies: F) -> HashSet<T>
+where
+    T: Copy + Eq + Hash,
+    F: FnMut(T) -> HashSet<T>,
+{
+    let mut result = HashSet::new();
+    let mut to_visit = vec![root];
+    while let Some(item) = to_visit.pop() {
+        for dependency in direct_dependencies(item) {
+            if result.insert(dependency) {
+                to_visit.push(dependency);
+            }
+        }
+    }
+    result
+}
+
+impl DependencyGraph {
+    fn path(crate_name: &str) -> PathBuf {
+        PathBuf::from(config::log_dir())
+            .join("incremental")
+            .join(format!("{}.json", crate_name))
+    }
+
+    /// Load the dependency graph of the last run, or an empty one if there is none.
+    pub fn load(crate_name: &str) -> Self {
+        let path = Self::path(crate_name);
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(graph) => Some(graph),
+                Err(err) => {
+                    debug!("Ignoring invalid dependency graph '{:?}': {}", path, err);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, crate_name: &str) {
+        let path = Self::path(crate_name);
+        let result = fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| fs::write(&path, serde_json::to_string_pretty(self).unwrap()));
+        if let Err(err) = result {
+            error!("Failed to write the dependency graph to '{:?}': {}", path, err);
+        }
+    }
+
+    /// Whether the procedure has been verified successfully in the last run, and neither it
+    /// nor any of its dependencies changed since then.
+    pub fn is_unchanged(&self, fingerprints: &mut Fingerprints, proc_def_id: ProcedureDefId) -> bool {
+        let def_path = fingerprints.env.get_item_def_path(proc_def_id);
+        let entry = match self.procedures.get(&def_path) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        let fingerprint = fingerprints.of_item(proc_def_id);
+        entry.is_unchanged(&def_path, fingerprint, |dependency| {
+            fingerprints.of_def_path(dependency)
+        })
+    }
+
+    /// Record that the procedure has been verified successfully, together with the transitive
+    /// dependencies of its encoding.
+    pub fn set_verified(&mut self, fingerprints: &mut Fingerprints, proc_def_id: ProcedureDefId) {
+        let env = fingerprints.env;
+        let encoder = fingerprints.encoder;
+        let dependencies = transitive_dependencies(proc_def_id, |def_id| {
+            encoder.get_dependencies(def_id)
+        });
+        let entry = ProcedureEntry {
+            fingerprint: fingerprints.of_item(proc_def_id),
+            dependencies: dependencies
+                .into_iter()
+                .filter(|&def_id| def_id != proc_def_id)
+                .map(|def_id| (env.get_item_def_path(def_id), fingerprints.of_item(def_id)))
+                .collect(),
+        };
+        self.procedures.insert(env.get_item_def_path(proc_def_id), entry);
+    }
+
+    /// Record that the procedure has not been verified successfully.
+    pub fn set_not_verified(&mut self, env: &Environment, proc_def_id: ProcedureDefId) {
+        self.procedures.remove(&env.get_item_def_path(proc_def_id));
+    }
+}
+
+/// Computes (and caches) the fingerprints of the items of the crate and of its dependencies.
+pub struct Fingerprints<'a, 'v: 'a, 'tcx: 'v> {
+    env: &'a Environment<'tcx>,
+    encoder: &'a Encoder<'v, 'tcx>,
+    cache: HashMap<DefId, u64>,
+    /// The local items, indexed by def path.
+    local_items: HashMap<String, DefId>,
+    /// The hashes of the external crates, indexed by crate name.
+    crate_hashes: HashMap<String, u64>,
+    /// The hash of the configuration and of the version of Prusti, which is part of every
+    /// fingerprint.
+    settings_hash: u64,
+}
+
+/// A hasher whose results are stable across runs and across versions of Rust, unlike those of
+/// `DefaultHasher`.
+fn stable_hasher() -> SipHasher {
+    SipHasher::new_with_keys(0, 0)
+}
+
+/// The hash of the given configuration (see `config::dump`) and Prusti version.
+fn settings_hash(config_dump: &str, version: &str) -> u64 {
+    let mut hasher = stable_hasher();
+    config_dump.hash(&mut hasher);
+    version.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The fingerprint of an item of an external crate, which changes only if the crate changes.
+fn external_fingerprint(settings_hash: u64, crate_hash: u64) -> u64 {
+    let mut hasher = stable_hasher();
+    settings_hash.hash(&mut hasher);
+    crate_hash.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<'a, 'v: 'a, 'tcx: 'v> Fingerprints<'a, 'v, 'tcx> {
+    pub fn new(env: &'a Environment<'tcx>, encoder: &'a Encoder<'v, 'tcx>) -> Self {
+        let tcx = env.tcx();
+        let mut local_items = HashMap::new();
+        for &local_def_id in tcx.mir_keys(LOCAL_CRATE).iter() {
+            let def_id = local_def_id.to_def_id();
+            local_items.insert(env.get_item_def_path(def_id), def_id);
+        }
+        for item in tcx.hir().krate().items.values() {
+            let def_id = tcx.hir().local_def_id(item.hir_id).to_def_id();
+            local_items.insert(env.get_item_def_path(def_id), def_id);
+        }
+        let crate_hashes = tcx
+            .crates()
+            .iter()
+            .map(|&krate| (tcx.crate_name(krate).to_string(), tcx.crate_hash(krate).as_u64()))
+            .collect();
+        Fingerprints {
+            env,
+            encoder,
+            cache: HashMap::new(),
+            local_items,
+            crate_hashes,
+            settings_hash: settings_hash(&config::dump(), env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// The fingerprint of the item with the given def path, if it still exists.
+    fn of_def_path(&mut self, def_path: &str) -> Option<u64> {
+        if let Some(&def_id) = self.local_items.get(def_path) {
+            return Some(self.of_item(def_id));
+        }
+        let crate_name = def_path.split("::").next()?;
+        let crate_hash = *self.crate_hashes.get(crate_name)?;
+        Some(external_fingerprint(self.settings_hash, crate_hash))
+    }
+
+    pub fn of_item(&mut self, def_id: DefId) -> u64 {
+        if let Some(&fingerprint) = self.cache.get(&def_id) {
+            return fingerprint;
+        }
+        // Break cycles between recursive types.
+        self.cache.insert(def_id, 0);
+        let fingerprint = self.compute(def_id);
+        self.cache.insert(def_id, fingerprint);
+        fingerprint
+    }
+
+    fn compute(&mut self, def_id: DefId) -> u64 {
+        let tcx = self.env.tcx();
+        if !def_id.is_local() {
+            return external_fingerprint(self.settings_hash, tcx.crate_hash(def_id.krate).as_u64());
+        }
+        let mut hasher = stable_hasher();
+        self.settings_hash.hash(&mut hasher);
+        let codemap = self.env.codemap();
+        if let Some(span) = tcx.hir().span_if_local(def_id) {
+            codemap.span_to_snippet(span).ok().hash(&mut hasher);
+        }
+        for attr in &["pure", "trusted"] {
+            self.env.has_attribute_name(def_id, attr).hash(&mut hasher);
+        }
+        if let Some(spec) = self.encoder.get_spec_by_def_id(def_id) {
+            for span in spec_spans(tcx, &spec) {
+                codemap.span_to_snippet(span).ok().hash(&mut hasher);
+            }
+        }
+        if let Some(predicate_body) = self.encoder.get_predicate_body(def_id) {
+            for span in predicate_body.get_spans(tcx) {
+                codemap.span_to_snippet(span).ok().hash(&mut hasher);
+            }
+        }
+        if tcx.is_mir_available(def_id) && !tcx.is_closure(def_id) {
+            let procedure = self.env.get_procedure(def_id);
+            hash_mir(procedure.get_mir(), &mut hasher);
+            // The settings and the `#[skip]`/`#[verify]` selection may be inherited from an
+            // enclosing item (e.g. a module), whose changes are not covered by the source code
+            // of the procedure. Invalid settings are reported when the procedure is verified.
+            VerifierSettings::read(self.env, def_id).0.hash(&mut hasher);
+            self.env.get_selection_attribute(def_id).hash(&mut hasher);
+        }
+        if let ty::TyKind::Adt(adt_def, _) = tcx.type_of(def_id).kind {
+            if adt_def.did == def_id {
+                for field in adt_def.all_fields() {
+                    field.ident.to_string().hash(&mut hasher);
+                    let field_ty = tcx.type_of(field.did);
+                    format!("{:?}", field_ty).hash(&mut hasher);
+                    for nested_ty in field_ty.walk() {
+                        if let ty::subst::GenericArgKind::Type(ty) = nested_ty.unpack() {
+                            if let ty::TyKind::Adt(nested_adt_def, _) = ty.kind {
+                                self.of_item(nested_adt_def.did).hash(&mut hasher);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        hasher.finish()
+    }
+}
+
+fn spec_spans<'tcx>(tcx: ty::TyCtxt<'tcx>, spec: &typed::SpecificationSet<'tcx>) -> Vec<rustc_span::Span> {
+    let mut spans = vec![];
+    if let typed::SpecificationSet::Procedure(ref proc_spec) = spec {
+        for assertion in proc_spec.pres.iter().chain(proc_spec.posts.iter()) {
+            spans.extend(assertion.get_spans(tcx));
+        }
+        for pledge in &proc_spec.pledges {
+            if let Some(ref lhs) = pledge.lhs {
+                spans.extend(lhs.get_spans(tcx));
+            }
+            spans.extend(pledge.rhs.get_spans(tcx));
+        }
+    }
+    spans
+}
+
+/// Hash the statements, terminators and local types of the MIR body, ignoring source positions.
+fn hash_mir<H: Hasher>(mir: &mir::Body, hasher: &mut H) {
+    for local_decl in mir.local_decls.iter() {
+        // The debug representation of closure types contains their source position.
+        if !local_decl.ty.is_closure() {
+            format!("{:?}", local_decl.ty).hash(hasher);
+        }
+    }
+    for bb_data in mir.basic_blocks().iter() {
+        for stmt in &bb_data.statements {
+            match stmt.kind {
+                mir::StatementKind::Assign(box (
+                    _,
+                    mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(..), _),
+                )) => {}
+                ref kind => format!("{:?}", kind).hash(hasher),
+            }
+        }
+        if let Some(ref terminator) = bb_data.terminator {
+            format!("{:?}", terminator.kind).hash(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(u32, u32)]) -> impl Fn(u32) -> HashSet<u32> + '_ {
+        move |item| {
+            edges
+                .iter()
+                .filter(|&&(from, _)| from == item)
+                .map(|&(_, to)| to)
+                .collect()
+        }
+    }
+
+    fn set(items: &[u32]) -> HashSet<u32> {
+        items.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_transitive_dependencies() {
+        // 1 calls the pure function 2, which calls 3 and 4; 4 uses the type 5.
+        let edges = [(1, 2), (2, 3), (2, 4), (4, 5), (6, 1)];
+        assert_eq!(transitive_dependencies(1, graph(&edges)), set(&[2, 3, 4, 5]));
+        assert_eq!(transitive_dependencies(4, graph(&edges)), set(&[5]));
+        assert_eq!(transitive_dependencies(5, graph(&edges)), set(&[]));
+    }
+
+    #[test]
+    fn test_transitive_dependencies_with_cycles() {
+        let edges = [(1, 2), (2, 3), (3, 2), (3, 1)];
+        assert_eq!(transitive_dependencies(1, graph(&edges)), set(&[1, 2, 3]));
+        assert_eq!(transitive_dependencies(2, graph(&edges)), set(&[1, 2, 3]));
+    }
+
+    fn entry() -> ProcedureEntry {
+        ProcedureEntry {
+            fingerprint: 42,
+            dependencies: vec![("krate::f".to_string(), 1), ("krate::g".to_string(), 2)]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_entry_unchanged() {
+        let fingerprints: HashMap<&str, u64> = vec![("krate::f", 1), ("krate::g", 2)]
+            .into_iter()
+            .collect();
+        let lookup = |path: &str| fingerprints.get(path).cloned();
+        assert!(entry().is_unchanged("krate::main", 42, lookup));
+        assert!(!entry().is_unchanged("krate::main", 43, lookup));
+    }
+
+    #[test]
+    fn test_entry_changed_dependency() {
+        let changed: HashMap<&str, u64> = vec![("krate::f", 1), ("krate::g", 3)]
+            .into_iter()
+            .collect();
+        assert!(!entry().is_unchanged("krate::main", 42, |path| changed.get(path).cloned()));
+        let removed: HashMap<&str, u64> = vec![("krate::f", 1)].into_iter().collect();
+        assert!(!entry().is_unchanged("krate::main", 42, |path| removed.get(path).cloned()));
+    }
+
+    #[test]
+    fn test_dependency_graph_serialization() {
+        let mut graph = DependencyGraph::default();
+        graph.procedures.insert("krate::main".to_string(), entry());
+        let json = serde_json::to_string(&graph).unwrap();
+        let loaded: DependencyGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.procedures, graph.procedures);
+    }
+
+    #[test]
+    fn test_settings_hash() {
+        let config = "CHECK_PANICS = true  # default";
+        assert_eq!(settings_hash(config, "0.1.0"), settings_hash(config, "0.1.0"));
+        assert_ne!(
+            settings_hash(config, "0.1.0"),
+            settings_hash("CHECK_PANICS = false  # default", "0.1.0")
+        );
+        assert_ne!(settings_hash(config, "0.1.0"), settings_hash(config, "0.2.0"));
+    }
+
+    #[test]
+    fn test_external_fingerprint() {
+        let settings = settings_hash("CHECK_PANICS = true", "0.1.0");
+        let other_settings = settings_hash("CHECK_PANICS = false", "0.1.0");
+        assert_eq!(external_fingerprint(settings, 7), external_fingerprint(settings, 7));
+        assert_ne!(external_fingerprint(settings, 7), external_fingerprint(settings, 8));
+        assert_ne!(external_fingerprint(settings, 7), external_fingerprint(other_settings, 7));
+    }
+}
@@ -48,5 +48,9 @@ extern crate lazy_static;
 // extern crate pretty_assertions;
 
 pub mod encoder;
+mod incremental;
+mod trusted_base;
 mod utils;
+pub mod verification_report;
 pub mod verifier;
+mod verifier_settings;
@@ -0,0 +1,417 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! An audit report of the trusted base of the verification of a crate, i.e. of everything that a
+//! successful verification result relies on without having verified it:
+//!
+//! * the `#[trusted]` items, whose contracts are assumed;
+//! * the callees without specification, whose calls are encoded with the empty contract;
+//! * the callees that have not been verified, because they use unsupported features;
+//! * the callees that have not been verified, because they have not been selected for the
+//!   verification (e.g. with `#[skip]`), whose contracts are assumed;
+//! * the configuration flags that weaken the verification of the whole crate.
+//!
+//! The assumptions of a procedure are collected by walking the dependencies recorded by the
+//! encoder, so they include the assumptions of the verified procedures that it (transitively)
+//! calls. The procedures skipped by the incremental verification are not encoded, so their
+//! dependencies are unknown; the report is therefore not written when it is enabled.
+//!
+//! The report is written both as JSON, whose schema is versioned by `REPORT_VERSION` as for the
+//! verification report, and as text.
+
+use crate::encoder::errors::EncodingError;
+use crate::encoder::Encoder;
+use crate::verification_report::SpanReport;
+use prusti_common::config;
+use prusti_interface::data::ProcedureDefId;
+use prusti_interface::environment::Environment;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+/// The version of the schema of the report.
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct TrustedBaseReport {
+    pub version: u32,
+    pub crate_name: String,
+    /// The configuration flags that weaken the verification of the whole crate.
+    pub config: Vec<ConfigAssumption>,
+    /// The procedures that have been verified, with the assumptions on which they rely.
+    pub procedures: Vec<ProcedureAssumptions>,
+    /// The procedures that have not been verified, because they use unsupported features.
+    pub skipped_procedures: Vec<SkippedProcedure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigAssumption {
+    pub flag: String,
+    pub value: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProcedureAssumptions {
+    pub def_path: String,
+    pub name: String,
+    pub span: Option<SpanReport>,
+    pub assumptions: Vec<Assumption>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Assumption {
+    pub kind: AssumptionKind,
+    pub def_path: String,
+    pub name: String,
+    pub span: Option<SpanReport>,
+    /// The names of the verified procedures through which the assumption is reached, starting
+    /// from a callee of the procedure. Empty if the procedure itself relies on the item.
+    pub via: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssumptionKind {
+    /// A `#[trusted]` item, whose contract is assumed without verifying its body.
+    Trusted,
+    /// A callee without specification, whose calls are encoded with the empty contract.
+    Unspecified,
+    /// A callee that has not been verified, because it uses unsupported features.
+    Unsupported,
+    /// A callee that has not been verified, because it has not been selected for the
+    /// verification.
+    Excluded,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedProcedure {
+    pub def_path: String,
+    pub name: String,
+    pub span: Option<SpanReport>,
+    pub reason: String,
+}
+
+impl AssumptionKind {
+    fn description(self) -> &'static str {
+        match self {
+            AssumptionKind::Trusted => "trusted",
+            AssumptionKind::Unspecified => "no specification",
+            AssumptionKind::Unsupported => "not verified (unsupported features)",
+            AssumptionKind::Excluded => "not verified (excluded from the verification)",
+        }
+    }
+}
+
+impl TrustedBaseReport {
+    /// Build the report of the given procedures, which must have been encoded by the encoder.
+    pub fn new(env: &Environment, encoder: &Encoder, procedures: &[ProcedureDefId]) -> Self {
+        let codemap = env.codemap();
+
+        let mut skipped: HashMap<ProcedureDefId, String> = HashMap::new();
+        for (proc_id, error) in encoder.get_encoding_errors() {
+            if let (Some(proc_id), EncodingError::Unsupported(message, _)) = (proc_id, error) {
+                skipped.entry(proc_id).or_insert(message);
+            }
+        }
+
+        let selected: HashSet<ProcedureDefId> = procedures.iter().cloned().collect();
+        let mut verified_procedures = vec![];
+        let mut skipped_procedures = vec![];
+        for &proc_id in procedures {
+            if env.has_attribute_name(proc_id, "trusted") {
+                continue;
+            }
+            let def_path = env.get_item_def_path(proc_id);
+            let name = env.get_absolute_item_name(proc_id);
+            let span = SpanReport::from_multi_span(codemap, &env.get_item_span(proc_id).into());
+            if let Some(reason) = skipped.get(&proc_id) {
+                skipped_procedures.push(SkippedProcedure {
+                    def_path,
+                    name,
+                    span,
+                    reason: reason.clone(),
+                });
+            } else {
+                verified_procedures.push(ProcedureAssumptions {
+                    def_path,
+                    name,
+                    span,
+                    assumptions: collect_assumptions(env, encoder, &skipped, &selected, proc_id),
+                });
+            }
+        }
+
+        TrustedBaseReport {
+            version: REPORT_VERSION,
+            crate_name: env.crate_name(),
+            config: collect_config_assumptions(),
+            procedures: verified_procedures,
+            skipped_procedures,
+        }
+    }
+
+    /// Render the report for humans.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "Trusted base of the verification of crate `{}`", self.crate_name).unwrap();
+
+        writeln!(text).unwrap();
+        writeln!(text, "Configuration:").unwrap();
+        if self.config.is_empty() {
+            writeln!(text, "  (no flag weakens the verification)").unwrap();
+        }
+        for assumption in &self.config {
+            writeln!(
+                text,
+                "  - {} = {}: {}",
+                assumption.flag, assumption.value, assumption.description
+            )
+            .unwrap();
+        }
+
+        writeln!(text).unwrap();
+        writeln!(text, "Verified procedures:").unwrap();
+        for procedure in &self.procedures {
+            writeln!(text, "  {}{}", procedure.name, format_span(&procedure.span)).unwrap();
+            if procedure.assumptions.is_empty() {
+                writeln!(text, "    (no assumptions)").unwrap();
+            }
+            for assumption in &procedure.assumptions {
+                write!(
+                    text,
+                    "    - {}{}: {}",
+                    assumption.name,
+                    format_span(&assumption.span),
+                    assumption.kind.description()
+                )
+                .unwrap();
+                if !assumption.via.is_empty() {
+                    write!(text, " (via {})", assumption.via.join(" -> ")).unwrap();
+                }
+                writeln!(text).unwrap();
+            }
+        }
+
+        if !self.skipped_procedures.is_empty() {
+            writeln!(text).unwrap();
+            writeln!(text, "Skipped procedures:").unwrap();
+            for procedure in &self.skipped_procedures {
+                writeln!(
+                    text,
+                    "  {}{}: {}",
+                    procedure.name,
+                    format_span(&procedure.span),
+                    procedure.reason
+                )
+                .unwrap();
+            }
+        }
+        text
+    }
+
+    /// Write the report to `<dir>/<crate name>.json` and `<dir>/<crate name>.txt`.
+    pub fn write_to_dir<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        let json_path = dir.as_ref().join(format!("{}.json", self.crate_name));
+        serde_json::to_writer_pretty(File::create(json_path)?, self)?;
+        let text_path = dir.as_ref().join(format!("{}.txt", self.crate_name));
+        fs::write(text_path, self.to_text())?;
+        Ok(())
+    }
+}
+
+fn format_span(span: &Option<SpanReport>) -> String {
+    match span {
+        Some(span) => format!(" ({}:{})", span.file, span.line_start),
+        None => String::new(),
+    }
+}
+
+/// Is the item a `#[trusted]` one? Predicates are marked as trusted only so that their
+/// placeholder body is not verified, but their definition is part of the specification.
+fn is_trusted(env: &Environment, def_id: ProcedureDefId) -> bool {
+    env.has_attribute_name(def_id, "trusted") && !env.has_attribute_name(def_id, "pred_spec_id_ref")
+}
+
+/// Walk the dependencies of the procedure, following those that are verified.
+fn collect_assumptions(
+    env: &Environment,
+    encoder: &Encoder,
+    skipped: &HashMap<ProcedureDefId, String>,
+    selected: &HashSet<ProcedureDefId>,
+    proc_def_id: ProcedureDefId,
+) -> Vec<Assumption> {
+    let codemap = env.codemap();
+    let mut assumptions = vec![];
+    let mut visited = HashSet::new();
+    visited.insert(proc_def_id);
+    // The procedures whose dependencies should be visited, with the chain of procedures through
+    // which they have been reached.
+    let mut queue = VecDeque::new();
+    queue.push_back((proc_def_id, vec![]));
+    while let Some((curr_def_id, via)) = queue.pop_front() {
+        let unspecified_callees = encoder.get_unspecified_callees(curr_def_id);
+        let mut dependencies: Vec<_> = encoder.get_dependencies(curr_def_id).into_iter().collect();
+        dependencies.sort_by_key(|&def_id| env.get_item_def_path(def_id));
+        for def_id in dependencies {
+            if !visited.insert(def_id) {
+                continue;
+            }
+            let kind = if is_trusted(env, def_id) {
+                Some(AssumptionKind::Trusted)
+            } else if skipped.contains_key(&def_id) {
+                Some(AssumptionKind::Unsupported)
+            } else if unspecified_callees.contains(&def_id) {
+                Some(AssumptionKind::Unspecified)
+            } else if def_id.is_local() && !selected.contains(&def_id) {
+                Some(AssumptionKind::Excluded)
+            } else {
+                None
+            };
+            let name = env.get_absolute_item_name(def_id);
+            if let Some(kind) = kind {
+                assumptions.push(Assumption {
+                    kind,
+                    def_path: env.get_item_def_path(def_id),
+                    name: name.clone(),
+                    span: SpanReport::from_multi_span(codemap, &env.get_item_span(def_id).into()),
+                    via: via.clone(),
+                });
+            }
+            // The body of a trusted, skipped or excluded dependency has not been verified, so its
+            // own dependencies are irrelevant.
+            if kind == None || kind == Some(AssumptionKind::Unspecified) {
+                let mut dependency_via = via.clone();
+                dependency_via.push(name);
+                queue.push_back((def_id, dependency_via));
+            }
+        }
+    }
+    assumptions
+}
+
+fn collect_config_assumptions() -> Vec<ConfigAssumption> {
+    let mut assumptions = vec![];
+    let mut push = |flag: &str, value: String, description: &str| {
+        assumptions.push(ConfigAssumption {
+            flag: flag.to_string(),
+            value,
+            description: description.to_string(),
+        })
+    };
+    if !config::check_panics() {
+        push("CHECK_PANICS", "false".to_string(), "the absence of panics is not checked");
+    } else {
+        if !config::check_index_bounds() {
+            push(
+                "CHECK_INDEX_BOUNDS",
+                "false".to_string(),
+                "indexing and slicing operations are assumed to be in bounds",
+            );
+        }
+        if !config::check_division_by_zero() {
+            push(
+                "CHECK_DIVISION_BY_ZERO",
+                "false".to_string(),
+                "divisors of divisions and remainder operations are assumed to be non-zero",
+            );
+        }
+        if !config::check_unwrap() {
+            push(
+                "CHECK_UNWRAP",
+                "false".to_string(),
+                "unwrapped `Option` and `Result` values are assumed to be `Some` and `Ok`",
+            );
+        }
+        if config::check_binary_operations() && !config::check_overflows() {
+            push(
+                "CHECK_OVERFLOWS",
+                "false".to_string(),
+                "arithmetic operations, shifts and casts are assumed not to overflow",
+            );
+        }
+    }
+    if !config::check_binary_operations() {
+        push(
+            "CHECK_BINARY_OPERATIONS",
+            "false".to_string(),
+            "the absence of overflows in arithmetic operations, shifts and casts is not checked",
+        );
+    } else {
+        push(
+            "TARGET_POINTER_WIDTH",
+            config::target_pointer_width().to_string(),
+            "the overflow checks assume that `usize` and `isize` have this number of bits",
+        );
+    }
+    if config::skip_unsupported_functions() && !config::error_on_partially_supported() {
+        push(
+            "SKIP_UNSUPPORTED_FUNCTIONS",
+            "true".to_string(),
+            "procedures that use unsupported features are skipped",
+        );
+    }
+    if config::enable_whitelist() {
+        push(
+            "ENABLE_WHITELIST",
+            "true".to_string(),
+            "only the whitelisted procedures are verified",
+        );
+    }
+    if config::enable_verify_only_basic_block_path() {
+        push(
+            "ENABLE_VERIFY_ONLY_BASIC_BLOCK_PATH",
+            "true".to_string(),
+            "only one path of each procedure is verified",
+        );
+    }
+    let include_patterns = config::verification_include_patterns();
+    if !include_patterns.is_empty() {
+        push(
+            "VERIFICATION_INCLUDE_PATTERNS",
+            format!("{:?}", include_patterns),
+            "only the procedures that match the patterns are verified",
+        );
+    }
+    let exclude_patterns = config::verification_exclude_patterns();
+    if !exclude_patterns.is_empty() {
+        push(
+            "VERIFICATION_EXCLUDE_PATTERNS",
+            format!("{:?}", exclude_patterns),
+            "the procedures that match the patterns are not verified",
+        );
+    }
+    if config::verify_only_specified() {
+        push(
+            "VERIFY_ONLY_SPECIFIED",
+            "true".to_string(),
+            "only the procedures that have a contract are verified",
+        );
+    }
+    let deleted_basic_blocks = config::delete_basic_blocks();
+    if !deleted_basic_blocks.is_empty() {
+        push(
+            "DELETE_BASIC_BLOCKS",
+            format!("{:?}", deleted_basic_blocks),
+            "the given basic blocks are replaced with `assume false`",
+        );
+    }
+    if config::verify_only_preamble() {
+        push(
+            "VERIFY_ONLY_PREAMBLE",
+            "true".to_string(),
+            "the procedures are not verified",
+        );
+    }
+    if config::no_verify() {
+        push("NO_VERIFY", "true".to_string(), "the verifier does not run");
+    }
+    assumptions
+}
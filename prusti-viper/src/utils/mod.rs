@@ -6,3 +6,12 @@
 
 pub mod to_string;
 pub mod type_visitor;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+
+/// Whether the path of the item `def_id` is one of `paths`, e.g. `std::vec::Vec`.
+pub fn is_item(tcx: TyCtxt, def_id: DefId, paths: &[&str]) -> bool {
+    let path = tcx.def_path_str(def_id);
+    paths.iter().any(|candidate| path == *candidate)
+}
@@ -0,0 +1,410 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A machine-readable report of the verification of a crate.
+//!
+//! The report is serialized as JSON. Its schema is versioned by `REPORT_VERSION`: fields may be
+//! added without changing the version, but renaming or removing fields requires a new version.
+
+use crate::encoder::errors::{EncodingError, ErrorCtxt, PrustiError};
+use prusti_interface::data::ProcedureDefId;
+use prusti_interface::environment::Environment;
+use rustc_span::source_map::SourceMap;
+use rustc_span::MultiSpan;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use viper::VerificationError;
+
+/// The version of the schema of the report.
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct CrateReport {
+    pub version: u32,
+    pub crate_name: String,
+    pub status: CrateStatus,
+    pub timings: CrateTimings,
+    pub procedures: Vec<ProcedureReport>,
+    /// Errors that could not be attributed to any procedure (e.g. Viper consistency errors).
+    pub errors: Vec<ErrorReport>,
+    #[serde(skip)]
+    procedure_index: HashMap<ProcedureDefId, usize>,
+    #[serde(skip)]
+    verified: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrateStatus {
+    Success,
+    Failure,
+}
+
+/// The time (in milliseconds) spent in each phase of the verification of the crate.
+#[derive(Debug, Default, Serialize)]
+pub struct CrateTimings {
+    pub encoding_ms: u64,
+    pub optimization_ms: u64,
+    pub verification_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProcedureReport {
+    pub def_path: String,
+    pub name: String,
+    pub span: Option<SpanReport>,
+    pub pure: bool,
+    pub status: ProcedureStatus,
+    /// The verification errors reported for the procedure.
+    pub failures: Vec<ErrorReport>,
+    pub encoding_errors: Vec<ErrorReport>,
+    pub encoding_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcedureStatus {
+    /// The procedure has been verified successfully.
+    Verified,
+    /// The verifier reported at least one error for the procedure.
+    Failed,
+    /// The procedure uses an unsupported feature.
+    Unsupported,
+    /// The encoding of the procedure failed for another reason.
+    EncodingFailed,
+    /// The procedure is marked as `#[trusted]`, so it has not been verified.
+    Trusted,
+    /// The verifier did not run (e.g. because of consistency errors in the Viper program).
+    NotVerified,
+    /// Neither the procedure nor its dependencies changed since the procedure was last verified
+    /// successfully, so it has not been verified again.
+    Unchanged,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// For verification errors, the kind of the `ErrorCtxt` of the failing node; for encoding
+    /// errors, one of `Unsupported`, `Incorrect` and `Internal`.
+    pub kind: String,
+    pub message: String,
+    pub span: Option<SpanReport>,
+    /// The full identifier of the Viper error, if this is a verification error.
+    pub viper_error: Option<String>,
+}
+
+/// A source code location. Lines and columns are 1-based.
+#[derive(Debug, Serialize)]
+pub struct SpanReport {
+    pub file: String,
+    pub line_start: usize,
+    pub column_start: usize,
+    pub line_end: usize,
+    pub column_end: usize,
+}
+
+impl SpanReport {
+    pub fn from_multi_span(codemap: &SourceMap, span: &MultiSpan) -> Option<Self> {
+        let primary_span = span.primary_span()?.source_callsite();
+        let start = codemap.lookup_char_pos(primary_span.lo());
+        let end = codemap.lookup_char_pos(primary_span.hi());
+        Some(SpanReport {
+            file: start.file.name.to_string(),
+            line_start: start.line,
+            column_start: start.col.0 + 1,
+            line_end: end.line,
+            column_end: end.col.0 + 1,
+        })
+    }
+}
+
+impl CrateReport {
+    pub fn new(env: &Environment, procedures: &[ProcedureDefId]) -> Self {
+        let codemap = env.codemap();
+        let mut procedure_index = HashMap::new();
+        let procedures = procedures
+            .iter()
+            .enumerate()
+            .map(|(index, &proc_def_id)| {
+                procedure_index.insert(proc_def_id, index);
+                let is_trusted = env.has_attribute_name(proc_def_id, "trusted");
+                ProcedureReport {
+                    def_path: env.get_item_def_path(proc_def_id),
+                    name: env.get_absolute_item_name(proc_def_id),
+                    span: SpanReport::from_multi_span(
+                        codemap,
+                        &env.get_item_span(proc_def_id).into(),
+                    ),
+                    pure: env.has_attribute_name(proc_def_id, "pure"),
+                    status: if is_trusted {
+                        ProcedureStatus::Trusted
+                    } else {
+                        ProcedureStatus::NotVerified
+                    },
+                    failures: vec![],
+                    encoding_errors: vec![],
+                    encoding_ms: None,
+                }
+            })
+            .collect();
+        CrateReport {
+            version: REPORT_VERSION,
+            crate_name: env.crate_name(),
+            status: CrateStatus::Success,
+            timings: CrateTimings::default(),
+            procedures,
+            errors: vec![],
+            procedure_index,
+            verified: false,
+        }
+    }
+
+    fn get_procedure_index(&self, proc_def_id: Option<ProcedureDefId>) -> Option<usize> {
+        self.procedure_index.get(&proc_def_id?).cloned()
+    }
+
+    pub fn set_encoding_duration(&mut self, proc_def_id: ProcedureDefId, duration: Duration) {
+        if let Some(index) = self.get_procedure_index(Some(proc_def_id)) {
+            self.procedures[index].encoding_ms = Some(duration.as_millis() as u64);
+        }
+    }
+
+    pub fn add_encoding_error(
+        &mut self,
+        codemap: &SourceMap,
+        proc_def_id: Option<ProcedureDefId>,
+        error: &EncodingError,
+    ) {
+        let (kind, message, span) = match error {
+            EncodingError::Unsupported(message, span) => ("Unsupported", message, span),
+            EncodingError::Incorrect(message, span) => ("Incorrect", message, span),
+            EncodingError::Internal(message, span) => ("Internal", message, span),
+        };
+        let report = ErrorReport {
+            kind: kind.to_string(),
+            message: message.to_string(),
+            span: SpanReport::from_multi_span(codemap, span),
+            viper_error: None,
+        };
+        match self.get_procedure_index(proc_def_id) {
+            Some(index) => self.procedures[index].encoding_errors.push(report),
+            None => self.errors.push(report),
+        }
+    }
+
+    pub fn add_verification_error(
+        &mut self,
+        codemap: &SourceMap,
+        proc_def_id: Option<ProcedureDefId>,
+        error_ctxt: Option<&ErrorCtxt>,
+        verification_error: &VerificationError,
+        prusti_error: &PrustiError,
+    ) {
+        let report = ErrorReport {
+            kind: error_ctxt.map(|ctxt| ctxt.kind()).unwrap_or("Unregistered").to_string(),
+            message: prusti_error.message().to_string(),
+            span: SpanReport::from_multi_span(codemap, prusti_error.span()),
+            viper_error: Some(verification_error.full_id.clone()),
+        };
+        match self.get_procedure_index(proc_def_id) {
+            Some(index) => self.procedures[index].failures.push(report),
+            None => self.errors.push(report),
+        }
+    }
+
+    /// Report an error of the verifier that is not related to any procedure.
+    pub fn add_internal_error(&mut self, message: String) {
+        self.errors.push(ErrorReport {
+            kind: "Internal".to_string(),
+            message,
+            span: None,
+            viper_error: None,
+        });
+    }
+
+    /// Record that the procedure has been skipped by the incremental verification.
+    pub fn set_unchanged(&mut self, proc_def_id: ProcedureDefId) {
+        if let Some(index) = self.get_procedure_index(Some(proc_def_id)) {
+            self.procedures[index].status = ProcedureStatus::Unchanged;
+        }
+    }
+
+    /// Record that the verifier ran on the encoded program.
+    pub fn set_verified(&mut self) {
+        self.verified = true;
+    }
+
+    /// Compute the status of each procedure, and set the status of the crate according to the
+    /// result of the verification.
+    pub fn finalize(&mut self, success: bool) {
+        let verified = self.verified;
+        for procedure in &mut self.procedures {
+            if procedure.status == ProcedureStatus::Trusted
+                || procedure.status == ProcedureStatus::Unchanged
+            {
+                continue;
+            }
+            procedure.status = if !procedure.encoding_errors.is_empty() {
+                if procedure.encoding_errors.iter().any(|e| e.kind == "Unsupported") {
+                    ProcedureStatus::Unsupported
+                } else {
+                    ProcedureStatus::EncodingFailed
+                }
+            } else if !procedure.failures.is_empty() {
+                ProcedureStatus::Failed
+            } else if verified {
+                ProcedureStatus::Verified
+            } else {
+                ProcedureStatus::NotVerified
+            };
+        }
+        self.status = if success {
+            CrateStatus::Success
+        } else {
+            CrateStatus::Failure
+        };
+    }
+
+    /// Write the report to `<dir>/<crate name>.json`.
+    pub fn write_to_dir<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        let path = dir.as_ref().join(format!("{}.json", self.crate_name));
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn procedure(name: &str, status: ProcedureStatus) -> ProcedureReport {
+        ProcedureReport {
+            def_path: format!("krate::{}", name),
+            name: name.to_string(),
+            span: Some(SpanReport {
+                file: "src/lib.rs".to_string(),
+                line_start: 3,
+                column_start: 1,
+                line_end: 5,
+                column_end: 2,
+            }),
+            pure: false,
+            status,
+            failures: vec![],
+            encoding_errors: vec![],
+            encoding_ms: Some(7),
+        }
+    }
+
+    fn error(kind: &str) -> ErrorReport {
+        ErrorReport {
+            kind: kind.to_string(),
+            message: "message".to_string(),
+            span: None,
+            viper_error: None,
+        }
+    }
+
+    fn report(procedures: Vec<ProcedureReport>, verified: bool) -> CrateReport {
+        CrateReport {
+            version: REPORT_VERSION,
+            crate_name: "krate".to_string(),
+            status: CrateStatus::Success,
+            timings: CrateTimings::default(),
+            procedures,
+            errors: vec![],
+            procedure_index: HashMap::new(),
+            verified,
+        }
+    }
+
+    #[test]
+    fn test_serialize_procedure_status() {
+        let serialize = |status: ProcedureStatus| serde_json::to_value(status).unwrap();
+        assert_eq!(serialize(ProcedureStatus::Verified), "verified");
+        assert_eq!(serialize(ProcedureStatus::Failed), "failed");
+        assert_eq!(serialize(ProcedureStatus::Unsupported), "unsupported");
+        assert_eq!(serialize(ProcedureStatus::EncodingFailed), "encoding_failed");
+        assert_eq!(serialize(ProcedureStatus::Trusted), "trusted");
+        assert_eq!(serialize(ProcedureStatus::NotVerified), "not_verified");
+    }
+
+    #[test]
+    fn test_serialize_crate_report() {
+        let mut failed = procedure("f", ProcedureStatus::NotVerified);
+        failed.failures.push(ErrorReport {
+            viper_error: Some("assert.failed:assertion.false".to_string()),
+            ..error("AssertTerminator")
+        });
+        let mut report = report(vec![failed], true);
+        report.add_internal_error("crash".to_string());
+        report.finalize(false);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["version"], REPORT_VERSION);
+        assert_eq!(json["crate_name"], "krate");
+        assert_eq!(json["status"], "failure");
+        assert_eq!(json["timings"]["optimization_ms"], 0);
+        assert_eq!(json["errors"][0]["kind"], "Internal");
+        assert_eq!(json["errors"][0]["message"], "crash");
+        let procedure = &json["procedures"][0];
+        assert_eq!(procedure["def_path"], "krate::f");
+        assert_eq!(procedure["status"], "failed");
+        assert_eq!(procedure["span"]["line_start"], 3);
+        assert_eq!(procedure["encoding_ms"], 7);
+        assert_eq!(procedure["failures"][0]["kind"], "AssertTerminator");
+        assert_eq!(
+            procedure["failures"][0]["viper_error"],
+            "assert.failed:assertion.false"
+        );
+        // The fields used to build the report are not serialized.
+        assert!(json.get("procedure_index").is_none());
+        assert!(json.get("verified").is_none());
+    }
+
+    #[test]
+    fn test_finalize_procedure_status() {
+        let mut unsupported = procedure("unsupported", ProcedureStatus::NotVerified);
+        unsupported.encoding_errors.push(error("Internal"));
+        unsupported.encoding_errors.push(error("Unsupported"));
+        let mut internal = procedure("internal", ProcedureStatus::NotVerified);
+        internal.encoding_errors.push(error("Internal"));
+        let mut trusted = procedure("trusted", ProcedureStatus::Trusted);
+        trusted.failures.push(error("AssertTerminator"));
+        let procedures = vec![
+            procedure("verified", ProcedureStatus::NotVerified),
+            unsupported,
+            internal,
+            trusted,
+        ];
+        let statuses = |report: &CrateReport| -> Vec<ProcedureStatus> {
+            report.procedures.iter().map(|p| p.status).collect()
+        };
+
+        let mut verified_report = report(procedures, true);
+        verified_report.finalize(true);
+        assert_eq!(verified_report.status, CrateStatus::Success);
+        assert_eq!(
+            statuses(&verified_report),
+            vec![
+                ProcedureStatus::Verified,
+                ProcedureStatus::Unsupported,
+                ProcedureStatus::EncodingFailed,
+                ProcedureStatus::Trusted,
+            ]
+        );
+
+        let mut not_verified_report =
+            report(vec![procedure("f", ProcedureStatus::NotVerified)], false);
+        not_verified_report.finalize(false);
+        assert_eq!(not_verified_report.status, CrateStatus::Failure);
+        assert_eq!(statuses(&not_verified_report), vec![ProcedureStatus::NotVerified]);
+    }
+}
@@ -6,22 +6,29 @@
 
 use prusti_common::vir::{self, optimizations, ToViper, ToViperDecl};
 use prusti_common::{
-    config, report::log, verification_context::VerifierBuilder, verification_service::*, Stopwatch,
+    config, report::{log, user}, verification_context::VerifierBuilder, verification_service::*, Stopwatch,
 };
 use crate::encoder::{Encoder, PrustiError};
+use crate::encoder::errors::ErrorCtxt;
+use crate::incremental::{DependencyGraph, Fingerprints};
+use crate::trusted_base::TrustedBaseReport;
+use crate::verification_report::CrateReport;
+use crate::verifier_settings::VerifierSettings;
 // use prusti_filter::validators::Validator;
+use prusti_interface::data::ProcedureDefId;
 use prusti_interface::data::VerificationResult;
 use prusti_interface::data::VerificationTask;
 use prusti_interface::environment::Environment;
 // use prusti_interface::specifications::TypedSpecificationMap;
+use std::collections::HashSet;
 use std::time::Instant;
 use viper::{self, VerificationBackend, Viper};
 use std::path::PathBuf;
 use std::fs::{create_dir_all, canonicalize};
 use std::ffi::OsString;
 use prusti_interface::specs::typed;
-use ::log::{info, debug};
-use prusti_server::{PrustiServerConnection, ServerSideService, VerifierRunner};
+use ::log::{info, debug, error};
+use prusti_server::{record_request, PrustiServerConnection, ServerSideService, VerifierRunner};
 use rustc_span::DUMMY_SP;
 
 // /// A verifier builder is an object that lives entire program's
@@ -234,21 +241,98 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         //     skipped_functions_count,
         // );
 
-        for &proc_id in task.procedures.iter().rev() {
+        // Skip the procedures that did not change since they were last verified successfully
+        let mut dependency_graph = if config::incremental_verification() {
+            Some(DependencyGraph::load(&self.env.crate_name()))
+        } else {
+            None
+        };
+        let mut unchanged_procedures = vec![];
+        if let Some(ref dependency_graph) = dependency_graph {
+            let mut fingerprints = Fingerprints::new(self.env, &self.encoder);
+            for &proc_id in &task.procedures {
+                if dependency_graph.is_unchanged(&mut fingerprints, proc_id) {
+                    debug!(
+                        "Skip verification of {}, as it did not change",
+                        self.env.get_absolute_item_name(proc_id)
+                    );
+                    unchanged_procedures.push(proc_id);
+                }
+            }
+            if !unchanged_procedures.is_empty() {
+                user::message(format!(
+                    "Skipping the verification of {} unchanged items",
+                    unchanged_procedures.len()
+                ));
+            }
+        }
+        let procedures: Vec<_> = task
+            .procedures
+            .iter()
+            .cloned()
+            .filter(|proc_id| !unchanged_procedures.contains(proc_id))
+            .collect();
+
+        for &proc_id in procedures.iter().rev() {
             // FIXME: Use the loop above.
             self.encoder.queue_procedure_encoding(proc_id);
         }
         self.encoder.process_encoding_queue();
 
+        if let Some(report_dir) = config::trusted_base_report_dir() {
+            if config::incremental_verification() {
+                // The dependencies of the skipped procedures are unknown, so the report would
+                // miss the assumptions reached through them.
+                user::message(
+                    "The trusted base report is not written, because it is incomplete when \
+                    INCREMENTAL_VERIFICATION is enabled"
+                );
+            } else {
+                let trusted_base = TrustedBaseReport::new(self.env, &self.encoder, &procedures);
+                if let Err(err) = trusted_base.write_to_dir(&report_dir) {
+                    error!("Failed to write the trusted base report to '{}': {}", report_dir, err);
+                }
+            }
+        }
+
         let encoding_errors_count = self.encoder.count_encoding_errors();
         let mut program = self.encoder.get_viper_program();
 
+        let mut report = if config::json_report_dir().is_some() {
+            Some(CrateReport::new(self.env, &task.procedures))
+        } else {
+            None
+        };
+        if let Some(ref mut report) = report {
+            for &proc_id in &unchanged_procedures {
+                report.set_unchanged(proc_id);
+            }
+            for &proc_id in &task.procedures {
+                if let Some(duration) = self.encoder.get_encoding_duration(proc_id) {
+                    report.set_encoding_duration(proc_id, duration);
+                }
+            }
+            for (proc_id, encoding_error) in self.encoder.get_encoding_errors() {
+                report.add_encoding_error(self.env.codemap(), proc_id, &encoding_error);
+            }
+        }
+
         if config::simplify_encoding() {
-            stopwatch.start_next("optimizing Viper program");
+            let duration = stopwatch.start_next("optimizing Viper program");
+            if let Some(ref mut report) = report {
+                report.timings.encoding_ms = duration.as_millis() as u64;
+            }
             program = program.optimized();
+            let duration = stopwatch.start_next("verifying Viper program");
+            if let Some(ref mut report) = report {
+                report.timings.optimization_ms = duration.as_millis() as u64;
+            }
+        } else {
+            let duration = stopwatch.start_next("verifying Viper program");
+            if let Some(ref mut report) = report {
+                report.timings.encoding_ms = duration.as_millis() as u64;
+            }
         }
-
-        stopwatch.start_next("verifying Viper program");
         let source_path = self.env.source_path();
         let program_name = source_path
             .file_name()
@@ -256,6 +340,12 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             .to_str()
             .unwrap()
             .to_owned();
+        let requests = self.build_verification_requests(&procedures, program, program_name);
+        if config::record_verification_requests() {
+            for request in &requests {
+                record_request(request);
+            }
+        }
         let verification_result: viper::VerificationResult = if let Some(server_address) =
             config::server_address()
         {
@@ -272,48 +362,254 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                 )
             });
 
-            let request = VerificationRequest {
-                program,
-                program_name,
-                backend_config: Default::default(),
-            };
-            service.verify(request)
+            merge_verification_results(
+                requests
+                    .into_iter()
+                    .map(|request| service.verify(request))
+                    .collect(),
+            )
         } else {
             let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
-            let verifier_builder = VerifierBuilder::new();
+            // All the requests use the same backend (see `build_verification_requests`).
+            let verifier_builder =
+                VerifierBuilder::new_with_backend(requests[0].backend_config.backend);
             stopwatch.start_next("running verifier");
-            VerifierRunner::with_default_configured_runner(&verifier_builder, |runner| {
-                runner.verify(program, program_name.as_str())
-            })
+            merge_verification_results(
+                requests
+                    .into_iter()
+                    .map(|request| {
+                        VerifierRunner::with_runner(
+                            &verifier_builder,
+                            &request.backend_config,
+                            |runner| runner.verify(request.program, request.program_name.as_str()),
+                        )
+                    })
+                    .collect(),
+            )
         };
 
-        stopwatch.finish();
+        let duration = stopwatch.finish();
+        if let Some(ref mut report) = report {
+            report.timings.verification_ms = duration.as_millis() as u64;
+        }
 
         let verification_errors = match verification_result {
             viper::VerificationResult::Failure(errors) => errors,
             viper::VerificationResult::ConsistencyErrors(errors) => {
                 debug_assert!(!errors.is_empty());
                 errors.iter().for_each(|e| {
+                    if let Some(ref mut report) = report {
+                        report.add_internal_error(format!("consistency error: {}", e));
+                    }
                     PrustiError::internal(format!("consistency error: {}", e), DUMMY_SP.into())
                         .emit(self.env)
                 });
+                self.write_report(report, false);
                 return VerificationResult::Failure;
             }
             viper::VerificationResult::Success() => vec![],
         };
+        if let Some(ref mut report) = report {
+            report.set_verified();
+        }
+
+        // The procedures that failed to verify. `None` stands for errors that could not be
+        // attributed to a procedure.
+        let mut failed_procedures: HashSet<Option<ProcedureDefId>> = self
+            .encoder
+            .get_encoding_errors()
+            .into_iter()
+            .map(|(proc_id, _)| proc_id)
+            .collect();
+
+        let verification_errors = if config::check_vacuity() {
+            self.report_vacuity_checks(verification_errors, &failed_procedures)
+        } else {
+            verification_errors
+        };
 
-        if encoding_errors_count == 0 && verification_errors.is_empty() {
+        let result = if encoding_errors_count == 0 && verification_errors.is_empty() {
             VerificationResult::Success
         } else {
             let error_manager = self.encoder.error_manager();
 
             for verification_error in verification_errors {
                 debug!("Verification error: {:?}", verification_error);
+                failed_procedures.insert(error_manager.get_procedure(&verification_error));
                 let prusti_error = error_manager.translate_verification_error(&verification_error);
                 debug!("Prusti error: {:?}", prusti_error);
+                if let Some(ref mut report) = report {
+                    report.add_verification_error(
+                        self.env.codemap(),
+                        error_manager.get_procedure(&verification_error),
+                        error_manager.get_error_ctxt(&verification_error),
+                        &verification_error,
+                        &prusti_error,
+                    );
+                }
                 prusti_error.emit(self.env);
             }
             VerificationResult::Failure
+        };
+        if let Some(ref mut dependency_graph) = dependency_graph {
+            self.update_dependency_graph(dependency_graph, &procedures, &failed_procedures);
+        }
+        self.write_report(report, result == VerificationResult::Success);
+        result
+    }
+
+    /// Vacuity checks are expected to fail: those that verify reveal an unsatisfiable
+    /// precondition, an inconsistent loop invariant or an unreachable branch, and are reported as
+    /// warnings. The checks of the procedures that failed to verify are not reported, because the
+    /// program points after a failing assertion are unreachable for the verifier.
+    ///
+    /// Returns the verification errors that are not failures of vacuity checks.
+    fn report_vacuity_checks(
+        &self,
+        verification_errors: Vec<viper::VerificationError>,
+        failed_procedures: &HashSet<Option<ProcedureDefId>>,
+    ) -> Vec<viper::VerificationError> {
+        let error_manager = self.encoder.error_manager();
+        let (vacuity_errors, verification_errors): (Vec<_>, Vec<_>) = verification_errors
+            .into_iter()
+            .partition(|error| {
+                matches!(error_manager.get_error_ctxt(error), Some(ErrorCtxt::VacuityCheck(_)))
+            });
+        let failed_checks: HashSet<u64> = vacuity_errors
+            .iter()
+            .filter_map(|error| error.pos_id.as_ref()?.parse().ok())
+            .collect();
+        let failed_procedures: HashSet<_> = verification_errors
+            .iter()
+            .map(|error| error_manager.get_procedure(error))
+            .chain(failed_procedures.iter().cloned())
+            .collect();
+        for (pos_id, proc_id, check) in error_manager.get_vacuity_checks() {
+            if !failed_checks.contains(&pos_id) && !failed_procedures.contains(&proc_id) {
+                error_manager
+                    .translate_vacuity_check(pos_id, check)
+                    .emit(self.env);
+            }
+        }
+        verification_errors
+    }
+
+    /// Split the verification of the crate into one request per distinct verifier settings of
+    /// the procedures (see `VerifierSettings`). Each request verifies the methods of its
+    /// procedures, taken from `program`, while the pure functions are verified by all of them.
+    ///
+    /// Since a single JVM cannot load both Viper backends, all the requests use the backend of
+    /// the crate if the procedures ask for different backends.
+    fn build_verification_requests(
+        &self,
+        procedures: &[ProcedureDefId],
+        program: vir::Program,
+        program_name: String,
+    ) -> Vec<VerificationRequest> {
+        let mut groups: Vec<(VerifierSettings, Vec<ProcedureDefId>)> = vec![];
+        for &proc_id in procedures {
+            let settings = VerifierSettings::of_procedure(self.env, proc_id);
+            let group = groups
+                .iter_mut()
+                .find(|(group_settings, _)| *group_settings == settings);
+            match group {
+                Some((_, group)) => group.push(proc_id),
+                None => groups.push((settings, vec![proc_id])),
+            }
+        }
+
+        let default_backend = VerifierSettings::default().backend;
+        if groups.iter().any(|(settings, _)| settings.backend != groups[0].0.backend) {
+            for (settings, group) in groups.iter_mut() {
+                if settings.backend != default_backend {
+                    for &proc_id in group.iter() {
+                        let mut warning = PrustiError::incorrect(
+                            format!(
+                                "the {} backend is ignored, because other procedures of the \
+                                crate are verified with another backend",
+                                settings.backend
+                            ),
+                            self.env.get_item_span(proc_id).into(),
+                        );
+                        warning.set_warning();
+                        warning.emit(self.env);
+                    }
+                    settings.backend = default_backend;
+                }
+            }
+            // Settings that differed only in the backend are now equal.
+            let mut merged_groups: Vec<(VerifierSettings, Vec<ProcedureDefId>)> = vec![];
+            for (settings, group) in groups {
+                let merged_group = merged_groups
+                    .iter_mut()
+                    .find(|(merged_settings, _)| *merged_settings == settings);
+                match merged_group {
+                    Some((_, merged_group)) => merged_group.extend(group),
+                    None => merged_groups.push((settings, group)),
+                }
+            }
+            groups = merged_groups;
+        }
+
+        if groups.len() <= 1 {
+            let settings = groups.pop().map(|(settings, _)| settings).unwrap_or_default();
+            return vec![VerificationRequest {
+                program,
+                program_name,
+                backend_config: settings.to_backend_config(),
+            }];
+        }
+        let program_of_group = |group: &[ProcedureDefId]| {
+            let method_names = self.encoder.get_procedure_method_names(group);
+            let mut group_program = program.clone();
+            group_program
+                .methods
+                .retain(|method| method_names.contains(&method.name()));
+            group_program
+        };
+        groups
+            .into_iter()
+            .enumerate()
+            .map(|(index, (settings, group))| VerificationRequest {
+                program: program_of_group(&group),
+                program_name: if index == 0 {
+                    program_name.clone()
+                } else {
+                    format!("{}-{}", program_name, index)
+                },
+                backend_config: settings.to_backend_config(),
+            })
+            .collect()
+    }
+
+    /// Record the procedures that have been verified successfully, together with their
+    /// dependencies, and persist the dependency graph for the next run.
+    fn update_dependency_graph(
+        &self,
+        dependency_graph: &mut DependencyGraph,
+        procedures: &[ProcedureDefId],
+        failed_procedures: &HashSet<Option<ProcedureDefId>>,
+    ) {
+        // If some error could not be attributed, we cannot know which procedures verified.
+        let all_attributed = !failed_procedures.contains(&None);
+        let mut fingerprints = Fingerprints::new(self.env, &self.encoder);
+        for &proc_id in procedures {
+            if all_attributed && !failed_procedures.contains(&Some(proc_id)) {
+                dependency_graph.set_verified(&mut fingerprints, proc_id);
+            } else {
+                dependency_graph.set_not_verified(self.env, proc_id);
+            }
+        }
+        dependency_graph.save(&self.env.crate_name());
+    }
+
+    /// Write the JSON report of the verification, if it has been requested.
+    fn write_report(&self, report: Option<CrateReport>, success: bool) {
+        if let (Some(mut report), Some(report_dir)) = (report, config::json_report_dir()) {
+            report.finalize(success);
+            if let Err(err) = report.write_to_dir(&report_dir) {
+                error!("Failed to write the verification report to '{}': {}", report_dir, err);
+            }
         }
     }
 
@@ -321,3 +617,30 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         unimplemented!()
     }
 }
+
+/// Combine the results of the requests that verify the parts of a crate. The errors reported by
+/// several requests (e.g. those in the pure functions) are reported only once.
+fn merge_verification_results(
+    results: Vec<viper::VerificationResult>,
+) -> viper::VerificationResult {
+    let mut verification_errors = vec![];
+    let mut consistency_errors = vec![];
+    for result in results {
+        match result {
+            viper::VerificationResult::Success() => {}
+            viper::VerificationResult::Failure(errors) => verification_errors.extend(errors),
+            viper::VerificationResult::ConsistencyErrors(errors) => consistency_errors.extend(errors),
+        }
+    }
+    let mut seen = HashSet::new();
+    verification_errors.retain(|error| seen.insert(error.clone()));
+    let mut seen = HashSet::new();
+    consistency_errors.retain(|error| seen.insert(error.clone()));
+    if !consistency_errors.is_empty() {
+        viper::VerificationResult::ConsistencyErrors(consistency_errors)
+    } else if !verification_errors.is_empty() {
+        viper::VerificationResult::Failure(verification_errors)
+    } else {
+        viper::VerificationResult::Success()
+    }
+}
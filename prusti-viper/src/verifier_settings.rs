@@ -0,0 +1,105 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Per-procedure settings of the verifier, which are given with attributes on the procedure or
+//! on one of its enclosing items (e.g. an `impl` block or a module):
+//!
+//! * `#[prusti::timeout(60000)]` sets the Z3 timeout of each assertion, in milliseconds
+//!   (Silicon only), overriding `ASSERT_TIMEOUT`;
+//! * `#[prusti::backend(carbon)]` sets the Viper backend, overriding `VIPER_BACKEND`;
+//! * `#[prusti::verifier_args("--arg", ...)]` adds arguments to `EXTRA_VERIFIER_ARGS`.
+
+use crate::encoder::PrustiError;
+use prusti_common::{config, verification_service::ViperBackendConfig};
+use prusti_interface::data::ProcedureDefId;
+use prusti_interface::environment::Environment;
+use viper::VerificationBackend;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VerifierSettings {
+    pub backend: VerificationBackend,
+    pub assert_timeout: u64,
+    pub verifier_args: Vec<String>,
+}
+
+impl Default for VerifierSettings {
+    fn default() -> Self {
+        VerifierSettings {
+            backend: VerificationBackend::from_str(&config::viper_backend()),
+            assert_timeout: config::assert_timeout(),
+            verifier_args: config::extra_verifier_args(),
+        }
+    }
+}
+
+impl VerifierSettings {
+    /// Reads the settings of a procedure from its attributes and from those of the enclosing
+    /// items. Invalid attributes are reported and ignored.
+    pub fn of_procedure(env: &Environment, proc_id: ProcedureDefId) -> Self {
+        let (settings, errors) = Self::read(env, proc_id);
+        for error in errors {
+            error.emit(env);
+        }
+        settings
+    }
+
+    /// Like `of_procedure`, but returns the errors of the invalid attributes instead of
+    /// reporting them.
+    pub fn read(env: &Environment, proc_id: ProcedureDefId) -> (Self, Vec<PrustiError>) {
+        let mut settings = Self::default();
+        let mut errors = vec![];
+        let span = env.get_item_span(proc_id);
+
+        if let Some(args) = env.get_inherited_attribute_args(proc_id, "timeout") {
+            match args.as_slice() {
+                [timeout] if timeout.parse::<u64>().is_ok() => {
+                    settings.assert_timeout = timeout.parse().unwrap();
+                }
+                _ => errors.push(
+                    PrustiError::incorrect(
+                        format!("invalid verifier timeout {:?}", args),
+                        span.into(),
+                    )
+                    .add_help(
+                        "the timeout should be a number of milliseconds, e.g. `timeout(60000)`",
+                    ),
+                ),
+            }
+        }
+
+        if let Some(args) = env.get_inherited_attribute_args(proc_id, "backend") {
+            match args.as_slice() {
+                [backend] if backend.eq_ignore_ascii_case("silicon") => {
+                    settings.backend = VerificationBackend::Silicon;
+                }
+                [backend] if backend.eq_ignore_ascii_case("carbon") => {
+                    settings.backend = VerificationBackend::Carbon;
+                }
+                _ => errors.push(
+                    PrustiError::incorrect(
+                        format!("invalid verification backend {:?}", args),
+                        span.into(),
+                    )
+                    .add_help("the backend should be either `silicon` or `carbon`"),
+                ),
+            }
+        }
+
+        if let Some(args) = env.get_inherited_attribute_args(proc_id, "verifier_args") {
+            settings.verifier_args.extend(args);
+        }
+
+        (settings, errors)
+    }
+
+    pub fn to_backend_config(&self) -> ViperBackendConfig {
+        ViperBackendConfig::new(
+            self.backend,
+            self.assert_timeout,
+            self.verifier_args.clone(),
+        )
+    }
+}
@@ -6,7 +6,7 @@ extern crate rustc_driver;
 use log::debug;
 use prusti::PrustiCompilerCalls;
 use std::env;
-use prusti_common::config::ConfigFlags;
+use prusti_common::config;
 
 /// Initialize Prusti and the Rust compiler loggers.
 fn init_loggers() {
@@ -27,25 +27,26 @@ fn main() {
 
     init_loggers();
 
+    // The configuration reads the `--prusti-*` command line arguments.
+    config::register_env_vars(prusti::ENV_VARS);
+    if let Err(message) = config::check() {
+        eprintln!("error: {}", message);
+        std::process::exit(1);
+    }
+
     // We assume that prusti-rustc alread took care of the the compiler
     // arguments.
     let rustc_args: Vec<String> = env::args().collect();
 
+    if rustc_args.iter().any(|arg| arg == config::PRINT_CONFIG_FLAG) {
+        println!("{}", config::dump());
+        return;
+    }
+
     let mut args = Vec::new();
-    let mut flags = ConfigFlags::default();
     for arg in rustc_args {
         debug!("Arg: {}", arg);
-        if arg == "-Zprint-desugared-specs" {
-            flags.print_desugared_specs = true;
-        } else if arg == "-Zprint-typeckd-specs" {
-            flags.print_typeckd_specs = true;
-        } else if arg == "-Zprint-collected-verification-items" {
-            flags.print_collected_verfication_items = true;
-        } else if arg == "-Zskip-verify" {
-            flags.skip_verify = true;
-        } else if arg == "-Zhide-uuids" {
-            flags.hide_uuids = true;
-        } else {
+        if !config::is_prusti_argument(&arg) {
             args.push(arg);
         }
     }
@@ -58,7 +59,7 @@ fn main() {
     args.push("-Zdump-mir=renumber".to_owned());
     args.push("-Zalways-encode-mir".to_owned());
 
-    let mut callbacks = PrustiCompilerCalls::new(flags);
+    let mut callbacks = PrustiCompilerCalls::default();
 
     // Invoke compiler, and handle return code.
     let exit_code = rustc_driver::catch_with_exit_code(move || {
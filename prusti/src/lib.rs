@@ -21,7 +21,7 @@ extern crate smallvec;
 extern crate regex;
 
 use prusti_interface::specs;
-use prusti_common::config::ConfigFlags;
+use prusti_common::config;
 use rustc_driver::Compilation;
 use rustc_hir::intravisit;
 use rustc_interface::interface::Compiler;
@@ -30,15 +30,18 @@ use regex::Regex;
 
 mod verifier;
 
-pub struct PrustiCompilerCalls {
-    flags: ConfigFlags,
-}
+/// The environment variables read by `prusti-rustc` and `prusti-driver` that have the `PRUSTI_`
+/// prefix but are not configuration flags. The processes that can see them register them with
+/// `config::register_env_vars` before loading the configuration.
+pub const ENV_VARS: &[&str] = &[
+    "PRUSTI_BE_RUSTC",
+    "PRUSTI_LOG",
+    "PRUSTI_LOG_STYLE",
+    "PRUSTI_LOAD_ALL_PROC_MACRO_CRATES",
+];
 
-impl PrustiCompilerCalls {
-    pub fn new(flags: ConfigFlags) -> Self {
-        Self { flags }
-    }
-}
+#[derive(Default)]
+pub struct PrustiCompilerCalls;
 
 impl rustc_driver::Callbacks for PrustiCompilerCalls {
     fn after_expansion<'tcx>(
@@ -48,7 +51,7 @@ impl rustc_driver::Callbacks for PrustiCompilerCalls {
     ) -> Compilation {
         compiler.session().abort_if_errors();
         let (krate, _resolver, _lint_store) = &mut *queries.expansion().unwrap().peek_mut();
-        if self.flags.print_desugared_specs {
+        if config::print_desugared_specs() {
             rustc_driver::pretty::print_after_parsing(
                 compiler.session(),
                 compiler.input(),
@@ -73,14 +76,14 @@ impl rustc_driver::Callbacks for PrustiCompilerCalls {
             let mut visitor = specs::SpecCollector::new(tcx);
             intravisit::walk_crate(&mut visitor, &krate);
             let type_map = visitor.determine_typed_procedure_specs();
-            if self.flags.print_typeckd_specs {
+            if config::print_typeckd_specs() {
                 let uuid = Regex::new("[a-z0-9]{8}-[a-z0-9]{4}-[a-z0-9]{4}-[a-z0-9]{4}-[a-z0-9]{12}").unwrap();
                 let num_uuid = Regex::new("[a-z0-9]{32}").unwrap();
                 let mut values: Vec<_> = type_map
                     .values()
                     .map(|spec| format!("{:?}", spec))
                     .collect();
-                if self.flags.hide_uuids {
+                if config::hide_uuids() {
                     let mut replaced_values: Vec<String> = vec![];
                     for item in values {
                         let item = num_uuid.replace_all(&item, "$(NUM_UUID)");
@@ -96,8 +99,8 @@ impl rustc_driver::Callbacks for PrustiCompilerCalls {
                     println!("{}", value);
                 }
             }
-            if !self.flags.skip_verify {
-                verifier::verify(self.flags, tcx, type_map);
+            if !config::no_verify() {
+                verifier::verify(tcx, type_map);
             }
         });
 
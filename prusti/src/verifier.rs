@@ -8,11 +8,11 @@ use prusti_interface::{
 };
 use prusti_viper::verifier::Verifier;
 use rustc_middle::ty::TyCtxt;
-use prusti_common::config::ConfigFlags;
+use prusti_common::config;
 use prusti_common::report::user;
 
 
-pub fn verify<'tcx>(flags: ConfigFlags, tcx: TyCtxt<'tcx>, spec: typed::SpecificationMap<'tcx>) {
+pub fn verify<'tcx>(tcx: TyCtxt<'tcx>, spec: typed::SpecificationMap<'tcx>) {
     trace!("[verify] enter");
 
     let env = Environment::new(tcx);
@@ -34,7 +34,7 @@ pub fn verify<'tcx>(flags: ConfigFlags, tcx: TyCtxt<'tcx>, spec: typed::Specific
             verification_task.procedures.len()
         ));
 
-        if flags.print_collected_verfication_items {
+        if config::print_collected_verification_items() {
             println!("Collected verification items {}:", verification_task.procedures.len());
             for procedure in &verification_task.procedures {
                 println!("procedure: {} at {:?}", env.get_item_def_path(*procedure), env.get_item_span(*procedure));
@@ -56,6 +56,9 @@ fn run_tests(mode: &str, path: &str) {
 }
 
 fn test_runner(_tests: &[&()]) {
+    // The server loads the configuration in this process, which sets some of these variables.
+    prusti_common::config::register_env_vars(prusti::ENV_VARS);
+
     // spawn server process as child (so it stays around until main function terminates)
     let server_address = ServerSideService::spawn_off_thread();
     set_var("PRUSTI_SERVER_ADDRESS", server_address.to_string());
@@ -64,6 +67,8 @@ fn test_runner(_tests: &[&()]) {
     run_tests("ui", "tests/pass/typecheck");
 
     set_var("PRUSTI_QUIET", "true");
+    run_tests("ui", "tests/pass/verify");
+    run_tests("compile-fail", "tests/fail/verify");
     //run_tests("ui", "tests/verify/pass");
     //run_tests("compile-fail", "tests/verify/fail");
 }
@@ -0,0 +1,28 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+trait Plugin {
+    #[requires(x >= 0)]
+    #[ensures(result >= x)]
+    fn run(&self, x: i32) -> i32;
+}
+
+struct Increment;
+
+impl Plugin for Increment {
+    fn run(&self, x: i32) -> i32 {
+        x
+    }
+}
+
+fn run_plugin(plugin: &dyn Plugin, x: i32) -> i32 {
+    plugin.run(x) //~ ERROR precondition might not hold
+}
+
+fn borrow_mut(increment: &mut Increment) {
+    let _plugin: &mut dyn Plugin = increment; //~ ERROR casts of mutable references to trait objects are not supported
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[requires(x > 0)] //~ NOTE the precondition of the called function is here
+//~| NOTE the failing assertion is here
+fn positive(x: i32) {}
+
+fn call_in_loop(n: i32) {
+    let mut i = 0;
+    while i < n { //~ NOTE the enclosing loop is here
+        invariant!(i >= 0); //~ NOTE the loop invariant assumed here
+        positive(i); //~ ERROR precondition might not hold.
+        i += 1;
+    }
+}
+
+fn main() {}
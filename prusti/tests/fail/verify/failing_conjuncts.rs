@@ -0,0 +1,21 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[ensures(result >= x && result < x)] //~ ERROR postcondition might not hold.
+fn second_conjunct(x: u32) -> u32 {
+    x
+}
+
+#[ensures(x > 10 ==> (result > 10 && result == x + 1))] //~ ERROR postcondition might not hold.
+fn implication_consequent(x: u32) -> u32 {
+    x
+}
+
+#[ensures(forall(|i: usize| (0 <= i && i < 10) ==> i < result))] //~ ERROR postcondition might not hold.
+fn quantifier_body() -> usize {
+    5
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// compile-flags: --prusti-check-binary-operations
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn add(x: u32, y: u32) -> u32 {
+    x + y //~ ERROR the addition might overflow
+}
+
+fn multiply(x: i64, y: i64) -> i64 {
+    x * y //~ ERROR the multiplication might overflow
+}
+
+fn negate(x: i32) -> i32 {
+    -x //~ ERROR the negation might overflow
+}
+
+#[requires(y != 0)]
+fn divide(x: i32, y: i32) -> i32 {
+    x / y //~ ERROR the division might overflow
+}
+
+#[requires(x < 1000 && y < 1000)]
+fn checked_add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+fn main() {}
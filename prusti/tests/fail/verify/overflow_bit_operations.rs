@@ -0,0 +1,16 @@
+// compile-flags: --prusti-check-binary-operations
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn shl_out_of_range(x: u32, s: u32) -> u32 {
+    x << s //~ ERROR the shift amount may exceed the bit width
+}
+
+fn shr_out_of_range(x: i64, s: u32) -> i64 {
+    x >> s //~ ERROR the shift amount may exceed the bit width
+}
+
+fn main() {}
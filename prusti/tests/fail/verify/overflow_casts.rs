@@ -0,0 +1,38 @@
+// compile-flags: --prusti-check-binary-operations
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn truncate(x: u32) -> u8 {
+    x as u8 //~ ERROR the cast may truncate the value
+}
+
+fn negative_to_unsigned(x: i32) -> u32 {
+    x as u32 //~ ERROR the cast may truncate the value
+}
+
+fn large_to_signed(x: u64) -> i64 {
+    x as i64 //~ ERROR the cast may truncate the value
+}
+
+fn usize_to_u32(x: usize) -> u32 {
+    x as u32 //~ ERROR the cast may truncate the value
+}
+
+fn char_to_byte(c: char) -> u8 {
+    c as u8 //~ ERROR the cast may truncate the value
+}
+
+#[requires(x < 300)]
+fn off_by_one(x: u32) -> u8 {
+    x as u8 //~ ERROR the cast may truncate the value
+}
+
+#[pure]
+fn pure_truncate(x: i64) -> i32 {
+    x as i32 //~ ERROR the cast may truncate the value
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn divide(x: u32, y: u32) -> u32 {
+    x / y //~ ERROR the divisor might be zero
+}
+
+fn remainder(x: u32, y: u32) -> u32 {
+    x % y //~ ERROR the divisor of the remainder operation might be zero
+}
+
+#[requires(y != 0)]
+fn checked_divide(x: u32, y: u32) -> u32 {
+    x / y + x % y
+}
+
+#[pure]
+fn pure_divide(x: u32, y: u32) -> u32 {
+    x / y //~ ERROR the divisor might be zero
+}
+
+#[requires(y != 0)]
+#[pure]
+fn checked_pure_divide(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+#![feature(proc_macro_hygiene)]
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+predicate! {
+    fn positive(x: i32) -> bool {
+        x > 0
+    }
+}
+
+#[requires(positive(x))]
+#[ensures(positive(result))] //~ ERROR postcondition might not hold.
+fn decrement(x: i32) -> i32 {
+    x - 1
+}
+
+fn call_from_code(x: i32) -> bool {
+    positive(x) //~ ERROR using predicate from non-specification code is not allowed
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[requires(acc(p))]
+#[ensures(acc(p))]
+unsafe fn reset(p: *mut u32) {
+    *p = 0;
+}
+
+#[requires(own(p))]
+unsafe fn free(p: *mut u32) {
+    drop(Box::from_raw(p));
+}
+
+#[requires(own(p))]
+unsafe fn use_after_free(p: *mut u32) {
+    free(p);
+    reset(p); //~ ERROR the permission required by the function call might not be held.
+}
+
+#[requires(acc(p))]
+#[ensures(acc(p))]
+#[ensures(unsafe { *p == 1 })] //~ ERROR postcondition might not hold.
+unsafe fn wrong_value(p: *mut u32) {
+    reset(p);
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn unwrap_none(x: Option<u32>) -> u32 {
+    x.unwrap() //~ ERROR unwrap might be called on a None or Err value
+}
+
+#[requires(v.len() == 1)]
+fn index_out_of_bounds(v: &Vec<i32>) -> i32 {
+    v[1] //~ ERROR the index might be out of bounds
+}
+
+#[requires(v.len() == 1)]
+fn index_mut_out_of_bounds(v: &mut Vec<i32>) {
+    v[1] = 0; //~ ERROR the index might be out of bounds
+}
+
+#[ensures(result.len() == 1)] //~ ERROR postcondition might not hold.
+fn wrong_len() -> Vec<i32> {
+    let mut v = Vec::new();
+    v.push(1);
+    v.push(2);
+    v
+}
+
+fn main() {}
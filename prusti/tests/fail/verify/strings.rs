@@ -0,0 +1,20 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[ensures(result.len() == 2)] //~ ERROR postcondition might not hold.
+fn wrong_len() -> &'static str {
+    "abc"
+}
+
+fn slice_out_of_bounds(s: &str) -> &str {
+    &s[0..2] //~ ERROR the string might be sliced out of bounds or not at a char boundary
+}
+
+#[requires(s.len() >= 2)]
+fn slice_in_char(s: &str) -> &str {
+    &s[0..1] //~ ERROR the string might be sliced out of bounds or not at a char boundary
+}
+
+fn main() {}
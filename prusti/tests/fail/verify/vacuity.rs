@@ -0,0 +1,41 @@
+// compile-flags: --prusti-check-vacuity
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[requires(x > 0 && x < 0)] //~ WARNING the precondition is unsatisfiable
+fn contradictory_precondition(x: i32) -> i32 {
+    x
+}
+
+#[requires(x > 0)]
+fn satisfiable_precondition(x: i32) -> i32 {
+    x
+}
+
+#[requires(x > 10)]
+fn unreachable_branch(x: i32) -> i32 {
+    if x > 5 { 1 } else { 2 } //~ WARNING this branch is unreachable
+}
+
+fn reachable_branches(x: i32) -> i32 {
+    if x > 5 { 1 } else { 2 }
+}
+
+// The unreachable branch is not reported, because the procedure does not verify.
+#[requires(x > 10)]
+#[ensures(result > 1)] //~ ERROR postcondition might not hold.
+fn failing_procedure(x: i32) -> i32 {
+    if x > 5 { 1 } else { 2 }
+}
+
+// The failing vacuity checks of the branches do not hide the error after them.
+fn error_after_branches(x: i32) -> i32 {
+    let y = if x > 5 { 1 } else { 2 };
+    assert!(y == 1); //~ ERROR
+    y
+}
+
+fn main() {}
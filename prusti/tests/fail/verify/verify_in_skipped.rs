@@ -0,0 +1,23 @@
+#![feature(proc_macro_hygiene)]
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[skip]
+mod skipped_module {
+    use prusti_contracts::*;
+
+    #[ensures(result == 1)]
+    pub fn one() -> i32 {
+        2
+    }
+
+    #[verify]
+    #[ensures(result == 3)] //~ ERROR postcondition might not hold.
+    pub fn three() -> i32 {
+        4
+    }
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+trait Plugin {
+    #[requires(x >= 0)]
+    #[ensures(result >= x)]
+    fn run(&self, x: i32) -> i32;
+}
+
+struct Identity;
+
+impl Plugin for Identity {
+    fn run(&self, x: i32) -> i32 {
+        x
+    }
+}
+
+struct Increment {
+    step: i32,
+}
+
+impl Plugin for Increment {
+    fn run(&self, x: i32) -> i32 {
+        if self.step > 0 && self.step < 1000 {
+            x + self.step
+        } else {
+            x
+        }
+    }
+}
+
+#[requires(x >= 0)]
+#[ensures(result >= x)]
+fn run_plugin(plugin: &dyn Plugin, x: i32) -> i32 {
+    plugin.run(x)
+}
+
+#[ensures(result >= 3)]
+fn run_boxed(plugin: Box<dyn Plugin>) -> i32 {
+    plugin.run(3)
+}
+
+fn test() {
+    let identity = Identity;
+    let result = run_plugin(&identity, 5);
+    assert!(result >= 5);
+}
+
+fn main() {}
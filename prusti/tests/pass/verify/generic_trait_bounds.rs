@@ -0,0 +1,51 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+trait Measure {
+    #[pure]
+    #[ensures(result >= 0)]
+    fn size(&self) -> i32;
+}
+
+struct Block {
+    len: i32,
+}
+
+impl Measure for Block {
+    #[pure]
+    fn size(&self) -> i32 {
+        if self.len >= 0 { self.len } else { 0 }
+    }
+}
+
+#[ensures(result >= 0)]
+fn total_size<T: Measure>(a: &T, b: &T) -> i32 {
+    let x = a.size();
+    let y = b.size();
+    if x < 1000 && y < 1000 { x + y } else { 0 }
+}
+
+#[ensures(result == a.size())]
+fn get_size<T: Measure>(a: &T) -> i32 {
+    a.size()
+}
+
+#[ensures(result == a || result == b)]
+fn max<T: PartialOrd + Copy>(a: T, b: T) -> T {
+    if a < b { b } else { a }
+}
+
+fn test_max() {
+    let m = max(3, 5);
+    assert!(m == 3 || m == 5);
+}
+
+fn test_size() {
+    let block = Block { len: 3 };
+    let s = get_size(&block);
+    assert!(s == 3);
+}
+
+fn main() {}
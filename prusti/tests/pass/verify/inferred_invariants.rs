@@ -0,0 +1,38 @@
+// compile-flags: --prusti-infer-loop-invariants --prusti-encode-unsigned-num-constraint
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn count_up(n: u32) {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    assert!(i == n);
+}
+
+fn count_together(n: u32) -> u32 {
+    let mut i = 0;
+    let mut j = 0;
+    while i < n {
+        i += 1;
+        j += 1;
+    }
+    assert!(i == j);
+    j
+}
+
+fn count_down(n: i32) {
+    let mut i = n;
+    let mut steps = 0;
+    while i > 0 {
+        i -= 1;
+        steps += 1;
+    }
+    assert!(i <= 0);
+    assert!(steps >= 0);
+}
+
+fn main() {}
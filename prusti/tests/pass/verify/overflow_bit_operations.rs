@@ -0,0 +1,60 @@
+// compile-flags: --prusti-check-binary-operations
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[ensures(result == x * 8)]
+fn shl_by_constant(x: u8) -> u32 {
+    (x as u32) << 3
+}
+
+#[ensures(result == x / 16)]
+fn shr_by_constant(x: u32) -> u32 {
+    x >> 4
+}
+
+#[ensures(result == 255 - x)]
+fn not_u8(x: u8) -> u8 {
+    !x
+}
+
+#[ensures(result == -x - 1)]
+fn not_i32(x: i32) -> i32 {
+    !x
+}
+
+#[ensures(result <= x && result <= y)]
+fn bitand_upper_bound(x: u32, y: u32) -> u32 {
+    x & y
+}
+
+#[ensures(result >= x && result >= y)]
+fn bitor_lower_bound(x: u32, y: u32) -> u32 {
+    x | y
+}
+
+#[ensures(result == 0)]
+fn xor_self(x: u64) -> u64 {
+    x ^ x
+}
+
+#[requires(s < 32)]
+fn shl_in_range(x: u32, s: u32) -> u32 {
+    x << s
+}
+
+#[ensures(result == 0)]
+fn wrapping_add_max() -> u8 {
+    255u8.wrapping_add(1)
+}
+
+fn checked_add(x: u8) {
+    match x.checked_add(1) {
+        Some(y) => assert!(y == x + 1),
+        None => assert!(x == 255),
+    }
+}
+
+fn main() {}
@@ -0,0 +1,64 @@
+// compile-flags: --prusti-check-binary-operations
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[ensures(result == x)]
+fn widen(x: u8) -> u64 {
+    x as u64
+}
+
+#[ensures(result == x)]
+fn unsigned_to_larger_signed(x: u32) -> i64 {
+    x as i64
+}
+
+#[ensures(result == x)]
+fn u64_to_i128(x: u64) -> i128 {
+    x as i128
+}
+
+#[requires(x < 256)]
+#[ensures(result == x)]
+fn narrow_in_range(x: u32) -> u8 {
+    x as u8
+}
+
+#[requires(x >= 0)]
+#[ensures(result == x)]
+fn non_negative_to_unsigned(x: i32) -> u32 {
+    x as u32
+}
+
+#[requires(x <= 4294967295)]
+#[ensures(result == x)]
+fn u64_to_usize_to_u32(x: u64) -> u32 {
+    let y = x as usize;
+    y as u32
+}
+
+#[ensures(result == 1)]
+fn bool_to_int() -> i32 {
+    true as i32
+}
+
+#[ensures(result == 97)]
+fn char_to_int() -> u32 {
+    'a' as u32
+}
+
+#[ensures(result == x)]
+fn byte_to_char_to_u32(x: u8) -> u32 {
+    (x as char) as u32
+}
+
+#[requires(x >= -128 && x <= 127)]
+#[ensures(result == x)]
+#[pure]
+fn pure_narrow_in_range(x: i64) -> i8 {
+    x as i8
+}
+
+fn main() {}
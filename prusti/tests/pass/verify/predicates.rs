@@ -0,0 +1,68 @@
+#![feature(proc_macro_hygiene)]
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+pub struct VecWrapperI32 {
+    v: Vec<i32>,
+}
+
+impl VecWrapperI32 {
+    #[trusted]
+    #[pure]
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    #[trusted]
+    #[pure]
+    #[requires(0 <= index && index < self.len())]
+    pub fn lookup(&self, index: usize) -> i32 {
+        self.v[index]
+    }
+}
+
+predicate! {
+    fn in_range(x: i32, lower: i32, upper: i32) -> bool {
+        lower <= x && x < upper
+    }
+}
+
+predicate! {
+    fn positive_if(b: bool, x: i32) -> bool {
+        b ==> x > 0
+    }
+}
+
+predicate! {
+    fn sorted(v: &VecWrapperI32) -> bool {
+        forall(|i: usize, j: usize| (0 <= i && i < j && j < v.len()) ==> v.lookup(i) <= v.lookup(j))
+    }
+}
+
+predicate! {
+    fn sorted_and_non_empty(v: &VecWrapperI32) -> bool {
+        sorted(v) && v.len() > 0
+    }
+}
+
+#[requires(in_range(x, 0, 10))]
+#[ensures(in_range(result, 1, 11))]
+fn increment(x: i32) -> i32 {
+    x + 1
+}
+
+#[requires(positive_if(b, x))]
+#[ensures(b ==> result > 1)]
+fn double_if(b: bool, x: i32) -> i32 {
+    if b { x + x } else { 0 }
+}
+
+#[requires(sorted_and_non_empty(v) && v.len() > 1)]
+#[ensures(result <= v.lookup(1))]
+fn first(v: &VecWrapperI32) -> i32 {
+    v.lookup(0)
+}
+
+fn main() {}
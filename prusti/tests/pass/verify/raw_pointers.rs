@@ -0,0 +1,47 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[requires(acc(p))]
+#[ensures(acc(p))]
+#[ensures(unsafe { *p == 0 })]
+unsafe fn reset(p: *mut u32) {
+    *p = 0;
+}
+
+#[requires(acc(p))]
+#[ensures(acc(p))]
+unsafe fn read_twice(p: *const u32) -> u32 {
+    let a = std::ptr::read(p);
+    let b = std::ptr::read(p);
+    assert!(a == b);
+    a
+}
+
+#[requires(own(p))]
+unsafe fn free(p: *mut u32) {
+    drop(Box::from_raw(p));
+}
+
+fn round_trip() {
+    let b = Box::new(5u32);
+    let p = Box::into_raw(b);
+    unsafe {
+        reset(p);
+        assert!(*p == 0);
+        std::ptr::write(p, 7);
+        assert!(read_twice(p) == 7);
+        let b = Box::from_raw(p);
+        assert!(*b == 7);
+    }
+}
+
+fn leak_and_free() {
+    let p = Box::into_raw(Box::new(3u32));
+    unsafe {
+        free(p);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+#![feature(proc_macro_hygiene)]
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+// The body does not satisfy the contract, but it is not verified.
+#[skip]
+#[ensures(result > 0)]
+fn skipped() -> i32 {
+    0
+}
+
+#[skip]
+mod skipped_module {
+    use prusti_contracts::*;
+
+    #[ensures(result == 1)]
+    pub fn one() -> i32 {
+        2
+    }
+
+    #[verify]
+    #[ensures(result == 3)]
+    pub fn three() -> i32 {
+        3
+    }
+}
+
+// The contracts of the skipped functions are used at call sites.
+#[ensures(result > 0)]
+fn use_skipped() -> i32 {
+    skipped()
+}
+
+#[ensures(result == 4)]
+fn use_skipped_module() -> i32 {
+    skipped_module::one() + skipped_module::three()
+}
+
+fn main() {}
@@ -0,0 +1,57 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[ensures(result.len() == 2)]
+#[ensures(result[0] == a && result[1] == b)]
+fn pair(a: i32, b: i32) -> Vec<i32> {
+    let mut v = Vec::new();
+    v.push(a);
+    v.push(b);
+    v
+}
+
+#[requires(v.len() > 0)]
+#[ensures(v.len() == old(v.len()) - 1)]
+fn drop_last(v: &mut Vec<i32>) {
+    let last = v.pop();
+    assert!(last.is_some());
+}
+
+#[requires(v.len() > 0)]
+fn reset_first(v: &mut Vec<i32>) {
+    v[0] = 0;
+}
+
+#[requires(x.is_some())]
+#[ensures(result == x.unwrap())]
+fn get(x: Option<u32>) -> u32 {
+    x.unwrap()
+}
+
+#[ensures(x.is_none() ==> result == 0)]
+fn get_or_zero(x: Option<u32>) -> u32 {
+    x.unwrap_or(0)
+}
+
+#[ensures(result.is_ok() == x.is_some())]
+fn to_result(x: Option<u32>) -> Result<u32, bool> {
+    x.ok_or(false)
+}
+
+#[ensures(x.is_err() ==> result.is_err())]
+fn increment(x: Result<u32, bool>) -> Result<u32, bool> {
+    let value = x?;
+    if value < 100 {
+        Ok(value + 1)
+    } else {
+        Ok(value)
+    }
+}
+
+fn main() {
+    let v = pair(1, 2);
+    assert!(v[0] + v[1] == 3);
+    assert!(get_or_zero(None) == 0);
+}
@@ -0,0 +1,47 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[pure]
+#[requires(s.len() > 0)]
+fn first_byte(s: &str) -> u8 {
+    s.as_bytes()[0]
+}
+
+#[requires(s.starts_with("ab"))]
+#[ensures(result == b'a')]
+fn first_of_ab(s: &str) -> u8 {
+    first_byte(s)
+}
+
+#[ensures(result.len() == 3)]
+#[ensures(result == "abc")]
+fn abc() -> &'static str {
+    "abc"
+}
+
+#[ensures(result.len() == a.len() + b.len())]
+#[ensures(result.starts_with(a))]
+fn concat(a: &str, b: &str) -> String {
+    let mut s = String::new();
+    s.push_str(a);
+    s.push_str(b);
+    s
+}
+
+#[requires(s.len() >= 2)]
+#[requires(s.as_bytes()[0] < 128 && s.as_bytes()[1] < 128)]
+#[requires(s.len() == 2 || s.as_bytes()[2] < 128)]
+#[ensures(result.len() == 2)]
+fn prefix(s: &str) -> &str {
+    &s[0..2]
+}
+
+fn main() {
+    let s = abc();
+    assert!(s.len() == 3);
+    assert!(!s.is_empty());
+    let t = concat(s, "de");
+    assert!(t.len() == 5);
+}
@@ -0,0 +1,29 @@
+// compile-flags: --prusti-check-binary-operations --prusti-check-overflows=false
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+fn negate(x: i32) -> i32 {
+    -x
+}
+
+fn truncate(x: u32) -> u8 {
+    x as u8
+}
+
+fn shift(x: u32, y: u32) -> u32 {
+    x << y
+}
+
+#[requires(y != 0)]
+fn divide(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// compile-flags: --prusti-check-division-by-zero=false --prusti-check-unwrap=false --prusti-check-index-bounds=false
+
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+fn divide(x: u32, y: u32) -> u32 {
+    x / y + x % y
+}
+
+#[pure]
+fn pure_divide(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+fn unwrap(x: Option<u32>) -> u32 {
+    x.unwrap()
+}
+
+fn index(v: &Vec<i32>) -> i32 {
+    v[1]
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+#![feature(register_tool)]
+#![register_tool(prusti)]
+
+use prusti_contracts::*;
+
+#[timeout(60000)]
+#[ensures(result == a * b * c)]
+fn product(a: u64, b: u64, c: u64) -> u64 {
+    a * b * c
+}
+
+#[verifier_args("--checkTimeout", "20")]
+#[ensures(result >= x)]
+fn at_least(x: u32) -> u32 {
+    if x < 10 { 10 } else { x }
+}
+
+#[backend(silicon)]
+#[timeout(1000)]
+#[requires(x < 100)]
+#[ensures(result == x + 1)]
+fn inc(x: u32) -> u32 {
+    x + 1
+}
+
+fn main() {}
@@ -94,7 +94,15 @@ fn main() {
             java_class!("java.nio.file.Paths", vec![
                 method!("get", "(Ljava/lang/String;[Ljava/lang/String;)Ljava/nio/file/Path;"),
             ]),
+            // Fastparse
+            java_class!("fastparse.core.Parsed$Success", vec![
+                method!("value"),
+            ]),
             // Scala
+            java_class!("scala.Option", vec![
+                method!("isDefined"),
+                method!("get"),
+            ]),
             java_class!("scala.Some", vec![
                 constructor!(),
             ]),
@@ -145,6 +153,40 @@ fn main() {
             java_class!("viper.silver.reporter.NoopReporter$", vec![
                 object_getter!(),
             ]),
+            java_class!("viper.silver.reporter.PollingReporter", vec![
+                constructor!("(Ljava/lang/String;Lviper/silver/reporter/Reporter;)V"),
+                method!("hasNewMessage"),
+                method!("getNewMessage"),
+            ]),
+            java_class!("viper.silver.reporter.EntitySuccessMessage", vec![
+                method!("verifier"),
+                method!("concerning"),
+                method!("verificationTime"),
+                method!("cached"),
+            ]),
+            java_class!("viper.silver.reporter.EntityFailureMessage", vec![
+                method!("verifier"),
+                method!("concerning"),
+                method!("verificationTime"),
+                method!("result"),
+                method!("cached"),
+            ]),
+            java_class!("viper.silver.reporter.OverallSuccessMessage", vec![
+                method!("verifier"),
+                method!("verificationTime"),
+            ]),
+            java_class!("viper.silver.reporter.OverallFailureMessage", vec![
+                method!("verifier"),
+                method!("verificationTime"),
+                method!("result"),
+            ]),
+            java_class!("viper.silver.reporter.StatisticsReport", vec![
+                method!("nOfMethods"),
+                method!("nOfFunctions"),
+                method!("nOfPredicates"),
+                method!("nOfDomains"),
+                method!("nOfFields"),
+            ]),
             java_class!("viper.silver.verifier.Verifier", vec![
                 method!("name"),
                 method!("buildVersion"),
@@ -153,6 +195,19 @@ fn main() {
                 method!("stop"),
                 method!("verify"),
             ]),
+            java_class!("viper.silver.parser.FastParser$", vec![
+                object_getter!(),
+                method!("parse"),
+            ]),
+            java_class!("viper.silver.parser.Resolver", vec![
+                constructor!(),
+                method!("run"),
+                method!("messages"),
+            ]),
+            java_class!("viper.silver.parser.Translator", vec![
+                constructor!(),
+                method!("translate"),
+            ]),
             java_class!("viper.silver.ast.pretty.FastPrettyPrinter$", vec![
                 object_getter!(),
                 method!("pretty", "(Lviper/silver/ast/Node;)Ljava/lang/String;")
@@ -395,6 +450,9 @@ fn main() {
             java_class!("viper.silver.ast.MagicWand", vec![
                 constructor!(),
             ]),
+            java_class!("viper.silver.ast.Member", vec![
+                method!("name"),
+            ]),
             java_class!("viper.silver.ast.Method", vec![
                 constructor!(),
             ]),
@@ -0,0 +1,115 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Parses and verifies a Viper program written in the textual syntax, e.g. a program dumped by
+//! Prusti with `DUMP_VIPER_PROGRAM`.
+//!
+//! Usage: `viper-verify [--backend silicon|carbon] FILE.vpr [-- VERIFIER_ARGS...]`
+
+extern crate env_logger;
+extern crate viper;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use viper::{VerificationBackend, VerificationResult, Viper};
+
+const USAGE: &str = "Usage: viper-verify [--backend silicon|carbon] FILE.vpr [-- VERIFIER_ARGS...]";
+
+struct Arguments {
+    backend: VerificationBackend,
+    file: PathBuf,
+    verifier_args: Vec<String>,
+}
+
+fn parse_arguments(args: Vec<String>) -> Result<Arguments, String> {
+    let mut backend = VerificationBackend::Silicon;
+    let mut file = None;
+    let mut verifier_args = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "Missing value for '--backend'".to_string())?;
+                backend = match value.to_lowercase().as_str() {
+                    "silicon" => VerificationBackend::Silicon,
+                    "carbon" => VerificationBackend::Carbon,
+                    _ => return Err(format!("Invalid verification backend: '{}'", value)),
+                };
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            "--" => verifier_args.extend(args.by_ref()),
+            _ if file.is_none() => file = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+    match file {
+        Some(file) => Ok(Arguments {
+            backend,
+            file,
+            verifier_args,
+        }),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let arguments = parse_arguments(env::args().skip(1).collect()).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+
+    let source = fs::read_to_string(&arguments.file).unwrap_or_else(|err| {
+        eprintln!("Failed to read '{}': {}", arguments.file.display(), err);
+        process::exit(2);
+    });
+
+    let viper = Viper::new_with_args(vec![], arguments.backend);
+    let verification_context = viper.new_verification_context();
+
+    let program = verification_context
+        .new_program_parser()
+        .parse(&source, &arguments.file)
+        .unwrap_or_else(|messages| {
+            eprintln!("Failed to parse '{}':", arguments.file.display());
+            for message in messages {
+                eprintln!("  {}", message);
+            }
+            process::exit(2);
+        });
+
+    let verifier = verification_context.new_verifier_with_args(
+        arguments.backend,
+        arguments.verifier_args,
+        None,
+    );
+
+    match verifier.verify(program) {
+        VerificationResult::Success() => {
+            println!("Verification of '{}' succeeded", arguments.file.display());
+        }
+        VerificationResult::Failure(errors) => {
+            println!("Verification of '{}' failed:", arguments.file.display());
+            for error in errors {
+                let position = error.pos_id.as_ref().map(String::as_str).unwrap_or("?");
+                println!("  [{}] {} ({})", position, error.message, error.full_id);
+            }
+            process::exit(1);
+        }
+        VerificationResult::ConsistencyErrors(errors) => {
+            println!("The program '{}' is not consistent:", arguments.file.display());
+            for error in errors {
+                println!("  {}", error);
+            }
+            process::exit(1);
+        }
+    }
+}
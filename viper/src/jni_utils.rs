@@ -102,6 +102,16 @@ impl<'a> JniUtils<'a> {
         }
     }
 
+    /// Converts a Scala Option to a Rust Option<JObject>
+    pub fn get_option(&self, option: JObject<'a>) -> Option<JObject<'a>> {
+        let option_wrapper = scala::Option::with(self.env);
+        if self.unwrap_result(option_wrapper.call_isDefined(option)) {
+            Some(self.unwrap_result(option_wrapper.call_get(option)))
+        } else {
+            None
+        }
+    }
+
     /// Converts a Rust String to a Java String
     pub fn new_string<S: Into<JNIString>>(&self, string: S) -> JObject {
         self.unwrap_result(self.env.new_string(string)).into()
@@ -21,18 +21,22 @@ mod ast_factory;
 mod ast_utils;
 pub mod errors;
 mod jni_utils;
+mod program_parser;
 #[macro_use]
 pub mod utils;
 mod verification_backend;
 mod verification_context;
+mod verification_message;
 mod verification_result;
 mod verifier;
 mod viper;
 
 pub use ast_factory::*;
 pub use ast_utils::*;
+pub use program_parser::*;
 pub use verification_backend::*;
 pub use verification_context::*;
+pub use verification_message::*;
 pub use verification_result::*;
 pub use verifier::*;
 pub use viper::*;
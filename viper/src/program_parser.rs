@@ -0,0 +1,86 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use ast_factory::Program;
+use jni::JNIEnv;
+use jni_utils::JniUtils;
+use std::path::Path;
+use viper_sys::wrappers::fastparse;
+use viper_sys::wrappers::java;
+use viper_sys::wrappers::viper::silver::parser;
+
+/// Parses Viper programs written in the textual syntax, using the frontend of Silver.
+#[derive(Clone, Copy)]
+pub struct ProgramParser<'a> {
+    env: &'a JNIEnv<'a>,
+    jni: JniUtils<'a>,
+}
+
+impl<'a> ProgramParser<'a> {
+    pub fn new(env: &'a JNIEnv) -> Self {
+        let jni = JniUtils::new(env);
+        ProgramParser { env, jni }
+    }
+
+    /// Parses, resolves and translates the source of a Viper program.
+    /// The path of the file is only used to resolve the imports and to report positions.
+    ///
+    /// On failure, returns the messages reported by the first phase that failed.
+    pub fn parse(&self, source: &str, file_path: &Path) -> Result<Program<'a>, Vec<String>> {
+        let path = self.jni.unwrap_result(java::nio::file::Paths::with(self.env).call_get(
+            self.jni.new_string(file_path.to_string_lossy()),
+            self.jni.new_object_array(0),
+        ));
+
+        let fast_parser_wrapper = parser::FastParser_object::with(self.env);
+        let parsed = self.jni.unwrap_result(fast_parser_wrapper.call_parse(
+            self.jni.unwrap_result(fast_parser_wrapper.singleton()),
+            self.jni.new_string(source),
+            path,
+            self.jni.new_option(None),
+        ));
+        if !self
+            .jni
+            .is_instance_of(parsed, "fastparse/core/Parsed$Success")
+        {
+            return Err(vec![self.jni.to_string(parsed)]);
+        }
+        let parse_program = self.jni.unwrap_result(
+            fastparse::core::Parsed_objectSuccess::with(self.env).call_value(parsed),
+        );
+
+        let resolver_wrapper = parser::Resolver::with(self.env);
+        let resolver = self.jni.unwrap_result(resolver_wrapper.new(parse_program));
+        let resolved_program = self.jni.get_option(
+            self.jni.unwrap_result(resolver_wrapper.call_run(resolver)),
+        );
+        let resolved_program = match resolved_program {
+            Some(resolved_program) => resolved_program,
+            None => {
+                let messages = self.jni.unwrap_result(resolver_wrapper.call_messages(resolver));
+                return Err(self
+                    .jni
+                    .seq_to_vec(messages)
+                    .into_iter()
+                    .map(|message| self.jni.to_string(message))
+                    .collect());
+            }
+        };
+
+        let translator_wrapper = parser::Translator::with(self.env);
+        let translator = self.jni.unwrap_result(translator_wrapper.new(resolved_program));
+        let program = self.jni.get_option(
+            self.jni.unwrap_result(translator_wrapper.call_translate(translator)),
+        );
+        match program {
+            Some(program) => Ok(Program::new(program)),
+            None => Err(vec![format!(
+                "Failed to translate the parsed program '{}'",
+                file_path.display()
+            )]),
+        }
+    }
+}
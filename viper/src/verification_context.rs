@@ -7,6 +7,7 @@
 use ast_factory::*;
 use ast_utils::*;
 use jni::AttachGuard;
+use program_parser::ProgramParser;
 use std::env;
 use std::path::{Path, PathBuf};
 use verification_backend::VerificationBackend;
@@ -30,6 +31,10 @@ impl<'a> VerificationContext<'a> {
         AstUtils::new(&self.env)
     }
 
+    pub fn new_program_parser(&self) -> ProgramParser {
+        ProgramParser::new(&self.env)
+    }
+
     pub fn new_verifier(&self, backend: VerificationBackend, report_path: Option<PathBuf>) -> Verifier<state::Started> {
         self.new_verifier_with_args(backend, vec![], report_path)
     }
@@ -0,0 +1,143 @@
+// © 2020, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use jni::objects::JObject;
+use jni::JNIEnv;
+use jni_utils::JniUtils;
+use viper_sys::wrappers::viper::*;
+
+/// A message reported by the Viper verifier *while* it verifies a program.
+///
+/// These messages are collected from Silver's reporter interface (see
+/// `viper.silver.reporter.Message`) and forwarded to Rust through a channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationMessage {
+    /// A member (method, function or predicate) has been verified successfully.
+    EntitySuccess {
+        verifier: String,
+        entity_name: String,
+        /// Verification time in milliseconds.
+        verification_time: u64,
+        cached: bool,
+    },
+    /// The verification of a member (method, function or predicate) failed.
+    EntityFailure {
+        verifier: String,
+        entity_name: String,
+        /// Verification time in milliseconds.
+        verification_time: u64,
+        /// The readable messages of the errors reported for the member.
+        errors: Vec<String>,
+        cached: bool,
+    },
+    /// The whole program has been verified successfully.
+    OverallSuccess {
+        verifier: String,
+        /// Verification time in milliseconds.
+        verification_time: u64,
+    },
+    /// The verification of the whole program failed.
+    OverallFailure {
+        verifier: String,
+        /// Verification time in milliseconds.
+        verification_time: u64,
+        errors: Vec<String>,
+    },
+    /// Statistics about the program that is going to be verified.
+    Statistics {
+        methods: u32,
+        functions: u32,
+        predicates: u32,
+        domains: u32,
+        fields: u32,
+    },
+    /// Any other message (e.g. a branch failure), identified by the name of its Java class.
+    Other { kind: String, text: String },
+}
+
+impl VerificationMessage {
+    /// Converts an instance of `viper.silver.reporter.Message` to a `VerificationMessage`.
+    pub(crate) fn from_jobject(env: &JNIEnv, message: JObject) -> Self {
+        let jni = JniUtils::new(env);
+        if jni.is_instance_of(message, "viper/silver/reporter/EntitySuccessMessage") {
+            let wrapper = silver::reporter::EntitySuccessMessage::with(env);
+            VerificationMessage::EntitySuccess {
+                verifier: jni.get_string(jni.unwrap_result(wrapper.call_verifier(message))),
+                entity_name: get_entity_name(env, jni.unwrap_result(wrapper.call_concerning(message))),
+                verification_time: jni.unwrap_result(wrapper.call_verificationTime(message)) as u64,
+                cached: jni.unwrap_result(wrapper.call_cached(message)) != 0,
+            }
+        } else if jni.is_instance_of(message, "viper/silver/reporter/EntityFailureMessage") {
+            let wrapper = silver::reporter::EntityFailureMessage::with(env);
+            VerificationMessage::EntityFailure {
+                verifier: jni.get_string(jni.unwrap_result(wrapper.call_verifier(message))),
+                entity_name: get_entity_name(env, jni.unwrap_result(wrapper.call_concerning(message))),
+                verification_time: jni.unwrap_result(wrapper.call_verificationTime(message)) as u64,
+                errors: get_failure_messages(env, jni.unwrap_result(wrapper.call_result(message))),
+                cached: jni.unwrap_result(wrapper.call_cached(message)) != 0,
+            }
+        } else if jni.is_instance_of(message, "viper/silver/reporter/OverallSuccessMessage") {
+            let wrapper = silver::reporter::OverallSuccessMessage::with(env);
+            VerificationMessage::OverallSuccess {
+                verifier: jni.get_string(jni.unwrap_result(wrapper.call_verifier(message))),
+                verification_time: jni.unwrap_result(wrapper.call_verificationTime(message)) as u64,
+            }
+        } else if jni.is_instance_of(message, "viper/silver/reporter/OverallFailureMessage") {
+            let wrapper = silver::reporter::OverallFailureMessage::with(env);
+            VerificationMessage::OverallFailure {
+                verifier: jni.get_string(jni.unwrap_result(wrapper.call_verifier(message))),
+                verification_time: jni.unwrap_result(wrapper.call_verificationTime(message)) as u64,
+                errors: get_failure_messages(env, jni.unwrap_result(wrapper.call_result(message))),
+            }
+        } else if jni.is_instance_of(message, "viper/silver/reporter/StatisticsReport") {
+            let wrapper = silver::reporter::StatisticsReport::with(env);
+            VerificationMessage::Statistics {
+                methods: jni.unwrap_result(wrapper.call_nOfMethods(message)) as u32,
+                functions: jni.unwrap_result(wrapper.call_nOfFunctions(message)) as u32,
+                predicates: jni.unwrap_result(wrapper.call_nOfPredicates(message)) as u32,
+                domains: jni.unwrap_result(wrapper.call_nOfDomains(message)) as u32,
+                fields: jni.unwrap_result(wrapper.call_nOfFields(message)) as u32,
+            }
+        } else {
+            VerificationMessage::Other {
+                kind: jni.class_name(message),
+                text: jni.to_string(message),
+            }
+        }
+    }
+
+    /// The name of the member this message is about, if any.
+    pub fn entity_name(&self) -> Option<&str> {
+        match self {
+            VerificationMessage::EntitySuccess { entity_name, .. }
+            | VerificationMessage::EntityFailure { entity_name, .. } => Some(entity_name),
+            _ => None,
+        }
+    }
+}
+
+fn get_entity_name(env: &JNIEnv, entity: JObject) -> String {
+    let jni = JniUtils::new(env);
+    jni.get_string(jni.unwrap_result(silver::ast::Member::with(env).call_name(entity)))
+}
+
+fn get_failure_messages(env: &JNIEnv, failure: JObject) -> Vec<String> {
+    let jni = JniUtils::new(env);
+    let verification_error_wrapper = silver::verifier::VerificationError::with(env);
+    let errors = jni.unwrap_result(silver::verifier::Failure::with(env).call_errors(failure));
+    jni.seq_to_vec(errors)
+        .into_iter()
+        .map(|error| {
+            if jni.is_instance_of(error, "viper/silver/verifier/VerificationError") {
+                jni.to_string(jni.unwrap_result(
+                    verification_error_wrapper.call_readableMessage(error),
+                ))
+            } else {
+                jni.to_string(error)
+            }
+        })
+        .collect()
+}
@@ -13,7 +13,13 @@ use jni::JNIEnv;
 use jni_utils::JniUtils;
 use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 use verification_backend::VerificationBackend;
+use verification_message::VerificationMessage;
 use verification_result::VerificationError;
 use verification_result::VerificationResult;
 use viper_sys::wrappers::viper::*;
@@ -28,6 +34,8 @@ pub struct Verifier<'a, VerifierState> {
     env: &'a JNIEnv<'a>,
     verifier_wrapper: silver::verifier::Verifier<'a>,
     verifier_instance: JObject<'a>,
+    /// The `PollingReporter` that collects the messages of the verifier, if the backend supports it.
+    reporter_instance: Option<JObject<'a>>,
     jni: JniUtils<'a>,
     state: PhantomData<VerifierState>,
 }
@@ -40,6 +48,7 @@ impl<'a, VerifierState> Verifier<'a, VerifierState> {
     ) -> Verifier<'a, state::Uninitialized> {
         let jni = JniUtils::new(env);
         let verifier_wrapper = silver::verifier::Verifier::with(env);
+        let mut reporter_instance = None;
         let verifier_instance = jni.unwrap_result(match backend {
             VerificationBackend::Silicon => {
                 let reporter = if let Some(real_report_path) = report_path {
@@ -50,8 +59,15 @@ impl<'a, VerifierState> Verifier<'a, VerifierState> {
                 } else {
                     jni.unwrap_result(silver::reporter::NoopReporter_object::with(env).singleton())
                 };
-                let plugin_aware_reporter = jni
-                    .unwrap_result(silver::plugin::PluginAwareReporter::with(&env).new(reporter));
+                // Store the messages of the verifier, so that they can be forwarded to Rust.
+                let polling_reporter = jni.unwrap_result(
+                    silver::reporter::PollingReporter::with(env)
+                        .new(jni.new_string("polling_reporter"), reporter),
+                );
+                reporter_instance = Some(polling_reporter);
+                let plugin_aware_reporter = jni.unwrap_result(
+                    silver::plugin::PluginAwareReporter::with(&env).new(polling_reporter),
+                );
                 let utils = JniUtils::new(env);
                 let debug_info = utils.new_seq(&[]);
                 silicon::Silicon::with(env).new(plugin_aware_reporter, debug_info)
@@ -68,6 +84,7 @@ impl<'a, VerifierState> Verifier<'a, VerifierState> {
             env,
             verifier_wrapper,
             verifier_instance,
+            reporter_instance,
             jni,
             state: PhantomData,
         }
@@ -92,6 +109,7 @@ impl<'a> Verifier<'a, state::Uninitialized> {
             env: self.env,
             verifier_wrapper: self.verifier_wrapper,
             verifier_instance: self.verifier_instance,
+            reporter_instance: self.reporter_instance,
             jni: self.jni,
             state: PhantomData,
         }
@@ -107,6 +125,7 @@ impl<'a> Verifier<'a, state::Stopped> {
             env: self.env,
             verifier_wrapper: self.verifier_wrapper,
             verifier_instance: self.verifier_instance,
+            reporter_instance: self.reporter_instance,
             jni: self.jni,
             state: PhantomData,
         }
@@ -115,6 +134,76 @@ impl<'a> Verifier<'a, state::Stopped> {
 
 impl<'a> Verifier<'a, state::Started> {
     pub fn verify(&self, program: Program) -> VerificationResult {
+        self.verify_with_messages(program, None)
+    }
+
+    /// Verifies the program, forwarding the messages reported by the verifier to `sender` as
+    /// soon as they are available.
+    ///
+    /// Messages are only reported by Silicon; with Carbon no message is sent.
+    pub fn verify_with_messages(
+        &self,
+        program: Program,
+        sender: Option<Sender<VerificationMessage>>,
+    ) -> VerificationResult {
+        let polling = match (sender, self.reporter_instance) {
+            (Some(sender), Some(reporter_instance)) => {
+                Some(self.start_polling(reporter_instance, sender))
+            }
+            _ => None,
+        };
+        let result = self.verify_program(program);
+        if let Some((is_finished, handle)) = polling {
+            is_finished.store(true, Ordering::SeqCst);
+            handle.join().expect("the thread polling the verifier messages panicked");
+        }
+        result
+    }
+
+    /// Spawns a thread that polls the messages collected by the `PollingReporter` and sends
+    /// them through the channel, until the returned flag is set.
+    fn start_polling(
+        &self,
+        reporter_instance: JObject<'a>,
+        sender: Sender<VerificationMessage>,
+    ) -> (Arc<AtomicBool>, thread::JoinHandle<()>) {
+        let jvm = self.jni.unwrap_result(self.env.get_java_vm());
+        let reporter = self.jni.unwrap_result(self.env.new_global_ref(reporter_instance));
+        let is_finished = Arc::new(AtomicBool::new(false));
+        let is_finished_clone = is_finished.clone();
+        let handle = thread::spawn(move || {
+            let env = jvm
+                .attach_current_thread()
+                .expect("failed to attach jvm thread");
+            let jni = JniUtils::new(&env);
+            let polling_reporter_wrapper = silver::reporter::PollingReporter::with(&env);
+            loop {
+                // Read the flag before draining, so that no message is lost.
+                let finished = is_finished_clone.load(Ordering::SeqCst);
+                while jni.unwrap_result(
+                    polling_reporter_wrapper.call_hasNewMessage(reporter.as_obj()),
+                ) != 0
+                {
+                    let message = jni.unwrap_result(
+                        polling_reporter_wrapper.call_getNewMessage(reporter.as_obj()),
+                    );
+                    let message = VerificationMessage::from_jobject(&env, message);
+                    trace!("Viper message: {:?}", message);
+                    if sender.send(message).is_err() {
+                        // The receiver is not interested in messages anymore.
+                        return;
+                    }
+                }
+                if finished {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+        (is_finished, handle)
+    }
+
+    fn verify_program(&self, program: Program) -> VerificationResult {
         let ast_utils = AstUtils::new(self.env);
 
         debug!(
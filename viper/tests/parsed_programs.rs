@@ -0,0 +1,87 @@
+extern crate env_logger;
+extern crate error_chain;
+#[macro_use]
+extern crate lazy_static;
+extern crate viper;
+
+use std::path::Path;
+use std::sync::Once;
+use viper::*;
+
+static INIT: Once = Once::new();
+
+lazy_static! {
+    static ref VIPER: Viper = Viper::new();
+}
+
+/// Setup function that is only run once, even if called multiple times.
+fn setup() {
+    INIT.call_once(|| {
+        env_logger::init();
+    });
+}
+
+#[test]
+fn success_with_parsed_program() {
+    setup();
+
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let parser = verification_context.new_program_parser();
+
+    let program = parser
+        .parse(
+            "method inc(x: Int) returns (res: Int)\n  requires 0 <= x\n  ensures x < res\n{\n  res := x + 1\n}\n",
+            Path::new("inc.vpr"),
+        )
+        .unwrap();
+
+    let verifier = verification_context.new_verifier(VerificationBackend::Silicon, None);
+
+    assert_eq!(verifier.verify(program), VerificationResult::Success());
+}
+
+#[test]
+fn failure_with_parsed_assert_false() {
+    setup();
+
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let parser = verification_context.new_program_parser();
+
+    let program = parser
+        .parse("method foo() {\n  assert false\n}\n", Path::new("foo.vpr"))
+        .unwrap();
+
+    let verifier = verification_context.new_verifier(VerificationBackend::Silicon, None);
+
+    match verifier.verify(program) {
+        VerificationResult::Failure(errors) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].full_id, "assert.failed:assertion.false");
+        }
+        other => panic!("Unexpected verification result: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_error() {
+    setup();
+
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let parser = verification_context.new_program_parser();
+
+    assert!(parser
+        .parse("method foo( {", Path::new("foo.vpr"))
+        .is_err());
+}
+
+#[test]
+fn type_error() {
+    setup();
+
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let parser = verification_context.new_program_parser();
+
+    assert!(parser
+        .parse("method foo() {\n  assert 1\n}\n", Path::new("foo.vpr"))
+        .is_err());
+}
@@ -0,0 +1,65 @@
+extern crate env_logger;
+extern crate error_chain;
+#[macro_use]
+extern crate lazy_static;
+extern crate viper;
+
+use std::sync::mpsc;
+use std::sync::Once;
+use viper::*;
+
+static INIT: Once = Once::new();
+
+lazy_static! {
+    static ref VIPER: Viper = Viper::new();
+}
+
+/// Setup function that is only run once, even if called multiple times.
+fn setup() {
+    INIT.call_once(|| {
+        env_logger::init();
+    });
+}
+
+#[test]
+fn entity_messages_are_forwarded() {
+    setup();
+
+    let verification_context: VerificationContext = VIPER.new_verification_context();
+    let ast = verification_context.new_ast_factory();
+
+    let assert_true = ast.assert(ast.true_lit(), ast.no_position());
+    let body_ok = ast.seqn(&[assert_true], &[]);
+    let method_ok = ast.method("ok", &[], &[], &[], &[], Some(body_ok));
+
+    let assert_false = ast.assert(ast.false_lit(), ast.no_position());
+    let body_fail = ast.seqn(&[assert_false], &[]);
+    let method_fail = ast.method("fail", &[], &[], &[], &[], Some(body_fail));
+
+    let program = ast.program(&[], &[], &[], &[], &[method_ok, method_fail]);
+
+    let verifier = verification_context.new_verifier(viper::VerificationBackend::Silicon, None);
+
+    let (sender, receiver) = mpsc::channel();
+    let verification_result = verifier.verify_with_messages(program, Some(sender));
+    let messages: Vec<VerificationMessage> = receiver.try_iter().collect();
+
+    if let VerificationResult::Failure(errors) = verification_result {
+        assert_eq!(errors.len(), 1);
+    } else {
+        assert!(false)
+    }
+
+    assert!(messages.iter().any(|message| match message {
+        VerificationMessage::EntitySuccess { entity_name, .. } => entity_name == "ok",
+        _ => false,
+    }));
+    assert!(messages.iter().any(|message| match message {
+        VerificationMessage::EntityFailure {
+            entity_name,
+            errors,
+            ..
+        } => entity_name == "fail" && errors.len() == 1,
+        _ => false,
+    }));
+}